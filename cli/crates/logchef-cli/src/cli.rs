@@ -3,8 +3,9 @@ use clap::{CommandFactory, Parser, Subcommand};
 use tracing_subscriber::EnvFilter;
 
 use crate::commands::{
-    auth, collections, completions, config, doctor, explain, fields, find, histogram, history,
-    open, query, saved_queries, schema, skills, sources, sql, tail, teams, whoami,
+    auth, browse, collections, completions, config, doctor, explain, export, fields, find,
+    histogram, history, lint, open, query, saved, saved_queries, schema, skills, sources, sql,
+    tail, teams, values, whoami,
 };
 
 const LONG_ABOUT: &str = "\
@@ -17,12 +18,18 @@ by what you want to do:
              BOTH engines. Start here. e.g. `level=\"error\" and service=\"api\"`.
   sql        Run a raw native query when LogchefQL isn't enough: ClickHouse SQL
              for ClickHouse sources, LogsQL for VictoriaLogs sources.
+  export     Write a large result set to disk, split into numbered files,
+             resumable if interrupted.
   explain    Show the ClickHouse SQL / LogsQL a LogchefQL query compiles to,
              without running it. Great for learning and debugging filters.
+  lint       Check LogchefQL syntax offline, with the error pointed out.
   histogram  Plot log counts over time (trends, spikes, error rates).
   fields     Discover a source's fields, or the observed values of one field.
+  values     Top values of one or more fields, optionally over a filter.
   find       Locate which source holds a given service, host, or message.
   tail       Follow matching logs live.
+  browse     Explore results interactively: scroll, search, inspect entries,
+             and edit and re-run the query without leaving the terminal UI.
 
 Set a default team and source once (`logchef config set team …` /
 `… set source …`) so you can drop -t/-S. Every data command supports
@@ -66,6 +73,13 @@ pub struct Cli {
     #[arg(long, short, global = true)]
     debug: bool,
 
+    #[arg(
+        long,
+        global = true,
+        help = "Print where each API call's time went (DNS, connect, server, download, parse) to stderr"
+    )]
+    timing: bool,
+
     #[arg(
         long,
         short,
@@ -73,6 +87,20 @@ pub struct Cli {
         help = "Suppress stderr stats, highlighting, and spinners (data still goes to stdout)"
     )]
     pub quiet: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Write results straight to the terminal instead of through $PAGER"
+    )]
+    no_pager: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Print timestamps in UTC instead of defaults.timezone or the local timezone"
+    )]
+    utc: bool,
 }
 
 #[derive(Subcommand)]
@@ -81,7 +109,7 @@ enum Commands {
     Auth(auth::AuthArgs),
 
     #[command(about = "Execute a LogchefQL query")]
-    Query(query::QueryArgs),
+    Query(Box<query::QueryArgs>),
 
     #[command(
         visible_alias = "native",
@@ -94,13 +122,22 @@ enum Commands {
     )]
     Explain(explain::ExplainArgs),
 
+    #[command(about = "Check LogchefQL syntax locally, without contacting the server")]
+    Lint(lint::LintArgs),
+
+    #[command(about = "Export query results to disk, split into size- or row-limited files")]
+    Export(export::ExportArgs),
+
     #[command(about = "Discover fields for a source, or observed values for a field")]
     Fields(fields::FieldsArgs),
 
+    #[command(about = "Show the most frequent values of one or more fields")]
+    Values(values::ValuesArgs),
+
     #[command(about = "Show log counts over time as a terminal bar chart")]
     Histogram(histogram::HistogramArgs),
 
-    #[command(about = "Show your recent query history")]
+    #[command(about = "List, search and re-run your recent queries")]
     History(history::HistoryArgs),
 
     #[command(about = "Open the current team/source (and optional query) in the web explorer")]
@@ -112,12 +149,18 @@ enum Commands {
     #[command(name = "saved-queries", about = "List and run saved queries")]
     SavedQueries(saved_queries::SavedQueriesArgs),
 
+    #[command(about = "Manage personal named queries stored in the current context")]
+    Saved(saved::SavedArgs),
+
     #[command(about = "Find sources that contain a service, job, host, or message pattern")]
     Find(find::FindArgs),
 
     #[command(about = "Follow matching LogChefQL results")]
     Tail(tail::TailArgs),
 
+    #[command(about = "Explore query results in an interactive terminal UI")]
+    Browse(browse::BrowseArgs),
+
     #[command(about = "List available teams")]
     Teams(teams::TeamsArgs),
 
@@ -143,11 +186,14 @@ enum Commands {
     Completions(completions::CompletionsArgs),
 }
 
+#[derive(Clone)]
 pub struct GlobalArgs {
     pub context: Option<String>,
     pub server: Option<String>,
     pub token: Option<String>,
     pub quiet: bool,
+    pub no_pager: bool,
+    pub utc: bool,
 }
 
 impl Cli {
@@ -163,7 +209,13 @@ impl Cli {
             .with_target(false)
             .init();
 
+        logchef_core::config::set_passphrase_prompt(crate::commands::config::prompt_passphrase);
+
         let quiet = self.quiet;
+        let show_timing = self.timing || (self.debug && !quiet);
+        if show_timing {
+            logchef_core::api::timing::enable();
+        }
         // The completions command emits a script; keep it free of any notice.
         let run_update_check = !matches!(self.command, Some(Commands::Completions(_)));
 
@@ -172,21 +224,28 @@ impl Cli {
             server: self.server,
             token: self.token,
             quiet: self.quiet,
+            no_pager: self.no_pager,
+            utc: self.utc,
         };
 
         let result = match self.command {
             Some(Commands::Auth(args)) => auth::run(args, global).await,
-            Some(Commands::Query(args)) => query::run(args, global).await,
+            Some(Commands::Query(args)) => query::run(*args, global).await,
             Some(Commands::Sql(args)) => sql::run(args, global).await,
             Some(Commands::Explain(args)) => explain::run(args, global).await,
+            Some(Commands::Lint(args)) => lint::run(args, global).await,
+            Some(Commands::Export(args)) => export::run(args, global).await,
             Some(Commands::Fields(args)) => fields::run(args, global).await,
+            Some(Commands::Values(args)) => values::run(args, global).await,
             Some(Commands::Histogram(args)) => histogram::run(args, global).await,
             Some(Commands::History(args)) => history::run(args, global).await,
             Some(Commands::Open(args)) => open::run(args, global).await,
             Some(Commands::Collections(args)) => collections::run(args, global).await,
             Some(Commands::SavedQueries(args)) => saved_queries::run(args, global).await,
+            Some(Commands::Saved(args)) => saved::run(args, global).await,
             Some(Commands::Find(args)) => find::run(args, global).await,
             Some(Commands::Tail(args)) => tail::run(args, global).await,
+            Some(Commands::Browse(args)) => browse::run(args, global).await,
             Some(Commands::Teams(args)) => teams::run(args, global).await,
             Some(Commands::Whoami(args)) => whoami::run(args, global).await,
             Some(Commands::Sources(args)) => sources::run(args, global).await,
@@ -207,6 +266,10 @@ impl Cli {
             }
         };
 
+        if show_timing {
+            crate::ui::print_timings(&logchef_core::api::timing::take());
+        }
+
         // Fire the update notifier only after a successful command, and never
         // for `completions`. It self-gates (config/TTY/quiet/env) and is
         // time-boxed, so it never delays or breaks the command.
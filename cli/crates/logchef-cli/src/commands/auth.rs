@@ -2,10 +2,13 @@ use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
 use inquire::Text;
 use logchef_core::Config;
-use logchef_core::api::Client;
+use logchef_core::api::{Client, IdentityInfo};
 use logchef_core::auth::AuthFlow;
-use logchef_core::config::{Context as CtxConfig, ContextDefaults, context_name_from_url};
+use logchef_core::config::{
+    Context as CtxConfig, ContextDefaults, TokenType, context_name_from_url,
+};
 
+use super::config::read_token;
 use crate::cli::GlobalArgs;
 
 #[derive(Args)]
@@ -16,8 +19,48 @@ pub struct AuthArgs {
     #[arg(long, short)]
     logout: bool,
 
+    /// Show the active context and the user its token authenticates as.
+    /// Exits non-zero when no valid token is available.
     #[arg(long)]
     status: bool,
+
+    /// Output format for --status.
+    #[arg(long, default_value = "text", requires = "status")]
+    output: OutputFormat,
+
+    /// OIDC discovery document URL, for IdPs that don't serve it at
+    /// `{issuer}/.well-known/openid-configuration`. Saved to the context.
+    #[arg(long, value_name = "URL")]
+    oidc_discovery_url: Option<String>,
+
+    /// Sign in with a device code instead of a local browser: prints a URL
+    /// and code to enter on any other device. Used automatically over SSH or
+    /// without a display, when the identity provider supports it.
+    #[arg(long)]
+    device: bool,
+
+    /// Don't open a browser or wait for its callback: print the sign-in URL,
+    /// then paste back the URL the browser is redirected to. For machines the
+    /// callback can't reach, e.g. over SSH without port forwarding.
+    #[arg(long, conflicts_with = "device")]
+    no_browser: bool,
+
+    /// Authenticate with a long-lived API token instead of the OIDC flow,
+    /// e.g. in CI jobs and cron scripts. Checked against the server before
+    /// it's saved. Use `-` to read it from stdin.
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        conflicts_with_all = ["logout", "status", "device", "no_browser", "oidc_discovery_url"]
+    )]
+    api_token: Option<String>,
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    Jsonl,
 }
 
 #[derive(Subcommand)]
@@ -39,10 +82,14 @@ pub async fn run(args: AuthArgs, global: GlobalArgs) -> Result<()> {
     }
 
     if args.status {
-        return status(&config, &global).await;
+        return status(&config, &global, args.output).await;
+    }
+
+    if let Some(token) = args.api_token {
+        return login_with_api_token(&mut config, token, &global).await;
     }
 
-    login(&mut config, global).await
+    login(&mut config, args, global).await
 }
 
 fn current(config: &Config, global: &GlobalArgs) -> Result<()> {
@@ -56,6 +103,7 @@ fn current(config: &Config, global: &GlobalArgs) -> Result<()> {
             ctx.token.is_some(),
             global.token.is_some(),
             false,
+            ctx.token_type,
             ctx.token_expires_at,
         );
         (name.clone(), ctx.server_url.clone(), line)
@@ -65,11 +113,12 @@ fn current(config: &Config, global: &GlobalArgs) -> Result<()> {
                 ctx.token.is_some(),
                 global.token.is_some(),
                 false,
+                ctx.token_type,
                 ctx.token_expires_at,
             );
             (name.to_string(), ctx.server_url.clone(), line)
         } else {
-            let line = token_line(false, global.token.is_some(), true, None);
+            let line = token_line(false, global.token.is_some(), true, None, None);
             ("(ephemeral)".to_string(), url.clone(), line)
         }
     } else if let Some(name) = config.current_context_name() {
@@ -80,11 +129,12 @@ fn current(config: &Config, global: &GlobalArgs) -> Result<()> {
             ctx.token.is_some(),
             global.token.is_some(),
             false,
+            ctx.token_type,
             ctx.token_expires_at,
         );
         (name.to_string(), ctx.server_url.clone(), line)
     } else if let Ok(env_url) = std::env::var("LOGCHEF_SERVER_URL") {
-        let line = token_line(false, global.token.is_some(), true, None);
+        let line = token_line(false, global.token.is_some(), true, None, None);
         ("(ephemeral)".to_string(), env_url, line)
     } else {
         anyhow::bail!("No context configured and no --server/LOGCHEF_SERVER_URL provided.");
@@ -108,6 +158,7 @@ fn token_line(
     saved_token: bool,
     env_token: bool,
     is_ephemeral: bool,
+    token_type: Option<TokenType>,
     expires_at: Option<chrono::DateTime<chrono::Utc>>,
 ) -> String {
     // --token / LOGCHEF_AUTH_TOKEN takes precedence over the saved token, and
@@ -117,6 +168,10 @@ fn token_line(
     }
     if saved_token {
         let mut s = "set (from config".to_string();
+        if let Some(kind) = token_type {
+            s.push_str(", ");
+            s.push_str(kind.label());
+        }
         if let Some(ts) = expires_at {
             let expired = ts < chrono::Utc::now();
             s.push_str(if expired { ", EXPIRED " } else { ", expires " });
@@ -141,6 +196,7 @@ fn logout(config: &mut Config, global: &GlobalArgs) -> Result<()> {
     if let Some(ctx) = config.get_context_mut(&ctx_name) {
         ctx.token = None;
         ctx.token_expires_at = None;
+        ctx.token_type = None;
         config.save().context("Failed to save config")?;
         println!("Logged out from context '{}'.", ctx_name);
     } else {
@@ -150,51 +206,95 @@ fn logout(config: &mut Config, global: &GlobalArgs) -> Result<()> {
     Ok(())
 }
 
-async fn status(config: &Config, global: &GlobalArgs) -> Result<()> {
-    let ctx_name = match resolve_context_name(config, global) {
-        Ok(name) => name,
-        Err(_) => {
-            println!("No contexts configured. Run 'logchef auth --server <url>' to set up.");
-            return Ok(());
+const NOT_AUTHENTICATED: &str = "not authenticated";
+
+async fn status(config: &Config, global: &GlobalArgs, output: OutputFormat) -> Result<()> {
+    let identity = match resolve_context_name(config, global) {
+        Ok(ctx_name) => {
+            let ctx = config
+                .get_context(&ctx_name)
+                .ok_or_else(|| anyhow::anyhow!("Context '{}' not found", ctx_name))?;
+            let identity = if !ctx.is_authenticated() {
+                IdentityInfo {
+                    error: Some(NOT_AUTHENTICATED.to_string()),
+                    ..Default::default()
+                }
+            } else {
+                let client = Client::from_context(ctx)?;
+                match client.get_current_user().await {
+                    Ok(user) => IdentityInfo::authenticated(user),
+                    Err(e) => IdentityInfo {
+                        error: Some(e.to_string()),
+                        ..Default::default()
+                    },
+                }
+            };
+            identity.with_context(ctx_name, ctx.server_url.clone())
         }
+        Err(_) => IdentityInfo {
+            error: Some("no contexts configured".to_string()),
+            ..Default::default()
+        },
     };
 
-    let ctx = config
-        .get_context(&ctx_name)
-        .ok_or_else(|| anyhow::anyhow!("Context '{}' not found", ctx_name))?;
-
-    println!("Context: {}", ctx_name);
-    println!("Server:  {}", ctx.server_url);
+    match output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&identity)?),
+        OutputFormat::Jsonl => println!("{}", serde_json::to_string(&identity)?),
+        OutputFormat::Text => print_status(&identity),
+    }
 
-    if !ctx.is_authenticated() {
-        println!("Status:  Not authenticated");
-        return Ok(());
+    // Scripts gate on the exit code rather than parsing the text above.
+    if !identity.authenticated {
+        std::process::exit(1);
     }
+    Ok(())
+}
+
+fn print_status(identity: &IdentityInfo) {
+    let (Some(context), Some(server)) = (&identity.context, &identity.server) else {
+        println!("No contexts configured. Run 'logchef auth --server <url>' to set up.");
+        return;
+    };
 
-    let client = Client::from_context(ctx)?;
-    match client.get_current_user().await {
-        Ok(user) => {
+    println!("Context: {}", context);
+    println!("Server:  {}", server);
+
+    match &identity.user {
+        Some(user) => {
             println!("User:    {}", user.email);
             if let Some(name) = &user.full_name {
                 println!("Name:    {}", name);
             }
             println!("Role:    {}", user.role);
         }
-        Err(e) => {
-            println!("Status:  Token may be invalid or expired ({})", e);
-        }
+        None => match identity.error.as_deref() {
+            Some(NOT_AUTHENTICATED) | None => println!("Status:  Not authenticated"),
+            Some(e) => println!("Status:  Token may be invalid or expired ({})", e),
+        },
     }
-
-    Ok(())
 }
 
-async fn login(config: &mut Config, global: GlobalArgs) -> Result<()> {
+async fn login(config: &mut Config, args: AuthArgs, global: GlobalArgs) -> Result<()> {
     let server_url = get_server_url(config, &global)?;
     let server_url = server_url.trim_end_matches('/').to_string();
 
+    let ctx_name = global
+        .context
+        .clone()
+        .or_else(|| {
+            config
+                .find_context_by_url(&server_url)
+                .map(|(n, _)| n.to_string())
+        })
+        .unwrap_or_else(|| context_name_from_url(&server_url));
+    let (tls, proxy_url) = config
+        .get_context(&ctx_name)
+        .map(|ctx| (ctx.tls.clone(), ctx.proxy_url.clone()))
+        .unwrap_or_default();
+
     println!("Connecting to {}...", server_url);
 
-    let client = Client::new(&server_url, 30)?;
+    let client = Client::with_network(&server_url, 30, &tls, proxy_url.as_deref())?;
     let meta = client
         .get_meta()
         .await
@@ -218,30 +318,92 @@ async fn login(config: &mut Config, global: GlobalArgs) -> Result<()> {
         .cli_client_id
         .ok_or_else(|| anyhow::anyhow!("Server did not provide CLI client ID"))?;
 
-    let auth_flow = AuthFlow::new(server_url.clone(), oidc_issuer, cli_client_id);
-    let result = auth_flow.run().await?;
+    let oidc_discovery_url = args.oidc_discovery_url.or_else(|| {
+        config
+            .get_context(&ctx_name)
+            .and_then(|ctx| ctx.oidc_discovery_url.clone())
+    });
 
-    let ctx_name = global
-        .context
-        .clone()
-        .or_else(|| {
-            config
-                .find_context_by_url(&server_url)
-                .map(|(n, _)| n.to_string())
-        })
-        .unwrap_or_else(|| context_name_from_url(&server_url));
+    let oidc_client_secret = config
+        .get_context(&ctx_name)
+        .and_then(|ctx| ctx.oidc_client_secret.clone());
+    let compress_requests = config
+        .get_context(&ctx_name)
+        .is_some_and(|ctx| ctx.compress_requests);
+
+    let auth_flow = AuthFlow::new(server_url.clone(), oidc_issuer, cli_client_id)
+        .with_discovery_url(oidc_discovery_url.clone())
+        .with_client_secret(oidc_client_secret.clone())
+        .with_tls(tls.clone())
+        .with_proxy(proxy_url.clone());
+    let headless = !args.device && !args.no_browser && no_display();
+    let outcome = if args.device || headless {
+        if headless {
+            println!("No display detected; signing in with a device code.");
+        }
+        match auth_flow.run_device().await {
+            Err(logchef_core::Error::DeviceFlowUnsupported) if headless => {
+                println!(
+                    "The identity provider doesn't support device codes; falling back to the browser flow."
+                );
+                auth_flow.run().await
+            }
+            outcome => outcome,
+        }
+    } else if args.no_browser {
+        auth_flow.run_manual().await
+    } else {
+        auth_flow.run().await
+    };
+    let result = match outcome {
+        Ok(result) => result,
+        Err(logchef_core::Error::CliAuthUnsupported) => anyhow::bail!(
+            "{} (server {} reports Logchef {}). To use an API token instead: logchef auth --server {} --api-token <token>",
+            logchef_core::Error::CliAuthUnsupported,
+            server_url,
+            meta.data.version,
+            server_url
+        ),
+        Err(logchef_core::Error::AuthCancelled) => {
+            eprintln!("\nAuthentication cancelled.");
+            // Conventional exit status for a SIGINT-terminated command.
+            std::process::exit(130);
+        }
+        Err(e) => return Err(e.into()),
+    };
 
     let timezone = iana_time_zone::get_timezone().ok();
+    // A fallback token, saved queries and time range caps are configured
+    // separately; re-authenticating the primary shouldn't drop them.
+    let existing = config.get_context(&ctx_name);
+    let fallback_token = existing.and_then(|ctx| ctx.fallback_token.clone());
+    let saved = existing.map(|ctx| ctx.saved.clone()).unwrap_or_default();
+    let retries = existing.and_then(|ctx| ctx.retries);
+    let max_time_range = existing.and_then(|ctx| ctx.max_time_range.clone());
+    let source_max_time_range = existing
+        .map(|ctx| ctx.source_max_time_range.clone())
+        .unwrap_or_default();
 
     let ctx = CtxConfig {
         server_url: server_url.clone(),
         timeout_secs: 30,
         token: Some(result.token),
         token_expires_at: result.expires_at,
+        token_type: Some(TokenType::Oidc),
+        fallback_token,
         defaults: ContextDefaults {
             timezone,
             ..Default::default()
         },
+        oidc_discovery_url,
+        oidc_client_secret,
+        compress_requests,
+        max_time_range,
+        source_max_time_range,
+        tls,
+        proxy_url,
+        retries,
+        saved,
     };
 
     config.add_or_update_context(ctx_name.clone(), ctx);
@@ -256,6 +418,74 @@ async fn login(config: &mut Config, global: GlobalArgs) -> Result<()> {
     Ok(())
 }
 
+/// Saves a personal access token to the context after checking it against
+/// `/api/v1/me`. Unlike the OIDC login, the rest of an existing context's
+/// settings are kept as they are.
+async fn login_with_api_token(
+    config: &mut Config,
+    token: String,
+    global: &GlobalArgs,
+) -> Result<()> {
+    let token = read_token(token)?;
+    let server_url = get_server_url(config, global)?;
+    let server_url = server_url.trim_end_matches('/').to_string();
+
+    let ctx_name = global
+        .context
+        .clone()
+        .or_else(|| {
+            config
+                .find_context_by_url(&server_url)
+                .map(|(n, _)| n.to_string())
+        })
+        .unwrap_or_else(|| context_name_from_url(&server_url));
+
+    let mut ctx = config.get_context(&ctx_name).cloned().unwrap_or_else(|| {
+        let mut ctx = CtxConfig::new(server_url.clone());
+        ctx.defaults.timezone = iana_time_zone::get_timezone().ok();
+        ctx
+    });
+    ctx.server_url = server_url.clone();
+    ctx.token = Some(token);
+    ctx.token_expires_at = None;
+    ctx.token_type = Some(TokenType::ApiToken);
+
+    let user = match Client::from_context(&ctx)?.get_current_user().await {
+        Ok(user) => user,
+        Err(logchef_core::Error::Api {
+            status: Some(401), ..
+        }) => {
+            anyhow::bail!("{} rejected the API token; nothing was saved", server_url)
+        }
+        Err(e) => {
+            return Err(anyhow::Error::new(e).context(format!(
+                "Failed to validate the API token against {}",
+                server_url
+            )));
+        }
+    };
+
+    config.add_or_update_context(ctx_name.clone(), ctx);
+    config.save().context("Failed to save config")?;
+
+    println!(
+        "Authenticated as {} with an API token (context: '{}')",
+        user.email, ctx_name
+    );
+    Ok(())
+}
+
+/// True where a browser likely can't be opened: an SSH session, or a
+/// Linux/BSD desktop-less machine with neither an X11 nor a Wayland display.
+fn no_display() -> bool {
+    if std::env::var_os("SSH_CONNECTION").is_some() || std::env::var_os("SSH_TTY").is_some() {
+        return true;
+    }
+    cfg!(all(unix, not(target_os = "macos")))
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+}
+
 fn resolve_context_name(config: &Config, global: &GlobalArgs) -> Result<String> {
     if let Some(name) = &global.context {
         return Ok(name.clone());
@@ -0,0 +1,758 @@
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::time::Duration as StdDuration;
+
+use anyhow::{Context, Result};
+use clap::Args;
+use logchef_core::Config;
+use logchef_core::api::{Client, Column, LogEntry, QueryRequest};
+use logchef_core::cache::Cache;
+use logchef_core::timerange::{TimeInput, from_to_window, resolve_time_range, since_window};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, TableState, Wrap,
+};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::cli::GlobalArgs;
+use crate::commands::{check_time_range_cap, resolve_source, resolve_team};
+use crate::session;
+
+/// How long the event loop waits for a key before redrawing.
+const TICK: StdDuration = StdDuration::from_millis(250);
+/// Widest a (non-last) table column grows before its cells are clipped.
+const MAX_COLUMN_WIDTH: usize = 40;
+/// Rows moved by PageUp / PageDown.
+const PAGE: usize = 10;
+
+#[derive(Args)]
+#[command(after_help = "EXAMPLES:
+  # Explore the last hour of errors
+  logchef browse 'level=\"error\"' --since 1h -t platform -S app-logs
+
+  # Start empty and type the query inside the explorer (press e)
+  logchef browse
+
+KEYS:
+  j/k, ↑/↓, PgUp/PgDn, g/G   move            Enter   toggle the detail pane
+  /                          search (live)   n/N     next/previous match
+  e                          edit and re-run the query
+  c                          show/hide columns (space toggles)
+  r                          re-run          q, Esc  quit")]
+pub struct BrowseArgs {
+    /// LogchefQL query to start with (editable inside the explorer)
+    query: Option<String>,
+
+    /// Team ID or name
+    #[arg(long, short = 't')]
+    team: Option<String>,
+
+    /// Source ID or name
+    #[arg(long, short = 'S')]
+    source: Option<String>,
+
+    /// Relative lookback window (e.g. 15m, 1h, 24h, today), re-applied on every run
+    #[arg(long, short = 's')]
+    since: Option<String>,
+
+    /// Absolute start time (RFC3339, YYYY-MM-DD HH:MM:SS in the effective
+    /// timezone, epoch ms, or "2 hours ago"). Requires --to.
+    #[arg(long)]
+    from: Option<String>,
+
+    /// Absolute end time, in the same formats as --from. Requires --from.
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Maximum rows to load per run
+    #[arg(long, short = 'l')]
+    limit: Option<u32>,
+
+    /// Query timeout in seconds
+    #[arg(long, default_value = "30")]
+    timeout: u32,
+
+    /// Run even if the time range exceeds the configured max_time_range
+    #[arg(long)]
+    force: bool,
+}
+
+pub async fn run(args: BrowseArgs, global: GlobalArgs) -> Result<()> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        anyhow::bail!(
+            "browse needs an interactive terminal; use 'logchef query' for scripts and pipes"
+        );
+    }
+    if args.from.is_some() != args.to.is_some() {
+        anyhow::bail!("--from and --to must be given together");
+    }
+
+    let config = Config::load().context("Failed to load config")?;
+    let s = session::authed(&config, &global)?;
+    let (client, ctx) = (&s.client, &s.ctx);
+
+    let mut cache = Cache::new(&ctx.server_url);
+    let team = args.team.clone().or_else(|| ctx.defaults.team_with_env());
+    let source = args
+        .source
+        .clone()
+        .or_else(|| ctx.defaults.source_with_env());
+    let team_id = resolve_team(client, &mut cache, team).await?;
+    let source_id = resolve_source(client, &mut cache, team_id, source.clone()).await?;
+
+    let since = args
+        .since
+        .clone()
+        .unwrap_or_else(|| ctx.defaults.since.clone());
+    let span = match (&args.from, &args.to) {
+        (Some(from), Some(to)) => {
+            let (start, end) = from_to_window(from, to, ctx.defaults.timezone.as_deref())?;
+            end - start
+        }
+        _ => {
+            let (start, end) = since_window(&since, ctx.defaults.timezone.as_deref())?;
+            end - start
+        }
+    };
+    check_time_range_cap(ctx, source_id, source.as_deref(), span, args.force)?;
+
+    let target = Target {
+        team_id,
+        source_id,
+        label: source.unwrap_or_else(|| source_id.to_string()),
+        since,
+        from: args.from.clone(),
+        to: args.to.clone(),
+        timezone: ctx.defaults.timezone.clone(),
+        limit: args.limit.unwrap_or(ctx.defaults.limit),
+        timeout: args.timeout,
+    };
+
+    let mut app = App::new(args.query.clone().unwrap_or_default());
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, client, &target).await;
+    ratatui::restore();
+    result
+}
+
+/// Where and how queries run; fixed for the session.
+struct Target {
+    team_id: i64,
+    source_id: i64,
+    label: String,
+    since: String,
+    from: Option<String>,
+    to: Option<String>,
+    timezone: Option<String>,
+    limit: u32,
+    timeout: u32,
+}
+
+impl Target {
+    /// A relative window is re-anchored at now, so re-running refreshes it.
+    fn request(&self, query: &str) -> Result<QueryRequest> {
+        let tz = self.timezone.as_deref();
+        let (start, end) = match (&self.from, &self.to) {
+            (Some(from), Some(to)) => from_to_window(from, to, tz)?,
+            _ => since_window(&self.since, tz)?,
+        };
+        let input = TimeInput::Instant { start, end };
+        let range = resolve_time_range(input, self.timezone.as_deref());
+        Ok(QueryRequest {
+            query: query.to_string(),
+            start_time: range.start,
+            end_time: range.end,
+            timezone: Some(range.timezone),
+            limit: Some(self.limit),
+            query_timeout: Some(self.timeout),
+        })
+    }
+
+    fn window(&self) -> String {
+        match (&self.from, &self.to) {
+            (Some(from), Some(to)) => format!("{} → {}", from, to),
+            _ => format!("last {}", self.since),
+        }
+    }
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    client: &Client,
+    target: &Target,
+) -> Result<()> {
+    let mut pending_run = true;
+    loop {
+        if pending_run {
+            pending_run = false;
+            app.status = Status::Info("Running query…".to_string());
+            terminal.draw(|frame| render(frame, app, target))?;
+            match target.request(&app.query) {
+                Ok(request) => {
+                    let result = client
+                        .query_logchefql(target.team_id, target.source_id, &request)
+                        .await;
+                    match result {
+                        Ok(response) => {
+                            let took = response.stats.execution_time_ms;
+                            app.set_results(response.columns.clone(), response.entries().to_vec());
+                            app.status =
+                                Status::Info(format!("{} rows in {}ms", app.entries.len(), took));
+                        }
+                        Err(e) => app.status = Status::Error(e.to_string()),
+                    }
+                }
+                Err(e) => app.status = Status::Error(format!("{:#}", e)),
+            }
+        }
+
+        terminal.draw(|frame| render(frame, app, target))?;
+        if !event::poll(TICK)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            match app.on_key(key) {
+                Action::None => {}
+                Action::Quit => return Ok(()),
+                Action::Run => pending_run = true,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    List,
+    Detail,
+    Search,
+    EditQuery,
+    Columns,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    None,
+    Quit,
+    Run,
+}
+
+enum Status {
+    Info(String),
+    Error(String),
+}
+
+/// Explorer state, kept separate from drawing so key handling is testable.
+struct App {
+    query: String,
+    columns: Vec<Column>,
+    hidden: HashSet<String>,
+    entries: Vec<LogEntry>,
+    /// Each entry's cells joined, lowercased, for search.
+    haystacks: Vec<String>,
+    selected: usize,
+    offset: usize,
+    mode: Mode,
+    /// Active search term (lowercased); empty when not searching.
+    search: String,
+    /// Edit buffer for the search and query prompts.
+    input: String,
+    column_cursor: usize,
+    detail_scroll: u16,
+    status: Status,
+}
+
+impl App {
+    fn new(query: String) -> Self {
+        Self {
+            query,
+            columns: Vec::new(),
+            hidden: HashSet::new(),
+            entries: Vec::new(),
+            haystacks: Vec::new(),
+            selected: 0,
+            offset: 0,
+            mode: Mode::List,
+            search: String::new(),
+            input: String::new(),
+            column_cursor: 0,
+            detail_scroll: 0,
+            status: Status::Info(String::new()),
+        }
+    }
+
+    /// Loads a new result set. Columns hidden earlier stay hidden; internal
+    /// `_` columns other than `_timestamp` start hidden, as in table output.
+    fn set_results(&mut self, columns: Vec<Column>, entries: Vec<LogEntry>) {
+        for col in &columns {
+            let known = self.columns.iter().any(|c| c.name == col.name);
+            if !known && col.name.starts_with('_') && col.name != "_timestamp" {
+                self.hidden.insert(col.name.clone());
+            }
+        }
+        self.haystacks = entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .values()
+                    .map(super::table_cell)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .to_lowercase()
+            })
+            .collect();
+        self.columns = columns;
+        self.entries = entries;
+        self.selected = 0;
+        self.offset = 0;
+        self.detail_scroll = 0;
+        self.column_cursor = self.column_cursor.min(self.columns.len().saturating_sub(1));
+    }
+
+    fn visible_columns(&self) -> Vec<&Column> {
+        self.columns
+            .iter()
+            .filter(|c| !self.hidden.contains(&c.name))
+            .collect()
+    }
+
+    fn is_match(&self, index: usize) -> bool {
+        !self.search.is_empty() && self.haystacks[index].contains(&self.search)
+    }
+
+    /// Moves the selection to the next (or previous) matching row, starting
+    /// at `from` and wrapping around.
+    fn jump_to_match(&mut self, from: usize, forward: bool) -> bool {
+        let len = self.entries.len();
+        if len == 0 || self.search.is_empty() {
+            return false;
+        }
+        for step in 0..len {
+            let index = if forward {
+                (from + step) % len
+            } else {
+                (from + len - step % len) % len
+            };
+            if self.is_match(index) {
+                self.selected = index;
+                self.detail_scroll = 0;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let last = self.entries.len() - 1;
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+        self.detail_scroll = 0;
+    }
+
+    fn on_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        match self.mode {
+            Mode::List | Mode::Detail => self.on_browse_key(key),
+            Mode::Search => {
+                match key.code {
+                    KeyCode::Esc => {
+                        self.search.clear();
+                        self.mode = Mode::List;
+                    }
+                    KeyCode::Enter => self.mode = Mode::List,
+                    KeyCode::Backspace => {
+                        self.input.pop();
+                        self.update_search();
+                    }
+                    KeyCode::Char(c) => {
+                        self.input.push(c);
+                        self.update_search();
+                    }
+                    _ => {}
+                }
+                Action::None
+            }
+            Mode::EditQuery => match key.code {
+                KeyCode::Esc => {
+                    self.mode = Mode::List;
+                    Action::None
+                }
+                KeyCode::Enter => {
+                    self.query = self.input.trim().to_string();
+                    self.mode = Mode::List;
+                    Action::Run
+                }
+                KeyCode::Backspace => {
+                    self.input.pop();
+                    Action::None
+                }
+                KeyCode::Char(c) => {
+                    self.input.push(c);
+                    Action::None
+                }
+                _ => Action::None,
+            },
+            Mode::Columns => {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Enter | KeyCode::Char('c') | KeyCode::Char('q') => {
+                        self.mode = Mode::List
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        if self.column_cursor + 1 < self.columns.len() {
+                            self.column_cursor += 1;
+                        }
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.column_cursor = self.column_cursor.saturating_sub(1);
+                    }
+                    KeyCode::Char(' ') => {
+                        if let Some(col) = self.columns.get(self.column_cursor)
+                            && !self.hidden.remove(&col.name)
+                        {
+                            self.hidden.insert(col.name.clone());
+                        }
+                    }
+                    _ => {}
+                }
+                Action::None
+            }
+        }
+    }
+
+    fn on_browse_key(&mut self, key: KeyEvent) -> Action {
+        let in_detail = self.mode == Mode::Detail;
+        match key.code {
+            KeyCode::Char('q') => return Action::Quit,
+            KeyCode::Esc if in_detail => self.mode = Mode::List,
+            KeyCode::Esc if !self.search.is_empty() => self.search.clear(),
+            KeyCode::Esc => return Action::Quit,
+            KeyCode::Enter => {
+                self.mode = if in_detail { Mode::List } else { Mode::Detail };
+                self.detail_scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(PAGE as isize),
+            KeyCode::PageUp => self.move_by(-(PAGE as isize)),
+            KeyCode::Home | KeyCode::Char('g') => self.move_by(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_by(isize::MAX),
+            KeyCode::Char('J') if in_detail => {
+                self.detail_scroll = self.detail_scroll.saturating_add(1)
+            }
+            KeyCode::Char('K') if in_detail => {
+                self.detail_scroll = self.detail_scroll.saturating_sub(1)
+            }
+            KeyCode::Char('/') => {
+                self.input = self.search.clone();
+                self.mode = Mode::Search;
+            }
+            KeyCode::Char('n') => {
+                self.jump_to_match(self.selected + 1, true);
+            }
+            KeyCode::Char('N') => {
+                let len = self.entries.len().max(1);
+                self.jump_to_match((self.selected + len - 1) % len, false);
+            }
+            KeyCode::Char('e') => {
+                self.input = self.query.clone();
+                self.mode = Mode::EditQuery;
+            }
+            KeyCode::Char('c') => self.mode = Mode::Columns,
+            KeyCode::Char('r') => return Action::Run,
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// Incremental search: every keystroke re-targets the first match at or
+    /// after the current row.
+    fn update_search(&mut self) {
+        self.search = self.input.to_lowercase();
+        self.jump_to_match(self.selected, true);
+    }
+}
+
+fn render(frame: &mut Frame, app: &mut App, target: &Target) {
+    let [header, main, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let query = if app.query.is_empty() {
+        "(all logs)".to_string()
+    } else {
+        app.query.clone()
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(
+                format!(" {} ", target.label),
+                Style::default().fg(Color::Black).bg(Color::Cyan),
+            ),
+            Span::raw(format!(" {}  ", target.window())),
+            Span::styled(query, Style::default().add_modifier(Modifier::BOLD)),
+        ])),
+        header,
+    );
+
+    if app.mode == Mode::Detail {
+        let [list, detail] =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(main);
+        render_table(frame, app, list);
+        render_detail(frame, app, detail);
+    } else {
+        render_table(frame, app, main);
+    }
+    if app.mode == Mode::Columns {
+        render_columns(frame, app, main);
+    }
+
+    let footer_line = match app.mode {
+        Mode::Search => Line::from(format!("/{}", app.input)),
+        Mode::EditQuery => Line::from(format!("query: {}", app.input)),
+        _ => match &app.status {
+            Status::Error(message) => Line::styled(
+                format!("error: {}", message),
+                Style::default().fg(Color::Red),
+            ),
+            Status::Info(message) => Line::from(vec![
+                Span::raw(message.clone()),
+                Span::styled(
+                    "   / search  e edit  c columns  r rerun  Enter detail  q quit",
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]),
+        },
+    };
+    frame.render_widget(Paragraph::new(footer_line), footer);
+    if matches!(app.mode, Mode::Search | Mode::EditQuery) {
+        let prefix = if app.mode == Mode::Search { 1 } else { 7 };
+        let x = footer.x + (prefix + app.input.chars().count()) as u16;
+        frame.set_cursor_position((x.min(footer.right().saturating_sub(1)), footer.y));
+    }
+}
+
+fn render_table(frame: &mut Frame, app: &mut App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL);
+    if app.entries.is_empty() {
+        frame.render_widget(Paragraph::new("No results").block(block), area);
+        return;
+    }
+
+    // Only the rows on screen are built, so large result sets stay cheap.
+    let height = area.height.saturating_sub(3).max(1) as usize;
+    if app.selected < app.offset {
+        app.offset = app.selected;
+    } else if app.selected >= app.offset + height {
+        app.offset = app.selected + 1 - height;
+    }
+    let window = app.offset..(app.offset + height).min(app.entries.len());
+
+    let columns = app.visible_columns();
+    let cell_text = |entry: &LogEntry, name: &str| {
+        entry
+            .get(name)
+            .map(super::table_cell)
+            .unwrap_or_default()
+            .replace(['\n', '\r'], " ")
+    };
+    let widths: Vec<Constraint> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, col)| {
+            if i + 1 == columns.len() {
+                return Constraint::Fill(1);
+            }
+            let widest = app.entries[window.clone()]
+                .iter()
+                .map(|entry| cell_text(entry, &col.name).chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(col.name.chars().count())
+                .min(MAX_COLUMN_WIDTH);
+            Constraint::Length(widest as u16)
+        })
+        .collect();
+
+    let match_style = Style::default().fg(Color::Yellow);
+    let rows: Vec<Row> = window
+        .clone()
+        .map(|index| {
+            let entry = &app.entries[index];
+            let row = Row::new(
+                columns
+                    .iter()
+                    .map(|col| Cell::from(cell_text(entry, &col.name)))
+                    .collect::<Vec<_>>(),
+            );
+            if app.is_match(index) {
+                row.style(match_style)
+            } else {
+                row
+            }
+        })
+        .collect();
+    let header = Row::new(columns.iter().map(|col| col.name.clone()))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let title = format!(" {}/{} ", app.selected + 1, app.entries.len());
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(block.title(title))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::default().with_selected(Some(app.selected - app.offset));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn render_detail(frame: &mut Frame, app: &App, area: Rect) {
+    let text = app
+        .entries
+        .get(app.selected)
+        .and_then(|entry| serde_json::to_string_pretty(entry).ok())
+        .unwrap_or_default();
+    frame.render_widget(
+        Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Entry (J/K scroll, Enter close) "),
+            )
+            .wrap(Wrap { trim: false })
+            .scroll((app.detail_scroll, 0)),
+        area,
+    );
+}
+
+fn render_columns(frame: &mut Frame, app: &App, area: Rect) {
+    let width = app
+        .columns
+        .iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(24) as u16
+        + 8;
+    let height = (app.columns.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width: width.min(area.width),
+        height,
+    };
+    let items: Vec<ListItem> = app
+        .columns
+        .iter()
+        .map(|col| {
+            let mark = if app.hidden.contains(&col.name) {
+                "[ ]"
+            } else {
+                "[x]"
+            };
+            ListItem::new(format!("{} {}", mark, col.name))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Columns (space toggles) "),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.column_cursor));
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_with(rows: &[serde_json::Value]) -> App {
+        let columns: Vec<Column> = serde_json::from_value(serde_json::json!([
+            {"name": "_timestamp", "type": "DateTime64(3)"},
+            {"name": "_internal", "type": "String"},
+            {"name": "msg", "type": "String"}
+        ]))
+        .unwrap();
+        let entries = rows
+            .iter()
+            .map(|row| serde_json::from_value(row.clone()).unwrap())
+            .collect();
+        let mut app = App::new(String::new());
+        app.set_results(columns, entries);
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Action {
+        app.on_key(KeyEvent::from(code))
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn incremental_search_jumps_and_cycles_matches() {
+        let mut app = app_with(&[
+            serde_json::json!({"msg": "started"}),
+            serde_json::json!({"msg": "Timeout talking to db"}),
+            serde_json::json!({"msg": "ok"}),
+            serde_json::json!({"msg": "timeout again"}),
+        ]);
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "time");
+        assert_eq!(app.selected, 1);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, Mode::List);
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.selected, 3);
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.selected, 1);
+        press(&mut app, KeyCode::Char('N'));
+        assert_eq!(app.selected, 3);
+    }
+
+    #[test]
+    fn columns_toggle_and_internal_ones_start_hidden() {
+        let mut app = app_with(&[serde_json::json!({"msg": "a"})]);
+        let names = |app: &App| {
+            app.visible_columns()
+                .iter()
+                .map(|c| c.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&app), ["_timestamp", "msg"]);
+        press(&mut app, KeyCode::Char('c'));
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(names(&app), ["msg"]);
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(names(&app), ["_internal", "msg"]);
+    }
+
+    #[test]
+    fn editing_the_query_requests_a_rerun() {
+        let mut app = app_with(&[]);
+        press(&mut app, KeyCode::Char('e'));
+        type_text(&mut app, "level=\"error\"");
+        assert_eq!(press(&mut app, KeyCode::Enter), Action::Run);
+        assert_eq!(app.query, "level=\"error\"");
+        press(&mut app, KeyCode::Char('e'));
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(press(&mut app, KeyCode::Esc), Action::None);
+        assert_eq!(app.query, "level=\"error\"");
+        assert_eq!(press(&mut app, KeyCode::Char('q')), Action::Quit);
+    }
+}
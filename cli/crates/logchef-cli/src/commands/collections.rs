@@ -1,18 +1,21 @@
 use anyhow::{Context, Result};
 use chrono::{Duration, TimeZone, Utc};
 use clap::Args;
+use futures::StreamExt;
 use inquire::Select;
 use logchef_core::Config;
 use logchef_core::api::{
-    Client, Collection, CollectionQueryContent, Column, QueryRequest, QueryStats, SqlQueryRequest,
+    Client, Collection, CollectionQueryContent, Column, QueryRequest, QueryResponse, QueryStats,
+    SqlQueryRequest,
 };
 use logchef_core::cache::{Cache, Identifier, parse_identifier};
 use logchef_core::highlight::{
-    FormatOptions, HighlightOptions, Highlighter, format_log_entry_with_options,
+    FormatOptions, HighlightOptions, Highlighter, LineTemplate, format_log_entry_with_options,
 };
-use logchef_core::timerange::{TimeInput, resolve_time_range};
+use logchef_core::timerange::{TimeInput, display_timezone, resolve_time_range, since_window};
 use serde::Serialize;
-use std::io::IsTerminal;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
 use crate::cli::GlobalArgs;
 use crate::session;
@@ -26,10 +29,22 @@ use crate::ui;
   # Run a collection by name over the last hour
   logchef collections 'Error Dashboard' --since 1h
 
+  # Run a collection by ID (see the ID column in the listing)
+  logchef collections 42
+
   # Run one with a variable override, as JSON
-  logchef collections 'By Service' --var service=api --output json")]
+  logchef collections 'By Service' --var service=api --output json
+
+  # See the SQL a LogchefQL collection compiles to
+  logchef collections 'Error Dashboard' --show-sql
+
+  # Run the same collection once per host
+  logchef collections 'By Host' --iterate-var host=web-1,web-2,web-3
+
+  # Nightly reports: run every 'daily-*' collection, one CSV file each
+  logchef collections --all --match 'daily-*' --since 24h --output csv --out-dir ./reports")]
 pub struct CollectionsArgs {
-    /// Collection name to run (optional - lists collections if not provided)
+    /// Collection ID or name to run (optional - lists collections if not provided)
     name: Option<String>,
 
     /// Team ID or name
@@ -40,8 +55,9 @@ pub struct CollectionsArgs {
     #[arg(long, short = 'S')]
     source: Option<String>,
 
-    /// Override time range with relative time (e.g., 15m, 1h, 24h),
-    /// evaluated against now in the effective timezone: `defaults.timezone`
+    /// Override time range with relative time (e.g., 15m, 1h, 24h) or a
+    /// named window (today, yesterday, this-week, ...), evaluated against
+    /// now in the effective timezone: `defaults.timezone`
     /// if configured, otherwise the system's local timezone (see `logchef
     /// config show`).
     #[arg(long, short = 's')]
@@ -51,18 +67,38 @@ pub struct CollectionsArgs {
     #[arg(long, short = 'l')]
     limit: Option<u32>,
 
+    /// Run even if the time range exceeds the configured `max_time_range`.
+    #[arg(long)]
+    force: bool,
+
     /// Output format
-    #[arg(long, default_value = "text")]
+    #[arg(long, default_value = "text", env = "LOGCHEF_OUTPUT")]
     output: OutputFormat,
 
     /// Disable syntax highlighting
     #[arg(long)]
     no_highlight: bool,
 
+    /// Print the executed backend query on stderr before the results: the
+    /// server-generated query for LogchefQL collections, the final query text
+    /// (variables substituted) for native ones
+    #[arg(long)]
+    show_sql: bool,
+
     /// Hide timestamp column in text output
     #[arg(long)]
     no_timestamp: bool,
 
+    /// Pad timestamp and level into fixed-width columns in text output
+    #[arg(long)]
+    aligned: bool,
+
+    /// Render each row with a template instead of the default text layout,
+    /// e.g. `'{timestamp} [{level}] {msg}'`. `{a.b}` reads nested fields,
+    /// `{a|b|"-"}` falls back to another field or a literal.
+    #[arg(long, value_name = "TEMPLATE", conflicts_with_all = ["output"])]
+    format: Option<String>,
+
     /// Custom highlight rules (format: COLOR:word1,word2)
     #[arg(long = "highlight", value_name = "COLOR:WORDS")]
     highlights: Vec<String>,
@@ -74,6 +110,27 @@ pub struct CollectionsArgs {
     /// Variable overrides (format: name=value)
     #[arg(long = "var", short = 'V', value_name = "NAME=VALUE")]
     variables: Vec<String>,
+
+    /// Run the collection once per value, substituting {{NAME}} each time
+    /// (format: name=value1,value2,...)
+    #[arg(long, value_name = "NAME=V1,V2,...")]
+    iterate_var: Option<String>,
+
+    /// Run every collection for the source concurrently and print a summary
+    /// (rows, duration, error) per collection instead of their rows
+    #[arg(long, conflicts_with_all = ["name", "iterate_var", "format"])]
+    all: bool,
+
+    /// With --all, only run collections whose name matches this glob (`*`
+    /// and `?`, case-insensitive), e.g. 'daily-*'
+    #[arg(long = "match", value_name = "GLOB", requires = "all")]
+    matching: Option<String>,
+
+    /// With --all, write each collection's rows to its own file in DIR,
+    /// named after the collection, in the --output format (json, jsonl, csv
+    /// or tsv)
+    #[arg(long, value_name = "DIR", requires = "all")]
+    out_dir: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, clap::ValueEnum)]
@@ -82,6 +139,8 @@ enum OutputFormat {
     Json,
     Jsonl,
     JsonFlat,
+    Csv,
+    Tsv,
     Table,
     List,
     Msg,
@@ -101,6 +160,18 @@ struct JsonOutput<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     generated_query_language: Option<&'a str>,
     columns: &'a [Column],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iteration: Option<IterationLabel<'a>>,
+}
+
+/// Identifies which `--iterate-var` value produced a result set.
+#[derive(Clone, Copy, Serialize)]
+struct IterationLabel<'a> {
+    variable: &'a str,
+    value: &'a str,
+    /// Whether this is the first value, for the single CSV/TSV header row.
+    #[serde(skip)]
+    first: bool,
 }
 
 pub async fn run(args: CollectionsArgs, global: GlobalArgs) -> Result<()> {
@@ -171,7 +242,9 @@ pub async fn run(args: CollectionsArgs, global: GlobalArgs) -> Result<()> {
         match parse_identifier(&source_input) {
             Identifier::Id(id) => id,
             Identifier::Name(name) => {
-                if let Some(id) = cache.get_source_id(team_id, &name) {
+                if let Some(id) =
+                    super::cached_source_id(client, &mut cache, team_id, &name).await?
+                {
                     id
                 } else {
                     let sources = client
@@ -186,6 +259,7 @@ pub async fn run(args: CollectionsArgs, global: GlobalArgs) -> Result<()> {
                             cache_entries.push((target_ref, s.id));
                         }
                     }
+                    cache.observe_sources(team_id, &sources);
                     cache.set_sources(team_id, &cache_entries);
 
                     sources
@@ -211,6 +285,19 @@ pub async fn run(args: CollectionsArgs, global: GlobalArgs) -> Result<()> {
         .await
         .context("Failed to list collections")?;
 
+    if args.all {
+        return run_all(
+            client,
+            team_id,
+            source_id,
+            &collections,
+            &args,
+            ctx,
+            &global,
+        )
+        .await;
+    }
+
     // If no name provided (or list output), show the list
     if arg_name.is_none() && !is_interactive {
         return list_collections(&collections, &args);
@@ -221,14 +308,41 @@ pub async fn run(args: CollectionsArgs, global: GlobalArgs) -> Result<()> {
         prompt_collection_interactive(&collections)?
     } else {
         let name = arg_name.as_ref().unwrap();
-        collections
-            .iter()
-            .find(|c| c.name.eq_ignore_ascii_case(name))
-            .ok_or_else(|| anyhow::anyhow!("Collection '{}' not found", name))?
-            .clone()
+        find_collection(&collections, name)?.clone()
+    };
+
+    // One pager for every iteration's output
+    let _pager = match args.output {
+        OutputFormat::Text | OutputFormat::List | OutputFormat::Table | OutputFormat::Msg => {
+            crate::pager::start(&config, &global)
+        }
+        _ => None,
     };
 
-    // Run the collection
+    // Run the collection, once per value when iterating a variable
+    if let Some(spec) = &args.iterate_var {
+        let (variable, values) = parse_iterate_var(spec)?;
+        for (i, value) in values.iter().enumerate() {
+            run_collection(
+                &config,
+                client,
+                team_id,
+                source_id,
+                &collection,
+                &args,
+                ctx,
+                &global,
+                Some(IterationLabel {
+                    variable: &variable,
+                    value,
+                    first: i == 0,
+                }),
+            )
+            .await?;
+        }
+        return Ok(());
+    }
+
     run_collection(
         &config,
         client,
@@ -237,11 +351,32 @@ pub async fn run(args: CollectionsArgs, global: GlobalArgs) -> Result<()> {
         &collection,
         &args,
         ctx,
-        global.quiet,
+        &global,
+        None,
     )
     .await
 }
 
+/// Finds a collection by numeric ID or case-insensitive name, matching how
+/// team and source references resolve. A name made only of digits is tried
+/// as an ID first.
+pub(crate) fn find_collection<'a>(
+    collections: &'a [Collection],
+    input: &str,
+) -> Result<&'a Collection> {
+    match parse_identifier(input) {
+        Identifier::Id(id) => collections
+            .iter()
+            .find(|c| c.id == id)
+            .or_else(|| collections.iter().find(|c| c.name == input))
+            .ok_or_else(|| anyhow::anyhow!("Collection with ID {} not found", id)),
+        Identifier::Name(name) => collections
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(&name))
+            .ok_or_else(|| anyhow::anyhow!("Collection '{}' not found", name)),
+    }
+}
+
 fn list_collections(collections: &[Collection], args: &CollectionsArgs) -> Result<()> {
     if collections.is_empty() {
         println!("No collections found for this source.");
@@ -262,9 +397,14 @@ fn list_collections(collections: &[Collection], args: &CollectionsArgs) -> Resul
                 "--output msg is for running collections, not listing. Use --output text|json|jsonl|table."
             );
         }
-        OutputFormat::JsonFlat => {
+        OutputFormat::JsonFlat | OutputFormat::Csv | OutputFormat::Tsv => {
             anyhow::bail!(
-                "--output json-flat is for running collections, not listing. Use --output json or jsonl."
+                "--output {} is for running collections, not listing. Use --output json or jsonl.",
+                match args.output {
+                    OutputFormat::Csv => "csv",
+                    OutputFormat::Tsv => "tsv",
+                    _ => "json-flat",
+                }
             );
         }
         OutputFormat::List | OutputFormat::Text | OutputFormat::Table => {
@@ -317,107 +457,72 @@ async fn run_collection(
     collection: &Collection,
     args: &CollectionsArgs,
     ctx: &logchef_core::config::Context,
-    quiet: bool,
+    global: &GlobalArgs,
+    iteration: Option<IterationLabel<'_>>,
 ) -> Result<()> {
-    // Parse the query content
-    let content: CollectionQueryContent =
-        serde_json::from_str(&collection.query_content).context("Failed to parse query content")?;
-
-    let query_str = content.content.unwrap_or_default();
-
-    // Apply variable overrides
-    let mut final_query = query_str.clone();
-    let var_overrides = parse_variable_overrides(&args.variables);
-
-    // Replace variables from collection
-    if let Some(vars) = &content.variables {
-        for var in vars {
-            let value = var_overrides
-                .get(&var.name)
-                .cloned()
-                .or_else(|| var.value.as_ref().map(json_value_to_string))
-                .unwrap_or_default();
-            // Replace {{name}} with value
-            final_query = final_query.replace(&format!("{{{{{}}}}}", var.name), &value);
-        }
+    let quiet = global.quiet;
+    let template = args
+        .format
+        .as_deref()
+        .map(LineTemplate::parse)
+        .transpose()
+        .context("Invalid --format template")?;
+
+    match iteration {
+        Some(label) => eprintln!(
+            "Running collection: {} ({}) [{}={}]",
+            collection.name,
+            collection_query_label(collection),
+            label.variable,
+            label.value
+        ),
+        None => eprintln!(
+            "Running collection: {} ({})",
+            collection.name,
+            collection_query_label(collection)
+        ),
     }
 
-    // Determine time range: every branch below resolves to a concrete UTC
-    // instant range, which resolve_time_range then formats as wall-clock in
-    // the effective timezone (never a mix of the two, which was the bug).
-    let (start, end) = if let Some(since) = &args.since {
-        // Use override
-        let end = Utc::now();
-        let start = end - parse_duration(since)?;
-        (start, end)
-    } else if let Some(tr) = &content.time_range {
-        if let Some(rel) = &tr.relative {
-            let end = Utc::now();
-            let start = end - parse_duration(rel)?;
-            (start, end)
-        } else if let Some(abs) = &tr.absolute {
-            let start = Utc
-                .timestamp_millis_opt(abs.start)
-                .single()
-                .ok_or_else(|| anyhow::anyhow!("Invalid start timestamp"))?;
-            let end = Utc
-                .timestamp_millis_opt(abs.end)
-                .single()
-                .ok_or_else(|| anyhow::anyhow!("Invalid end timestamp"))?;
-            (start, end)
-        } else {
-            // Default to last 15 minutes
-            let end = Utc::now();
-            (end - Duration::minutes(15), end)
-        }
-    } else {
-        // Default to last 15 minutes
-        let end = Utc::now();
-        (end - Duration::minutes(15), end)
-    };
-    let time_range = resolve_time_range(
-        TimeInput::Instant { start, end },
-        ctx.defaults.timezone.as_deref(),
-    );
-
-    let limit = args.limit.or(content.limit).unwrap_or(100);
-
-    eprintln!(
-        "Running collection: {} ({})",
-        collection.name,
-        collection_query_label(collection)
-    );
-
-    let response = if collection.query_language == "logchefql" {
-        let request = QueryRequest {
-            query: final_query,
-            start_time: time_range.start,
-            end_time: time_range.end,
-            timezone: Some(time_range.timezone),
-            limit: Some(limit),
-            query_timeout: None,
-        };
-        client
-            .query_logchefql(team_id, source_id, &request)
-            .await
-            .context("Query failed")?
-    } else {
-        let request = SqlQueryRequest {
-            query_text: final_query,
-            limit: Some(limit),
-            timezone: Some(time_range.timezone),
-            start_time: Some(time_range.start),
-            end_time: Some(time_range.end),
-            query_timeout: Some(30),
+    let (response, native_query) =
+        execute_collection(client, team_id, source_id, collection, args, ctx, iteration).await?;
+
+    if args.show_sql {
+        // Native collections run verbatim, so the substituted text is exactly
+        // what executed even when the server doesn't echo it back.
+        let shown = match &native_query {
+            Some(query) => Some((
+                "Executed query",
+                query.as_str(),
+                Some(collection_query_label(collection)),
+            )),
+            None => response.generated_query().map(|query| {
+                let label = match response.generated_query_language() {
+                    Some("logsql") => "Generated LogsQL",
+                    Some("clickhouse-sql") => "Generated SQL",
+                    _ => "Generated query",
+                };
+                (label, query, response.generated_query_language())
+            }),
         };
-        client
-            .query_sql(team_id, source_id, &request)
-            .await
-            .context("Native query failed")?
-    };
+        if let Some((label, query, language)) = shown {
+            let rendered = ui::highlight_query(query, language, ui::stderr_human(quiet));
+            eprintln!("{}: {}\n", label, rendered);
+        }
+    }
 
     let entries = response.entries();
 
+    // Label each iteration's block in the human-readable formats; JSON carries
+    // the label in the document instead, and line formats stay untouched.
+    if let Some(label) = iteration
+        && matches!(
+            args.output,
+            OutputFormat::Text | OutputFormat::List | OutputFormat::Table | OutputFormat::Msg
+        )
+    {
+        println!("==> {}={} <==", label.variable, label.value);
+    }
+
     match args.output {
         OutputFormat::Json => {
             let output = JsonOutput {
@@ -429,8 +534,15 @@ async fn run_collection(
                 generated_query: response.generated_query(),
                 generated_query_language: response.generated_query_language(),
                 columns: &response.columns,
+                iteration,
             };
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            println!(
+                "{}",
+                ui::highlight_json(
+                    &serde_json::to_string_pretty(&output)?,
+                    !args.no_highlight && ui::human(quiet),
+                )
+            );
         }
         OutputFormat::Jsonl => {
             for entry in entries {
@@ -446,6 +558,21 @@ async fn run_collection(
         OutputFormat::JsonFlat => {
             print_json_flat(entries)?;
         }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            // Iterations share one table: the variable becomes a leading
+            // column and only the first block writes the header.
+            let lead: Vec<(&str, &str)> = iteration
+                .iter()
+                .map(|label| (label.variable, label.value))
+                .collect();
+            let header = iteration.is_none_or(|label| label.first);
+            let out = &mut std::io::stdout().lock();
+            if matches!(args.output, OutputFormat::Csv) {
+                super::write_csv(out, entries, &response.columns, header, &lead)?;
+            } else {
+                super::write_tsv(out, entries, &response.columns, header, &lead)?;
+            }
+        }
         OutputFormat::Table => {
             print_table(entries, &response.columns);
             ui::print_stats(
@@ -475,10 +602,19 @@ async fn run_collection(
 
             let fmt_options = FormatOptions {
                 show_timestamp: !args.no_timestamp,
+                aligned: args.aligned,
+                timezone: Some(display_timezone(
+                    ctx.defaults.timezone.as_deref(),
+                    global.utc,
+                )),
+                ..Default::default()
             };
 
             for entry in entries {
-                let line = format_log_entry_with_options(entry, &response.columns, &fmt_options);
+                let line = match &template {
+                    Some(template) => template.render(entry),
+                    None => format_log_entry_with_options(entry, &response.columns, &fmt_options),
+                };
                 if let Some(ref h) = highlighter {
                     println!("{}", h.highlight(&line));
                 } else {
@@ -497,6 +633,345 @@ async fn run_collection(
     Ok(())
 }
 
+/// One row of the `--all` summary.
+#[derive(Serialize)]
+struct BatchResult {
+    id: i64,
+    name: String,
+    rows: Option<usize>,
+    duration_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// `--all`: runs the matching collections a few at a time, writes each
+/// result to `--out-dir` if given, and prints a summary. Fails (after the
+/// summary) if any collection did.
+async fn run_all(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    collections: &[Collection],
+    args: &CollectionsArgs,
+    ctx: &logchef_core::config::Context,
+    global: &GlobalArgs,
+) -> Result<()> {
+    let selected: Vec<&Collection> = collections
+        .iter()
+        .filter(|c| {
+            args.matching
+                .as_deref()
+                .is_none_or(|pattern| glob_match(pattern, &c.name))
+        })
+        .collect();
+    if selected.is_empty() {
+        match &args.matching {
+            Some(pattern) => anyhow::bail!("No collections match '{}'", pattern),
+            None => anyhow::bail!("No collections found for this source."),
+        }
+    }
+    let extension = match (&args.out_dir, &args.output) {
+        (None, _) => None,
+        (Some(_), OutputFormat::Json) => Some("json"),
+        (Some(_), OutputFormat::Jsonl) => Some("jsonl"),
+        (Some(_), OutputFormat::Csv) => Some("csv"),
+        (Some(_), OutputFormat::Tsv) => Some("tsv"),
+        (Some(_), _) => {
+            anyhow::bail!("--out-dir writes json, jsonl, csv or tsv; pick one with --output")
+        }
+    };
+    if let Some(dir) = &args.out_dir {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+
+    let spinner = ui::Spinner::start(global.quiet, "running collections");
+    let runs: Vec<_> = futures::stream::iter(selected.iter().map(|collection| async move {
+        let started = std::time::Instant::now();
+        let result =
+            execute_collection(client, team_id, source_id, collection, args, ctx, None).await;
+        (collection, result, started.elapsed().as_millis())
+    }))
+    .buffered(ctx.defaults.concurrency())
+    .collect()
+    .await;
+    spinner.finish();
+
+    let mut used_names = Vec::new();
+    let mut results = Vec::with_capacity(runs.len());
+    for (collection, result, duration_ms) in runs {
+        let mut summary = BatchResult {
+            id: collection.id,
+            name: collection.name.clone(),
+            rows: None,
+            duration_ms,
+            file: None,
+            error: None,
+        };
+        match result {
+            Ok((response, _)) => {
+                summary.rows = Some(response.entries().len());
+                if let (Some(dir), Some(extension)) = (&args.out_dir, extension) {
+                    let mut stem = file_stem(&collection.name);
+                    if used_names.contains(&stem) {
+                        stem = format!("{}-{}", stem, collection.id);
+                    }
+                    let path = dir.join(format!("{}.{}", stem, extension));
+                    used_names.push(stem);
+                    match write_result(&path, &response, &args.output) {
+                        Ok(()) => summary.file = Some(path.display().to_string()),
+                        Err(err) => summary.error = Some(format!("{:#}", err)),
+                    }
+                }
+            }
+            Err(err) => summary.error = Some(format!("{:#}", err)),
+        }
+        results.push(summary);
+    }
+
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    match (&args.out_dir, &args.output) {
+        (None, OutputFormat::Json) => println!("{}", serde_json::to_string_pretty(&results)?),
+        (None, OutputFormat::Jsonl) => {
+            for result in &results {
+                println!("{}", serde_json::to_string(result)?);
+            }
+        }
+        _ => print_batch_table(&results),
+    }
+    if failed > 0 {
+        anyhow::bail!("{} of {} collections failed", failed, results.len());
+    }
+    Ok(())
+}
+
+fn print_batch_table(results: &[BatchResult]) {
+    println!(
+        "{:<4} {:<30} {:>8} {:>10}  RESULT",
+        "ID", "NAME", "ROWS", "DURATION"
+    );
+    println!("{}", "-".repeat(70));
+    for result in results {
+        let rows = result
+            .rows
+            .map(|rows| ui::thousands(rows as i64))
+            .unwrap_or_else(|| "-".to_string());
+        let outcome = match (&result.error, &result.file) {
+            (Some(err), _) => format!("error: {}", err),
+            (None, Some(file)) => file.clone(),
+            (None, None) => "ok".to_string(),
+        };
+        println!(
+            "{:<4} {:<30} {:>8} {:>8}ms  {}",
+            result.id,
+            truncate_str(&result.name, 28),
+            rows,
+            ui::thousands(result.duration_ms as i64),
+            outcome
+        );
+    }
+}
+
+/// Writes one collection's rows to `path` in `format` (json, jsonl, csv or
+/// tsv).
+fn write_result(path: &Path, response: &QueryResponse, format: &OutputFormat) -> Result<()> {
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut out = std::io::BufWriter::new(file);
+    let entries = response.entries();
+    match format {
+        OutputFormat::Json => {
+            let output = JsonOutput {
+                logs: entries,
+                count: entries.len(),
+                stats: &response.stats,
+                query_id: response.query_id.as_deref(),
+                generated_sql: response.generated_sql.as_deref(),
+                generated_query: response.generated_query(),
+                generated_query_language: response.generated_query_language(),
+                columns: &response.columns,
+                iteration: None,
+            };
+            serde_json::to_writer_pretty(&mut out, &output)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Jsonl => {
+            for entry in entries {
+                serde_json::to_writer(&mut out, entry)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => super::write_csv(&mut out, entries, &response.columns, true, &[])?,
+        _ => super::write_tsv(&mut out, entries, &response.columns, true, &[])?,
+    }
+    out.flush()
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// A file name for a collection: lowercase, with runs of anything but
+/// letters and digits turned into `-`.
+fn file_stem(name: &str) -> String {
+    let mut stem = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            stem.extend(c.to_lowercase());
+        } else if !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_matches('-');
+    if stem.is_empty() {
+        "collection".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// Case-insensitive glob match supporting `*` (any run) and `?` (one char).
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Substitutes variables, resolves the time range and runs `collection`,
+/// returning the response and, for native collections, the query text that
+/// was executed.
+async fn execute_collection(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    collection: &Collection,
+    args: &CollectionsArgs,
+    ctx: &logchef_core::config::Context,
+    iteration: Option<IterationLabel<'_>>,
+) -> Result<(QueryResponse, Option<String>)> {
+    // Parse the query content
+    let content = CollectionQueryContent::parse(&collection.query_content)
+        .context("Failed to parse query content")?;
+
+    let query_str = content.content.unwrap_or_default();
+
+    // Apply variable overrides
+    let mut final_query = query_str.clone();
+    let mut var_overrides = parse_variable_overrides(&args.variables);
+    if let Some(label) = iteration {
+        var_overrides.insert(label.variable.to_string(), label.value.to_string());
+    }
+
+    // Replace variables from collection
+    if let Some(vars) = &content.variables {
+        for var in vars {
+            let value = var_overrides
+                .get(&var.name)
+                .cloned()
+                .or_else(|| var.value.as_ref().map(json_value_to_string))
+                .unwrap_or_default();
+            // Replace {{name}} with value
+            final_query = final_query.replace(&format!("{{{{{}}}}}", var.name), &value);
+        }
+    }
+    // The iterated variable applies even if the collection doesn't declare it.
+    if let Some(label) = iteration {
+        final_query = final_query.replace(&format!("{{{{{}}}}}", label.variable), label.value);
+    }
+
+    // Determine time range: every branch below resolves to a concrete UTC
+    // instant range, which resolve_time_range then formats as wall-clock in
+    // the effective timezone (never a mix of the two, which was the bug).
+    let tz = ctx.defaults.timezone.as_deref();
+    let (start, end) = if let Some(since) = &args.since {
+        // Use override
+        since_window(since, tz)?
+    } else if let Some(tr) = &content.time_range {
+        if let Some(rel) = &tr.relative {
+            since_window(rel, tz)?
+        } else if let Some(abs) = &tr.absolute {
+            let start = Utc
+                .timestamp_millis_opt(abs.start)
+                .single()
+                .ok_or_else(|| anyhow::anyhow!("Invalid start timestamp"))?;
+            let end = Utc
+                .timestamp_millis_opt(abs.end)
+                .single()
+                .ok_or_else(|| anyhow::anyhow!("Invalid end timestamp"))?;
+            (start, end)
+        } else {
+            // Default to last 15 minutes
+            let end = Utc::now();
+            (end - Duration::minutes(15), end)
+        }
+    } else {
+        // Default to last 15 minutes
+        let end = Utc::now();
+        (end - Duration::minutes(15), end)
+    };
+    super::check_time_range_cap(
+        ctx,
+        source_id,
+        collection.source_name.as_deref(),
+        end - start,
+        args.force,
+    )?;
+    let time_range = resolve_time_range(TimeInput::Instant { start, end }, tz);
+
+    let limit = args.limit.or(content.limit).unwrap_or(100);
+
+    let native_query = (collection.query_language != "logchefql").then(|| final_query.clone());
+    let response = if collection.query_language == "logchefql" {
+        let request = QueryRequest {
+            query: final_query,
+            start_time: time_range.start,
+            end_time: time_range.end,
+            timezone: Some(time_range.timezone),
+            limit: Some(limit),
+            query_timeout: None,
+        };
+        client
+            .query_logchefql(team_id, source_id, &request)
+            .await
+            .context("Query failed")?
+    } else {
+        let request = SqlQueryRequest {
+            query_text: final_query,
+            limit: Some(limit),
+            timezone: Some(time_range.timezone),
+            start_time: Some(time_range.start),
+            end_time: Some(time_range.end),
+            query_timeout: Some(30),
+        };
+        client
+            .query_sql(team_id, source_id, &request)
+            .await
+            .context("Native query failed")?
+    };
+    Ok((response, native_query))
+}
+
 fn print_json_flat(entries: &[logchef_core::api::LogEntry]) -> Result<()> {
     for entry in entries {
         println!("{}", serde_json::to_string(&flatten_msg(entry))?);
@@ -570,33 +1045,28 @@ fn parse_variable_overrides(vars: &[String]) -> std::collections::HashMap<String
         .collect()
 }
 
-fn parse_duration(s: &str) -> Result<Duration> {
-    let s = s.trim();
-    if s.is_empty() {
-        return Ok(Duration::minutes(15));
-    }
-
-    let (num, unit) = if s.ends_with('m') {
-        (s.trim_end_matches('m'), "m")
-    } else if s.ends_with('h') {
-        (s.trim_end_matches('h'), "h")
-    } else if s.ends_with('d') {
-        (s.trim_end_matches('d'), "d")
-    } else if s.ends_with('w') {
-        (s.trim_end_matches('w'), "w")
-    } else {
-        (s, "m")
-    };
-
-    let num: i64 = num.parse().context("Invalid duration number")?;
-
-    match unit {
-        "m" => Ok(Duration::minutes(num)),
-        "h" => Ok(Duration::hours(num)),
-        "d" => Ok(Duration::days(num)),
-        "w" => Ok(Duration::weeks(num)),
-        _ => Ok(Duration::minutes(num)),
+/// Parses `--iterate-var name=v1,v2,...` into the variable name and its
+/// non-empty values.
+fn parse_iterate_var(spec: &str) -> Result<(String, Vec<String>)> {
+    let (name, values) = spec.split_once('=').ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid --iterate-var '{}'. Use name=value1,value2,...",
+            spec
+        )
+    })?;
+    let name = name.trim();
+    let values: Vec<String> = values
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect();
+    if name.is_empty() || values.is_empty() {
+        anyhow::bail!(
+            "Invalid --iterate-var '{}'. Use name=value1,value2,...",
+            spec
+        );
     }
+    Ok((name.to_string(), values))
 }
 
 fn parse_highlight_args(args: &[String]) -> Vec<(String, Vec<String>)> {
@@ -637,10 +1107,7 @@ fn print_table(entries: &[logchef_core::api::LogEntry], columns: &[logchef_core:
             .map(|c| {
                 entry
                     .get(&c.name)
-                    .map(|v| match v {
-                        serde_json::Value::String(s) => s.clone(),
-                        _ => v.to_string(),
-                    })
+                    .map(super::table_cell)
                     .unwrap_or_default()
             })
             .collect();
@@ -705,6 +1172,7 @@ async fn prompt_source_interactive(
             cache_entries.push((target_ref, s.id));
         }
     }
+    cache.observe_sources(team_id, &sources);
     cache.set_sources(team_id, &cache_entries);
 
     Ok(source.id)
@@ -730,3 +1198,18 @@ fn prompt_collection_interactive(collections: &[Collection]) -> Result<Collectio
 
     Ok(collection.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_names_case_insensitively() {
+        assert!(glob_match("daily-*", "Daily-Errors"));
+        assert!(glob_match("*5?x*", "api 5xx rate"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("daily-*", "weekly-errors"));
+        assert!(!glob_match("a?c", "abbc"));
+        assert_eq!(file_stem("Errors / API (5xx)"), "errors-api-5xx");
+    }
+}
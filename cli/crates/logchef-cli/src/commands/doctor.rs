@@ -187,7 +187,11 @@ pub async fn run(args: DoctorArgs, global: GlobalArgs) -> Result<()> {
     };
     checks.push(Check::ok("Server URL", server_url.clone()));
 
-    let client = match Client::new(&server_url, 15) {
+    let (tls, proxy_url) = resolved
+        .as_ref()
+        .map(|(_, c)| (c.tls.clone(), c.proxy_url.clone()))
+        .unwrap_or_default();
+    let client = match Client::with_network(&server_url, 15, &tls, proxy_url.as_deref()) {
         Ok(client) => match &token {
             Some(t) => client.with_token(t.clone()),
             None => client,
@@ -196,7 +200,7 @@ pub async fn run(args: DoctorArgs, global: GlobalArgs) -> Result<()> {
             checks.push(Check::fail(
                 "Server reachable",
                 format!("could not build HTTP client: {}", err),
-                "check the server URL is a valid http(s) URL",
+                "check the server URL is a valid http(s) URL, and the tls.* and proxy_url settings",
             ));
             return finish(checks, args.json);
         }
@@ -0,0 +1,831 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use futures::StreamExt;
+use logchef_core::Config;
+use logchef_core::api::{Column, ExportSqlRequest, LogEntry, TranslateRequest};
+use logchef_core::cache::Cache;
+use logchef_core::timerange::{TimeInput, from_to_window, resolve_time_range, since_window};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cli::GlobalArgs;
+use crate::commands::{resolve_source, resolve_team};
+use crate::session;
+use crate::ui;
+
+/// Seconds added on top of the query timeout for the HTTP client, which
+/// bounds the whole download.
+const HTTP_TIMEOUT_HEADROOM_SECS: u64 = 60;
+
+/// Rows per transaction in `--output sqlite`.
+const SQLITE_BATCH_ROWS: usize = 10_000;
+
+#[derive(Args)]
+#[command(after_help = "EXAMPLES:
+  # Last day of errors into 500 MB NDJSON files: errors-0001.jsonl, errors-0002.jsonl, …
+  logchef export 'level=\"error\"' --since 24h --out errors --chunk-size 500M
+
+  # A fixed window as CSV, 1M rows per file
+  logchef export --from '2026-07-01 00:00:00' --to '2026-07-02 00:00:00' \\
+    --output csv --chunk-rows 1000000 --out day
+
+  # Pick up an interrupted export after the last finished file
+  logchef export --out errors --resume
+
+  # Into a SQLite database (errors.db, table `logs`) for offline analysis
+  logchef export 'level=\"error\"' --since 7d --output sqlite --out errors
+  sqlite3 errors.db 'SELECT service, count(*) FROM logs GROUP BY 1'")]
+pub struct ExportArgs {
+    /// LogchefQL query to export (e.g. `level="error"`); omit for every row
+    /// in the window
+    query: Option<String>,
+
+    /// Team ID or name
+    #[arg(long, short = 't')]
+    team: Option<String>,
+
+    /// Source ID or name
+    #[arg(long, short = 'S')]
+    source: Option<String>,
+
+    /// Relative lookback window (e.g. 15m, 1h, 24h) or a named window such
+    /// as today, yesterday or last-week
+    #[arg(long, short = 's')]
+    since: Option<String>,
+
+    /// Absolute start time (RFC3339, YYYY-MM-DD HH:MM:SS in the effective
+    /// timezone, epoch ms, or "2 hours ago"). Requires --to.
+    #[arg(long)]
+    from: Option<String>,
+
+    /// Absolute end time, in the same formats as --from. Requires --from.
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Path prefix for the output files: `<out>-0001.jsonl`, … (or
+    /// `<out>.db`) plus the `<out>.progress.json` manifest
+    #[arg(long, short = 'o', default_value = "export")]
+    out: PathBuf,
+
+    /// File format. `sqlite` writes one database with a table shaped like
+    /// the source schema
+    #[arg(long, default_value = "jsonl")]
+    output: OutputFormat,
+
+    /// Start a new file after this many rows
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    chunk_rows: Option<u64>,
+
+    /// Start a new file before it grows past this size (e.g. 500M, 2G)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    chunk_size: Option<u64>,
+
+    /// Table to create in `--output sqlite` [default: logs]
+    #[arg(long, value_name = "NAME")]
+    table: Option<String>,
+
+    /// Stop after this many rows in total (the server's export cap still applies)
+    #[arg(long)]
+    limit: Option<u32>,
+
+    /// Query timeout in seconds; bounds the whole download
+    #[arg(long, default_value = "600")]
+    timeout: u32,
+
+    /// Continue the export recorded in `<out>.progress.json`, re-running its
+    /// query and skipping the rows already written to finished files
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "query", "team", "source", "since", "from", "to", "output", "chunk_rows",
+            "chunk_size", "table", "limit",
+        ]
+    )]
+    resume: bool,
+
+    /// Run even if the window exceeds the configured `max_time_range`
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+enum OutputFormat {
+    Jsonl,
+    Csv,
+    Sqlite,
+}
+
+impl OutputFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Sqlite => "db",
+        }
+    }
+}
+
+/// `<out>.progress.json`: everything needed to re-run the export, and the
+/// files finished so far. Written after every finished file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Progress {
+    team_id: i64,
+    source_id: i64,
+    /// The translated query with the time window already resolved, so a
+    /// resumed run reads the same rows however much later it starts.
+    sql: String,
+    format: OutputFormat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chunk_rows: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chunk_size: Option<u64>,
+    /// CSV header, fixed by the first row so every file shares it; for
+    /// SQLite, the table's columns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    columns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    table: Option<String>,
+    chunks: Vec<Chunk>,
+    complete: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Chunk {
+    file: String,
+    rows: u64,
+    bytes: u64,
+}
+
+impl Progress {
+    fn rows(&self) -> u64 {
+        self.chunks.iter().map(|chunk| chunk.rows).sum()
+    }
+
+    fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("No export to resume at {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+pub async fn run(args: ExportArgs, global: GlobalArgs) -> Result<()> {
+    if args.output == OutputFormat::Sqlite {
+        if args.chunk_rows.is_some() || args.chunk_size.is_some() {
+            anyhow::bail!("--chunk-rows and --chunk-size don't apply to --output sqlite");
+        }
+    } else if args.table.is_some() {
+        anyhow::bail!("--table requires --output sqlite");
+    }
+    let config = Config::load().context("Failed to load config")?;
+    let s = session::authed_with_timeout(&config, &global, |ctx| {
+        ctx.timeout_secs
+            .max(u64::from(args.timeout) + HTTP_TIMEOUT_HEADROOM_SECS)
+    })?;
+    let (client, ctx) = (&s.client, &s.ctx);
+    let manifest = manifest_path(&args.out);
+
+    let mut progress = if args.resume {
+        let progress = Progress::load(&manifest)?;
+        if progress.complete {
+            eprintln!(
+                "Export already complete: {} rows in {} files",
+                ui::thousands(progress.rows() as i64),
+                progress.chunks.len()
+            );
+            return Ok(());
+        }
+        progress
+    } else {
+        if manifest.exists() {
+            anyhow::bail!(
+                "{} already exists; pass --resume to continue that export, or pick another --out",
+                manifest.display()
+            );
+        }
+        let first = match args.output {
+            OutputFormat::Sqlite => database_path(&args.out),
+            _ => chunk_path(&args.out, 1, args.output.extension()),
+        };
+        if first.exists() {
+            anyhow::bail!(
+                "{} already exists; pick another --out or remove it",
+                first.display()
+            );
+        }
+
+        let mut cache = Cache::new(&ctx.server_url);
+        let team = args.team.clone().or_else(|| ctx.defaults.team_with_env());
+        let source = args
+            .source
+            .clone()
+            .or_else(|| ctx.defaults.source_with_env());
+        let team_id = resolve_team(client, &mut cache, team).await?;
+        let source_id = resolve_source(client, &mut cache, team_id, source).await?;
+
+        let since = args
+            .since
+            .clone()
+            .unwrap_or_else(|| ctx.defaults.since.clone());
+        let tz = ctx.defaults.timezone.as_deref();
+        let (window, span) = match (args.from.as_deref(), args.to.as_deref()) {
+            (Some(from), Some(to)) => {
+                let (start, end) = from_to_window(from, to, tz)?;
+                let window = TimeInput::Instant { start, end };
+                (resolve_time_range(window, tz), end - start)
+            }
+            (Some(_), None) => anyhow::bail!("--from requires --to to be specified"),
+            (None, Some(_)) => anyhow::bail!("--to requires --from to be specified"),
+            (None, None) => {
+                let (start, end) = since_window(&since, tz)?;
+                let window = TimeInput::Instant { start, end };
+                (resolve_time_range(window, tz), end - start)
+            }
+        };
+        super::check_time_range_cap(ctx, source_id, None, span, args.force)?;
+
+        let translate = client
+            .translate_logchefql(
+                team_id,
+                source_id,
+                &TranslateRequest {
+                    query: args.query.clone().unwrap_or_default(),
+                    start_time: Some(window.start),
+                    end_time: Some(window.end),
+                    timezone: Some(window.timezone),
+                    limit: None,
+                },
+            )
+            .await
+            .context("Failed to translate query")?;
+        if !translate.valid {
+            let message = translate
+                .error
+                .map(|e| e.message)
+                .unwrap_or_else(|| "invalid LogchefQL query".to_string());
+            anyhow::bail!("{}", message);
+        }
+
+        let mut progress = Progress {
+            team_id,
+            source_id,
+            sql: translate.generated_query().to_string(),
+            format: args.output,
+            limit: args.limit,
+            chunk_rows: args.chunk_rows,
+            chunk_size: args.chunk_size,
+            columns: Vec::new(),
+            table: None,
+            chunks: Vec::new(),
+            complete: false,
+        };
+        if args.output == OutputFormat::Sqlite {
+            let schema = client
+                .get_schema(team_id, source_id)
+                .await
+                .context("Failed to fetch source schema")?;
+            let table = args.table.clone().unwrap_or_else(|| "logs".to_string());
+            let conn = open_database(&database_path(&args.out))?;
+            create_table(&conn, &table, &schema)?;
+            progress.columns = schema.into_iter().map(|col| col.name).collect();
+            progress.table = Some(table);
+        }
+        progress.save(&manifest)?;
+        progress
+    };
+
+    let human = ui::stderr_human(global.quiet);
+    let conn = match progress.format {
+        OutputFormat::Sqlite => Some(open_database(&database_path(&args.out))?),
+        _ => None,
+    };
+    // A SQLite batch and its row count commit together, so the table itself
+    // says how far an interrupted run got.
+    let skip = match (&conn, &progress.table) {
+        (Some(conn), Some(table)) => {
+            conn.query_row(
+                &format!("SELECT count(*) FROM {}", sql_ident(table)),
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .context("Failed to read the SQLite table")? as u64
+        }
+        _ => progress.rows(),
+    };
+    let request = ExportSqlRequest {
+        raw_sql: progress.sql.clone(),
+        format: "ndjson".to_string(),
+        limit: progress.limit,
+        query_timeout: Some(args.timeout),
+    };
+    let rows = client
+        .stream_sql(progress.team_id, progress.source_id, &request)
+        .await
+        .context("Export failed")?;
+    let mut rows = std::pin::pin!(rows.skip(skip as usize));
+
+    if human && skip > 0 {
+        eprintln!("Resuming after {} rows", ui::thousands(skip as i64));
+    }
+
+    if let (Some(conn), Some(table)) = (conn, progress.table.clone()) {
+        let path = database_path(&args.out);
+        let mut inserter = SqliteInserter::new(conn, table, progress.columns.clone());
+        let mut total = skip;
+        let mut batch = Vec::with_capacity(SQLITE_BATCH_ROWS);
+        let mut ended = false;
+        while !ended {
+            match rows.next().await {
+                Some(entry) => {
+                    batch.push(entry.context("Failed to read export stream")?);
+                    if batch.len() < SQLITE_BATCH_ROWS {
+                        continue;
+                    }
+                }
+                None => ended = true,
+            }
+            if batch.is_empty() {
+                continue;
+            }
+            inserter.insert(&batch)?;
+            total += batch.len() as u64;
+            batch.clear();
+            if human {
+                eprint!("\r  {} rows", ui::thousands(total as i64));
+            }
+        }
+        if human && total > skip {
+            eprintln!();
+        }
+        let bytes = std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+        progress.chunks = vec![Chunk {
+            file: path.display().to_string(),
+            rows: total,
+            bytes,
+        }];
+    } else {
+        write_files(&mut rows, &mut progress, &args.out, &manifest, human).await?;
+    }
+    progress.complete = true;
+    progress.save(&manifest)?;
+
+    if !global.quiet {
+        let total = progress.rows();
+        if total == 0 {
+            eprintln!("No rows matched; nothing written");
+        } else if let Some(table) = &progress.table {
+            eprintln!(
+                "Exported {} rows to table {} in {}",
+                ui::thousands(total as i64),
+                table,
+                database_path(&args.out).display()
+            );
+        } else {
+            eprintln!(
+                "Exported {} rows to {} files ({})",
+                ui::thousands(total as i64),
+                progress.chunks.len(),
+                manifest.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Streams rows into `<out>-NNNN.<ext>` files, recording each finished one
+/// in the manifest.
+async fn write_files(
+    rows: &mut (impl futures::Stream<Item = logchef_core::Result<LogEntry>> + Unpin),
+    progress: &mut Progress,
+    out: &Path,
+    manifest: &Path,
+    human: bool,
+) -> Result<()> {
+    let mut writer = ChunkWriter::new(
+        out,
+        progress.format,
+        progress.chunks.len() + 1,
+        progress.chunk_rows,
+        progress.chunk_size,
+    );
+    while let Some(entry) = rows.next().await {
+        let entry = entry.context("Failed to read export stream")?;
+        if progress.format == OutputFormat::Csv && progress.columns.is_empty() {
+            progress.columns = entry.keys().cloned().collect();
+        }
+        let record = encode(&entry, progress.format, &progress.columns)?;
+        if let Some(done) = writer.write(&record, &progress.columns)? {
+            finish_chunk(progress, manifest, done, human)?;
+        }
+    }
+    if let Some(done) = writer.finish()? {
+        finish_chunk(progress, manifest, done, human)?;
+    }
+    Ok(())
+}
+
+fn finish_chunk(progress: &mut Progress, manifest: &Path, done: Chunk, human: bool) -> Result<()> {
+    if human {
+        eprintln!(
+            "  {}  {} rows, {}",
+            done.file,
+            ui::thousands(done.rows as i64),
+            format_size(done.bytes)
+        );
+    }
+    progress.chunks.push(done);
+    progress.save(manifest)
+}
+
+/// One row in the file format: an NDJSON line, or a CSV record.
+fn encode(entry: &LogEntry, format: OutputFormat, columns: &[String]) -> Result<Vec<u8>> {
+    let mut record = Vec::new();
+    match format {
+        OutputFormat::Jsonl => {
+            serde_json::to_writer(&mut record, entry)?;
+            record.push(b'\n');
+        }
+        OutputFormat::Csv => {
+            super::write_csv(
+                &mut record,
+                std::slice::from_ref(entry),
+                &csv_columns(columns),
+                false,
+                &[],
+            )?;
+        }
+        OutputFormat::Sqlite => unreachable!("SQLite rows go through SqliteInserter"),
+    }
+    Ok(record)
+}
+
+fn csv_columns(names: &[String]) -> Vec<Column> {
+    names
+        .iter()
+        .map(|name| Column {
+            name: name.clone(),
+            column_type: String::new(),
+            description: None,
+        })
+        .collect()
+}
+
+fn manifest_path(out: &Path) -> PathBuf {
+    let mut name = out.as_os_str().to_owned();
+    name.push(".progress.json");
+    PathBuf::from(name)
+}
+
+fn database_path(out: &Path) -> PathBuf {
+    let mut name = out.as_os_str().to_owned();
+    name.push(".db");
+    PathBuf::from(name)
+}
+
+fn chunk_path(out: &Path, index: usize, extension: &str) -> PathBuf {
+    let mut name = out.as_os_str().to_owned();
+    name.push(format!("-{:04}.{}", index, extension));
+    PathBuf::from(name)
+}
+
+/// Writes encoded rows to `<out>-NNNN.<ext>`, moving to the next file when
+/// the current one reaches `max_rows` or would grow past `max_bytes`. A file
+/// is only ever opened with truncation, so a half-written file left by an
+/// interrupted run is replaced on resume.
+struct ChunkWriter {
+    out: PathBuf,
+    format: OutputFormat,
+    index: usize,
+    max_rows: Option<u64>,
+    max_bytes: Option<u64>,
+    file: Option<BufWriter<File>>,
+    rows: u64,
+    bytes: u64,
+}
+
+impl ChunkWriter {
+    fn new(
+        out: &Path,
+        format: OutputFormat,
+        index: usize,
+        max_rows: Option<u64>,
+        max_bytes: Option<u64>,
+    ) -> Self {
+        Self {
+            out: out.to_path_buf(),
+            format,
+            index,
+            max_rows,
+            max_bytes,
+            file: None,
+            rows: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Appends one record; returns the previous file if this record started
+    /// a new one.
+    fn write(&mut self, record: &[u8], columns: &[String]) -> Result<Option<Chunk>> {
+        let full = self.file.is_some()
+            && (self.max_rows.is_some_and(|max| self.rows >= max)
+                || self
+                    .max_bytes
+                    .is_some_and(|max| self.bytes + record.len() as u64 > max));
+        let done = if full { self.finish()? } else { None };
+
+        if self.file.is_none() {
+            let path = chunk_path(&self.out, self.index, self.format.extension());
+            let file = File::create(&path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+            let mut file = BufWriter::new(file);
+            if self.format == OutputFormat::Csv {
+                let mut header = Vec::new();
+                super::write_csv(&mut header, &[], &csv_columns(columns), true, &[])?;
+                file.write_all(&header)?;
+                self.bytes = header.len() as u64;
+            }
+            self.file = Some(file);
+        }
+        self.file
+            .as_mut()
+            .expect("opened above")
+            .write_all(record)
+            .context("Failed to write export file")?;
+        self.rows += 1;
+        self.bytes += record.len() as u64;
+        Ok(done)
+    }
+
+    /// Flushes and closes the current file, if any.
+    fn finish(&mut self) -> Result<Option<Chunk>> {
+        let Some(mut file) = self.file.take() else {
+            return Ok(None);
+        };
+        file.flush().context("Failed to write export file")?;
+        file.get_ref()
+            .sync_all()
+            .context("Failed to write export file")?;
+        let path = chunk_path(&self.out, self.index, self.format.extension());
+        let chunk = Chunk {
+            file: path.display().to_string(),
+            rows: std::mem::take(&mut self.rows),
+            bytes: std::mem::take(&mut self.bytes),
+        };
+        self.index += 1;
+        Ok(Some(chunk))
+    }
+}
+
+fn open_database(path: &Path) -> Result<rusqlite::Connection> {
+    let conn = rusqlite::Connection::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    // WAL keeps each batch commit cheap; the export is the only writer.
+    conn.pragma_update(None, "journal_mode", "WAL")
+        .context("Failed to configure the SQLite database")?;
+    Ok(conn)
+}
+
+/// Creates `table` with one column per source column, typed by the closest
+/// SQLite affinity.
+fn create_table(conn: &rusqlite::Connection, table: &str, schema: &[Column]) -> Result<()> {
+    let columns: Vec<String> = schema
+        .iter()
+        .map(|col| format!("{} {}", sql_ident(&col.name), sqlite_type(&col.column_type)))
+        .collect();
+    conn.execute(
+        &format!("CREATE TABLE {} ({})", sql_ident(table), columns.join(", ")),
+        [],
+    )
+    .with_context(|| format!("Failed to create table '{}'", table))?;
+    Ok(())
+}
+
+/// Maps a ClickHouse column type to a SQLite type: integers (and Bool) to
+/// INTEGER, floats and decimals to REAL, everything else — strings, dates,
+/// maps, arrays — to TEXT, with nested values stored as JSON.
+fn sqlite_type(column_type: &str) -> &'static str {
+    let mut inner = column_type.trim();
+    while let Some(rest) = inner
+        .strip_prefix("Nullable(")
+        .or_else(|| inner.strip_prefix("LowCardinality("))
+    {
+        inner = rest.strip_suffix(')').unwrap_or(rest);
+    }
+    if inner.starts_with("Int") || inner.starts_with("UInt") || inner == "Bool" {
+        "INTEGER"
+    } else if inner.starts_with("Float") || inner.starts_with("Decimal") {
+        "REAL"
+    } else {
+        "TEXT"
+    }
+}
+
+fn sql_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Inserts rows into the export table, one transaction per batch. Row keys
+/// outside the table are dropped; missing ones are NULL.
+struct SqliteInserter {
+    conn: rusqlite::Connection,
+    insert: String,
+    columns: Vec<String>,
+}
+
+impl SqliteInserter {
+    fn new(conn: rusqlite::Connection, table: String, columns: Vec<String>) -> Self {
+        let names: Vec<String> = columns.iter().map(|name| sql_ident(name)).collect();
+        let params: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+        let insert = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            sql_ident(&table),
+            names.join(", "),
+            params.join(", ")
+        );
+        Self {
+            conn,
+            insert,
+            columns,
+        }
+    }
+
+    fn insert(&mut self, batch: &[LogEntry]) -> Result<()> {
+        let tx = self
+            .conn
+            .transaction()
+            .context("Failed to write to SQLite")?;
+        {
+            let mut stmt = tx
+                .prepare_cached(&self.insert)
+                .context("Failed to write to SQLite")?;
+            for entry in batch {
+                let values = self
+                    .columns
+                    .iter()
+                    .map(|name| sqlite_value(entry.get(name)));
+                stmt.execute(rusqlite::params_from_iter(values))
+                    .context("Failed to write to SQLite")?;
+            }
+        }
+        tx.commit().context("Failed to write to SQLite")
+    }
+}
+
+fn sqlite_value(value: Option<&serde_json::Value>) -> rusqlite::types::Value {
+    use rusqlite::types::Value;
+    match value {
+        None | Some(serde_json::Value::Null) => Value::Null,
+        Some(serde_json::Value::Bool(b)) => Value::Integer(i64::from(*b)),
+        Some(serde_json::Value::Number(n)) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => Value::Integer(i),
+            // u64 beyond i64 keeps its digits as text.
+            (None, _) if n.is_u64() => Value::Text(n.to_string()),
+            (None, Some(f)) => Value::Real(f),
+            (None, None) => Value::Text(n.to_string()),
+        },
+        Some(serde_json::Value::String(s)) => Value::Text(s.clone()),
+        Some(other) => Value::Text(other.to_string()),
+    }
+}
+
+/// Parses a size like `500M`, `2G`, `64k` or a plain byte count. Units are
+/// binary (1K = 1024 bytes); a trailing `B`/`iB` is accepted.
+fn parse_size(s: &str) -> std::result::Result<u64, String> {
+    let upper = s.trim().to_ascii_uppercase();
+    let trimmed = upper
+        .strip_suffix("IB")
+        .or_else(|| upper.strip_suffix('B'))
+        .unwrap_or(&upper);
+    let (digits, shift) = match trimmed.chars().last() {
+        Some('K') => (&trimmed[..trimmed.len() - 1], 10),
+        Some('M') => (&trimmed[..trimmed.len() - 1], 20),
+        Some('G') => (&trimmed[..trimmed.len() - 1], 30),
+        Some('T') => (&trimmed[..trimmed.len() - 1], 40),
+        _ => (trimmed, 0),
+    };
+    let value: u64 = digits
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid size '{}' (e.g. 500M, 2G, 1048576)", s))?;
+    match value.checked_mul(1u64 << shift) {
+        Some(0) => Err("size must be greater than 0".to_string()),
+        Some(bytes) => Ok(bytes),
+        None => Err(format!("size '{}' is too large", s)),
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    if unit == "B" {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes_with_binary_units() {
+        assert_eq!(parse_size("1048576"), Ok(1 << 20));
+        assert_eq!(parse_size("500M"), Ok(500 << 20));
+        assert_eq!(parse_size("2g"), Ok(2 << 30));
+        assert_eq!(parse_size("64KiB"), Ok(64 << 10));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("big").is_err());
+    }
+
+    #[test]
+    fn sqlite_table_follows_the_source_schema() {
+        let column = |name: &str, column_type: &str| Column {
+            name: name.to_string(),
+            column_type: column_type.to_string(),
+            description: None,
+        };
+        let schema = [
+            column("timestamp", "DateTime64(3)"),
+            column("status", "Nullable(UInt16)"),
+            column("latency", "Float64"),
+            column("level", "LowCardinality(String)"),
+            column("attrs", "Map(String, String)"),
+        ];
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        create_table(&conn, "logs", &schema).unwrap();
+        let mut inserter = SqliteInserter::new(
+            conn,
+            "logs".to_string(),
+            schema.iter().map(|col| col.name.clone()).collect(),
+        );
+        let row: LogEntry = serde_json::from_value(serde_json::json!({
+            "timestamp": "2026-07-14 09:00:00.000",
+            "status": "503",
+            "latency": 0.25,
+            "attrs": {"region": "eu"},
+            "extra": "dropped",
+        }))
+        .unwrap();
+        inserter.insert(&[row]).unwrap();
+
+        let (status, latency, level, attrs): (i64, f64, Option<String>, String) = inserter
+            .conn
+            .query_row("SELECT status, latency, level, attrs FROM logs", [], |r| {
+                Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?))
+            })
+            .unwrap();
+        // "503" lands as an integer through the column's affinity.
+        assert_eq!(status, 503);
+        assert_eq!(latency, 0.25);
+        assert_eq!(level, None);
+        assert_eq!(attrs, r#"{"region":"eu"}"#);
+    }
+
+    #[test]
+    fn chunk_writer_rotates_on_rows_and_size() {
+        let dir = std::env::temp_dir().join(format!("logchef-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let out = dir.join("out");
+
+        let mut writer = ChunkWriter::new(&out, OutputFormat::Jsonl, 1, Some(2), Some(11));
+        let mut done = Vec::new();
+        for record in ["aaa\n", "bbb\n", "ccc\n", "dddddddd\n", "e\n"] {
+            done.extend(writer.write(record.as_bytes(), &[]).unwrap());
+        }
+        done.extend(writer.finish().unwrap());
+
+        // Two rows max, and "dddddddd\n" would push the second file past 11 bytes.
+        let rows: Vec<_> = done.iter().map(|chunk| chunk.rows).collect();
+        assert_eq!(rows, [2, 1, 2]);
+        assert!(done[0].file.ends_with("out-0001.jsonl"));
+        assert_eq!(
+            std::fs::read_to_string(chunk_path(&out, 3, "jsonl")).unwrap(),
+            "dddddddd\ne\n"
+        );
+        assert_eq!(
+            manifest_path(&out).file_name().unwrap(),
+            "out.progress.json"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
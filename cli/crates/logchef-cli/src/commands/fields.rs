@@ -1,12 +1,11 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
 use clap::Args;
 use logchef_core::Config;
 use logchef_core::api::{Column, FieldValueInfo, FieldValuesQuery};
 use logchef_core::cache::Cache;
 
 use crate::cli::GlobalArgs;
-use crate::commands::{parse_lookback, resolve_source, resolve_team};
+use crate::commands::{resolve_source, resolve_team};
 use crate::session;
 use crate::ui;
 
@@ -32,7 +31,8 @@ pub struct FieldsArgs {
     #[arg(long, short = 'S')]
     source: Option<String>,
 
-    /// Relative lookback window for value enumeration (e.g. 15m, 1h, 24h)
+    /// Relative lookback window for value enumeration (e.g. 15m, 1h, 24h,
+    /// today)
     #[arg(long, short = 's')]
     since: Option<String>,
 
@@ -40,6 +40,10 @@ pub struct FieldsArgs {
     #[arg(long, default_value = "20")]
     limit: u32,
 
+    /// Run even if the time range exceeds the configured `max_time_range`.
+    #[arg(long)]
+    force: bool,
+
     /// Output format
     #[arg(long, default_value = "text")]
     output: OutputFormat,
@@ -135,8 +139,13 @@ async fn enumerate_values(
         .since
         .clone()
         .unwrap_or_else(|| ctx.defaults.since.clone());
-    let end = Utc::now();
-    let start = end - parse_lookback(&since)?;
+    let (start, end) =
+        logchef_core::timerange::since_window(&since, ctx.defaults.timezone.as_deref())?;
+    let source = args
+        .source
+        .clone()
+        .or_else(|| ctx.defaults.source_with_env());
+    super::check_time_range_cap(ctx, source_id, source.as_deref(), end - start, args.force)?;
 
     let result = client
         .get_field_values(
@@ -149,6 +158,7 @@ async fn enumerate_values(
                 end: &end.to_rfc3339(),
                 timezone: "UTC",
                 limit: args.limit,
+                logchefql: None,
             },
         )
         .await?;
@@ -14,6 +14,9 @@ const DEFAULT_COLUMNS: &[&str] = &["service", "service_name", "job_name", "app",
 /// informative than a GROUP BY of unique strings.
 const SAMPLE_ONLY_COLUMNS: &[&str] = &["msg", "message", "body"];
 const SAMPLE_VALUE_TRUNCATE: usize = 80;
+/// Fraction of rows read by `--approx`; counts are scaled back up through
+/// ClickHouse's `_sample_factor`.
+const APPROX_SAMPLE_RATIO: &str = "0.1";
 
 #[derive(Args)]
 #[command(after_help = "EXAMPLES:
@@ -24,7 +27,10 @@ const SAMPLE_VALUE_TRUNCATE: usize = 80;
   logchef find payment-gateway -t platform --since 7d
 
   # Just the match counts, no per-column samples, as JSON
-  logchef find nginx --no-samples --output jsonl")]
+  logchef find nginx --no-samples --output jsonl
+
+  # Ballpark counts on very large tables (sampled, labelled with ~)
+  logchef find checkout --since 30d --approx")]
 pub struct FindArgs {
     /// Service, job, host, or message pattern to search for.
     pattern: String,
@@ -53,11 +59,21 @@ pub struct FindArgs {
     #[arg(long, default_value = "30")]
     timeout: u32,
 
+    /// Run even if the time range exceeds the configured `max_time_range`.
+    #[arg(long)]
+    force: bool,
+
     /// Skip the per-column sample fetch. Useful when you only need the
     /// match-count summary.
     #[arg(long)]
     no_samples: bool,
 
+    /// Estimate counts from a 10% sample (ClickHouse `SAMPLE`) instead of a
+    /// full scan. Sources without a sampling key, and VictoriaLogs sources,
+    /// fall back to exact counts.
+    #[arg(long)]
+    approx: bool,
+
     /// Output format.
     #[arg(long, default_value = "text")]
     output: OutputFormat,
@@ -78,6 +94,9 @@ struct FindResult {
     source_name: String,
     table: String,
     matches: i64,
+    /// True when `matches` and sample counts are sampled estimates.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    approximate: bool,
     columns: Vec<String>,
     lookback: String,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -113,6 +132,8 @@ pub async fn run(args: FindArgs, global: GlobalArgs) -> Result<()> {
         args.columns.clone()
     };
 
+    let (start, end) =
+        logchef_core::timerange::since_window(&args.since, ctx.defaults.timezone.as_deref())?;
     let mut results = Vec::new();
     let mut skipped = 0usize;
     for team in teams {
@@ -125,6 +146,13 @@ pub async fn run(args: FindArgs, global: GlobalArgs) -> Result<()> {
             if !source_matches_filter(&source, args.source.as_deref()) {
                 continue;
             }
+            super::check_time_range_cap(
+                ctx,
+                source.id,
+                Some(&source.name),
+                end - start,
+                args.force,
+            )?;
 
             // VictoriaLogs sources have no ClickHouse-style `db.table` ref, so
             // they took the old `table_ref()` early-`continue` and were skipped
@@ -166,26 +194,43 @@ pub async fn run(args: FindArgs, global: GlobalArgs) -> Result<()> {
             // Count matches through the same `/logs/query` path for both
             // engines (raw ClickHouse SQL vs. raw LogsQL); only the query text
             // differs by engine.
-            let count_query = if is_vl {
-                vl_find_query(&searchable, &args.pattern, &args.since)
-            } else {
-                find_sql(
-                    &target,
-                    timestamp_field,
-                    &searchable,
-                    &args.pattern,
-                    &args.since,
-                )
-            };
-            let request = SqlQueryRequest {
-                query_text: count_query,
+            let count_request = |approx: bool| SqlQueryRequest {
+                query_text: if is_vl {
+                    vl_find_query(&searchable, &args.pattern, &args.since)
+                } else {
+                    find_sql(
+                        &target,
+                        timestamp_field,
+                        &searchable,
+                        &args.pattern,
+                        &args.since,
+                        approx,
+                    )
+                },
                 limit: Some(1),
                 timezone: ctx.defaults.timezone.clone(),
                 start_time: None,
                 end_time: None,
                 query_timeout: Some(args.timeout),
             };
-            let response = match client.query_sql(team.id, source.id, &request).await {
+            let mut approximate = args.approx && !is_vl;
+            let mut result = client
+                .query_sql(team.id, source.id, &count_request(approximate))
+                .await;
+            if approximate && let Err(err) = &result {
+                // Most likely a table without a SAMPLE BY key; pay for the
+                // exact count rather than dropping the source.
+                tracing::debug!(
+                    team = team.id,
+                    source = source.id,
+                    "approximate count failed, retrying exact: {err:#}"
+                );
+                approximate = false;
+                result = client
+                    .query_sql(team.id, source.id, &count_request(false))
+                    .await;
+            }
+            let response = match result {
                 Ok(response) => response,
                 Err(err) => {
                     tracing::debug!(
@@ -230,6 +275,7 @@ pub async fn run(args: FindArgs, global: GlobalArgs) -> Result<()> {
                         &args.pattern,
                         &args.since,
                         args.timeout,
+                        approximate,
                     )
                     .await
                 };
@@ -240,6 +286,7 @@ pub async fn run(args: FindArgs, global: GlobalArgs) -> Result<()> {
                     source_name: source.name.clone(),
                     table: target,
                     matches,
+                    approximate,
                     columns: searchable,
                     lookback: args.since.clone(),
                     samples,
@@ -267,10 +314,11 @@ fn print_results(results: &[FindResult], output: &OutputFormat, skipped: usize)
             } else {
                 for result in results {
                     println!(
-                        "team={} source={} ({}) matches={} in {} columns={}",
+                        "team={} source={} ({}) matches={}{} in {} columns={}",
                         result.team_id,
                         result.source_id,
                         result.table,
+                        approx_marker(result.approximate),
                         ui::thousands(result.matches),
                         result.lookback,
                         result.columns.join(",")
@@ -280,7 +328,12 @@ fn print_results(results: &[FindResult], output: &OutputFormat, skipped: usize)
                             .values
                             .iter()
                             .map(|v| match v.count {
-                                Some(c) => format!("\"{}\" ({})", v.value, ui::thousands(c)),
+                                Some(c) => format!(
+                                    "\"{}\" ({}{})",
+                                    v.value,
+                                    approx_marker(result.approximate),
+                                    ui::thousands(c)
+                                ),
                                 None => format!("\"{}\" (sample)", v.value),
                             })
                             .collect::<Vec<_>>()
@@ -301,6 +354,10 @@ fn print_results(results: &[FindResult], output: &OutputFormat, skipped: usize)
     Ok(())
 }
 
+fn approx_marker(approximate: bool) -> &'static str {
+    if approximate { "~" } else { "" }
+}
+
 async fn resolve_teams(
     client: &Client,
     cache: &mut Cache,
@@ -368,6 +425,7 @@ fn find_sql(
     columns: &[String],
     pattern: &str,
     since: &str,
+    approx: bool,
 ) -> String {
     let predicates = columns
         .iter()
@@ -382,8 +440,10 @@ fn find_sql(
         .join(" OR ");
     let (num, unit) = clickhouse_interval(since);
     format!(
-        "SELECT count() AS matches FROM {} WHERE ({}) AND {} >= now() - INTERVAL {} {}",
+        "SELECT {} AS matches FROM {}{} WHERE ({}) AND {} >= now() - INTERVAL {} {}",
+        count_expr(approx),
         table,
+        sample_clause(approx),
         predicates,
         sql_identifier(timestamp_field),
         num,
@@ -403,11 +463,12 @@ async fn fetch_samples(
     pattern: &str,
     since: &str,
     timeout: u32,
+    approx: bool,
 ) -> Vec<ColumnSamples> {
     let mut out = Vec::new();
     for column in matched_columns {
         let with_count = column_uses_group_by(schema, column);
-        let sql = sample_sql(table, timestamp_field, column, pattern, since);
+        let sql = sample_sql(table, timestamp_field, column, pattern, since, approx);
         // Mirror the SQL's intent in the request envelope too: the server's
         // own `limit` clamp would otherwise override the inline LIMIT 1 we
         // bake into sample-only-column SQL.
@@ -612,6 +673,7 @@ fn sample_sql(
     column: &str,
     pattern: &str,
     since: &str,
+    approx: bool,
 ) -> String {
     let (num, unit) = clickhouse_interval(since);
     let ts = sql_identifier(timestamp_field);
@@ -628,12 +690,31 @@ fn sample_sql(
     } else {
         // Top-3 by frequency. Cheap because the predicate is the same as the
         // count query.
+        let count = count_expr(approx);
+        let sample = sample_clause(approx);
         format!(
-            "SELECT {col}, count() AS c FROM {table} WHERE {where_clause} GROUP BY {col} ORDER BY c DESC LIMIT 3"
+            "SELECT {col}, {count} AS c FROM {table}{sample} WHERE {where_clause} GROUP BY {col} ORDER BY c DESC LIMIT 3"
         )
     }
 }
 
+/// `sum(_sample_factor)` scales a sampled count back to the full table.
+fn count_expr(approx: bool) -> &'static str {
+    if approx {
+        "toInt64(sum(_sample_factor))"
+    } else {
+        "count()"
+    }
+}
+
+fn sample_clause(approx: bool) -> String {
+    if approx {
+        format!(" SAMPLE {}", APPROX_SAMPLE_RATIO)
+    } else {
+        String::new()
+    }
+}
+
 fn is_sample_only_column(column: &str) -> bool {
     SAMPLE_ONLY_COLUMNS
         .iter()
@@ -713,21 +794,40 @@ mod tests {
             &["service".to_string()],
             "api",
             "2h",
+            false,
         );
         assert!(sql.contains("positionCaseInsensitive(toString(`service`), 'api') > 0"));
         assert!(sql.contains("INTERVAL 2 HOUR"));
     }
 
+    #[test]
+    fn approx_find_sql_samples_and_scales() {
+        let sql = find_sql(
+            "logs.app",
+            "_timestamp",
+            &["service".to_string()],
+            "api",
+            "2h",
+            true,
+        );
+        assert!(sql.starts_with(
+            "SELECT toInt64(sum(_sample_factor)) AS matches FROM logs.app SAMPLE 0.1 WHERE"
+        ));
+        let sql = sample_sql("logs.app", "_timestamp", "service", "api", "1h", true);
+        assert!(sql.contains("FROM logs.app SAMPLE 0.1 WHERE"));
+        assert!(sql.contains("toInt64(sum(_sample_factor)) AS c"));
+    }
+
     #[test]
     fn sample_sql_uses_group_by_for_label_columns() {
-        let sql = sample_sql("logs.app", "_timestamp", "service", "api", "1h");
+        let sql = sample_sql("logs.app", "_timestamp", "service", "api", "1h", false);
         assert!(sql.contains("GROUP BY `service`"));
         assert!(sql.contains("ORDER BY c DESC LIMIT 3"));
     }
 
     #[test]
     fn sample_sql_uses_single_row_for_msg() {
-        let sql = sample_sql("logs.app", "_timestamp", "msg", "api", "1h");
+        let sql = sample_sql("logs.app", "_timestamp", "msg", "api", "1h", false);
         assert!(sql.contains("SELECT `msg` FROM logs.app"));
         assert!(sql.ends_with("LIMIT 1"));
         assert!(!sql.contains("GROUP BY"));
@@ -1,17 +1,18 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, Utc};
 use clap::Args;
 use logchef_core::Config;
-use logchef_core::api::{HistogramBucket, HistogramRequest, TranslateRequest};
+use logchef_core::api::{Client, HistogramBucket, HistogramRequest, TranslateRequest};
 use logchef_core::cache::Cache;
-use logchef_core::timerange::{TimeInput, resolve_time_range, resolve_timezone};
+use logchef_core::timerange::{
+    TimeInput, from_to_window, parse_duration, resolve_time_range, since_window,
+};
 
 use crate::cli::GlobalArgs;
-use crate::commands::{parse_lookback, resolve_source, resolve_team};
+use crate::commands::{resolve_source, resolve_team};
 use crate::session;
 use crate::ui;
 
-const WALL_CLOCK_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 const BAR_WIDTH: usize = 40;
 
 /// Bucket sizes the histogram endpoint accepts, smallest first. Used to snap
@@ -43,6 +44,9 @@ const WINDOWS: &[(&str, i64)] = &[
   # Total volume in 5-minute buckets, broken down by service (top 10)
   logchef histogram --since 6h --interval 5m --group-by service
 
+  # One-line sparkline per service
+  logchef histogram 'level=\"error\"' --since 6h --group-by service --output sparkline
+
   # Machine-readable buckets for plotting elsewhere
   logchef histogram 'status>=500' --since 1h --output jsonl")]
 pub struct HistogramArgs {
@@ -57,15 +61,17 @@ pub struct HistogramArgs {
     #[arg(long, short = 'S')]
     source: Option<String>,
 
-    /// Relative lookback window (e.g. 15m, 1h, 24h)
+    /// Relative lookback window (e.g. 15m, 1h, 24h) or a named window such
+    /// as today or this-week
     #[arg(long, short = 's')]
     since: Option<String>,
 
-    /// Absolute start time (YYYY-MM-DD HH:MM:SS) in the effective timezone. Requires --to.
+    /// Absolute start time (RFC3339, YYYY-MM-DD HH:MM:SS in the effective
+    /// timezone, epoch ms, or "2 hours ago"). Requires --to.
     #[arg(long)]
     from: Option<String>,
 
-    /// Absolute end time (YYYY-MM-DD HH:MM:SS) in the effective timezone. Requires --from.
+    /// Absolute end time, in the same formats as --from. Requires --from.
     #[arg(long)]
     to: Option<String>,
 
@@ -77,13 +83,17 @@ pub struct HistogramArgs {
     #[arg(long)]
     group_by: Option<String>,
 
-    /// Output format
+    /// Output format. `sparkline` prints one compact line per series.
     #[arg(long, default_value = "text")]
     output: OutputFormat,
 
     /// Query timeout in seconds
     #[arg(long, default_value = "30")]
     timeout: u32,
+
+    /// Run even if the time range exceeds the configured `max_time_range`.
+    #[arg(long)]
+    force: bool,
 }
 
 #[derive(Clone, Debug, clap::ValueEnum)]
@@ -92,6 +102,7 @@ enum OutputFormat {
     Json,
     Jsonl,
     Table,
+    Sparkline,
 }
 
 pub async fn run(args: HistogramArgs, global: GlobalArgs) -> Result<()> {
@@ -107,7 +118,7 @@ pub async fn run(args: HistogramArgs, global: GlobalArgs) -> Result<()> {
         .or_else(|| ctx.defaults.source_with_env());
 
     let team_id = resolve_team(client, &mut cache, team).await?;
-    let source_id = resolve_source(client, &mut cache, team_id, source).await?;
+    let source_id = resolve_source(client, &mut cache, team_id, source.clone()).await?;
 
     let since = args
         .since
@@ -119,7 +130,72 @@ pub async fn run(args: HistogramArgs, global: GlobalArgs) -> Result<()> {
         args.to.as_deref(),
         ctx.defaults.timezone.as_deref(),
     )?;
+    super::check_time_range_cap(
+        ctx,
+        source_id,
+        source.as_deref(),
+        end_utc - start_utc,
+        args.force,
+    )?;
+    let window = resolve_window(&args.interval, end_utc - start_utc);
+    let mut request = histogram_request(
+        client,
+        team_id,
+        source_id,
+        args.query.as_deref().unwrap_or_default(),
+        (start_utc, end_utc),
+        ctx.defaults.timezone.as_deref(),
+        window,
+        args.timeout,
+    )
+    .await?;
+    request.group_by = args.group_by.clone();
+
+    let response = client
+        .get_histogram(team_id, source_id, &request)
+        .await
+        .context("Histogram query failed")?;
+
+    match args.output {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        }
+        OutputFormat::Jsonl => {
+            for bucket in &response.data {
+                println!("{}", serde_json::to_string(bucket)?);
+            }
+        }
+        OutputFormat::Table => {
+            print_table(&response.data, args.group_by.is_some());
+        }
+        OutputFormat::Text => {
+            print_chart(&response, args.group_by.is_some(), global.quiet);
+        }
+        OutputFormat::Sparkline => {
+            print_sparklines(&response, global.quiet);
+        }
+    }
 
+    Ok(())
+}
+
+/// Builds a histogram request for a LogchefQL query over `window`.
+///
+/// The histogram endpoint expects a source-native query_text (full SQL for
+/// ClickHouse, LogsQL for VictoriaLogs), so the LogchefQL is translated
+/// first — exactly what the web explorer sends. The time range is baked into
+/// the ClickHouse SQL and passed alongside for VictoriaLogs.
+#[allow(clippy::too_many_arguments)]
+async fn histogram_request(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    query: &str,
+    (start_utc, end_utc): (DateTime<Utc>, DateTime<Utc>),
+    configured_tz: Option<&str>,
+    window: String,
+    timeout: u32,
+) -> Result<HistogramRequest> {
     // Wall-clock strings (in the effective timezone) for translation; the
     // ClickHouse translator bakes these into the generated SQL.
     let wall = resolve_time_range(
@@ -127,20 +203,14 @@ pub async fn run(args: HistogramArgs, global: GlobalArgs) -> Result<()> {
             start: start_utc,
             end: end_utc,
         },
-        ctx.defaults.timezone.as_deref(),
+        configured_tz,
     );
-
-    // The histogram endpoint expects a source-native query_text (full SQL for
-    // ClickHouse, LogsQL for VictoriaLogs), so translate the LogchefQL first —
-    // exactly what the web explorer sends. The time range is baked into the
-    // ClickHouse SQL and passed alongside (below) for VictoriaLogs.
-    let query = args.query.clone().unwrap_or_default();
     let translate = client
         .translate_logchefql(
             team_id,
             source_id,
             &TranslateRequest {
-                query,
+                query: query.to_string(),
                 start_time: Some(wall.start.clone()),
                 end_time: Some(wall.end.clone()),
                 timezone: Some(wall.timezone.clone()),
@@ -158,78 +228,66 @@ pub async fn run(args: HistogramArgs, global: GlobalArgs) -> Result<()> {
         anyhow::bail!("{}", message);
     }
 
-    let window = resolve_window(&args.interval, end_utc - start_utc);
-
-    let request = HistogramRequest {
+    Ok(HistogramRequest {
         query_text: translate.generated_query().to_string(),
         start_timestamp: Some(start_utc.timestamp_millis()),
         end_timestamp: Some(end_utc.timestamp_millis()),
         window: Some(window),
-        group_by: args.group_by.clone(),
-        timezone: Some(wall.timezone.clone()),
+        group_by: None,
+        timezone: Some(wall.timezone),
         limit: Some(100),
-        query_timeout: Some(args.timeout),
-    };
+        query_timeout: Some(timeout),
+    })
+}
 
+/// Number of logs matching a LogchefQL query between two instants, counted
+/// server-side: the histogram endpoint aggregates the matches into the
+/// widest buckets it supports and the buckets are summed, so no log rows are
+/// transferred.
+pub(super) async fn count_matches(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    query: &str,
+    range: (DateTime<Utc>, DateTime<Utc>),
+    configured_tz: Option<&str>,
+    timeout: u32,
+) -> Result<i64> {
+    let widest = WINDOWS.last().map_or("24h", |(label, _)| label);
+    let request = histogram_request(
+        client,
+        team_id,
+        source_id,
+        query,
+        range,
+        configured_tz,
+        widest.to_string(),
+        timeout,
+    )
+    .await?;
     let response = client
         .get_histogram(team_id, source_id, &request)
         .await
-        .context("Histogram query failed")?;
-
-    match args.output {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&response)?);
-        }
-        OutputFormat::Jsonl => {
-            for bucket in &response.data {
-                println!("{}", serde_json::to_string(bucket)?);
-            }
-        }
-        OutputFormat::Table => {
-            print_table(&response.data, args.group_by.is_some());
-        }
-        OutputFormat::Text => {
-            print_chart(&response, args.group_by.is_some(), global.quiet);
-        }
-    }
-
-    Ok(())
+        .context("Count query failed")?;
+    Ok(response.data.iter().map(|bucket| bucket.log_count).sum())
 }
 
-/// Resolves the query window to a pair of UTC instants. `--from/--to` are
-/// wall-clock times in the effective timezone; otherwise `now - since`.
-fn resolve_instants(
+/// Resolves the query window to a pair of UTC instants, from `--from/--to`
+/// or else `--since`.
+pub(super) fn resolve_instants(
     since: &str,
     from: Option<&str>,
     to: Option<&str>,
     configured_tz: Option<&str>,
 ) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
     match (from, to) {
-        (Some(from), Some(to)) => {
-            let tz = resolve_timezone(configured_tz);
-            let start = parse_wall_clock(from, tz).context("Invalid --from time")?;
-            let end = parse_wall_clock(to, tz).context("Invalid --to time")?;
-            Ok((start, end))
-        }
+        (Some(from), Some(to)) => Ok(from_to_window(from, to, configured_tz)?),
         (Some(_), None) => anyhow::bail!("--from requires --to to be specified"),
         (None, Some(_)) => anyhow::bail!("--to requires --from to be specified"),
-        (None, None) => {
-            let end = Utc::now();
-            Ok((end - parse_lookback(since)?, end))
-        }
+        (None, None) => Ok(since_window(since, configured_tz)?),
     }
 }
 
-fn parse_wall_clock<Tz: TimeZone>(value: &str, tz: Tz) -> Result<DateTime<Utc>> {
-    let naive = NaiveDateTime::parse_from_str(value.trim(), WALL_CLOCK_FORMAT)
-        .context("expected format YYYY-MM-DD HH:MM:SS")?;
-    let local = tz
-        .from_local_datetime(&naive)
-        .single()
-        .ok_or_else(|| anyhow::anyhow!("ambiguous or invalid local time '{}'", value))?;
-    Ok(local.with_timezone(&Utc))
-}
-
 /// Returns the explicit interval, or an auto-selected window sized so the range
 /// yields roughly 60 buckets, snapped up to a supported window.
 fn resolve_window(interval: &str, span: chrono::Duration) -> String {
@@ -337,6 +395,135 @@ fn print_chart(response: &logchef_core::api::HistogramResponse, has_group_by: bo
     }
 }
 
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One character per count, scaled to `max`. Empty buckets get the lowest
+/// bar and any non-zero count at least the next one up, so quiet-but-active
+/// stretches stay distinguishable from silence.
+fn sparkline(counts: &[i64], max: i64) -> String {
+    counts
+        .iter()
+        .map(|&count| {
+            if count <= 0 || max <= 0 {
+                return SPARKS[0];
+            }
+            let level = (count as f64 / max as f64 * (SPARKS.len() - 1) as f64).ceil() as usize;
+            SPARKS[level.clamp(1, SPARKS.len() - 1)]
+        })
+        .collect()
+}
+
+/// The chart's x-axis: every bucket start the server returned, plus the
+/// empty buckets between them (the server omits those) when the bucket size
+/// is known.
+fn bucket_axis(buckets: &[HistogramBucket], granularity: &str) -> Vec<String> {
+    let mut axis: Vec<&str> = buckets.iter().map(|b| b.bucket.as_str()).collect();
+    axis.sort_unstable();
+    axis.dedup();
+    let parsed: Option<Vec<DateTime<Utc>>> = axis
+        .iter()
+        .map(|raw| DateTime::parse_from_rfc3339(raw).ok().map(|dt| dt.to_utc()))
+        .collect();
+    let (Some(parsed), Ok(step)) = (parsed, parse_duration(granularity)) else {
+        return axis.into_iter().map(str::to_string).collect();
+    };
+    let (Some(first), Some(last)) = (parsed.first(), parsed.last()) else {
+        return Vec::new();
+    };
+    if step <= chrono::Duration::zero()
+        || (*last - *first).num_seconds() / step.num_seconds() > 10_000
+    {
+        return axis.into_iter().map(str::to_string).collect();
+    }
+    let mut filled = Vec::new();
+    let mut at = *first;
+    while at <= *last {
+        filled.push(at.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true));
+        at += step;
+    }
+    filled
+}
+
+fn print_sparklines(response: &logchef_core::api::HistogramResponse, quiet: bool) {
+    if response.data.is_empty() {
+        println!("No data in the selected time range.");
+        return;
+    }
+    let color = ui::human(quiet);
+    let axis = bucket_axis(&response.data, &response.granularity);
+    let position = |raw: &str| {
+        DateTime::parse_from_rfc3339(raw)
+            .map(|dt| {
+                dt.to_utc()
+                    .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+            })
+            .unwrap_or_else(|_| raw.to_string())
+    };
+
+    // One series per group value (a single unnamed one without --group-by),
+    // largest first.
+    let mut series: Vec<(String, Vec<i64>)> = Vec::new();
+    for bucket in &response.data {
+        let name = bucket.group_value.clone().unwrap_or_default();
+        let index = match series.iter().position(|(n, _)| *n == name) {
+            Some(index) => index,
+            None => {
+                series.push((name, vec![0; axis.len()]));
+                series.len() - 1
+            }
+        };
+        let at = position(&bucket.bucket);
+        if let Some(slot) = axis.iter().position(|a| position(a) == at) {
+            series[index].1[slot] += bucket.log_count;
+        }
+    }
+    series.sort_by_key(|(_, counts)| std::cmp::Reverse(counts.iter().sum::<i64>()));
+
+    let header = format!(
+        "span {} → {} · bucket {}",
+        format_bucket(axis.first().map(String::as_str).unwrap_or_default()),
+        format_bucket(axis.last().map(String::as_str).unwrap_or_default()),
+        response.granularity
+    );
+    if color {
+        println!("\x1b[2m{}\x1b[0m", header);
+    } else {
+        println!("{}", header);
+    }
+
+    let max = series
+        .iter()
+        .flat_map(|(_, counts)| counts.iter().copied())
+        .max()
+        .unwrap_or(0);
+    let label_width = series.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, counts) in &series {
+        let line = sparkline(counts, max);
+        let line = if color {
+            format!("\x1b[36m{}\x1b[0m", line)
+        } else {
+            line
+        };
+        let label = if label_width > 0 {
+            format!("{:<width$}  ", name, width = label_width)
+        } else {
+            String::new()
+        };
+        println!(
+            "{}{}  {} logs · peak {}",
+            label,
+            line,
+            ui::thousands(counts.iter().sum()),
+            ui::thousands(counts.iter().copied().max().unwrap_or(0))
+        );
+    }
+    if let Some(notice) = &response.notice
+        && !notice.is_empty()
+    {
+        eprintln!("note: {}", notice);
+    }
+}
+
 fn print_table(buckets: &[HistogramBucket], has_group_by: bool) {
     if buckets.is_empty() {
         println!("No data in the selected time range.");
@@ -364,3 +551,28 @@ fn print_table(buckets: &[HistogramBucket], has_group_by: bool) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparkline_fills_gaps_and_scales_to_the_peak() {
+        let bucket = |at: &str, count| HistogramBucket {
+            bucket: at.to_string(),
+            log_count: count,
+            group_value: None,
+        };
+        let buckets = [
+            bucket("2026-07-14T09:00:00Z", 2),
+            bucket("2026-07-14T09:03:00Z", 80),
+            bucket("2026-07-14T09:01:00Z", 1),
+        ];
+        let axis = bucket_axis(&buckets, "1m");
+        assert_eq!(axis.len(), 4);
+        assert_eq!(axis[2], "2026-07-14T09:02:00Z");
+
+        assert_eq!(sparkline(&[2, 1, 0, 80], 80), "▂▂▁█");
+        assert_eq!(sparkline(&[0, 40], 80), "▁▅");
+    }
+}
@@ -1,30 +1,63 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use clap::Args;
+use clap::{Args, Parser, Subcommand};
 use logchef_core::Config;
 use logchef_core::api::QueryHistoryEntry;
+use logchef_core::history::{History, HistoryEntry, QueryKind};
+use logchef_core::timerange::from_to_window;
+use serde::Serialize;
 
 use crate::cli::GlobalArgs;
-use crate::session;
+use crate::commands::{query, sql};
+use crate::session::{self, AuthedSession};
 
 #[derive(Args)]
 #[command(after_help = "EXAMPLES:
-  # Show your last 50 queries
-  logchef history
-
-  # Last 10, most recent first
+  # Your history as recorded by the server
   logchef history --limit 10
 
-  # Scriptable: re-run the most recent query's text
+  # Queries run from this machine, most recent first
+  logchef history --local
+
+  # Only those mentioning checkout
+  logchef history --local checkout
+
+  # Run local entry 3 again, as JSON lines this time
+  logchef history --run 3 -- --output jsonl
+
+  # Scriptable: the most recent query's text
   logchef history --limit 1 --output jsonl | jq -r '.query_text'")]
 pub struct HistoryArgs {
-    /// Number of entries to fetch (server default 50, max 200).
+    /// With --local, only show entries whose query, source or context
+    /// contains this text (case-insensitive).
+    #[arg(requires = "local")]
+    search: Option<String>,
+
+    /// Number of entries to show (server default 50, max 200).
     #[arg(long, short = 'l', default_value_t = 50)]
     limit: u32,
 
     /// Output format.
     #[arg(long, default_value = "text")]
     output: OutputFormat,
+
+    /// Re-run the local entry with this index (1 is the most recent). Arguments
+    /// after `--` are passed on to `query` / `sql`, e.g. `-- --since 1h`.
+    #[arg(long, value_name = "INDEX", conflicts_with_all = ["search", "clear"])]
+    run: Option<usize>,
+
+    /// Extra `query` / `sql` arguments for --run.
+    #[arg(last = true, requires = "run")]
+    extra: Vec<String>,
+
+    /// List the queries recorded on this machine instead of the server's
+    /// history.
+    #[arg(long)]
+    local: bool,
+
+    /// Delete the local history.
+    #[arg(long)]
+    clear: bool,
 }
 
 #[derive(Clone, Debug, clap::ValueEnum)]
@@ -36,6 +69,337 @@ enum OutputFormat {
 }
 
 pub async fn run(args: HistoryArgs, global: GlobalArgs) -> Result<()> {
+    if !args.local && args.run.is_none() && !args.clear {
+        return run_remote(args, global).await;
+    }
+    let history = History::open();
+    if args.clear {
+        history.clear().context("Failed to clear query history")?;
+        if !global.quiet {
+            eprintln!("Cleared {}", history.path().display());
+        }
+        return Ok(());
+    }
+    if let Some(index) = args.run {
+        let entry = history
+            .get(index)
+            .context("Failed to read query history")?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No history entry #{index}. List entries with 'logchef history --local'."
+                )
+            })?;
+        return rerun(&entry, index, &args.extra, global).await;
+    }
+
+    let entries: Vec<IndexedEntry> = history
+        .entries()
+        .context("Failed to read query history")?
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| IndexedEntry {
+            index: i + 1,
+            entry,
+        })
+        .filter(|e| args.search.as_deref().is_none_or(|s| e.entry.matches(s)))
+        .take(args.limit as usize)
+        .collect();
+
+    if entries.is_empty() {
+        if matches!(args.output, OutputFormat::Json) {
+            println!("[]");
+        } else if !matches!(args.output, OutputFormat::Jsonl) {
+            println!("No query history found.");
+        }
+        return Ok(());
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&entries)?);
+        }
+        OutputFormat::Jsonl => {
+            for entry in &entries {
+                println!("{}", serde_json::to_string(entry)?);
+            }
+        }
+        OutputFormat::Text => print_text(&entries),
+        OutputFormat::Table => print_table(&entries),
+    }
+
+    Ok(())
+}
+
+/// A local entry with its position, for `--run`.
+#[derive(Serialize)]
+struct IndexedEntry {
+    index: usize,
+    #[serde(flatten)]
+    entry: HistoryEntry,
+}
+
+/// Starts an entry for a query that just ran in session `s`; callers fill in
+/// the window and stats before [`record`]ing it.
+pub(crate) fn new_entry(
+    s: &AuthedSession,
+    kind: QueryKind,
+    query: &str,
+    team_id: i64,
+    source_id: i64,
+) -> HistoryEntry {
+    HistoryEntry {
+        executed_at: Utc::now(),
+        context: s.name.clone(),
+        server_url: s.ctx.server_url.clone(),
+        team_id,
+        source_id,
+        source: None,
+        kind,
+        query: query.to_string(),
+        since: None,
+        start: None,
+        end: None,
+        limit: None,
+        rows: None,
+        duration_ms: None,
+    }
+}
+
+/// Sets the entry's window: the absolute `--from`/`--to` instants when given,
+/// else the `since` lookback (if any).
+pub(crate) fn with_window(
+    mut entry: HistoryEntry,
+    since: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+    timezone: Option<&str>,
+) -> HistoryEntry {
+    match (from, to) {
+        (Some(from), Some(to)) => {
+            if let Ok((start, end)) = from_to_window(from, to, timezone) {
+                entry.start = Some(start);
+                entry.end = Some(end);
+            }
+        }
+        _ => entry.since = since.map(str::to_string),
+    }
+    entry
+}
+
+/// Appends an executed query to the local history, unless
+/// `LOGCHEF_NO_HISTORY` is set. Failures only log: history never fails the
+/// query itself.
+pub(crate) fn record(entry: &HistoryEntry) {
+    if crate::env_flags::env_off("LOGCHEF_NO_HISTORY") {
+        return;
+    }
+    if let Err(err) = History::open().record(entry) {
+        tracing::debug!("could not record query history: {err}");
+    }
+}
+
+/// Re-runs an entry through `query` / `sql`, against the context it ran in
+/// unless `--context` / `--server` say otherwise.
+async fn rerun(
+    entry: &HistoryEntry,
+    index: usize,
+    extra: &[String],
+    mut global: GlobalArgs,
+) -> Result<()> {
+    target_entry_context(entry, &mut global);
+    announce_rerun(entry, index, &global);
+    let parsed = Rerun::try_parse_from(rerun_argv(entry, extra)).map_err(|err| {
+        let message = err.render().to_string();
+        anyhow::anyhow!("{}", message.trim().trim_start_matches("error: "))
+    })?;
+    match parsed.command {
+        RerunCommand::Query(args) => query::run(*args, global).await,
+        RerunCommand::Sql(args) => sql::run(*args, global).await,
+    }
+}
+
+/// Points `global` at the context `entry` ran in, unless `--context` /
+/// `--server` already pick one.
+pub(crate) fn target_entry_context(entry: &HistoryEntry, global: &mut GlobalArgs) {
+    if global.context.is_none() && global.server.is_none() {
+        if entry.context == "(ephemeral)" {
+            global.server = Some(entry.server_url.clone());
+        } else {
+            global.context = Some(entry.context.clone());
+        }
+    }
+}
+
+pub(crate) fn announce_rerun(entry: &HistoryEntry, index: usize, global: &GlobalArgs) {
+    if !global.quiet {
+        eprintln!("Re-running #{}: {}", index, single_line(&entry.query));
+    }
+}
+
+/// The LogchefQL entry for `query --last [N]`: entry `N`, or the most recent
+/// LogchefQL one when `None`. Returns it with its index.
+pub(crate) fn logchefql_entry(index: Option<usize>) -> Result<(usize, HistoryEntry)> {
+    let entries = History::open()
+        .entries()
+        .context("Failed to read query history")?;
+    match index {
+        Some(index) => {
+            let entry = index
+                .checked_sub(1)
+                .and_then(|i| entries.into_iter().nth(i))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No history entry #{index}. List entries with 'logchef history --local'."
+                    )
+                })?;
+            if entry.kind != QueryKind::Logchefql {
+                anyhow::bail!(
+                    "History entry #{index} is a native query; re-run it with 'logchef history --run {index}'"
+                );
+            }
+            Ok((index, entry))
+        }
+        None => entries
+            .into_iter()
+            .enumerate()
+            .find(|(_, entry)| entry.kind == QueryKind::Logchefql)
+            .map(|(i, entry)| (i + 1, entry))
+            .ok_or_else(|| anyhow::anyhow!("No LogchefQL queries in the local history yet")),
+    }
+}
+
+#[derive(Parser)]
+#[command(no_binary_name = true)]
+struct Rerun {
+    #[command(subcommand)]
+    command: RerunCommand,
+}
+
+#[derive(Subcommand)]
+enum RerunCommand {
+    Query(Box<query::QueryArgs>),
+    Sql(Box<sql::SqlArgs>),
+}
+
+/// The `query` / `sql` command line that repeats `entry`, followed by
+/// `extra`. A recorded team, source, window or limit is left out when
+/// `extra` sets its own.
+fn rerun_argv(entry: &HistoryEntry, extra: &[String]) -> Vec<String> {
+    let mut argv = vec![
+        match entry.kind {
+            QueryKind::Logchefql => "query",
+            QueryKind::Native => "sql",
+        }
+        .to_string(),
+    ];
+    if !overrides(extra, &["--team", "-t"]) {
+        argv.push(format!("--team={}", entry.team_id));
+    }
+    if !overrides(extra, &["--source", "-S"]) {
+        argv.push(format!("--source={}", entry.source_id));
+    }
+    if !overrides(extra, &["--since", "-s", "--from", "--to"]) {
+        match (entry.start, entry.end, &entry.since) {
+            (Some(start), Some(end), _) => {
+                argv.push(format!("--from={}", start.to_rfc3339()));
+                argv.push(format!("--to={}", end.to_rfc3339()));
+            }
+            (_, _, Some(since)) => argv.push(format!("--since={since}")),
+            _ => {}
+        }
+    }
+    if let Some(limit) = entry.limit
+        && !overrides(extra, &["--limit", "-l"])
+    {
+        argv.push(format!("--limit={limit}"));
+    }
+    argv.extend(extra.iter().cloned());
+    argv.push("--".to_string());
+    argv.push(entry.query.clone());
+    argv
+}
+
+/// Whether `extra` sets any of `flags` (`--since 1h`, `--since=1h`, `-s1h`).
+fn overrides(extra: &[String], flags: &[&str]) -> bool {
+    extra.iter().any(|arg| {
+        flags.iter().any(|flag| {
+            if flag.starts_with("--") {
+                arg == flag || arg.starts_with(&format!("{flag}="))
+            } else {
+                arg.starts_with(flag)
+            }
+        })
+    })
+}
+
+fn print_text(entries: &[IndexedEntry]) {
+    for IndexedEntry { index, entry } in entries {
+        let mut line = format!(
+            "#{}  {}  {}  source={}  {}",
+            index,
+            relative_time(entry.executed_at),
+            entry.context,
+            entry
+                .source
+                .as_deref()
+                .unwrap_or(&entry.source_id.to_string()),
+            entry.kind.label(),
+        );
+        line.push_str(&format!("  {}", window_label(entry)));
+        if let Some(rows) = entry.rows {
+            line.push_str(&format!("  {} rows", rows));
+        }
+        if let Some(ms) = entry.duration_ms {
+            line.push_str(&format!("  {}ms", ms));
+        }
+        println!("{}", line);
+        println!("  {}", entry.query);
+    }
+    println!("\n{} queries", entries.len());
+}
+
+fn print_table(entries: &[IndexedEntry]) {
+    println!(
+        "{:<5} {:<10} {:<12} {:<12} {:<35} {:>8}  QUERY",
+        "#", "WHEN", "CONTEXT", "SOURCE", "WINDOW", "ROWS"
+    );
+    println!("{}", "-".repeat(130));
+    for IndexedEntry { index, entry } in entries {
+        println!(
+            "{:<5} {:<10} {:<12} {:<12} {:<35} {:>8}  {}",
+            index,
+            relative_time(entry.executed_at),
+            truncate_str(&entry.context, 12),
+            truncate_str(
+                entry
+                    .source
+                    .as_deref()
+                    .unwrap_or(&entry.source_id.to_string()),
+                12
+            ),
+            window_label(entry),
+            entry.rows.map(|rows| rows.to_string()).unwrap_or_default(),
+            truncate_str(&single_line(&entry.query), 50),
+        );
+    }
+    println!("\n{} queries", entries.len());
+}
+
+/// `since 1h`, `2026-07-14 09:00Z → 2026-07-14 10:00Z`-style summary of an
+/// entry's window.
+fn window_label(entry: &HistoryEntry) -> String {
+    match (entry.start, entry.end, &entry.since) {
+        (Some(start), Some(end), _) => format!(
+            "{} → {}",
+            start.format("%Y-%m-%d %H:%MZ"),
+            end.format("%Y-%m-%d %H:%MZ")
+        ),
+        (_, _, Some(since)) => format!("since {}", since),
+        _ => "no window".to_string(),
+    }
+}
+
+async fn run_remote(args: HistoryArgs, global: GlobalArgs) -> Result<()> {
     let config = Config::load().context("Failed to load config")?;
     let s = session::authed(&config, &global)?;
 
@@ -63,14 +427,14 @@ pub async fn run(args: HistoryArgs, global: GlobalArgs) -> Result<()> {
                 println!("{}", serde_json::to_string(entry)?);
             }
         }
-        OutputFormat::Text => print_text(&entries),
-        OutputFormat::Table => print_table(&entries),
+        OutputFormat::Text => print_remote_text(&entries),
+        OutputFormat::Table => print_remote_table(&entries),
     }
 
     Ok(())
 }
 
-fn print_text(entries: &[QueryHistoryEntry]) {
+fn print_remote_text(entries: &[QueryHistoryEntry]) {
     for entry in entries {
         println!(
             "#{}  {}  source={}  {}  {}ms  {} rows",
@@ -86,7 +450,7 @@ fn print_text(entries: &[QueryHistoryEntry]) {
     println!("\n{} queries", entries.len());
 }
 
-fn print_table(entries: &[QueryHistoryEntry]) {
+fn print_remote_table(entries: &[QueryHistoryEntry]) {
     println!(
         "{:<5} {:<10} {:<8} {:<10} {:>8} {:>8}  QUERY",
         "ID", "WHEN", "SOURCE", "LANG", "MS", "ROWS"
@@ -173,6 +537,46 @@ mod tests {
         assert_eq!(single_line("a\n  b\tc"), "a b c");
     }
 
+    #[test]
+    fn rerun_argv_repeats_the_entry_unless_overridden() {
+        let entry: HistoryEntry = serde_json::from_value(serde_json::json!({
+            "executed_at": "2026-07-14T09:00:00Z",
+            "context": "prod",
+            "server_url": "https://logs.example.com",
+            "team_id": 1,
+            "source_id": 7,
+            "kind": "logchefql",
+            "query": "-level=\"debug\"",
+            "since": "1h",
+            "limit": 20,
+        }))
+        .unwrap();
+        assert_eq!(
+            rerun_argv(&entry, &[]),
+            [
+                "query",
+                "--team=1",
+                "--source=7",
+                "--since=1h",
+                "--limit=20",
+                "--",
+                "-level=\"debug\""
+            ]
+        );
+        let extra = [
+            "--since".to_string(),
+            "today".to_string(),
+            "-l5".to_string(),
+        ];
+        let argv = rerun_argv(&entry, &extra);
+        assert!(
+            !argv
+                .iter()
+                .any(|arg| arg == "--since=1h" || arg == "--limit=20")
+        );
+        assert!(Rerun::try_parse_from(argv).is_ok());
+    }
+
     #[test]
     fn truncate_str_adds_ellipsis() {
         assert_eq!(truncate_str("hello world", 8), "hello...");
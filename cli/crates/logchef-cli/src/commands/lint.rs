@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use logchef_core::logchefql::{self, ParseError};
+use serde::Serialize;
+use std::io::Read;
+use std::path::PathBuf;
+
+use crate::cli::GlobalArgs;
+
+#[derive(Args)]
+#[command(after_help = "EXAMPLES:
+  # Check a query's syntax without contacting the server
+  logchef lint 'level=\"error\" and (status>=500 or service=\"api\"'
+
+  # Check every query file in a repo (exits 1 if any is invalid)
+  logchef lint -f queries/*.lcq
+
+  # From stdin, with a machine-readable result
+  cat errors.lcq | logchef lint - --output json")]
+pub struct LintArgs {
+    /// LogchefQL query to check. Use '-' to read it from stdin.
+    #[arg(conflicts_with = "files", required_unless_present = "files")]
+    query: Option<String>,
+
+    /// Check the query in each of these files instead.
+    #[arg(long = "file", short = 'f', value_name = "PATH", num_args = 1..)]
+    files: Vec<PathBuf>,
+
+    /// Output format
+    #[arg(long, default_value = "text")]
+    output: OutputFormat,
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    input: &'a str,
+    query: &'a str,
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a ParseError>,
+}
+
+pub async fn run(args: LintArgs, global: GlobalArgs) -> Result<()> {
+    let mut inputs = Vec::new();
+    match args.query {
+        Some(query) if query == "-" => {
+            let mut buffer = String::new();
+            std::io::stdin()
+                .read_to_string(&mut buffer)
+                .context("Failed to read query from stdin")?;
+            inputs.push(("<stdin>".to_string(), buffer));
+        }
+        Some(query) => inputs.push(("<query>".to_string(), query)),
+        None => {
+            for path in &args.files {
+                let query = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                inputs.push((path.display().to_string(), query));
+            }
+        }
+    }
+
+    let results: Vec<_> = inputs
+        .iter()
+        .map(|(input, query)| {
+            let query = query.trim();
+            (input.as_str(), query, logchefql::validate(query).err())
+        })
+        .collect();
+    let failed = results.iter().filter(|(_, _, err)| err.is_some()).count();
+
+    match args.output {
+        OutputFormat::Json => {
+            let output: Vec<_> = results
+                .iter()
+                .map(|(input, query, error)| JsonOutput {
+                    input,
+                    query,
+                    valid: error.is_none(),
+                    error: error.as_ref(),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Text => {
+            for (input, query, error) in &results {
+                match error {
+                    Some(err) => eprintln!(
+                        "{}:{}:{}: {}\n",
+                        input,
+                        err.position.line,
+                        err.position.column,
+                        err.render(query)
+                    ),
+                    None if !global.quiet => println!("{}: ok", input),
+                    None => {}
+                }
+            }
+            if failed > 0 && results.len() > 1 && !global.quiet {
+                eprintln!("{} of {} queries have syntax errors", failed, results.len());
+            }
+        }
+    }
+
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Rejects a query the server would fail to parse, pointing at the problem,
+/// before any request is made.
+pub(crate) fn check_syntax(query: &str) -> Result<()> {
+    logchefql::validate(query).map_err(|err| {
+        anyhow::anyhow!(
+            "Invalid LogchefQL: {}\n\nSkip this check with --no-lint if the server accepts syntax this CLI doesn't know yet.",
+            err.render(query)
+        )
+    })
+}
@@ -1,15 +1,19 @@
 pub mod auth;
+pub mod browse;
 pub mod collections;
 pub mod completions;
 pub mod config;
 pub mod doctor;
 pub mod explain;
+pub mod export;
 pub mod fields;
 pub mod find;
 pub mod histogram;
 pub mod history;
+pub mod lint;
 pub mod open;
 pub mod query;
+pub mod saved;
 pub mod saved_queries;
 pub mod schema;
 pub mod skills;
@@ -17,44 +21,212 @@ pub mod sources;
 pub mod sql;
 pub mod tail;
 pub mod teams;
+pub mod values;
 pub mod whoami;
 
 use anyhow::{Context, Result};
 use chrono::Duration;
-use logchef_core::api::Client;
+use logchef_core::api::{Client, Column, LogEntry};
 use logchef_core::cache::{Cache, Identifier, parse_identifier};
+use logchef_core::config::{Context as CtxConfig, HighlightsConfig};
+use logchef_core::highlight::Severity;
 
-/// Parses a relative lookback string (e.g. `15m`, `1h`, `24h`, `7d`, `2w`)
-/// into a `chrono::Duration`. A bare number is treated as minutes. Shared by
-/// the commands that build a `now - lookback` window.
-pub(crate) fn parse_lookback(s: &str) -> Result<Duration> {
-    let s = s.trim();
-    if s.is_empty() {
-        return Ok(Duration::minutes(15));
-    }
-
-    let (num, unit) = if let Some(rest) = s.strip_suffix('m') {
-        (rest, "m")
-    } else if let Some(rest) = s.strip_suffix('h') {
-        (rest, "h")
-    } else if let Some(rest) = s.strip_suffix('d') {
-        (rest, "d")
-    } else if let Some(rest) = s.strip_suffix('w') {
-        (rest, "w")
-    } else {
-        (s, "m")
+/// Rejects a query window wider than the cap configured for the source
+/// (`source_max_time_range`, else the context's `max_time_range`) unless
+/// `force` is set. Guards shared environments against runaway scans.
+pub(crate) fn check_time_range_cap(
+    ctx: &CtxConfig,
+    source_id: i64,
+    source_name: Option<&str>,
+    span: Duration,
+    force: bool,
+) -> Result<()> {
+    if force {
+        return Ok(());
+    }
+    let Some(cap) = ctx.max_time_range_for(source_id, source_name) else {
+        return Ok(());
     };
+    let max = logchef_core::timerange::parse_duration(cap)
+        .with_context(|| format!("Invalid max_time_range '{}' in config", cap))?;
+    if span > max {
+        anyhow::bail!(
+            "Time range of {} exceeds the configured maximum of {} for this source. \
+             Narrow --since/--from/--to, or pass --force to run it anyway.",
+            format_span(span),
+            cap
+        );
+    }
+    Ok(())
+}
+
+/// Renders a duration in the largest whole unit used by `--since` values.
+fn format_span(span: Duration) -> String {
+    let minutes = span.num_minutes();
+    if minutes % (24 * 60) == 0 {
+        format!("{}d", minutes / (24 * 60))
+    } else if minutes % 60 == 0 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
 
-    let num: i64 = num.parse().context("Invalid duration number")?;
+/// Default `--max-display`: rows rendered in text/table/msg output before the
+/// rest is cut off with a notice. JSON output is never capped.
+pub(crate) const DISPLAY_CAP: usize = 10_000;
 
-    match unit {
-        "h" => Ok(Duration::hours(num)),
-        "d" => Ok(Duration::days(num)),
-        "w" => Ok(Duration::weeks(num)),
-        _ => Ok(Duration::minutes(num)),
+/// Truncates entries for terminal rendering to `cap` rows (0 = no cap) and
+/// says so on stderr, so a forgotten `--limit` can't flood the terminal.
+pub(crate) fn cap_for_display(entries: &[LogEntry], cap: usize) -> &[LogEntry] {
+    if cap == 0 || entries.len() <= cap {
+        return entries;
+    }
+    eprintln!(
+        "Showing first {} of {} rows. Use --max-display 0 to show all, or --output jsonl to stream them.",
+        cap,
+        entries.len()
+    );
+    &entries[..cap]
+}
+
+/// `--min-level` post-filter. Entries without a recognisable level are
+/// dropped, since their severity can't be shown to meet the threshold.
+pub(crate) fn filter_min_level(entries: &[LogEntry], min: Severity) -> Vec<LogEntry> {
+    entries
+        .iter()
+        .filter(|entry| Severity::of_entry(entry).is_some_and(|level| level >= min))
+        .cloned()
+        .collect()
+}
+
+/// Renders a value for `--output table` exactly as the server sent it:
+/// strings (including DateTime64 timestamps) are kept whole, integers of any
+/// width keep every digit, and floats are written out in positional notation
+/// rather than `1e21`-style exponents.
+pub(crate) fn table_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) if n.is_f64() => match n.as_f64() {
+            Some(f) if f.is_finite() => {
+                let s = f.to_string();
+                if s.contains('.') {
+                    s
+                } else {
+                    format!("{}.0", s)
+                }
+            }
+            _ => n.to_string(),
+        },
+        other => other.to_string(),
     }
 }
 
+/// Writes entries as RFC 4180 CSV: an optional header row of column names,
+/// then one CRLF-terminated record per entry in `columns` order. `lead` adds
+/// fixed leading columns, e.g. the variable of a collection iteration.
+/// Nulls and missing fields are empty; nested values are written as JSON.
+pub(crate) fn write_csv(
+    out: &mut impl std::io::Write,
+    entries: &[LogEntry],
+    columns: &[Column],
+    header: bool,
+    lead: &[(&str, &str)],
+) -> std::io::Result<()> {
+    if header {
+        let names = lead
+            .iter()
+            .map(|(name, _)| csv_quote(name))
+            .chain(columns.iter().map(|col| csv_quote(&col.name)));
+        write!(out, "{}\r\n", names.collect::<Vec<_>>().join(","))?;
+    }
+    for entry in entries {
+        let fields = lead
+            .iter()
+            .map(|(_, value)| csv_quote(value))
+            .chain(columns.iter().map(|col| match entry.get(&col.name) {
+                None | Some(serde_json::Value::Null) => String::new(),
+                Some(value) => csv_quote(&table_cell(value)),
+            }));
+        write!(out, "{}\r\n", fields.collect::<Vec<_>>().join(","))?;
+    }
+    out.flush()
+}
+
+/// Quotes a CSV field when it holds a comma, quote or line break, doubling
+/// any embedded quotes.
+fn csv_quote(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes entries as TSV for `cut`/`awk`/`sort`: an optional header row,
+/// then one LF-terminated line per entry in `columns` order. Tabs, line
+/// breaks and backslashes inside fields are escaped (`\t`, `\n`, `\r`,
+/// `\\`), so every line has exactly one field per column. Nulls are empty.
+pub(crate) fn write_tsv(
+    out: &mut impl std::io::Write,
+    entries: &[LogEntry],
+    columns: &[Column],
+    header: bool,
+    lead: &[(&str, &str)],
+) -> std::io::Result<()> {
+    if header {
+        let names = lead
+            .iter()
+            .map(|(name, _)| tsv_escape(name))
+            .chain(columns.iter().map(|col| tsv_escape(&col.name)));
+        writeln!(out, "{}", names.collect::<Vec<_>>().join("\t"))?;
+    }
+    for entry in entries {
+        let fields = lead
+            .iter()
+            .map(|(_, value)| tsv_escape(value))
+            .chain(columns.iter().map(|col| match entry.get(&col.name) {
+                None | Some(serde_json::Value::Null) => String::new(),
+                Some(value) => tsv_escape(&table_cell(value)),
+            }));
+        writeln!(out, "{}", fields.collect::<Vec<_>>().join("\t"))?;
+    }
+    out.flush()
+}
+
+fn tsv_escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `--dim-unmatched` needs at least one user rule to tell matches from
+/// context; otherwise every line would be dimmed.
+pub(crate) fn check_dim_unmatched(
+    enabled: bool,
+    adhoc: &[String],
+    config: &HighlightsConfig,
+) -> Result<()> {
+    if enabled
+        && adhoc.is_empty()
+        && config.custom_keywords.is_empty()
+        && config.custom_regexes.is_empty()
+    {
+        anyhow::bail!(
+            "--dim-unmatched needs a rule to match: pass --highlight COLOR:WORDS or configure highlights.custom_keywords / custom_regexes"
+        );
+    }
+    Ok(())
+}
+
 /// Resolves a team identifier (ID or name) to a team ID, populating the cache
 /// on a name lookup. Shared by the non-interactive commands.
 pub(crate) async fn resolve_team(
@@ -90,6 +262,43 @@ pub(crate) async fn resolve_team(
     }
 }
 
+/// The ID cached for source `name` in `team_id`, once the server confirms
+/// it still has that source under that name. A deleted, recreated or renamed
+/// source is dropped from the cache so the caller lists sources again; a
+/// source that's still there has its version tag recorded as a listing would.
+pub(crate) async fn cached_source_id(
+    client: &Client,
+    cache: &mut Cache,
+    team_id: i64,
+    name: &str,
+) -> Result<Option<i64>> {
+    let Some(id) = cache.get_source_id(team_id, name) else {
+        return Ok(None);
+    };
+    match client.get_source(team_id, id).await {
+        Ok(source)
+            if source.name.eq_ignore_ascii_case(name)
+                || source
+                    .target_ref()
+                    .is_some_and(|target| target.eq_ignore_ascii_case(name)) =>
+        {
+            cache.observe_source(&source);
+            Ok(Some(id))
+        }
+        Ok(_) => {
+            tracing::debug!(id, name, "cached source was renamed; resolving again");
+            cache.forget_source(id);
+            Ok(None)
+        }
+        Err(err) if err.kind() == logchef_core::ErrorKind::NotFound => {
+            tracing::debug!(id, name, "cached source ID is gone; resolving again");
+            cache.forget_source(id);
+            Ok(None)
+        }
+        Err(err) => Err(err).context("Failed to fetch source"),
+    }
+}
+
 /// Resolves a source identifier (ID, name, or target ref) to a source ID
 /// within a team, populating the cache on a name lookup. Shared by the
 /// non-interactive commands.
@@ -108,7 +317,7 @@ pub(crate) async fn resolve_source(
     match parse_identifier(&source) {
         Identifier::Id(id) => Ok(id),
         Identifier::Name(name) => {
-            if let Some(id) = cache.get_source_id(team_id, &name) {
+            if let Some(id) = cached_source_id(client, cache, team_id, &name).await? {
                 return Ok(id);
             }
             let sources = client
@@ -124,6 +333,7 @@ pub(crate) async fn resolve_source(
                     cache_entries.push((target_ref, source.id));
                 }
             }
+            cache.observe_sources(team_id, &sources);
             cache.set_sources(team_id, &cache_entries);
             sources
                 .iter()
@@ -141,3 +351,126 @@ pub(crate) async fn resolve_source(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_quotes_and_orders_by_columns() {
+        let columns: Vec<Column> = serde_json::from_value(serde_json::json!([
+            {"name": "msg", "type": "String"},
+            {"name": "status", "type": "UInt16"},
+            {"name": "tags", "type": "Array(String)"},
+            {"name": "user", "type": "Nullable(String)"}
+        ]))
+        .unwrap();
+        let entries: Vec<LogEntry> = vec![
+            serde_json::from_value(serde_json::json!({
+                "user": null,
+                "status": 500,
+                "msg": "said \"no\", twice\nthen quit",
+                "tags": ["a", "b"]
+            }))
+            .unwrap(),
+            serde_json::from_value(serde_json::json!({"msg": "ok"})).unwrap(),
+        ];
+        let mut out = Vec::new();
+        write_csv(&mut out, &entries, &columns, true, &[("svc", "api")]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "svc,msg,status,tags,user\r\n\
+             api,\"said \"\"no\"\", twice\nthen quit\",500,\"[\"\"a\"\",\"\"b\"\"]\",\r\n\
+             api,ok,,,\r\n"
+        );
+    }
+
+    #[test]
+    fn tsv_escapes_separators_inside_fields() {
+        let columns: Vec<Column> = serde_json::from_value(serde_json::json!([
+            {"name": "msg", "type": "String"},
+            {"name": "path", "type": "String"},
+            {"name": "user", "type": "Nullable(String)"}
+        ]))
+        .unwrap();
+        let entries: Vec<LogEntry> = vec![
+            serde_json::from_value(serde_json::json!({
+                "msg": "a\tb\nc",
+                "path": "C:\\tmp",
+                "user": null
+            }))
+            .unwrap(),
+        ];
+        let mut out = Vec::new();
+        write_tsv(&mut out, &entries, &columns, true, &[]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "msg\tpath\tuser\na\\tb\\nc\tC:\\\\tmp\t\n"
+        );
+    }
+
+    #[test]
+    fn table_cell_keeps_full_precision() {
+        let entry: LogEntry = serde_json::from_str(
+            r#"{
+                "ts": "2026-07-14 09:00:00.123456789",
+                "big": 18446744073709551615,
+                "neg": -9223372036854775808,
+                "huge": 1e21,
+                "tiny": 1.5e-7,
+                "ratio": 0.1,
+                "whole": 2.0
+            }"#,
+        )
+        .unwrap();
+        let cell = |key: &str| table_cell(&entry[key]);
+        assert_eq!(cell("ts"), "2026-07-14 09:00:00.123456789");
+        assert_eq!(cell("big"), "18446744073709551615");
+        assert_eq!(cell("neg"), "-9223372036854775808");
+        assert_eq!(cell("huge"), "1000000000000000000000.0");
+        assert_eq!(cell("tiny"), "0.00000015");
+        assert_eq!(cell("ratio"), "0.1");
+        assert_eq!(cell("whole"), "2.0");
+    }
+
+    #[tokio::test]
+    async fn stale_cached_source_is_resolved_again() {
+        use logchef_core::api::MockTransport;
+        use reqwest::Method;
+        use serde_json::json;
+
+        let server = format!("http://stale-source-{}.test", std::process::id());
+        let mut cache = Cache::new(&server);
+        cache.set_sources(1, &[("app".to_string(), 5)]);
+        let source = |id: i64| json!({"id": id, "name": "app", "source_type": "clickhouse"});
+        let client = Client::with_transport(
+            &server,
+            MockTransport::new()
+                .on(
+                    Method::GET,
+                    "/api/v1/teams/1/sources/5",
+                    404,
+                    json!({"status": "error", "message": "source not found"}),
+                )
+                .on(
+                    Method::GET,
+                    "/api/v1/teams/1/sources",
+                    200,
+                    json!({"status": "success", "data": [source(9)]}),
+                )
+                .on(
+                    Method::GET,
+                    "/api/v1/teams/1/sources/9",
+                    200,
+                    json!({"status": "success", "data": source(9)}),
+                ),
+        );
+
+        let resolved = resolve_source(&client, &mut cache, 1, Some("app".to_string())).await;
+        assert_eq!(resolved.unwrap(), 9);
+        // Cached now, and confirmed rather than listed again.
+        let resolved = resolve_source(&client, &mut cache, 1, Some("app".to_string())).await;
+        assert_eq!(resolved.unwrap(), 9);
+        cache.clear();
+    }
+}
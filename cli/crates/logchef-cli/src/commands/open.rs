@@ -2,7 +2,7 @@ use anyhow::{Context, Result, bail};
 use clap::Args;
 use logchef_core::Config;
 use logchef_core::cache::Cache;
-use logchef_core::timerange::wall_clock_to_epoch_millis;
+use logchef_core::timerange::from_to_window;
 use url::Url;
 
 use crate::cli::GlobalArgs;
@@ -43,11 +43,12 @@ pub struct OpenArgs {
     #[arg(long, short = 's')]
     since: Option<String>,
 
-    /// Absolute start, 'YYYY-MM-DD HH:MM:SS' in the effective timezone. Requires --to.
+    /// Absolute start (RFC3339, 'YYYY-MM-DD HH:MM:SS' in the effective
+    /// timezone, epoch ms, or '2 hours ago'). Requires --to.
     #[arg(long)]
     from: Option<String>,
 
-    /// Absolute end, 'YYYY-MM-DD HH:MM:SS' in the effective timezone. Requires --from.
+    /// Absolute end, in the same formats as --from. Requires --from.
     #[arg(long)]
     to: Option<String>,
 
@@ -98,14 +99,9 @@ pub async fn run(args: OpenArgs, global: GlobalArgs) -> Result<()> {
 
     match (args.from.as_deref(), args.to.as_deref()) {
         (Some(from), Some(to)) => {
-            let tz = ctx.defaults.timezone.as_deref();
-            let start = wall_clock_to_epoch_millis(from, tz).with_context(|| {
-                format!("Invalid --from '{from}' (expected 'YYYY-MM-DD HH:MM:SS')")
-            })?;
-            let end = wall_clock_to_epoch_millis(to, tz)
-                .with_context(|| format!("Invalid --to '{to}' (expected 'YYYY-MM-DD HH:MM:SS')"))?;
-            pairs.push(("start", start.to_string()));
-            pairs.push(("end", end.to_string()));
+            let (start, end) = from_to_window(from, to, ctx.defaults.timezone.as_deref())?;
+            pairs.push(("start", start.timestamp_millis().to_string()));
+            pairs.push(("end", end.timestamp_millis().to_string()));
         }
         (Some(_), None) | (None, Some(_)) => bail!("--from and --to must be provided together"),
         (None, None) => {
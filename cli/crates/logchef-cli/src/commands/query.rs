@@ -3,20 +3,32 @@ use chrono::{Duration, Utc};
 use clap::Args;
 use inquire::{Select, Text};
 use logchef_core::Config;
-use logchef_core::api::{Client, Column, QueryRequest, QueryStats};
+use logchef_core::api::{
+    CONTEXT_FIELD, Client, CollectionAbsoluteTime, CollectionQueryContent, CollectionTimeRange,
+    Column, CreateSavedQueryRequest, LogEntry, QueryPage, QueryRequest, QueryResponse, QueryStats,
+    SOURCE_FIELD, TranslateRequest,
+};
 use logchef_core::cache::{Cache, Identifier, parse_identifier};
+use logchef_core::config::{HighlightsConfig, SavedQuery};
 use logchef_core::highlight::{
-    FormatOptions, HighlightOptions, Highlighter, format_log_entry_with_options,
+    ContextColumn, FieldWidths, FormatOptions, HighlightOptions, Highlighter, LineTemplate,
+    RepeatCollapser, Severity, TimeDisplay, format_log_entry_with_options, strip_ansi,
+};
+use logchef_core::history::{HistoryEntry, QueryKind};
+use logchef_core::timerange::{
+    TimeInput, display_timezone, from_to_window, parse_duration, resolve_time_range, since_window,
 };
-use logchef_core::timerange::{TimeInput, resolve_time_range};
 use serde::Serialize;
-use std::io::IsTerminal;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, IsTerminal, Read, Seek, Write};
+use std::path::{Path, PathBuf};
 
 use crate::cli::GlobalArgs;
+use crate::commands::history;
 use crate::session;
 use crate::ui;
 
-#[derive(Args)]
+#[derive(Args, Clone)]
 #[command(after_help = "EXAMPLES:
   # Errors from the api service in the last hour (LogchefQL)
   logchef query 'level=\"error\" and service=\"api\"' --since 1h
@@ -26,46 +38,200 @@ use crate::ui;
     --limit 500 --output jsonl | jq 'select(.status >= 500)'
 
   # See the ClickHouse SQL / LogsQL a query compiles to, then run it
-  logchef query 'status>=500' --since 15m --show-sql")]
+  logchef query 'status>=500' --since 15m --show-sql
+
+  # Only these columns, in this order
+  logchef query 'level=\"error\"' --since 1h --fields timestamp,service,msg
+
+  # Every matching row in the window, fetched page by page
+  logchef query 'level=\"error\"' --since 24h --all --limit 5000 --output jsonl
+
+  # Render a downloaded export locally, following it as it grows
+  logchef query --watch-file export.jsonl --follow --min-level warn
+
+  # A long query kept in a file (or piped in with -f -)
+  logchef query -f ./queries/errors.lcq --since 1h
+
+  # Post-deploy gate: exit 3 if any 5xx was logged in the last 10 minutes
+  logchef query 'status>=500' --since 10m --count --fail-if-matches
+
+  # One incident, two services: merged by time, each line tagged with its source
+  logchef query 'trace_id=\"4bf92f35\"' --source api-logs,worker-logs --since 1h
+
+  # The same query on every regional server, interleaved by time
+  logchef query 'status>=500' --context prod-eu,prod-us --since 30m
+
+  # Keep an eye on 5xx during a deploy, refreshed every 30 seconds
+  logchef query 'status>=500' --since 10m --watch 30s")]
 pub struct QueryArgs {
+    /// LogchefQL query. Use '-' to read from stdin.
     query: Option<String>,
 
+    /// Read the LogchefQL query from a file ('-' for stdin) instead of the
+    /// command line.
+    #[arg(
+        long,
+        short = 'f',
+        value_name = "PATH",
+        conflicts_with_all = ["query", "watch_file"]
+    )]
+    file: Option<PathBuf>,
+
+    /// Re-run the most recent LogchefQL query from `logchef history --local`,
+    /// or entry N. Flags given alongside (e.g. --since 1h) override the
+    /// recorded ones. A query of `!!` or `!N` does the same.
+    #[arg(long, value_name = "N", conflicts_with_all = ["query", "file", "watch_file"])]
+    last: Option<Option<usize>>,
+
+    /// Run a query saved with `logchef saved add`. Its team, source and
+    /// lookback apply unless given here.
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["query", "file", "last", "watch_file"]
+    )]
+    saved: Option<String>,
+
     /// Relative lookback window (e.g. 15m, 1h, 24h) evaluated against now,
+    /// or a named window: today, yesterday, this-week, last-week,
+    /// this-month, last-month, last-hour. Day, week and month boundaries are
     /// in the effective timezone: `defaults.timezone` if configured,
     /// otherwise the system's local timezone (see `logchef config show`).
     #[arg(long, short = 's')]
     since: Option<String>,
 
-    /// Absolute start time (YYYY-MM-DD HH:MM:SS), interpreted as wall-clock
-    /// in the effective timezone. Requires --to.
+    /// Absolute start time: RFC3339, `YYYY-MM-DD HH:MM:SS` or a date
+    /// (wall-clock in the effective timezone), epoch milliseconds, or a
+    /// relative time like "2 hours ago". Requires --to.
     #[arg(long)]
     from: Option<String>,
 
-    /// Absolute end time (YYYY-MM-DD HH:MM:SS), interpreted as wall-clock
-    /// in the effective timezone. Requires --from.
+    /// Absolute end time, in the same formats as --from (e.g. `now`).
+    /// Requires --from.
     #[arg(long)]
     to: Option<String>,
 
     #[arg(long, short = 't')]
     team: Option<String>,
 
+    /// Source ID or name. Several comma-separated sources (or `all`) run the
+    /// query against each concurrently and merge the results newest first,
+    /// tagging every row with its source.
     #[arg(long, short = 'S')]
     source: Option<String>,
 
+    /// Maximum number of results. With --limit-per-source, caps the merged
+    /// output instead of each fetch.
     #[arg(long, short = 'l')]
     limit: Option<u32>,
 
-    #[arg(long, default_value = "text")]
+    /// Maximum number of rows to fetch from each source. Bounds every
+    /// per-source query independently, so one noisy source can't crowd out
+    /// the others; --limit then caps the merged output.
+    #[arg(long, value_name = "N")]
+    limit_per_source: Option<u32>,
+
+    #[arg(long, default_value = "text", env = "LOGCHEF_OUTPUT")]
     output: OutputFormat,
 
+    /// Rows to render in text, table and msg output before cutting off with
+    /// a notice (0 = no cap). JSON output always includes every row.
+    #[arg(long, value_name = "N", default_value_t = super::DISPLAY_CAP)]
+    max_display: usize,
+
+    /// With `--output json`, stream the logs as a bare JSON array, one
+    /// element at a time, instead of building the pretty-printed document.
+    #[arg(long)]
+    array_stream: bool,
+
+    /// In JSON output, write every column on every entry, with `null` where
+    /// the row has no value, so all objects share one key set.
+    #[arg(long, conflicts_with = "omit_nulls")]
+    include_nulls: bool,
+
+    /// In JSON output, drop fields whose value is `null`.
+    #[arg(long)]
+    omit_nulls: bool,
+
     #[arg(long)]
     no_highlight: bool,
 
+    /// Run highlighting even when stdout isn't a terminal, but remove the ANSI
+    /// escape codes before writing text output.
+    #[arg(long, conflicts_with = "no_highlight")]
+    strip_ansi: bool,
+
     #[arg(long)]
     no_timestamp: bool,
 
+    /// Show each entry's age (`2m ago`) instead of its timestamp in text
+    /// output; `--relative-time=both` keeps the timestamp and appends the age.
+    #[arg(
+        long,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "relative"
+    )]
+    relative_time: Option<TimeDisplay>,
+
+    /// Pad timestamp and level into fixed-width columns in text output
+    #[arg(long)]
+    aligned: bool,
+
+    /// Dim text-output lines that match none of your own highlight rules
+    /// (`--highlight` words, custom keywords/regexes), so matches stand out.
+    #[arg(long, alias = "highlight-matches-only-color")]
+    dim_unmatched: bool,
+
+    /// Collapse consecutive identical messages within this window (e.g. 5s,
+    /// 1m) into one line with a repeat count, in text output
+    #[arg(long, value_name = "DURATION")]
+    dedupe_window: Option<String>,
+
+    /// Keep only entries whose `level`/`severity` is at or above this
+    /// (trace, debug, info, warn, error, fatal); filtered client-side.
+    #[arg(long, value_name = "LEVEL")]
+    min_level: Option<Severity>,
+
+    /// Print only field values in text output, without `key=` prefixes.
+    #[arg(long)]
+    no_key_names: bool,
+
+    /// In text output, sort the fetched entries by this field's value and
+    /// print a `=== field: value ===` header before each group. Display-only;
+    /// no server-side aggregation.
+    #[arg(long, value_name = "FIELD")]
+    group_by_field: Option<String>,
+
+    /// In text output, hide columns whose value is the same on every returned
+    /// row; timestamp, level and message are always shown.
+    #[arg(long, conflicts_with = "watch_file")]
+    columns_auto: bool,
+
+    /// In text output, lead each line with this field (e.g. `host`) as a
+    /// fixed-width, colored column, so you can track where each line came
+    /// from.
+    #[arg(long, value_name = "FIELD", conflicts_with = "watch_file")]
+    context_column: Option<String>,
+
+    /// In text output, pad each field to its widest value across the results
+    /// so `key=value` parts line up in columns.
+    #[arg(long, conflicts_with = "watch_file")]
+    inline_kv_align: bool,
+
+    /// Render each row with a template instead of the default text layout,
+    /// e.g. `'{timestamp} [{level}] {msg}'`. `{a.b}` reads nested fields,
+    /// `{a|b|"-"}` falls back to another field or a literal.
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["output", "watch_file", "context_column", "inline_kv_align", "columns_auto"]
+    )]
+    format: Option<String>,
+
     /// Trace the server-generated query on stderr after executing. Use
-    /// `--dry-run` to print the query and exit without keeping the results.
+    /// `--dry-run` to print the query and exit without running it.
     #[arg(
         long,
         visible_alias = "explain",
@@ -73,19 +239,138 @@ pub struct QueryArgs {
     )]
     show_sql: bool,
 
-    /// Print the server-generated SQL to stdout and exit. (The server is
-    /// still called once to translate LogChefQL.)
+    /// Fetch every log line of a trace: filters on `defaults.trace_id_column`
+    /// (`trace_id` unless configured), widens the default lookback to
+    /// `defaults.trace_since` (7d), and prints oldest first. Combined with
+    /// any query given.
+    #[arg(long, value_name = "ID", conflicts_with = "watch_file")]
+    trace_id: Option<String>,
+
+    /// Show only the display fields saved in this collection (ID or name,
+    /// looked up on the query's source).
+    #[arg(long, value_name = "COLLECTION")]
+    fields_from_collection: Option<String>,
+
+    /// Show only these columns (comma-separated), in this order. Text output
+    /// prints them as listed instead of leading with timestamp, level and
+    /// message. Names are checked against the source schema.
+    #[arg(
+        long,
+        value_name = "FIELDS",
+        value_delimiter = ',',
+        conflicts_with_all = ["fields_from_collection", "watch_file"]
+    )]
+    fields: Vec<String>,
+
+    /// Read log lines from a local file instead of the server and render them
+    /// like query results: JSON lines through the text formatter, anything
+    /// else highlighted as-is. No server or login involved.
+    #[arg(long, value_name = "PATH", conflicts_with = "query")]
+    watch_file: Option<PathBuf>,
+
+    /// With --watch-file, keep reading as the file grows (like `tail -f`).
+    #[arg(long, requires = "watch_file")]
+    follow: bool,
+
+    /// Report on stderr whether the team and source IDs came from the local
+    /// resolve cache (and how old it is) or a fresh API lookup.
+    #[arg(long)]
+    explain_cache: bool,
+
+    /// Print the full backend query the server would run (time range and
+    /// limit included) to stdout and exit. The query is only translated,
+    /// never executed, so no logs are read.
     #[arg(long)]
     dry_run: bool,
 
+    /// Print only the number of matching logs in the time range. Counted
+    /// server-side, so no rows are transferred; `--limit` doesn't apply.
+    #[arg(
+        long,
+        conflicts_with_all = ["watch_file", "dry_run", "save_as", "show_sql", "all"]
+    )]
+    count: bool,
+
+    /// Send the query without checking its syntax locally first.
+    #[arg(long)]
+    no_lint: bool,
+
+    /// Re-run the query every INTERVAL (e.g. 30s, 5m) until interrupted. On
+    /// a terminal the screen is redrawn under a last-updated banner; when
+    /// piped, only rows newer than the previous run are printed.
+    #[arg(
+        long,
+        value_name = "INTERVAL",
+        conflicts_with_all = ["watch_file", "dry_run", "save_as", "all"]
+    )]
+    watch: Option<String>,
+
+    /// With --watch, keep earlier output on screen and print only the rows
+    /// that arrived since the previous run.
+    #[arg(long, requires = "watch")]
+    watch_append: bool,
+
+    /// Exit with status 3 if the query matches any logs. A JSON summary of
+    /// the check is printed on stderr either way.
+    #[arg(
+        long,
+        conflicts_with_all = ["fail_if_empty", "min_count", "watch", "watch_file", "dry_run", "all"]
+    )]
+    fail_if_matches: bool,
+
+    /// Exit with status 3 if the query matches no logs.
+    #[arg(long, conflicts_with_all = ["watch", "watch_file", "dry_run", "all"])]
+    fail_if_empty: bool,
+
+    /// Exit with status 3 if fewer than N logs match. Without --count, N
+    /// can't exceed --limit, since only that many rows are fetched.
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["watch", "watch_file", "dry_run", "all"]
+    )]
+    min_count: Option<u64>,
+
+    /// Set on every --watch run after the first, so history gets one entry.
+    #[arg(skip)]
+    rerun: bool,
+
     #[arg(long = "highlight", value_name = "COLOR:WORDS")]
     highlights: Vec<String>,
 
     #[arg(long = "disable-highlight", value_name = "GROUP")]
     disable_highlights: Vec<String>,
 
+    /// Load extra highlight rules from a JSON or TOML file (same shape as the
+    /// config's `highlights` section) and add them to the configured ones
+    /// for this run only.
+    #[arg(long, value_name = "FILE")]
+    highlight_config: Option<PathBuf>,
+
     #[arg(long, default_value = "30")]
     timeout: u32,
+
+    /// Run even if the time range exceeds the configured `max_time_range`.
+    #[arg(long)]
+    force: bool,
+
+    /// Save this query (with its time range and limit) as a collection
+    /// after it runs successfully
+    #[arg(long, value_name = "NAME")]
+    save_as: Option<String>,
+
+    /// Fetch every matching row in the time range, one --limit-sized page at
+    /// a time, printing each page as it arrives. Stops at the context's
+    /// `defaults.all_max_rows` (1,000,000 unless set).
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "watch_file", "trace_id", "dry_run", "save_as", "show_sql", "group_by_field",
+            "columns_auto", "context_column", "inline_kv_align", "dedupe_window",
+            "array_stream", "limit_per_source",
+        ]
+    )]
+    all: bool,
 }
 
 #[derive(Clone, Debug, clap::ValueEnum)]
@@ -94,6 +379,8 @@ enum OutputFormat {
     Json,
     Jsonl,
     JsonFlat,
+    Csv,
+    Tsv,
     Table,
     Msg,
 }
@@ -114,14 +401,97 @@ struct JsonOutput<'a> {
     columns: &'a [Column],
 }
 
-pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
-    let config = Config::load().context("Failed to load config")?;
+pub async fn run(mut args: QueryArgs, mut global: GlobalArgs) -> Result<()> {
+    if let Some(query) = read_query_input(args.query.as_deref(), args.file.as_deref())? {
+        args.query = Some(query);
+    }
+    let recall = args
+        .last
+        .or_else(|| args.query.as_deref().and_then(history_reference));
+    if let Some(index) = recall {
+        let (index, entry) = history::logchefql_entry(index)?;
+        apply_history_entry(&mut args, &entry);
+        history::target_entry_context(&entry, &mut global);
+        history::announce_rerun(&entry, index, &global);
+    }
+    if let Some(interval) = args.watch.take() {
+        return watch(args, global, &interval).await;
+    }
+    if args.array_stream && !matches!(args.output, OutputFormat::Json) {
+        anyhow::bail!("--array-stream requires --output json");
+    }
+    if (args.include_nulls || args.omit_nulls)
+        && !matches!(
+            args.output,
+            OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::JsonFlat
+        )
+    {
+        anyhow::bail!("--include-nulls and --omit-nulls require --output json, jsonl or json-flat");
+    }
+    if args.group_by_field.is_some() && !matches!(args.output, OutputFormat::Text) {
+        anyhow::bail!("--group-by-field requires --output text");
+    }
+    if args.columns_auto && !matches!(args.output, OutputFormat::Text) {
+        anyhow::bail!("--columns-auto requires --output text");
+    }
+    if args.context_column.is_some() && !matches!(args.output, OutputFormat::Text) {
+        anyhow::bail!("--context-column requires --output text");
+    }
+    if args.inline_kv_align && !matches!(args.output, OutputFormat::Text) {
+        anyhow::bail!("--inline-kv-align requires --output text");
+    }
+    let dedupe_window = args
+        .dedupe_window
+        .as_deref()
+        .map(parse_window)
+        .transpose()?;
+    let template = args
+        .format
+        .as_deref()
+        .map(LineTemplate::parse)
+        .transpose()
+        .context("Invalid --format template")?;
+
+    let mut config = Config::load().context("Failed to load config")?;
+    if let Some(path) = &args.highlight_config {
+        config
+            .highlights
+            .merge(HighlightsConfig::load_from(path).context("Failed to load --highlight-config")?);
+    }
+    super::check_dim_unmatched(args.dim_unmatched, &args.highlights, &config.highlights)?;
+    if let Some(path) = &args.watch_file {
+        if dedupe_window.is_some() {
+            anyhow::bail!("--dedupe-window is not supported with --watch-file");
+        }
+        if args.group_by_field.is_some() {
+            anyhow::bail!("--group-by-field is not supported with --watch-file");
+        }
+        return view_local_file(path, &args, &config, &global).await;
+    }
+    if let Some(name) = &args.saved {
+        let saved = super::saved::lookup(&config, &global, name)?;
+        apply_saved_query(&mut args, saved);
+    }
+    if let Some(query) = &args.query
+        && !args.no_lint
+    {
+        super::lint::check_syntax(query)?;
+    }
+    // `--context a,b`: the first context drives the run (defaults, timezone,
+    // formatting) and the others are queried alongside it.
+    let mut other_contexts = Vec::new();
+    if let Some(names) = global.context.as_deref().and_then(context_list) {
+        check_fan_out_flags(&args)?;
+        global.context = names.first().cloned();
+        other_contexts = names[1..].to_vec();
+    }
     let s = session::authed(&config, &global)?;
     let (client, ctx) = (&s.client, &s.ctx);
 
     let mut cache = Cache::new(&ctx.server_url);
     let default_team = ctx.defaults.team_with_env();
     let default_source = ctx.defaults.source_with_env();
+    let source_name = args.source.clone().or_else(|| default_source.clone());
 
     // Detect interactive mode: no query provided, no team/source args, and running in a TTY
     let is_interactive = args.query.is_none()
@@ -135,18 +505,29 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
     let team_id = if is_interactive {
         prompt_team_interactive(client, &mut cache).await?
     } else {
-        let team_input = args.team.or(default_team).ok_or_else(|| {
+        let team_input = args.team.clone().or(default_team).ok_or_else(|| {
             anyhow::anyhow!(
                 "Team not specified. Use --team or set defaults.team. List teams with 'logchef teams'."
             )
         })?;
 
         match parse_identifier(&team_input) {
-            Identifier::Id(id) => id,
+            Identifier::Id(id) => {
+                explain_cache(args.explain_cache, "team", &team_input, id, "numeric ID");
+                id
+            }
             Identifier::Name(name) => {
                 if let Some(id) = cache.get_team_id(&name) {
+                    explain_cache(
+                        args.explain_cache,
+                        "team",
+                        &name,
+                        id,
+                        &cache_outcome(&cache, true),
+                    );
                     id
                 } else {
+                    let outcome = cache_outcome(&cache, false);
                     let teams = client.list_teams().await.context("Failed to list teams")?;
                     cache.set_teams(
                         &teams
@@ -154,32 +535,65 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
                             .map(|t| (t.name.clone(), t.id))
                             .collect::<Vec<_>>(),
                     );
-                    teams
+                    let id = teams
                         .iter()
                         .find(|t| t.name.eq_ignore_ascii_case(&name))
                         .map(|t| t.id)
-                        .ok_or_else(|| anyhow::anyhow!("Team '{}' not found", name))?
+                        .ok_or_else(|| anyhow::anyhow!("Team '{}' not found", name))?;
+                    explain_cache(args.explain_cache, "team", &name, id, &outcome);
+                    id
                 }
             }
         }
     };
 
-    // Resolve source
-    let source_id = if is_interactive {
+    // Resolve source(s); several are queried side by side and merged
+    let sources = match source_name.as_deref().and_then(source_list) {
+        Some(_) if !other_contexts.is_empty() => {
+            anyhow::bail!("Fan out across several contexts or several sources, not both")
+        }
+        Some(list) => {
+            check_fan_out_flags(&args)?;
+            Some(fan_out_sources(client, &mut cache, team_id, &list).await?)
+        }
+        None => None,
+    };
+    let source_id = if let Some((id, _)) = sources.as_ref().and_then(|s| s.first()) {
+        *id
+    } else if is_interactive {
         prompt_source_interactive(client, team_id, &mut cache).await?
     } else {
-        let source_input = args.source.or(default_source).ok_or_else(|| {
+        let source_input = args.source.clone().or(default_source).ok_or_else(|| {
             anyhow::anyhow!(
                 "Source not specified. Use --source or set defaults.source. List sources with 'logchef sources --team <team>'."
             )
         })?;
 
         match parse_identifier(&source_input) {
-            Identifier::Id(id) => id,
+            Identifier::Id(id) => {
+                explain_cache(
+                    args.explain_cache,
+                    "source",
+                    &source_input,
+                    id,
+                    "numeric ID",
+                );
+                id
+            }
             Identifier::Name(name) => {
-                if let Some(id) = cache.get_source_id(team_id, &name) {
+                if let Some(id) =
+                    super::cached_source_id(client, &mut cache, team_id, &name).await?
+                {
+                    explain_cache(
+                        args.explain_cache,
+                        "source",
+                        &name,
+                        id,
+                        &cache_outcome(&cache, true),
+                    );
                     id
                 } else {
+                    let outcome = cache_outcome(&cache, false);
                     let sources = client
                         .list_sources(team_id)
                         .await
@@ -192,9 +606,10 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
                             cache_entries.push((target_ref, s.id));
                         }
                     }
+                    cache.observe_sources(team_id, &sources);
                     cache.set_sources(team_id, &cache_entries);
 
-                    sources
+                    let id = sources
                         .iter()
                         .find(|s| s.name.eq_ignore_ascii_case(&name))
                         .or_else(|| {
@@ -205,13 +620,18 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
                             })
                         })
                         .map(|s| s.id)
-                        .ok_or_else(|| anyhow::anyhow!("Source '{}' not found", name))?
+                        .ok_or_else(|| anyhow::anyhow!("Source '{}' not found", name))?;
+                    explain_cache(args.explain_cache, "source", &name, id, &outcome);
+                    id
                 }
             }
         }
     };
 
-    let since = args.since.unwrap_or_else(|| ctx.defaults.since.clone());
+    let since = args.since.clone().unwrap_or_else(|| match args.trace_id {
+        Some(_) => ctx.defaults.trace_since().to_string(),
+        None => ctx.defaults.since.clone(),
+    });
     let limit = args.limit.unwrap_or(ctx.defaults.limit);
 
     let time_range = parse_time_range(
@@ -220,39 +640,194 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
         args.to.as_deref(),
         ctx.defaults.timezone.as_deref(),
     )?;
+    let mut remotes = Vec::with_capacity(other_contexts.len());
+    for name in &other_contexts {
+        remotes.push(remote_context(&config, &global, name, &args).await?);
+    }
+    let tag = if sources.is_some() {
+        Some(SOURCE_FIELD)
+    } else if !remotes.is_empty() {
+        Some(CONTEXT_FIELD)
+    } else {
+        None
+    };
+
+    let span = time_range_span(
+        &since,
+        args.from.as_deref(),
+        args.to.as_deref(),
+        ctx.defaults.timezone.as_deref(),
+    )?;
+    match &sources {
+        Some(sources) => {
+            for (id, name) in sources {
+                super::check_time_range_cap(ctx, *id, Some(name), span, args.force)?;
+            }
+        }
+        None => {
+            super::check_time_range_cap(ctx, source_id, source_name.as_deref(), span, args.force)?
+        }
+    }
+
+    let fields = match &args.fields_from_collection {
+        Some(name) => Some(collection_fields(client, team_id, source_id, name).await?),
+        // Fields differ between sources; one missing from a source is left
+        // empty on its rows rather than rejected.
+        None if !args.fields.is_empty() && tag.is_some() => Some(args.fields.clone()),
+        None if !args.fields.is_empty() => {
+            let schema = client
+                .get_schema(team_id, source_id)
+                .await
+                .context("Failed to fetch source schema")?;
+            check_fields(&args.fields, &schema)?;
+            Some(args.fields.clone())
+        }
+        None => None,
+    };
 
     // Resolve query (prompt in interactive mode if not provided)
-    let query = if is_interactive && args.query.is_none() {
-        prompt_query_interactive()?
+    let query = if is_interactive && args.query.is_none() && args.trace_id.is_none() {
+        let query = prompt_query_interactive()?;
+        if !args.no_lint {
+            super::lint::check_syntax(&query)?;
+        }
+        query
     } else {
-        args.query.unwrap_or_default()
+        args.query.clone().unwrap_or_default()
+    };
+    let query = match &args.trace_id {
+        Some(id) => trace_query(ctx.defaults.trace_id_column(), id, &query),
+        None => query,
     };
 
+    if args.count {
+        let range = super::histogram::resolve_instants(
+            &since,
+            args.from.as_deref(),
+            args.to.as_deref(),
+            ctx.defaults.timezone.as_deref(),
+        )?;
+        let count = print_count(
+            client,
+            team_id,
+            source_id,
+            &query,
+            range,
+            &args,
+            ctx.defaults.timezone.as_deref(),
+            &global,
+        )
+        .await?;
+        return check_assertions(
+            &args,
+            count as u64,
+            &query,
+            (&time_range.start, &time_range.end),
+        );
+    }
+    if let Some(min) = args.min_count
+        && min > u64::from(limit)
+    {
+        anyhow::bail!(
+            "--min-count {} is above --limit {}, so it could never be met. Raise --limit or add --count to count server-side.",
+            min,
+            limit
+        );
+    }
+
     let request = QueryRequest {
-        query,
+        query: query.clone(),
         start_time: time_range.start,
         end_time: time_range.end,
         timezone: Some(time_range.timezone),
-        limit: Some(limit),
+        limit: Some(args.limit_per_source.unwrap_or(limit)),
         query_timeout: Some(args.timeout),
     };
 
+    if args.dry_run {
+        return print_dry_run(client, team_id, source_id, &request, global.quiet).await;
+    }
+
+    let mut executed = history::with_window(
+        history::new_entry(&s, QueryKind::Logchefql, &query, team_id, source_id),
+        Some(&since),
+        args.from.as_deref(),
+        args.to.as_deref(),
+        ctx.defaults.timezone.as_deref(),
+    );
+    executed.source = source_name.clone();
+    executed.limit = args.limit;
+
+    if args.all {
+        fetch_all(
+            client,
+            ctx,
+            team_id,
+            source_id,
+            request,
+            &args,
+            &config,
+            &global,
+            fields.as_deref(),
+            template.as_ref(),
+        )
+        .await?;
+        history::record(&executed);
+        return Ok(());
+    }
+
     let spinner = ui::Spinner::start(global.quiet, "querying");
-    let result = client.query_logchefql(team_id, source_id, &request).await;
+    let result = match (&sources, tag) {
+        (Some(sources), Some(tag)) => {
+            let legs: Vec<_> = sources
+                .iter()
+                .map(|(id, name)| (name.as_str(), client, team_id, *id))
+                .collect();
+            query_fan_out(&legs, &request, tag).await
+        }
+        (None, Some(tag)) => {
+            let legs: Vec<_> = std::iter::once((s.name.as_str(), client, team_id, source_id))
+                .chain(remotes.iter().map(|r| {
+                    (
+                        r.session.name.as_str(),
+                        &r.session.client,
+                        r.team_id,
+                        r.source_id,
+                    )
+                }))
+                .collect();
+            query_fan_out(&legs, &request, tag).await
+        }
+        _ => client
+            .query_logchefql(team_id, source_id, &request)
+            .await
+            .map_err(anyhow::Error::from),
+    };
     spinner.finish();
-    let response = result.context("Query failed")?;
-
-    if args.dry_run {
-        // Print the generated backend query to stdout (clean, pipeable) and
-        // exit. `generated_query()` falls back to `generated_sql`, so this
-        // works for both engines: ClickHouse returns `generated_sql`, while
-        // VictoriaLogs returns `generated_query` + `generated_query_language:
-        // "logsql"`. Never error just because the source is VictoriaLogs.
-        match response.generated_query() {
-            Some(query) => println!("{}", query),
-            None => anyhow::bail!("Server did not return a generated query; cannot --dry-run."),
+    let mut response = result.context("Query failed")?;
+    if let Some(fields) = &fields {
+        match tag {
+            Some(tag) => {
+                let mut keep = vec![tag.to_string()];
+                keep.extend(fields.iter().cloned());
+                response.project(&keep);
+            }
+            None => response.project(fields),
         }
-        return Ok(());
+    }
+    if args.trace_id.is_some() {
+        response.sort_oldest_first();
+    }
+    if args.include_nulls {
+        response.fill_nulls();
+    } else if args.omit_nulls {
+        response.omit_nulls();
+    }
+
+    executed.rows = Some(response.entries().len() as u64);
+    executed.duration_ms = Some(response.stats.execution_time_ms);
+    if !args.rerun {
+        history::record(&executed);
     }
 
     if args.show_sql
@@ -271,9 +846,33 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
         eprintln!("{}: {}\n", label, rendered);
     }
 
-    let entries = response.entries();
+    let filtered;
+    let entries = match args.min_level {
+        Some(min) => {
+            filtered = super::filter_min_level(response.entries(), min);
+            &filtered[..]
+        }
+        None => response.entries(),
+    };
+    let entries = &entries[..entries.len().min(limit as usize)];
+    let matched = entries.len() as u64;
+    let entries = match args.output {
+        OutputFormat::Text | OutputFormat::Table | OutputFormat::Msg => {
+            super::cap_for_display(entries, args.max_display)
+        }
+        _ => entries,
+    };
 
+    let _pager = match args.output {
+        OutputFormat::Text | OutputFormat::Table | OutputFormat::Msg => {
+            crate::pager::start(&config, &global)
+        }
+        _ => None,
+    };
     match args.output {
+        OutputFormat::Json if args.array_stream => {
+            write_json_array_stream(entries)?;
+        }
         OutputFormat::Json => {
             let output = JsonOutput {
                 logs: entries,
@@ -285,7 +884,13 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
                 generated_query_language: response.generated_query_language(),
                 columns: &response.columns,
             };
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            println!(
+                "{}",
+                ui::highlight_json(
+                    &serde_json::to_string_pretty(&output)?,
+                    !args.no_highlight && ui::human(global.quiet),
+                )
+            );
         }
         OutputFormat::Jsonl => {
             for entry in entries {
@@ -301,6 +906,24 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
         OutputFormat::JsonFlat => {
             print_json_flat(entries)?;
         }
+        OutputFormat::Csv => {
+            super::write_csv(
+                &mut std::io::stdout().lock(),
+                entries,
+                &response.columns,
+                true,
+                &[],
+            )?;
+        }
+        OutputFormat::Tsv => {
+            super::write_tsv(
+                &mut std::io::stdout().lock(),
+                entries,
+                &response.columns,
+                true,
+                &[],
+            )?;
+        }
         OutputFormat::Table => {
             print_table(entries, &response.columns);
             ui::print_stats(
@@ -314,27 +937,75 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
             print_msg(entries, &response.columns, false);
         }
         OutputFormat::Text => {
-            let highlighter = if args.no_highlight || !ui::human(global.quiet) {
-                None
+            let highlighter = text_highlighter(&args, &config, global.quiet);
+            let mut fmt_options =
+                text_format_options(&args, ctx.defaults.timezone.as_deref(), global.utc);
+            fmt_options.context_column = args
+                .context_column
+                .as_deref()
+                .or(tag)
+                .map(|field| ContextColumn::fit(field, entries));
+            let columns = if args.columns_auto {
+                let (varying, constant) = split_constant_columns(entries, &response.columns);
+                if !constant.is_empty() && ui::stderr_human(global.quiet) {
+                    eprintln!("Hiding constant columns: {}\n", constant.join(", "));
+                }
+                varying
             } else {
-                let hl_options = HighlightOptions {
-                    adhoc_highlights: parse_highlight_args(&args.highlights),
-                    disabled_groups: args.disable_highlights.clone(),
-                };
-                Highlighter::with_options(&config.highlights, &hl_options).ok()
+                response.columns.clone()
             };
-
-            let fmt_options = FormatOptions {
-                show_timestamp: !args.no_timestamp,
+            if args.inline_kv_align {
+                fmt_options.field_widths =
+                    Some(FieldWidths::measure(entries, &columns, &fmt_options));
+            }
+            let print_line = |line: &str| match &fmt_options.context_column {
+                Some(context) if highlighter.is_some() => {
+                    let (prefix, rest) = context.split_line(line);
+                    let rest = render_text_line(highlighter.as_ref(), rest, &args);
+                    if args.strip_ansi {
+                        println!("{} {}", prefix, rest);
+                    } else {
+                        println!("{} {}", ContextColumn::paint(prefix), rest);
+                    }
+                }
+                _ => println!("{}", render_text_line(highlighter.as_ref(), line, &args)),
+            };
+            let format_line = |entry: &LogEntry| match &template {
+                Some(template) => template.render(entry),
+                None => format_log_entry_with_options(entry, &columns, &fmt_options),
+            };
+            let print_entries = |entries: &[&LogEntry]| match dedupe_window {
+                Some(window) => {
+                    let mut collapser = RepeatCollapser::new(window);
+                    for entry in entries {
+                        let line = format_line(entry);
+                        if let Some(done) = collapser.push(entry, line) {
+                            print_line(&done);
+                        }
+                    }
+                    if let Some(done) = collapser.finish() {
+                        print_line(&done);
+                    }
+                }
+                None => {
+                    for entry in entries {
+                        let line = format_line(entry);
+                        print_line(&line);
+                    }
+                }
             };
 
-            for entry in entries {
-                let line = format_log_entry_with_options(entry, &response.columns, &fmt_options);
-                if let Some(ref h) = highlighter {
-                    println!("{}", h.highlight(&line));
-                } else {
-                    println!("{}", line);
+            match &args.group_by_field {
+                Some(field) => {
+                    for (i, (value, group)) in group_entries(entries, field).iter().enumerate() {
+                        if i > 0 {
+                            println!();
+                        }
+                        println!("=== {}: {} ===", field, value);
+                        print_entries(group);
+                    }
                 }
+                None => print_entries(&entries.iter().collect::<Vec<_>>()),
             }
             ui::print_stats(
                 global.quiet,
@@ -345,6 +1016,384 @@ pub async fn run(args: QueryArgs, global: GlobalArgs) -> Result<()> {
         }
     }
 
+    if let Some(name) = &args.save_as {
+        let time_range = match (args.from.as_deref(), args.to.as_deref()) {
+            (Some(from), Some(to)) => {
+                let (start, end) = from_to_window(from, to, ctx.defaults.timezone.as_deref())?;
+                CollectionTimeRange {
+                    relative: None,
+                    absolute: Some(CollectionAbsoluteTime {
+                        start: start.timestamp_millis(),
+                        end: end.timestamp_millis(),
+                    }),
+                }
+            }
+            _ => CollectionTimeRange {
+                relative: Some(since.clone()),
+                absolute: None,
+            },
+        };
+        let content = CollectionQueryContent {
+            version: Some(1),
+            source_id: Some(source_id),
+            time_range: Some(time_range),
+            limit: Some(limit),
+            content: Some(query),
+            variables: None,
+            fields: fields.clone(),
+        };
+        let saved = client
+            .create_saved_query(&CreateSavedQueryRequest {
+                name: name.clone(),
+                description: String::new(),
+                source_id,
+                created_from_team_id: Some(team_id),
+                query_language: "logchefql".to_string(),
+                query_content: serde_json::to_string(&content)?,
+            })
+            .await
+            .context("Failed to save collection")?;
+        eprintln!("Saved as collection '{}' (ID {})", saved.name, saved.id);
+    }
+
+    drop(_pager);
+    check_assertions(
+        &args,
+        matched,
+        &request.query,
+        (&request.start_time, &request.end_time),
+    )
+}
+
+/// `--source a,b` or `--source all`: the sources to fan the query out to, or
+/// `None` for an ordinary single source.
+fn source_list(input: &str) -> Option<Vec<&str>> {
+    if input.trim().eq_ignore_ascii_case("all") {
+        return Some(Vec::new());
+    }
+    if !input.contains(',') {
+        return None;
+    }
+    Some(
+        input
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect(),
+    )
+}
+
+fn check_fan_out_flags(args: &QueryArgs) -> Result<()> {
+    let unsupported = [
+        (args.count, "--count"),
+        (args.dry_run, "--dry-run"),
+        (args.all, "--all"),
+        (args.save_as.is_some(), "--save-as"),
+        (
+            args.fields_from_collection.is_some(),
+            "--fields-from-collection",
+        ),
+    ];
+    if let Some((_, flag)) = unsupported.iter().find(|(set, _)| *set) {
+        anyhow::bail!("{} needs a single --source and --context", flag);
+    }
+    Ok(())
+}
+
+/// Resolves a fanned-out `--source` to `(id, name)` pairs in the order given;
+/// an empty list means every source in the team.
+async fn fan_out_sources(
+    client: &Client,
+    cache: &mut Cache,
+    team_id: i64,
+    list: &[&str],
+) -> Result<Vec<(i64, String)>> {
+    if list.is_empty() {
+        let sources = client
+            .list_sources(team_id)
+            .await
+            .context("Failed to list sources")?;
+        if sources.is_empty() {
+            anyhow::bail!("Team has no sources to query");
+        }
+        return Ok(sources.into_iter().map(|s| (s.id, s.name)).collect());
+    }
+    let mut resolved: Vec<(i64, String)> = Vec::with_capacity(list.len());
+    for name in list {
+        let id = super::resolve_source(client, cache, team_id, Some(name.to_string())).await?;
+        if !resolved.iter().any(|(seen, _)| *seen == id) {
+            resolved.push((id, name.to_string()));
+        }
+    }
+    Ok(resolved)
+}
+
+/// `--context a,b`: every context named, split on commas with repeats
+/// dropped, or `None` for an ordinary single context.
+fn context_list(input: &str) -> Option<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for name in input.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        if !names.iter().any(|seen| seen == name) {
+            names.push(name.to_string());
+        }
+    }
+    (names.len() > 1).then_some(names)
+}
+
+/// Another context a `--context a,b` query runs against, with the team and
+/// source resolved on that server.
+struct RemoteContext {
+    session: session::AuthedSession,
+    team_id: i64,
+    source_id: i64,
+}
+
+/// Signs in to context `name` and resolves `--team`/`--source` there,
+/// falling back to that context's own defaults.
+async fn remote_context(
+    config: &Config,
+    global: &GlobalArgs,
+    name: &str,
+    args: &QueryArgs,
+) -> Result<RemoteContext> {
+    let global = GlobalArgs {
+        context: Some(name.to_string()),
+        ..global.clone()
+    };
+    let session = session::authed(config, &global)?;
+    let mut cache = Cache::new(&session.ctx.server_url);
+    let team = args
+        .team
+        .clone()
+        .or_else(|| session.ctx.defaults.team_with_env());
+    let source = args
+        .source
+        .clone()
+        .or_else(|| session.ctx.defaults.source_with_env());
+    let team_id = super::resolve_team(&session.client, &mut cache, team)
+        .await
+        .with_context(|| format!("Context '{}'", name))?;
+    let source_id = super::resolve_source(&session.client, &mut cache, team_id, source)
+        .await
+        .with_context(|| format!("Context '{}'", name))?;
+    Ok(RemoteContext {
+        session,
+        team_id,
+        source_id,
+    })
+}
+
+/// Runs `request` on every `(label, client, team, source)` leg at once and
+/// merges the results, tagging rows with `tag`. A leg that fails is reported
+/// on stderr and left out; only if every one fails is the query an error.
+async fn query_fan_out(
+    legs: &[(&str, &Client, i64, i64)],
+    request: &QueryRequest,
+    tag: &str,
+) -> Result<QueryResponse> {
+    let results = futures::future::join_all(legs.iter().map(|(_, client, team_id, source_id)| {
+        client.query_logchefql(*team_id, *source_id, request)
+    }))
+    .await;
+    let mut responses = Vec::with_capacity(legs.len());
+    let mut first_err = None;
+    for ((label, ..), result) in legs.iter().zip(results) {
+        match result {
+            Ok(response) => responses.push((label.to_string(), response)),
+            Err(err) => {
+                eprintln!("Warning: '{}' failed: {}", label, err);
+                first_err.get_or_insert(err);
+            }
+        }
+    }
+    match first_err {
+        Some(err) if responses.is_empty() => Err(err.into()),
+        _ => Ok(QueryResponse::merge(tag, responses)),
+    }
+}
+
+/// Exit status for a `--fail-if-*` / `--min-count` check that didn't hold,
+/// distinct from 1 (an error) and 2 (bad usage).
+const ASSERTION_FAILED_EXIT: i32 = 3;
+
+#[derive(Serialize)]
+struct AssertionSummary<'a> {
+    passed: bool,
+    count: u64,
+    failed: Vec<String>,
+    query: &'a str,
+    start: &'a str,
+    end: &'a str,
+}
+
+/// Evaluates the CI assertion flags against `count`, printing a one-line JSON
+/// summary on stderr and exiting with [`ASSERTION_FAILED_EXIT`] if any fails.
+fn check_assertions(args: &QueryArgs, count: u64, query: &str, window: (&str, &str)) -> Result<()> {
+    if !args.fail_if_matches && !args.fail_if_empty && args.min_count.is_none() {
+        return Ok(());
+    }
+    let failed = failed_assertions(args, count);
+    let summary = AssertionSummary {
+        passed: failed.is_empty(),
+        count,
+        failed,
+        query,
+        start: window.0,
+        end: window.1,
+    };
+    eprintln!("{}", serde_json::to_string(&summary)?);
+    if !summary.passed {
+        std::process::exit(ASSERTION_FAILED_EXIT);
+    }
+    Ok(())
+}
+
+fn failed_assertions(args: &QueryArgs, count: u64) -> Vec<String> {
+    let mut failed = Vec::new();
+    if args.fail_if_matches && count > 0 {
+        failed.push("--fail-if-matches".to_string());
+    }
+    if args.fail_if_empty && count == 0 {
+        failed.push("--fail-if-empty".to_string());
+    }
+    if let Some(min) = args.min_count
+        && count < min
+    {
+        failed.push(format!("--min-count {}", min));
+    }
+    failed
+}
+
+/// `--all`: pages newest-first through the window with
+/// [`Client::query_pages`], printing rows as each page arrives.
+#[allow(clippy::too_many_arguments)]
+async fn fetch_all(
+    client: &Client,
+    ctx: &logchef_core::config::Context,
+    team_id: i64,
+    source_id: i64,
+    request: QueryRequest,
+    args: &QueryArgs,
+    config: &Config,
+    global: &GlobalArgs,
+    fields: Option<&[String]>,
+    template: Option<&LineTemplate>,
+) -> Result<()> {
+    use futures::StreamExt;
+
+    if matches!(args.output, OutputFormat::Json | OutputFormat::Table) {
+        anyhow::bail!(
+            "--all prints rows as pages arrive; use --output text, jsonl, json-flat, csv, tsv or msg"
+        );
+    }
+    let tz = ctx.defaults.timezone.as_deref();
+    let max_rows = ctx.defaults.all_max_rows();
+
+    let highlighter = text_highlighter(args, config, global.quiet);
+    let fmt_options = text_format_options(args, tz, global.utc);
+    let _pager = match args.output {
+        OutputFormat::Text | OutputFormat::Msg => crate::pager::start(config, global),
+        _ => None,
+    };
+    let progress = ui::stderr_human(global.quiet);
+
+    let mut stream = std::pin::pin!(client.query_pages(team_id, source_id, request));
+    let mut printed: u64 = 0;
+    let mut pages = 0;
+    while let Some(page) = stream.next().await {
+        let QueryPage {
+            number,
+            mut response,
+            next_end,
+        } = page.with_context(|| match pages {
+            0 => "Query failed".to_string(),
+            _ => format!("Failed to fetch page {}", pages + 1),
+        })?;
+        pages = number;
+        if let Some(fields) = fields {
+            response.project(fields);
+        }
+        if args.include_nulls {
+            response.fill_nulls();
+        } else if args.omit_nulls {
+            response.omit_nulls();
+        }
+
+        let mut fresh: Vec<LogEntry> = std::mem::take(&mut response.logs)
+            .into_iter()
+            .filter(|entry| {
+                args.min_level
+                    .is_none_or(|min| Severity::of_entry(entry).is_some_and(|level| level >= min))
+            })
+            .collect();
+        let remaining = max_rows.saturating_sub(printed);
+        let capped = fresh.len() as u64 > remaining;
+        fresh.truncate(remaining as usize);
+        printed += fresh.len() as u64;
+
+        let out = &mut std::io::stdout().lock();
+        match args.output {
+            OutputFormat::Text => {
+                for entry in &fresh {
+                    let line = match template {
+                        Some(template) => template.render(entry),
+                        None => {
+                            format_log_entry_with_options(entry, &response.columns, &fmt_options)
+                        }
+                    };
+                    writeln!(
+                        out,
+                        "{}",
+                        render_text_line(highlighter.as_ref(), &line, args)
+                    )?;
+                }
+            }
+            OutputFormat::Jsonl => {
+                for entry in &fresh {
+                    writeln!(out, "{}", serde_json::to_string(entry)?)?;
+                }
+            }
+            OutputFormat::JsonFlat => print_json_flat(&fresh)?,
+            OutputFormat::Msg => print_msg(&fresh, &response.columns, false),
+            OutputFormat::Csv => {
+                super::write_csv(out, &fresh, &response.columns, number == 1, &[])?
+            }
+            OutputFormat::Tsv => {
+                super::write_tsv(out, &fresh, &response.columns, number == 1, &[])?
+            }
+            OutputFormat::Json | OutputFormat::Table => unreachable!("rejected above"),
+        }
+        out.flush()?;
+
+        if capped {
+            eprintln!(
+                "Stopped at {} rows (defaults.all_max_rows); narrow the time range or raise the cap.",
+                ui::thousands(max_rows as i64)
+            );
+            break;
+        }
+        if let Some(next_end) = next_end
+            && progress
+        {
+            eprintln!(
+                "all: {} rows in {} pages, continuing before {}",
+                ui::thousands(printed as i64),
+                pages,
+                resolve_time_range(
+                    TimeInput::Instant {
+                        start: next_end,
+                        end: next_end,
+                    },
+                    tz,
+                )
+                .end
+            );
+        }
+    }
+
+    if ui::human(global.quiet) {
+        eprintln!("\n{} logs | {} pages", ui::thousands(printed as i64), pages);
+    }
     Ok(())
 }
 
@@ -354,48 +1403,104 @@ fn parse_time_range(
     to: Option<&str>,
     configured_tz: Option<&str>,
 ) -> Result<logchef_core::timerange::ResolvedTimeRange> {
-    let input = match (from, to) {
-        (Some(from), Some(to)) => TimeInput::WallClock {
-            start: from,
-            end: to,
-        },
+    let (start, end) = match (from, to) {
+        (Some(from), Some(to)) => from_to_window(from, to, configured_tz)?,
         (Some(_), None) => anyhow::bail!("--from requires --to to be specified"),
         (None, Some(_)) => anyhow::bail!("--to requires --from to be specified"),
-        (None, None) => {
-            let end = Utc::now();
-            let start = end - parse_duration(since)?;
-            TimeInput::Instant { start, end }
-        }
+        (None, None) => since_window(since, configured_tz)?,
     };
-    Ok(resolve_time_range(input, configured_tz))
+    Ok(resolve_time_range(
+        TimeInput::Instant { start, end },
+        configured_tz,
+    ))
 }
 
-fn parse_duration(s: &str) -> Result<Duration> {
-    let s = s.trim();
-    if s.is_empty() {
-        return Ok(Duration::minutes(15));
+/// Width of the window selected by --since or --from/--to, for the
+/// `max_time_range` guard.
+fn time_range_span(
+    since: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    configured_tz: Option<&str>,
+) -> Result<Duration> {
+    match (from, to) {
+        (Some(from), Some(to)) => {
+            let (start, end) = from_to_window(from, to, configured_tz)?;
+            Ok(end - start)
+        }
+        _ => {
+            let (start, end) = since_window(since, configured_tz)?;
+            Ok(end - start)
+        }
     }
+}
 
-    let (num, unit) = if s.ends_with('m') {
-        (s.trim_end_matches('m'), "m")
-    } else if s.ends_with('h') {
-        (s.trim_end_matches('h'), "h")
-    } else if s.ends_with('d') {
-        (s.trim_end_matches('d'), "d")
-    } else if s.ends_with('w') {
-        (s.trim_end_matches('w'), "w")
-    } else {
-        (s, "m")
+/// `--watch`: runs the query every `interval` until interrupted. A failed
+/// first run is fatal; later failures are reported and retried, so a brief
+/// outage mid-deploy doesn't end the session.
+async fn watch(mut args: QueryArgs, mut global: GlobalArgs, interval: &str) -> Result<()> {
+    let every = parse_window(interval).context("Invalid --watch interval")?;
+    if every < Duration::seconds(1) {
+        anyhow::bail!("--watch interval must be at least 1s");
+    }
+    let every = every.to_std()?;
+    let append = args.watch_append || !std::io::stdout().is_terminal();
+
+    // Stdin and history references were resolved by the caller; every run
+    // reuses that query rather than prompting or reading again.
+    args.file = None;
+    args.last = None;
+    args.query.get_or_insert_with(String::new);
+    global.no_pager = true;
+
+    let label = match &args.saved {
+        Some(name) => format!("saved query '{}'", name),
+        None => match args.query.as_deref() {
+            Some("") | None => "all logs".to_string(),
+            Some(query) => query.to_string(),
+        },
     };
+    let mut covered_until: Option<chrono::DateTime<Utc>> = None;
+    let mut first = true;
+    loop {
+        let started = Utc::now();
+        let mut round = args.clone();
+        round.rerun = !first;
+        if append && let Some(from) = covered_until {
+            round.since = None;
+            round.from = Some(from.to_rfc3339());
+            round.to = Some(started.to_rfc3339());
+        }
+        if !append {
+            print!("\x1b[2J\x1b[H");
+            std::io::stdout().flush()?;
+        }
+        if (first || !append) && ui::stderr_human(global.quiet) {
+            eprintln!(
+                "\x1b[2mEvery {}: {} · updated {} · Ctrl-C to stop\x1b[0m\n",
+                interval,
+                label,
+                chrono::Local::now().format("%H:%M:%S")
+            );
+        }
+        match Box::pin(run(round, global.clone())).await {
+            Ok(()) => covered_until = Some(started),
+            Err(err) if first => return Err(err),
+            Err(err) => eprintln!("Error: {:#} (retrying in {})", err, interval),
+        }
+        first = false;
+        tokio::time::sleep(every).await;
+    }
+}
 
-    let num: i64 = num.parse().context("Invalid duration number")?;
-
-    match unit {
-        "m" => Ok(Duration::minutes(num)),
-        "h" => Ok(Duration::hours(num)),
-        "d" => Ok(Duration::days(num)),
-        "w" => Ok(Duration::weeks(num)),
-        _ => Ok(Duration::minutes(num)),
+/// Parses a `--dedupe-window` value: like `--since`, plus an `s` suffix for
+/// seconds.
+fn parse_window(s: &str) -> Result<Duration> {
+    match s.trim().strip_suffix('s') {
+        Some(secs) => Ok(Duration::seconds(
+            secs.parse().context("Invalid duration number")?,
+        )),
+        None => Ok(parse_duration(s)?),
     }
 }
 
@@ -415,6 +1520,460 @@ fn parse_highlight_args(args: &[String]) -> Vec<(String, Vec<String>)> {
         .collect()
 }
 
+/// Highlighter for text output: on for terminals, and also off-terminal
+/// with `--strip-ansi` so the rules still run.
+fn text_highlighter(args: &QueryArgs, config: &Config, quiet: bool) -> Option<Highlighter> {
+    if args.no_highlight || !(args.strip_ansi || ui::human(quiet)) {
+        return None;
+    }
+    let hl_options = HighlightOptions {
+        adhoc_highlights: parse_highlight_args(&args.highlights),
+        disabled_groups: args.disable_highlights.clone(),
+    };
+    Highlighter::with_options(&config.highlights, &hl_options).ok()
+}
+
+/// Fills the query, and any team, source or lookback the command line left
+/// unset, from a saved query.
+fn apply_saved_query(args: &mut QueryArgs, saved: SavedQuery) {
+    args.query = Some(saved.query);
+    if args.team.is_none() {
+        args.team = saved.team;
+    }
+    if args.source.is_none() {
+        args.source = saved.source;
+    }
+    if args.since.is_none() && args.from.is_none() && args.to.is_none() {
+        args.since = saved.since;
+    }
+}
+
+/// `!!` (the latest LogchefQL query) or `!N` (history entry N) given as the
+/// query; `None` for an ordinary query.
+fn history_reference(query: &str) -> Option<Option<usize>> {
+    match query.trim() {
+        "!!" => Some(None),
+        other => other.strip_prefix('!')?.parse().ok().map(Some),
+    }
+}
+
+/// Fills whatever the command line left unset from a history entry; an
+/// explicit --since, --from or --to replaces the recorded window as a whole.
+fn apply_history_entry(args: &mut QueryArgs, entry: &HistoryEntry) {
+    args.query = Some(entry.query.clone());
+    args.team.get_or_insert_with(|| entry.team_id.to_string());
+    args.source
+        .get_or_insert_with(|| entry.source_id.to_string());
+    if args.since.is_none() && args.from.is_none() && args.to.is_none() {
+        match (entry.start, entry.end) {
+            (Some(start), Some(end)) => {
+                args.from = Some(start.to_rfc3339());
+                args.to = Some(end.to_rfc3339());
+            }
+            _ => args.since = entry.since.clone(),
+        }
+    }
+    if args.limit.is_none() {
+        args.limit = entry.limit;
+    }
+}
+
+/// The query from `-f <path>`, or from stdin for `-f -` or a `-` query
+/// argument; `None` when it's given inline (or not at all).
+pub(super) fn read_query_input(query: Option<&str>, file: Option<&Path>) -> Result<Option<String>> {
+    let text = match (query, file) {
+        (_, Some(path)) if path != Path::new("-") => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read query from {}", path.display()))?,
+        (Some("-"), _) | (_, Some(_)) => {
+            let mut buffer = String::new();
+            std::io::stdin()
+                .read_to_string(&mut buffer)
+                .context("Failed to read query from stdin")?;
+            buffer
+        }
+        _ => return Ok(None),
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        anyhow::bail!("The query is empty");
+    }
+    Ok(Some(text.to_string()))
+}
+
+fn text_format_options(args: &QueryArgs, timezone: Option<&str>, utc: bool) -> FormatOptions {
+    FormatOptions {
+        show_timestamp: !args.no_timestamp,
+        aligned: args.aligned,
+        show_key_names: !args.no_key_names,
+        time_display: args.relative_time.unwrap_or_default(),
+        timezone: Some(display_timezone(timezone, utc)),
+        // Sized from the results, so filled in once they're known.
+        context_column: None,
+        field_widths: None,
+        column_order: !args.fields.is_empty(),
+    }
+}
+
+fn render_text_line(highlighter: Option<&Highlighter>, line: &str, args: &QueryArgs) -> String {
+    let Some(h) = highlighter else {
+        return line.to_string();
+    };
+    let highlighted = if args.dim_unmatched {
+        h.highlight_focused(line)
+    } else {
+        h.highlight(line)
+    };
+    if args.strip_ansi {
+        strip_ansi(&highlighted)
+    } else {
+        highlighted
+    }
+}
+
+/// How often `--follow` checks the file for new data.
+const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// `--watch-file`: renders a local log file through the same formatter and
+/// highlighter as server results, optionally following it as it grows.
+async fn view_local_file(
+    path: &Path,
+    args: &QueryArgs,
+    config: &Config,
+    global: &GlobalArgs,
+) -> Result<()> {
+    if !matches!(args.output, OutputFormat::Text) {
+        anyhow::bail!("--watch-file only supports --output text");
+    }
+    let quiet = global.quiet;
+    let open = || {
+        std::fs::File::open(path)
+            .map(BufReader::new)
+            .with_context(|| format!("Failed to open {}", path.display()))
+    };
+    let mut reader = open()?;
+    let highlighter = text_highlighter(args, config, quiet);
+    let timezone = config
+        .current_context()
+        .and_then(|ctx| ctx.defaults.timezone.as_deref());
+    let fmt_options = text_format_options(args, timezone, global.utc);
+
+    let emit = |raw: &str| {
+        let raw = raw.trim_end_matches(['\r', '\n']);
+        let line = match serde_json::from_str::<logchef_core::api::LogEntry>(raw) {
+            Ok(entry) => {
+                if let Some(min) = args.min_level
+                    && !Severity::of_entry(&entry).is_some_and(|level| level >= min)
+                {
+                    return;
+                }
+                let columns = super::tail::columns_from_entry(&entry);
+                format_log_entry_with_options(&entry, &columns, &fmt_options)
+            }
+            // Plain text has no level field to compare against.
+            Err(_) if args.min_level.is_some() => return,
+            Err(_) => raw.to_string(),
+        };
+        println!("{}", render_text_line(highlighter.as_ref(), &line, args));
+    };
+
+    let mut pending = String::new();
+    loop {
+        if reader.read_line(&mut pending)? > 0 {
+            // A writer may be mid-line at EOF; wait for the rest when following.
+            if pending.ends_with('\n') || !args.follow {
+                emit(&pending);
+                pending.clear();
+            }
+            continue;
+        }
+        if !args.follow {
+            break;
+        }
+        // Truncated or rotated in place: start over from the top.
+        if std::fs::metadata(path)
+            .is_ok_and(|meta| meta.len() < reader.stream_position().unwrap_or(0))
+        {
+            reader = open()?;
+            pending.clear();
+        }
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+    }
+    Ok(())
+}
+
+/// Display fields saved in a collection's content, for
+/// `--fields-from-collection`.
+async fn collection_fields(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    name: &str,
+) -> Result<Vec<String>> {
+    let collections = client
+        .list_collections(team_id, source_id)
+        .await
+        .context("Failed to list collections")?;
+    let collection = super::collections::find_collection(&collections, name)?;
+    let content = CollectionQueryContent::parse(&collection.query_content).with_context(|| {
+        format!(
+            "Failed to parse content of collection '{}'",
+            collection.name
+        )
+    })?;
+    content
+        .fields
+        .filter(|fields| !fields.is_empty())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Collection '{}' has no saved display fields",
+                collection.name
+            )
+        })
+}
+
+/// Rejects `--fields` names the source doesn't have, listing the ones it
+/// does.
+fn check_fields(fields: &[String], schema: &[Column]) -> Result<()> {
+    let unknown: Vec<&str> = fields
+        .iter()
+        .filter(|field| !schema.iter().any(|col| &col.name == *field))
+        .map(String::as_str)
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    let available: Vec<&str> = schema.iter().map(|col| col.name.as_str()).collect();
+    anyhow::bail!(
+        "Unknown field{} in --fields: {}\nAvailable columns: {}",
+        if unknown.len() == 1 { "" } else { "s" },
+        unknown.join(", "),
+        available.join(", ")
+    )
+}
+
+#[derive(Serialize)]
+struct CountOutput {
+    count: i64,
+    start: String,
+    end: String,
+    timezone: String,
+    elapsed_ms: i64,
+}
+
+/// `--count`: the number of matches on stdout (bare, or as JSON for the JSON
+/// outputs), with the window and timing on stderr.
+#[allow(clippy::too_many_arguments)]
+async fn print_count(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    query: &str,
+    (start, end): (chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>),
+    args: &QueryArgs,
+    configured_tz: Option<&str>,
+    global: &GlobalArgs,
+) -> Result<i64> {
+    let started = std::time::Instant::now();
+    let spinner = ui::Spinner::start(global.quiet, "counting");
+    let result = super::histogram::count_matches(
+        client,
+        team_id,
+        source_id,
+        query,
+        (start, end),
+        configured_tz,
+        args.timeout,
+    )
+    .await;
+    spinner.finish();
+    let count = result?;
+    let elapsed_ms = started.elapsed().as_millis() as i64;
+    let window = resolve_time_range(TimeInput::Instant { start, end }, configured_tz);
+
+    match args.output {
+        OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::JsonFlat => {
+            let output = CountOutput {
+                count,
+                start: window.start,
+                end: window.end,
+                timezone: window.timezone,
+                elapsed_ms,
+            };
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        _ => {
+            println!("{}", count);
+            if ui::human(global.quiet) {
+                eprintln!(
+                    "\n{} logs | {} → {} ({}) | {}ms",
+                    ui::thousands(count),
+                    window.start,
+                    window.end,
+                    window.timezone,
+                    ui::thousands(elapsed_ms)
+                );
+            }
+        }
+    }
+    Ok(count)
+}
+
+/// `--dry-run`: translates the query with the request's time range and
+/// limit, so the server returns the complete query it would execute
+/// (`full_sql` for ClickHouse, the LogsQL for VictoriaLogs), and prints that
+/// to stdout. Nothing is executed.
+async fn print_dry_run(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    request: &QueryRequest,
+    quiet: bool,
+) -> Result<()> {
+    let translated = client
+        .translate_logchefql(
+            team_id,
+            source_id,
+            &TranslateRequest {
+                query: request.query.clone(),
+                start_time: Some(request.start_time.clone()),
+                end_time: Some(request.end_time.clone()),
+                timezone: request.timezone.clone(),
+                limit: request.limit,
+            },
+        )
+        .await
+        .context("Failed to translate query")?;
+    if !translated.valid {
+        let mut message = translated
+            .error
+            .as_ref()
+            .map_or_else(|| "query is not valid".to_string(), |e| e.message.clone());
+        if let Some(pos) = translated.error.as_ref().and_then(|e| e.position.as_ref()) {
+            message.push_str(&format!(" (line {}, column {})", pos.line, pos.column));
+        }
+        anyhow::bail!("Invalid LogchefQL: {}", message);
+    }
+    let generated = translated.generated_query();
+    if generated.trim().is_empty() {
+        anyhow::bail!("Server did not return a generated query; cannot --dry-run.");
+    }
+    println!(
+        "{}",
+        ui::highlight_query(
+            generated,
+            translated.generated_query_language.as_deref(),
+            ui::human(quiet),
+        )
+    );
+    Ok(())
+}
+
+/// One `--explain-cache` line on stderr per resolved identifier.
+fn explain_cache(enabled: bool, kind: &str, input: &str, id: i64, outcome: &str) {
+    if enabled {
+        eprintln!("cache: {} '{}' -> {} ({})", kind, input, id, outcome);
+    }
+}
+
+/// Describes where a name lookup was answered from. Call before a miss
+/// refreshes the cache, so the reported age is the one that caused the miss.
+fn cache_outcome(cache: &Cache, hit: bool) -> String {
+    let age = cache.age().map(|age| format_age(age.as_secs()));
+    match (hit, age) {
+        (true, Some(age)) => format!("cache hit, age {}", age),
+        (true, None) => "cache hit".to_string(),
+        (false, Some(age)) if !cache.is_fresh() => {
+            format!("cache expired (age {}), fetched from API", age)
+        }
+        (false, _) => "not cached, fetched from API".to_string(),
+    }
+}
+
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m{}s", secs / 60, secs % 60),
+        _ => format!("{}h{}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// `--columns-auto`: splits `columns` into those whose value varies across
+/// `entries` and the names of those that are identical on every row (missing
+/// counts as a value). With fewer than two rows nothing is hidden.
+fn split_constant_columns(entries: &[LogEntry], columns: &[Column]) -> (Vec<Column>, Vec<String>) {
+    if entries.len() < 2 {
+        return (columns.to_vec(), Vec::new());
+    }
+    let (varying, constant): (Vec<&Column>, Vec<&Column>) = columns.iter().partition(|column| {
+        let first = entries[0].get(&column.name);
+        entries[1..]
+            .iter()
+            .any(|entry| entry.get(&column.name) != first)
+    });
+    (
+        varying.into_iter().cloned().collect(),
+        constant
+            .into_iter()
+            .map(|column| column.name.clone())
+            .collect(),
+    )
+}
+
+/// ANDs a `column="id"` filter onto the user's LogchefQL, if any.
+fn trace_query(column: &str, id: &str, query: &str) -> String {
+    let filter = format!(
+        "{}=\"{}\"",
+        column,
+        id.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    if query.trim().is_empty() {
+        filter
+    } else {
+        format!("{} and ({})", filter, query.trim())
+    }
+}
+
+/// Placeholder group value for entries that lack the `--group-by-field` field.
+const MISSING_GROUP: &str = "(none)";
+
+/// Buckets entries by the string form of `field`, sorted by value, keeping the
+/// fetched order inside each group. Entries without the field come last.
+fn group_entries<'a>(entries: &'a [LogEntry], field: &str) -> Vec<(String, Vec<&'a LogEntry>)> {
+    let mut groups: BTreeMap<Option<String>, Vec<&LogEntry>> = BTreeMap::new();
+    for entry in entries {
+        let key = entry.get(field).and_then(|value| match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        });
+        groups.entry(key).or_default().push(entry);
+    }
+    let missing = groups.remove(&None);
+    groups
+        .into_iter()
+        .map(|(key, group)| (key.unwrap_or_default(), group))
+        .chain(missing.map(|group| (MISSING_GROUP.to_string(), group)))
+        .collect()
+}
+
+/// Writes entries as a valid JSON array incrementally (`[`, one compact entry
+/// per line, `]`) through a buffered stdout, so large results never need a
+/// second, fully formatted copy in memory.
+fn write_json_array_stream(entries: &[logchef_core::api::LogEntry]) -> Result<()> {
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    out.write_all(b"[")?;
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        out.write_all(b"\n")?;
+        serde_json::to_writer(&mut out, entry)?;
+    }
+    out.write_all(if entries.is_empty() { b"]\n" } else { b"\n]\n" })?;
+    out.flush()?;
+    Ok(())
+}
+
 fn print_json_flat(entries: &[logchef_core::api::LogEntry]) -> Result<()> {
     for entry in entries {
         println!("{}", serde_json::to_string(&flatten_msg(entry))?);
@@ -489,10 +2048,7 @@ fn print_table(entries: &[logchef_core::api::LogEntry], columns: &[logchef_core:
             .map(|c| {
                 entry
                     .get(&c.name)
-                    .map(|v| match v {
-                        serde_json::Value::String(s) => s.clone(),
-                        _ => v.to_string(),
-                    })
+                    .map(super::table_cell)
                     .unwrap_or_default()
             })
             .collect();
@@ -558,6 +2114,7 @@ async fn prompt_source_interactive(
             cache_entries.push((target_ref, s.id));
         }
     }
+    cache.observe_sources(team_id, &sources);
     cache.set_sources(team_id, &cache_entries);
 
     Ok(source.id)
@@ -570,3 +2127,238 @@ fn prompt_query_interactive() -> Result<String> {
         .context("Failed to read query")?;
     Ok(query)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(service: Option<serde_json::Value>, msg: &str) -> LogEntry {
+        let mut entry = LogEntry::new();
+        entry.insert("msg".to_string(), serde_json::json!(msg));
+        if let Some(service) = service {
+            entry.insert("service".to_string(), service);
+        }
+        entry
+    }
+
+    #[test]
+    fn split_constant_columns_hides_uniform_values() {
+        let columns: Vec<Column> = serde_json::from_value(serde_json::json!([
+            {"name": "env", "type": "String"},
+            {"name": "host", "type": "String"},
+            {"name": "region", "type": "String"},
+        ]))
+        .unwrap();
+        let mut a = entry(None, "one");
+        a.insert("env".to_string(), serde_json::json!("prod"));
+        a.insert("host".to_string(), serde_json::json!("web-1"));
+        let mut b = entry(None, "two");
+        b.insert("env".to_string(), serde_json::json!("prod"));
+        b.insert("host".to_string(), serde_json::json!("web-2"));
+        b.insert("region".to_string(), serde_json::json!("eu"));
+
+        let (varying, constant) = split_constant_columns(&[a.clone(), b], &columns);
+        let varying: Vec<&str> = varying.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(varying, ["host", "region"]);
+        assert_eq!(constant, ["env"]);
+
+        let (varying, constant) = split_constant_columns(&[a], &columns);
+        assert_eq!(varying.len(), 3);
+        assert!(constant.is_empty());
+    }
+
+    #[test]
+    fn context_list_dedupes_and_ignores_a_single_name() {
+        assert_eq!(
+            context_list("prod, staging,prod,"),
+            Some(vec!["prod".to_string(), "staging".to_string()])
+        );
+        assert_eq!(context_list("prod"), None);
+        assert_eq!(context_list("prod,prod"), None);
+    }
+
+    #[test]
+    fn check_fields_lists_available_columns() {
+        let schema: Vec<Column> = serde_json::from_value(serde_json::json!([
+            {"name": "timestamp", "type": "DateTime64(3)"},
+            {"name": "level", "type": "String"},
+            {"name": "msg", "type": "String"}
+        ]))
+        .unwrap();
+        let fields = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert!(check_fields(&fields(&["msg", "timestamp"]), &schema).is_ok());
+        let err = check_fields(&fields(&["msg", "svc"]), &schema).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown field in --fields: svc\nAvailable columns: timestamp, level, msg"
+        );
+    }
+
+    #[tokio::test]
+    async fn fan_out_leaves_out_failed_legs() {
+        use logchef_core::api::MockTransport;
+        use reqwest::Method;
+
+        let path = "/api/v1/teams/1/sources/2/logchefql/query";
+        let rows = serde_json::json!({"status": "success", "data": {
+            "logs": [{"timestamp": "2026-07-14T09:00:00Z", "msg": "up"}],
+        }});
+        let down = serde_json::json!({"status": "error", "message": "source unreachable"});
+        let api = Client::with_transport(
+            "http://api.test",
+            MockTransport::new().on(Method::POST, path, 200, rows),
+        );
+        let web = Client::with_transport(
+            "http://web.test",
+            MockTransport::new().on(Method::POST, path, 500, down),
+        );
+        let request = QueryRequest {
+            query: "level=\"error\"".to_string(),
+            start_time: "2026-07-14 08:00:00".to_string(),
+            end_time: "2026-07-14 10:00:00".to_string(),
+            timezone: Some("UTC".to_string()),
+            limit: Some(10),
+            query_timeout: None,
+        };
+
+        let legs = [("api", &api, 1, 2), ("web", &web, 1, 2)];
+        let merged = query_fan_out(&legs, &request, SOURCE_FIELD).await.unwrap();
+        assert_eq!(merged.logs.len(), 1);
+        assert_eq!(merged.logs[0][SOURCE_FIELD], "api");
+
+        let err = query_fan_out(&legs[1..], &request, SOURCE_FIELD)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("source unreachable"));
+    }
+
+    #[test]
+    fn query_file_is_read_and_trimmed() {
+        let path = std::env::temp_dir().join(format!("logchef-query-{}.lcq", std::process::id()));
+        std::fs::write(&path, "level=\"error\" and service=\"api\"\n").unwrap();
+        assert_eq!(
+            read_query_input(None, Some(&path)).unwrap().as_deref(),
+            Some(r#"level="error" and service="api""#)
+        );
+        std::fs::write(&path, "  \n").unwrap();
+        assert!(read_query_input(None, Some(&path)).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(read_query_input(None, Some(Path::new("/nonexistent/q.lcq"))).is_err());
+        assert_eq!(read_query_input(Some("status>=500"), None).unwrap(), None);
+    }
+
+    fn parse_args(argv: &[&str]) -> QueryArgs {
+        #[derive(clap::Parser)]
+        struct Cmd {
+            #[command(flatten)]
+            args: QueryArgs,
+        }
+        let argv = std::iter::once("query").chain(argv.iter().copied());
+        <Cmd as clap::Parser>::try_parse_from(argv).unwrap().args
+    }
+
+    #[test]
+    fn history_references_pick_an_entry() {
+        assert_eq!(history_reference("!!"), Some(None));
+        assert_eq!(history_reference("!3"), Some(Some(3)));
+        assert_eq!(history_reference("!level=\"error\""), None);
+        assert_eq!(history_reference("level!=\"debug\""), None);
+
+        let entry: HistoryEntry = serde_json::from_value(serde_json::json!({
+            "executed_at": "2026-07-14T09:00:00Z",
+            "context": "prod",
+            "server_url": "https://logs.example.com",
+            "team_id": 1,
+            "source_id": 7,
+            "kind": "logchefql",
+            "query": "status>=500",
+            "since": "15m",
+            "limit": 20,
+        }))
+        .unwrap();
+        let mut args = parse_args(&["--last", "--since", "1h"]);
+        assert_eq!(args.last, Some(None));
+        apply_history_entry(&mut args, &entry);
+        assert_eq!(args.query.as_deref(), Some("status>=500"));
+        assert_eq!(args.source.as_deref(), Some("7"));
+        assert_eq!(args.since.as_deref(), Some("1h"));
+        assert_eq!(args.limit, Some(20));
+    }
+
+    #[test]
+    fn saved_queries_fill_only_what_is_unset() {
+        let saved = SavedQuery {
+            query: "status>=500".to_string(),
+            team: Some("platform".to_string()),
+            source: Some("nginx".to_string()),
+            since: Some("30m".to_string()),
+        };
+        let mut args = parse_args(&["--saved", "errors5xx", "--source", "api"]);
+        apply_saved_query(&mut args, saved.clone());
+        assert_eq!(args.query.as_deref(), Some("status>=500"));
+        assert_eq!(args.team.as_deref(), Some("platform"));
+        assert_eq!(args.source.as_deref(), Some("api"));
+        assert_eq!(args.since.as_deref(), Some("30m"));
+
+        let mut args = parse_args(&["--saved", "errors5xx", "--from", "2026-07-14"]);
+        apply_saved_query(&mut args, saved);
+        assert_eq!(args.since, None);
+    }
+
+    #[test]
+    fn assertions_fail_on_the_right_counts() {
+        let args = parse_args(&["status>=500", "--fail-if-matches"]);
+        assert!(failed_assertions(&args, 0).is_empty());
+        assert_eq!(failed_assertions(&args, 2), ["--fail-if-matches"]);
+
+        let args = parse_args(&["", "--fail-if-empty", "--min-count", "5"]);
+        assert_eq!(
+            failed_assertions(&args, 0),
+            ["--fail-if-empty", "--min-count 5"]
+        );
+        assert_eq!(failed_assertions(&args, 4), ["--min-count 5"]);
+        assert!(failed_assertions(&args, 5).is_empty());
+    }
+
+    #[test]
+    fn trace_query_filters_and_combines() {
+        assert_eq!(
+            trace_query("trace_id", "abc123", ""),
+            r#"trace_id="abc123""#
+        );
+        assert_eq!(
+            trace_query("traceId", r#"a"b"#, r#"level="error" or status>=500"#),
+            r#"traceId="a\"b" and (level="error" or status>=500)"#
+        );
+    }
+
+    #[test]
+    fn group_entries_sorts_groups_and_keeps_order_within() {
+        let entries = vec![
+            entry(Some(serde_json::json!("worker")), "w1"),
+            entry(None, "orphan"),
+            entry(Some(serde_json::json!("api")), "a1"),
+            entry(Some(serde_json::json!("worker")), "w2"),
+            entry(Some(serde_json::Value::Null), "null"),
+            entry(Some(serde_json::json!("api")), "a2"),
+        ];
+
+        let groups: Vec<(String, Vec<&str>)> = group_entries(&entries, "service")
+            .into_iter()
+            .map(|(key, group)| {
+                let msgs = group.iter().map(|e| e["msg"].as_str().unwrap()).collect();
+                (key, msgs)
+            })
+            .collect();
+
+        assert_eq!(
+            groups,
+            vec![
+                ("api".to_string(), vec!["a1", "a2"]),
+                ("worker".to_string(), vec!["w1", "w2"]),
+                ("(none)".to_string(), vec!["orphan", "null"]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use logchef_core::Config;
+use logchef_core::config::SavedQuery;
+use std::collections::BTreeMap;
+
+use crate::cli::GlobalArgs;
+use crate::session;
+
+#[derive(Args)]
+#[command(after_help = "EXAMPLES:
+  # Save a query, then run it
+  logchef saved add errors5xx 'status>=500'
+  logchef query --saved errors5xx --since 1h
+
+  # Pin the source and lookback it's meant for
+  logchef saved add slow-checkout 'path=\"/checkout\" and duration_ms>2000' \\
+    --source nginx --since 30m
+
+  # List them, or drop one
+  logchef saved
+  logchef saved remove errors5xx")]
+pub struct SavedArgs {
+    #[command(subcommand)]
+    command: Option<SavedCommands>,
+}
+
+#[derive(Subcommand)]
+enum SavedCommands {
+    #[command(about = "Save a LogchefQL query under a name in the current context")]
+    Add(AddArgs),
+
+    #[command(about = "List saved queries (the default)")]
+    List {
+        /// Output format
+        #[arg(long, default_value = "text")]
+        output: OutputFormat,
+    },
+
+    #[command(about = "Print a saved query")]
+    Show { name: String },
+
+    #[command(visible_alias = "rm", about = "Delete a saved query")]
+    Remove { name: String },
+}
+
+#[derive(Args)]
+struct AddArgs {
+    /// Name to run it by with `query --saved`.
+    name: String,
+
+    /// LogchefQL query. Use '-' to read it from stdin.
+    query: String,
+
+    /// Team used when `query` isn't given --team.
+    #[arg(long, short = 't')]
+    team: Option<String>,
+
+    /// Source used when `query` isn't given --source.
+    #[arg(long, short = 'S')]
+    source: Option<String>,
+
+    /// Lookback used when `query` isn't given --since, --from or --to.
+    #[arg(long, short = 's')]
+    since: Option<String>,
+
+    /// Replace an existing query with the same name.
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+pub async fn run(args: SavedArgs, global: GlobalArgs) -> Result<()> {
+    match args.command {
+        Some(SavedCommands::Add(add)) => add_query(add, &global),
+        Some(SavedCommands::List { output }) => list_queries(output, &global),
+        None => list_queries(OutputFormat::Text, &global),
+        Some(SavedCommands::Show { name }) => {
+            let config = Config::load().context("Failed to load config")?;
+            let saved = lookup(&config, &global, &name)?;
+            println!("{}", saved.query);
+            Ok(())
+        }
+        Some(SavedCommands::Remove { name }) => remove_query(&name, &global),
+    }
+}
+
+/// The saved query `name` in the context `global` resolves to.
+pub(crate) fn lookup(config: &Config, global: &GlobalArgs, name: &str) -> Result<SavedQuery> {
+    let resolved = session::resolve(config, global)?;
+    resolved.ctx.saved.get(name).cloned().ok_or_else(|| {
+        anyhow::anyhow!(
+            "No saved query '{}' in context '{}'. List them with 'logchef saved'.",
+            name,
+            resolved.name
+        )
+    })
+}
+
+/// Loads the config and the name of the (non-ephemeral) context that
+/// `global` resolves to, for commands that change its saved queries.
+fn context_for_update(global: &GlobalArgs) -> Result<(Config, String)> {
+    let config = Config::load().context("Failed to load config")?;
+    let resolved = session::resolve(&config, global)?;
+    if resolved.is_ephemeral {
+        anyhow::bail!(
+            "Saved queries are stored in a context; create one for {} with 'logchef auth --server {}'",
+            resolved.ctx.server_url,
+            resolved.ctx.server_url
+        );
+    }
+    Ok((config, resolved.name))
+}
+
+fn add_query(args: AddArgs, global: &GlobalArgs) -> Result<()> {
+    let query = match super::query::read_query_input(Some(&args.query), None)? {
+        Some(query) => query,
+        None => args.query.trim().to_string(),
+    };
+    if query.is_empty() {
+        anyhow::bail!("The query is empty");
+    }
+    if let Some(since) = &args.since {
+        logchef_core::timerange::since_window(since, None)?;
+    }
+
+    let (mut config, context) = context_for_update(global)?;
+    let ctx = config
+        .get_context_mut(&context)
+        .with_context(|| format!("Context '{}' not found", context))?;
+    if ctx.saved.contains_key(&args.name) && !args.force {
+        anyhow::bail!(
+            "A saved query named '{}' already exists. Pass --force to replace it.",
+            args.name
+        );
+    }
+    ctx.saved.insert(
+        args.name.clone(),
+        SavedQuery {
+            query,
+            team: args.team,
+            source: args.source,
+            since: args.since,
+        },
+    );
+    config.save().context("Failed to save config")?;
+    if !global.quiet {
+        eprintln!(
+            "Saved '{}' in context '{}'. Run it with: logchef query --saved {}",
+            args.name, context, args.name
+        );
+    }
+    Ok(())
+}
+
+fn remove_query(name: &str, global: &GlobalArgs) -> Result<()> {
+    let (mut config, context) = context_for_update(global)?;
+    let ctx = config
+        .get_context_mut(&context)
+        .with_context(|| format!("Context '{}' not found", context))?;
+    if ctx.saved.remove(name).is_none() {
+        anyhow::bail!("No saved query '{}' in context '{}'", name, context);
+    }
+    config.save().context("Failed to save config")?;
+    if !global.quiet {
+        eprintln!("Removed '{}' from context '{}'", name, context);
+    }
+    Ok(())
+}
+
+fn list_queries(output: OutputFormat, global: &GlobalArgs) -> Result<()> {
+    let config = Config::load().context("Failed to load config")?;
+    let resolved = session::resolve(&config, global)?;
+    let mut saved: Vec<(&String, &SavedQuery)> = resolved.ctx.saved.iter().collect();
+    saved.sort_by(|a, b| a.0.cmp(b.0));
+
+    if matches!(output, OutputFormat::Json) {
+        let map: BTreeMap<_, _> = saved.into_iter().collect();
+        println!("{}", serde_json::to_string_pretty(&map)?);
+        return Ok(());
+    }
+
+    if saved.is_empty() {
+        println!(
+            "No saved queries in context '{}'. Add one with 'logchef saved add <name> <query>'.",
+            resolved.name
+        );
+        return Ok(());
+    }
+    let width = saved.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, query) in saved {
+        let mut scope = Vec::new();
+        if let Some(team) = &query.team {
+            scope.push(format!("team={}", team));
+        }
+        if let Some(source) = &query.source {
+            scope.push(format!("source={}", source));
+        }
+        if let Some(since) = &query.since {
+            scope.push(format!("since={}", since));
+        }
+        let scope = if scope.is_empty() {
+            String::new()
+        } else {
+            format!("  ({})", scope.join(" "))
+        };
+        println!("{:<width$}  {}{}", name, query.query, scope, width = width);
+    }
+    Ok(())
+}
@@ -9,6 +9,7 @@ use logchef_core::cache::{Cache, Identifier, parse_identifier};
 use logchef_core::highlight::{
     FormatOptions, HighlightOptions, Highlighter, format_log_entry_with_options,
 };
+use logchef_core::timerange::display_timezone;
 use serde::Serialize;
 use url::Url;
 
@@ -61,6 +62,10 @@ pub struct SavedQueriesArgs {
     #[arg(long)]
     no_timestamp: bool,
 
+    /// Pad timestamp and level into fixed-width columns in text output
+    #[arg(long)]
+    aligned: bool,
+
     /// Custom highlight rules (format: COLOR:word1,word2)
     #[arg(long = "highlight", value_name = "COLOR:WORDS")]
     highlights: Vec<String>,
@@ -149,6 +154,12 @@ pub async fn run(args: SavedQueriesArgs, global: GlobalArgs) -> Result<()> {
         resolved.query.source_id = source_id;
     }
 
+    let _pager = match args.output {
+        OutputFormat::Text | OutputFormat::Table | OutputFormat::Msg => {
+            crate::pager::start(&config, &global)
+        }
+        _ => None,
+    };
     run_saved_query(
         &config,
         client,
@@ -157,7 +168,7 @@ pub async fn run(args: SavedQueriesArgs, global: GlobalArgs) -> Result<()> {
         &resolved.query,
         &args,
         ctx,
-        global.quiet,
+        &global,
     )
     .await
 }
@@ -220,10 +231,10 @@ async fn run_saved_query(
     query: &Collection,
     args: &SavedQueriesArgs,
     ctx: &logchef_core::config::Context,
-    quiet: bool,
+    global: &GlobalArgs,
 ) -> Result<()> {
-    let content: CollectionQueryContent =
-        serde_json::from_str(&query.query_content).context("Failed to parse query content")?;
+    let content = CollectionQueryContent::parse(&query.query_content)
+        .context("Failed to parse query content")?;
 
     let mut final_query = content.content.clone().unwrap_or_default();
     let var_overrides = parse_variable_overrides(&args.variables);
@@ -279,7 +290,7 @@ async fn run_saved_query(
             .context("Query failed")?
     };
 
-    print_query_response(config, query, args, &response, quiet)
+    print_query_response(config, query, args, &response, ctx, global)
 }
 
 fn print_query_response(
@@ -287,8 +298,10 @@ fn print_query_response(
     _query: &Collection,
     args: &SavedQueriesArgs,
     response: &logchef_core::api::QueryResponse,
-    quiet: bool,
+    ctx: &logchef_core::config::Context,
+    global: &GlobalArgs,
 ) -> Result<()> {
+    let quiet = global.quiet;
     let entries = response.entries();
 
     match args.output {
@@ -301,7 +314,13 @@ fn print_query_response(
                 generated_sql: response.generated_sql.as_deref(),
                 columns: &response.columns,
             };
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            println!(
+                "{}",
+                ui::highlight_json(
+                    &serde_json::to_string_pretty(&output)?,
+                    !args.no_highlight && ui::human(quiet),
+                )
+            );
         }
         OutputFormat::Jsonl => {
             for entry in entries {
@@ -346,6 +365,12 @@ fn print_query_response(
 
             let fmt_options = FormatOptions {
                 show_timestamp: !args.no_timestamp,
+                aligned: args.aligned,
+                timezone: Some(display_timezone(
+                    ctx.defaults.timezone.as_deref(),
+                    global.utc,
+                )),
+                ..Default::default()
             };
 
             for entry in entries {
@@ -583,7 +608,7 @@ async fn resolve_source_id(
     match parse_identifier(source) {
         Identifier::Id(id) => Ok(id),
         Identifier::Name(name) => {
-            if let Some(id) = cache.get_source_id(team_id, &name) {
+            if let Some(id) = super::cached_source_id(client, cache, team_id, &name).await? {
                 return Ok(id);
             }
 
@@ -599,6 +624,7 @@ async fn resolve_source_id(
                     cache_entries.push((table_ref, s.id));
                 }
             }
+            cache.observe_sources(team_id, &sources);
             cache.set_sources(team_id, &cache_entries);
 
             sources
@@ -662,8 +688,8 @@ fn parse_time_range(content: &CollectionQueryContent) -> Result<(String, String)
 
     if let Some(tr) = &content.time_range {
         if let Some(rel) = &tr.relative {
-            let end = Utc::now();
-            let start = end - parse_duration(rel)?;
+            // Formatted as UTC below, so named windows are resolved in UTC.
+            let (start, end) = logchef_core::timerange::since_window(rel, Some("UTC"))?;
             return Ok((
                 start.format(format).to_string(),
                 end.format(format).to_string(),
@@ -694,35 +720,6 @@ fn parse_time_range(content: &CollectionQueryContent) -> Result<(String, String)
     ))
 }
 
-fn parse_duration(s: &str) -> Result<Duration> {
-    let s = s.trim();
-    if s.is_empty() {
-        return Ok(Duration::minutes(15));
-    }
-
-    let (num, unit) = if s.ends_with('m') {
-        (s.trim_end_matches('m'), "m")
-    } else if s.ends_with('h') {
-        (s.trim_end_matches('h'), "h")
-    } else if s.ends_with('d') {
-        (s.trim_end_matches('d'), "d")
-    } else if s.ends_with('w') {
-        (s.trim_end_matches('w'), "w")
-    } else {
-        (s, "m")
-    };
-
-    let num: i64 = num.parse().context("Invalid duration number")?;
-
-    match unit {
-        "m" => Ok(Duration::minutes(num)),
-        "h" => Ok(Duration::hours(num)),
-        "d" => Ok(Duration::days(num)),
-        "w" => Ok(Duration::weeks(num)),
-        _ => Ok(Duration::minutes(num)),
-    }
-}
-
 fn parse_variable_overrides(vars: &[String]) -> std::collections::HashMap<String, String> {
     vars.iter()
         .filter_map(|v| {
@@ -790,10 +787,7 @@ fn print_table(entries: &[logchef_core::api::LogEntry], columns: &[logchef_core:
             .map(|c| {
                 entry
                     .get(&c.name)
-                    .map(|v| match v {
-                        serde_json::Value::String(s) => s.clone(),
-                        _ => v.to_string(),
-                    })
+                    .map(super::table_cell)
                     .unwrap_or_default()
             })
             .collect();
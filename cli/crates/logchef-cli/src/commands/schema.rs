@@ -91,7 +91,9 @@ pub async fn run(args: SchemaArgs, global: GlobalArgs) -> Result<()> {
         match parse_identifier(&source_input) {
             Identifier::Id(id) => id,
             Identifier::Name(name) => {
-                if let Some(id) = cache.get_source_id(team_id, &name) {
+                if let Some(id) =
+                    super::cached_source_id(client, &mut cache, team_id, &name).await?
+                {
                     id
                 } else {
                     let sources = client
@@ -106,6 +108,7 @@ pub async fn run(args: SchemaArgs, global: GlobalArgs) -> Result<()> {
                             cache_entries.push((target_ref, s.id));
                         }
                     }
+                    cache.observe_sources(team_id, &sources);
                     cache.set_sources(team_id, &cache_entries);
 
                     sources
@@ -210,6 +213,7 @@ async fn prompt_source_interactive(
             cache_entries.push((target_ref, s.id));
         }
     }
+    cache.observe_sources(team_id, &sources);
     cache.set_sources(team_id, &cache_entries);
 
     Ok(source.id)
@@ -1,24 +1,80 @@
 use anyhow::{Context, Result};
-use clap::Args;
+use chrono::{SecondsFormat, Utc};
+use clap::{Args, Subcommand};
 use inquire::Select;
 use logchef_core::Config;
-use logchef_core::api::Client;
+use logchef_core::api::{Client, QueryRequest, SqlQueryRequest};
 use logchef_core::cache::{Cache, Identifier, parse_identifier};
+use logchef_core::timerange::{TimeInput, resolve_time_range};
 use serde::Serialize;
 use std::io::IsTerminal;
+use std::time::{Duration, Instant};
 
 use crate::cli::GlobalArgs;
 use crate::session;
+use crate::ui;
+
+/// Upper bound on a single `--check-connectivity` probe, so one hung source
+/// can't stall the whole listing.
+const PROBE_TIMEOUT_SECS: u64 = 10;
 
 #[derive(Args)]
+#[command(after_help = "EXAMPLES:
+  # List a team's sources
+  logchef sources --team production
+
+  # Probe every source with a trivial LogchefQL query
+  logchef sources --team production --check-connectivity
+
+  # Check one source's backing store directly (SELECT 1 / LogsQL equivalent)
+  logchef sources ping app-logs --team production
+
+  # Is data flowing? Watch the newest logs arrive, stop after 20
+  logchef sources sample app-logs --team production --max-lines 20")]
 pub struct SourcesArgs {
+    #[command(subcommand)]
+    command: Option<SourcesCommand>,
+
     /// Team ID or name
-    #[arg(long, short = 't')]
+    #[arg(long, short = 't', global = true)]
     team: Option<String>,
 
     /// Output format
-    #[arg(long, default_value = "text")]
+    #[arg(long, default_value = "text", global = true)]
     output: OutputFormat,
+
+    /// Run a trivial query against each source and report reachability and
+    /// latency
+    #[arg(long)]
+    check_connectivity: bool,
+}
+
+#[derive(Subcommand)]
+enum SourcesCommand {
+    /// Check that a source's backing store (ClickHouse or VictoriaLogs) is
+    /// reachable by running a minimal native query through the server. Exits 1
+    /// when it isn't.
+    Ping {
+        /// Source ID, name, or `database.table_name` (defaults to
+        /// defaults.source)
+        source: Option<String>,
+    },
+
+    /// Follow a source's newest logs with no filter, to check that data is
+    /// flowing. Shorthand for `logchef tail '' -S <source>`; Ctrl-C to stop.
+    Sample {
+        /// Source ID, name, or `database.table_name` (defaults to
+        /// defaults.source)
+        source: Option<String>,
+
+        /// Stop after printing this many rows
+        #[arg(long)]
+        max_lines: Option<usize>,
+
+        /// Poll with short queries instead of the server's live-tail stream
+        #[arg(long)]
+        poll: bool,
+    },
 }
 
 #[derive(Clone, Debug, clap::ValueEnum)]
@@ -39,9 +95,49 @@ struct SourceOut {
     connected: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    probe: Option<Probe>,
+}
+
+/// Result of a `--check-connectivity` probe query.
+#[derive(Serialize)]
+struct Probe {
+    reachable: bool,
+    latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Result of `sources ping`.
+#[derive(Serialize)]
+struct PingOut {
+    id: i64,
+    name: String,
+    source_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+    #[serde(flatten)]
+    probe: Probe,
 }
 
 pub async fn run(args: SourcesArgs, global: GlobalArgs) -> Result<()> {
+    match args.command {
+        Some(SourcesCommand::Ping { source }) => {
+            return ping(source, args.team, args.output, global).await;
+        }
+        Some(SourcesCommand::Sample {
+            source,
+            max_lines,
+            poll,
+        }) => {
+            let jsonl = matches!(args.output, OutputFormat::Json | OutputFormat::Jsonl);
+            let tail_args =
+                super::tail::TailArgs::sample(args.team, source, max_lines, poll, jsonl);
+            return super::tail::run(tail_args, global).await;
+        }
+        None => {}
+    }
+
     let config = Config::load().context("Failed to load config")?;
     let s = session::authed(&config, &global)?;
     let (client, ctx) = (&s.client, &s.ctx);
@@ -94,7 +190,7 @@ pub async fn run(args: SourcesArgs, global: GlobalArgs) -> Result<()> {
         return Ok(());
     }
 
-    let rows: Vec<SourceOut> = sources
+    let mut rows: Vec<SourceOut> = sources
         .into_iter()
         .map(|s| {
             let source_type = s.source_type_label().to_string();
@@ -106,10 +202,19 @@ pub async fn run(args: SourcesArgs, global: GlobalArgs) -> Result<()> {
                 target,
                 connected: s.is_connected,
                 description: s.description,
+                probe: None,
             }
         })
         .collect();
 
+    if args.check_connectivity {
+        let spinner = ui::Spinner::start(global.quiet, "checking sources");
+        for row in &mut rows {
+            row.probe = Some(probe_source(client, team_id, row.id, ctx).await);
+        }
+        spinner.finish();
+    }
+
     match args.output {
         OutputFormat::Json => {
             println!("{}", serde_json::to_string_pretty(&rows)?);
@@ -119,6 +224,30 @@ pub async fn run(args: SourcesArgs, global: GlobalArgs) -> Result<()> {
                 println!("{}", serde_json::to_string(&row)?);
             }
         }
+        OutputFormat::Text | OutputFormat::Table if args.check_connectivity => {
+            println!(
+                "{:<4} {:<24} {:<16} {:<10} {:<10} ERROR",
+                "ID", "NAME", "TYPE", "REACHABLE", "LATENCY"
+            );
+            println!("{}", "-".repeat(90));
+            for row in &rows {
+                let Some(probe) = &row.probe else { continue };
+                println!(
+                    "{:<4} {:<24} {:<16} {:<10} {:<10} {}",
+                    row.id,
+                    truncate_str(&row.name, 24),
+                    truncate_str(&row.source_type, 16),
+                    if probe.reachable { "yes" } else { "no" },
+                    format!("{}ms", probe.latency_ms),
+                    probe.error.as_deref().unwrap_or("")
+                );
+            }
+            let unreachable = rows
+                .iter()
+                .filter(|row| row.probe.as_ref().is_some_and(|p| !p.reachable))
+                .count();
+            println!("\n{} sources, {} unreachable", rows.len(), unreachable);
+        }
         OutputFormat::Text | OutputFormat::Table => {
             println!(
                 "{:<4} {:<24} {:<16} {:<32} {:<10} DESCRIPTION",
@@ -148,6 +277,168 @@ pub async fn run(args: SourcesArgs, global: GlobalArgs) -> Result<()> {
     Ok(())
 }
 
+/// Issues a one-row query over the last minute and times the round trip.
+/// Failures (including the probe timeout) are reported, never propagated, so
+/// one broken source doesn't hide the rest.
+async fn probe_source(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    ctx: &logchef_core::config::Context,
+) -> Probe {
+    let end = Utc::now();
+    let time_range = resolve_time_range(
+        TimeInput::Instant {
+            start: end - chrono::Duration::minutes(1),
+            end,
+        },
+        ctx.defaults.timezone.as_deref(),
+    );
+    let request = QueryRequest {
+        query: String::new(),
+        start_time: time_range.start,
+        end_time: time_range.end,
+        timezone: Some(time_range.timezone),
+        limit: Some(1),
+        query_timeout: Some(PROBE_TIMEOUT_SECS as u32),
+    };
+
+    let started = Instant::now();
+    let result = tokio::time::timeout(
+        Duration::from_secs(PROBE_TIMEOUT_SECS),
+        client.query_logchefql(team_id, source_id, &request),
+    )
+    .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let error = match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(_) => Some(format!("timed out after {}s", PROBE_TIMEOUT_SECS)),
+    };
+    Probe {
+        reachable: error.is_none(),
+        latency_ms,
+        error,
+    }
+}
+
+async fn ping(
+    source: Option<String>,
+    team: Option<String>,
+    output: OutputFormat,
+    global: GlobalArgs,
+) -> Result<()> {
+    let config = Config::load().context("Failed to load config")?;
+    let s = session::authed(&config, &global)?;
+    let (client, ctx) = (&s.client, &s.ctx);
+
+    let mut cache = Cache::new(&ctx.server_url);
+    let team_id =
+        super::resolve_team(client, &mut cache, team.or(ctx.defaults.team_with_env())).await?;
+    let source_id = super::resolve_source(
+        client,
+        &mut cache,
+        team_id,
+        source.or(ctx.defaults.source_with_env()),
+    )
+    .await?;
+    let source = client
+        .get_source(team_id, source_id)
+        .await
+        .context("Failed to fetch source")?;
+    cache.observe_source(&source);
+
+    let spinner = ui::Spinner::start(global.quiet, "pinging source");
+    let is_victorialogs = source.source_type.eq_ignore_ascii_case("victorialogs");
+    let probe = ping_source(client, team_id, source_id, is_victorialogs).await;
+    spinner.finish();
+
+    let out = PingOut {
+        id: source.id,
+        name: source.name.clone(),
+        source_type: source.source_type_label().to_string(),
+        target: source.target_ref(),
+        probe,
+    };
+
+    match output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&out)?),
+        OutputFormat::Jsonl => println!("{}", serde_json::to_string(&out)?),
+        OutputFormat::Text | OutputFormat::Table => {
+            let label = out.target.as_deref().unwrap_or(&out.name);
+            match &out.probe.error {
+                None => println!(
+                    "{} ({}) is reachable: {}ms",
+                    label, out.source_type, out.probe.latency_ms
+                ),
+                Some(err) => println!(
+                    "{} ({}) is unreachable after {}ms: {}",
+                    label, out.source_type, out.probe.latency_ms, err
+                ),
+            }
+        }
+    }
+
+    if !out.probe.reachable {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Runs the cheapest native query the source's engine accepts: `SELECT 1` for
+/// ClickHouse, and a one-row LogsQL query over the last minute for
+/// VictoriaLogs (which always needs a time window). Unlike `probe_source`, this
+/// never touches the source's table schema, so it isolates the store itself.
+async fn ping_source(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    is_victorialogs: bool,
+) -> Probe {
+    let request = if is_victorialogs {
+        let end = Utc::now();
+        SqlQueryRequest {
+            query_text: "* | limit 1".to_string(),
+            limit: Some(1),
+            timezone: None,
+            start_time: Some(
+                (end - chrono::Duration::minutes(1)).to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
+            end_time: Some(end.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            query_timeout: Some(PROBE_TIMEOUT_SECS as u32),
+        }
+    } else {
+        SqlQueryRequest {
+            query_text: "SELECT 1".to_string(),
+            limit: Some(1),
+            timezone: None,
+            start_time: None,
+            end_time: None,
+            query_timeout: Some(PROBE_TIMEOUT_SECS as u32),
+        }
+    };
+
+    let started = Instant::now();
+    let result = tokio::time::timeout(
+        Duration::from_secs(PROBE_TIMEOUT_SECS),
+        client.query_sql(team_id, source_id, &request),
+    )
+    .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let error = match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(_) => Some(format!("timed out after {}s", PROBE_TIMEOUT_SECS)),
+    };
+    Probe {
+        reachable: error.is_none(),
+        latency_ms,
+        error,
+    }
+}
+
 async fn prompt_team_interactive(client: &Client, cache: &mut Cache) -> Result<i64> {
     let teams = client.list_teams().await.context("Failed to list teams")?;
     if teams.is_empty() {
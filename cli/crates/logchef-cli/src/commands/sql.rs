@@ -1,20 +1,26 @@
 use anyhow::{Context as _, Result};
-use chrono::{DateTime, Duration as ChronoDuration, NaiveDateTime, SecondsFormat, Utc};
+use chrono::SecondsFormat;
 use clap::Args;
+use futures::StreamExt;
 use inquire::{Select, Text};
 use logchef_core::Config;
 use logchef_core::api::{Client, Column, ExportSqlRequest, QueryStats, Source, SqlQueryRequest};
 use logchef_core::cache::{Cache, Identifier, parse_identifier};
 use logchef_core::config::Context;
 use logchef_core::highlight::{
-    FormatOptions, HighlightOptions, Highlighter, format_log_entry_with_options,
+    FormatOptions, HighlightOptions, Highlighter, LineTemplate, Severity,
+    format_log_entry_with_options,
+};
+use logchef_core::history::QueryKind;
+use logchef_core::timerange::{
+    TimeInput, display_timezone, from_to_window, resolve_time_range, resolve_timezone, since_window,
 };
-use logchef_core::timerange::{TimeInput, resolve_time_range, resolve_timezone};
 use serde::Serialize;
 use std::io::{IsTerminal, Read, Write};
 use tokio::time::{Duration, sleep};
 
 use crate::cli::GlobalArgs;
+use crate::commands::history;
 use crate::session;
 use crate::ui;
 
@@ -34,7 +40,10 @@ const SQL_HTTP_TIMEOUT_HEADROOM_SECS: u64 = 60;
   logchef sql 'SELECT * FROM logs.app' --since 6h --stream --output jsonl > out.ndjson
 
   # Read the query from stdin, export as CSV
-  echo 'SELECT * FROM logs.app LIMIT 1000' | logchef sql - --output csv > rows.csv")]
+  echo 'SELECT * FROM logs.app LIMIT 1000' | logchef sql - --output csv > rows.csv
+
+  # Append semicolon-separated rows to an existing file, without the header
+  logchef sql 'SELECT * FROM logs.app' --output csv --delimiter ';' --no-header >> rows.csv")]
 pub struct SqlArgs {
     /// Raw native query to execute. Use SQL for ClickHouse and LogsQL for VictoriaLogs. Use '-' to read from stdin.
     sql: Option<String>,
@@ -47,20 +56,21 @@ pub struct SqlArgs {
     #[arg(long, short = 'S')]
     source: Option<String>,
 
-    /// Apply a relative time range to SQL (e.g., 15m, 1h, 24h), evaluated
-    /// against now in the effective timezone: `defaults.timezone` if
+    /// Apply a relative time range to SQL (e.g., 15m, 1h, 24h, today,
+    /// yesterday, this-week), evaluated against now in the effective
+    /// timezone: `defaults.timezone` if
     /// configured, otherwise the system's local timezone (see `logchef
     /// config show`).
     #[arg(long, short = 's')]
     since: Option<String>,
 
-    /// Apply an absolute start time (YYYY-MM-DD HH:MM:SS), interpreted as
-    /// wall-clock in the effective timezone.
+    /// Apply an absolute start time: RFC3339, `YYYY-MM-DD HH:MM:SS` or a
+    /// date (wall-clock in the effective timezone), epoch milliseconds, or a
+    /// relative time like "2 hours ago".
     #[arg(long)]
     from: Option<String>,
 
-    /// Apply an absolute end time (YYYY-MM-DD HH:MM:SS), interpreted as
-    /// wall-clock in the effective timezone.
+    /// Apply an absolute end time, in the same formats as --from.
     #[arg(long)]
     to: Option<String>,
 
@@ -68,7 +78,13 @@ pub struct SqlArgs {
     #[arg(long, default_value = "30")]
     timeout: u32,
 
-    /// Stream results directly from the server instead of buffering a preview response
+    /// Run even if the time range exceeds the configured `max_time_range`.
+    #[arg(long)]
+    force: bool,
+
+    /// Stream results directly from the server instead of buffering a preview
+    /// response: raw NDJSON with `--output jsonl`, or rows formatted as they
+    /// arrive with `--output text`
     #[arg(long)]
     stream: bool,
 
@@ -77,9 +93,22 @@ pub struct SqlArgs {
     limit: Option<u32>,
 
     /// Output format
-    #[arg(long, default_value = "text")]
+    #[arg(long, default_value = "text", env = "LOGCHEF_OUTPUT")]
     output: OutputFormat,
 
+    /// Rows to render in text, table and msg output before cutting off with
+    /// a notice (0 = no cap). JSON output always includes every row
+    #[arg(long, value_name = "N", default_value_t = super::DISPLAY_CAP)]
+    max_display: usize,
+
+    /// Omit the header row from CSV or TSV output
+    #[arg(long, alias = "headers-off")]
+    no_header: bool,
+
+    /// Field delimiter for CSV output: a single character, or `\t` / `tab`
+    #[arg(long, value_name = "CHAR", value_parser = parse_delimiter)]
+    delimiter: Option<u8>,
+
     /// Disable syntax highlighting
     #[arg(long)]
     no_highlight: bool,
@@ -88,6 +117,30 @@ pub struct SqlArgs {
     #[arg(long)]
     no_timestamp: bool,
 
+    /// Pad timestamp and level into fixed-width columns in text output
+    #[arg(long)]
+    aligned: bool,
+
+    /// Render each row with a template instead of the default text layout,
+    /// e.g. `'{timestamp} [{level}] {msg}'`. `{a.b}` reads nested fields,
+    /// `{a|b|"-"}` falls back to another field or a literal.
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "output")]
+    format: Option<String>,
+
+    /// In JSON output, write every column on every entry, with `null` where
+    /// the row has no value, so all objects share one key set
+    #[arg(long, conflicts_with = "omit_nulls")]
+    include_nulls: bool,
+
+    /// In JSON output, drop fields whose value is `null`
+    #[arg(long)]
+    omit_nulls: bool,
+
+    /// Keep only entries whose `level`/`severity` is at or above this
+    /// (trace, debug, info, warn, error, fatal); filtered client-side
+    #[arg(long, value_name = "LEVEL")]
+    min_level: Option<Severity>,
+
     /// Custom highlight rules (format: COLOR:word1,word2)
     #[arg(long = "highlight", value_name = "COLOR:WORDS")]
     highlights: Vec<String>,
@@ -114,6 +167,7 @@ enum OutputFormat {
     Jsonl,
     JsonFlat,
     Csv,
+    Tsv,
     Table,
     Msg,
 }
@@ -129,6 +183,31 @@ struct JsonOutput<'a> {
 }
 
 pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
+    if args.no_header && !matches!(args.output, OutputFormat::Csv | OutputFormat::Tsv) {
+        anyhow::bail!("--no-header requires --output csv or tsv");
+    }
+    if args.delimiter.is_some() && !matches!(args.output, OutputFormat::Csv) {
+        anyhow::bail!("--delimiter requires --output csv");
+    }
+    if args.include_nulls || args.omit_nulls {
+        if !matches!(
+            args.output,
+            OutputFormat::Json | OutputFormat::Jsonl | OutputFormat::JsonFlat
+        ) {
+            anyhow::bail!(
+                "--include-nulls and --omit-nulls require --output json, jsonl or json-flat"
+            );
+        }
+        if args.stream {
+            anyhow::bail!("--include-nulls and --omit-nulls don't apply to --stream output");
+        }
+    }
+    let template = args
+        .format
+        .as_deref()
+        .map(LineTemplate::parse)
+        .transpose()
+        .context("Invalid --format template")?;
     let config = Config::load().context("Failed to load config")?;
 
     let effective_query_timeout_secs =
@@ -200,7 +279,9 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
         match parse_identifier(&source_input) {
             Identifier::Id(id) => id,
             Identifier::Name(name) => {
-                if let Some(id) = cache.get_source_id(team_id, &name) {
+                if let Some(id) =
+                    super::cached_source_id(client, &mut cache, team_id, &name).await?
+                {
                     id
                 } else {
                     let sources = client
@@ -215,6 +296,7 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
                             cache_entries.push((target_ref, s.id));
                         }
                     }
+                    cache.observe_sources(team_id, &sources);
                     cache.set_sources(team_id, &cache_entries);
 
                     sources
@@ -259,14 +341,38 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
         anyhow::bail!("Raw query cannot be empty");
     }
 
+    let mut executed = history::with_window(
+        history::new_entry(&s, QueryKind::Native, &sql, team_id, source_id),
+        args.since.as_deref(),
+        args.from.as_deref(),
+        args.to.as_deref(),
+        ctx.defaults.timezone.as_deref(),
+    );
+    executed.source = args
+        .source
+        .clone()
+        .or_else(|| ctx.defaults.source_with_env());
+    executed.limit = args.limit;
+
     // Fetch the source once: we need its engine (to pick the time-range
     // strategy) and its timestamp field (for the ClickHouse injection).
     let source = client
         .get_source(team_id, source_id)
         .await
         .context("Failed to fetch source")?;
+    cache.observe_source(&source);
     let is_victorialogs = source.source_type.eq_ignore_ascii_case("victorialogs");
 
+    if args.since.is_some() || args.from.is_some() || args.to.is_some() {
+        let (start, end) = super::histogram::resolve_instants(
+            args.since.as_deref().unwrap_or("15m"),
+            args.from.as_deref(),
+            args.to.as_deref(),
+            ctx.defaults.timezone.as_deref(),
+        )?;
+        super::check_time_range_cap(ctx, source_id, Some(&source.name), end - start, args.force)?;
+    }
+
     // Time-range handling differs by engine:
     //   ClickHouse   — splice a `toDateTime(...) BETWEEN` condition into the
     //                  SQL string (or fill __START__/__END__ placeholders).
@@ -331,16 +437,30 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
                         .context("Failed to download CSV export")?;
 
                     let mut stdout = std::io::stdout().lock();
+                    // The server always writes a comma-separated file with a
+                    // header; rewrite records only when asked to.
+                    let mut rewriter = (args.no_header || args.delimiter.is_some())
+                        .then(|| CsvRewriter::new(args.delimiter.unwrap_or(b','), args.no_header));
                     while let Some(chunk) = response
                         .chunk()
                         .await
                         .context("Failed to read CSV export")?
                     {
+                        let chunk = match rewriter.as_mut() {
+                            Some(rewriter) => rewriter.feed(&chunk),
+                            None => chunk.to_vec(),
+                        };
                         stdout
                             .write_all(&chunk)
                             .context("Failed to write CSV export to stdout")?;
                     }
+                    if let Some(rewriter) = rewriter {
+                        stdout
+                            .write_all(&rewriter.finish())
+                            .context("Failed to write CSV export to stdout")?;
+                    }
                     stdout.flush().context("Failed to flush stdout")?;
+                    history::record(&executed);
                     return Ok(());
                 }
                 "failed" => {
@@ -364,17 +484,12 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
 
     if args.stream {
         let format = match args.output {
-            OutputFormat::Jsonl => "ndjson",
+            OutputFormat::Jsonl | OutputFormat::Text => "ndjson",
             OutputFormat::Json => {
                 anyhow::bail!(
                     "--stream does not support --output json. Use --output jsonl for streamed JSON or drop --stream for buffered JSON output."
                 );
             }
-            OutputFormat::Text => {
-                anyhow::bail!(
-                    "--stream does not support --output text. Use --stream --output jsonl for live streaming or --output csv for a completed-file export."
-                );
-            }
             OutputFormat::Table => {
                 anyhow::bail!(
                     "--stream does not support --output table. Use --stream --output jsonl for live streaming or --output csv for a completed-file export."
@@ -390,6 +505,11 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
                     "--stream does not support --output msg. Use --output msg without --stream for buffered message output."
                 );
             }
+            OutputFormat::Tsv => {
+                anyhow::bail!(
+                    "--stream does not support --output tsv. Use --output tsv without --stream for buffered TSV output."
+                );
+            }
             OutputFormat::Csv => unreachable!("CSV output is handled by export jobs"),
         };
 
@@ -400,6 +520,24 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
             query_timeout: Some(effective_query_timeout_secs),
         };
 
+        if matches!(args.output, OutputFormat::Text) {
+            let _pager = crate::pager::start(&config, &global);
+            stream_text(
+                client,
+                team_id,
+                source_id,
+                &request,
+                &args,
+                &config,
+                global.quiet,
+                &text_format_options(&args, ctx.defaults.timezone.as_deref(), global.utc),
+                template.as_ref(),
+            )
+            .await?;
+            history::record(&executed);
+            return Ok(());
+        }
+
         let mut response = client
             .export_sql(team_id, source_id, &request)
             .await
@@ -412,6 +550,7 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
                 .context("Failed to write stream to stdout")?;
         }
         stdout.flush().context("Failed to flush stdout")?;
+        history::record(&executed);
         return Ok(());
     }
 
@@ -435,10 +574,37 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
     let spinner = ui::Spinner::start(global.quiet, "querying");
     let result = client.query_sql(team_id, source_id, &request).await;
     spinner.finish();
-    let response = result.context("Raw query failed")?;
+    let mut response = result.context("Raw query failed")?;
+    executed.rows = Some(response.entries().len() as u64);
+    executed.duration_ms = Some(response.stats.execution_time_ms);
+    history::record(&executed);
+    if args.include_nulls {
+        response.fill_nulls();
+    } else if args.omit_nulls {
+        response.omit_nulls();
+    }
 
-    let entries = response.entries();
+    let filtered;
+    let entries = match args.min_level {
+        Some(min) => {
+            filtered = super::filter_min_level(response.entries(), min);
+            &filtered[..]
+        }
+        None => response.entries(),
+    };
+    let entries = match args.output {
+        OutputFormat::Text | OutputFormat::Table | OutputFormat::Msg => {
+            super::cap_for_display(entries, args.max_display)
+        }
+        _ => entries,
+    };
 
+    let _pager = match args.output {
+        OutputFormat::Text | OutputFormat::Table | OutputFormat::Msg => {
+            crate::pager::start(&config, &global)
+        }
+        _ => None,
+    };
     match args.output {
         OutputFormat::Json => {
             let output = JsonOutput {
@@ -448,7 +614,13 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
                 query_id: response.query_id.as_deref(),
                 columns: &response.columns,
             };
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            println!(
+                "{}",
+                ui::highlight_json(
+                    &serde_json::to_string_pretty(&output)?,
+                    !args.no_highlight && ui::human(global.quiet),
+                )
+            );
         }
         OutputFormat::Jsonl => {
             for entry in entries {
@@ -476,26 +648,28 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
         OutputFormat::Csv => {
             anyhow::bail!("Use --stream --output csv for CSV output");
         }
+        OutputFormat::Tsv => {
+            super::write_tsv(
+                &mut std::io::stdout().lock(),
+                entries,
+                &response.columns,
+                !args.no_header,
+                &[],
+            )?;
+        }
         OutputFormat::Msg => {
             print_msg(entries, &response.columns, true);
         }
         OutputFormat::Text => {
-            let highlighter = if args.no_highlight || !ui::human(global.quiet) {
-                None
-            } else {
-                let hl_options = HighlightOptions {
-                    adhoc_highlights: parse_highlight_args(&args.highlights),
-                    disabled_groups: args.disable_highlights.clone(),
-                };
-                Highlighter::with_options(&config.highlights, &hl_options).ok()
-            };
-
-            let fmt_options = FormatOptions {
-                show_timestamp: !args.no_timestamp,
-            };
+            let highlighter = text_highlighter(&args, &config, global.quiet);
+            let fmt_options =
+                text_format_options(&args, ctx.defaults.timezone.as_deref(), global.utc);
 
             for entry in entries {
-                let line = format_log_entry_with_options(entry, &response.columns, &fmt_options);
+                let line = match &template {
+                    Some(template) => template.render(entry),
+                    None => format_log_entry_with_options(entry, &response.columns, &fmt_options),
+                };
                 if let Some(ref h) = highlighter {
                     println!("{}", h.highlight(&line));
                 } else {
@@ -514,6 +688,80 @@ pub async fn run(args: SqlArgs, global: GlobalArgs) -> Result<()> {
     Ok(())
 }
 
+fn text_highlighter(args: &SqlArgs, config: &Config, quiet: bool) -> Option<Highlighter> {
+    if args.no_highlight || !ui::human(quiet) {
+        return None;
+    }
+    let hl_options = HighlightOptions {
+        adhoc_highlights: parse_highlight_args(&args.highlights),
+        disabled_groups: args.disable_highlights.clone(),
+    };
+    Highlighter::with_options(&config.highlights, &hl_options).ok()
+}
+
+fn text_format_options(args: &SqlArgs, timezone: Option<&str>, utc: bool) -> FormatOptions {
+    FormatOptions {
+        show_timestamp: !args.no_timestamp,
+        aligned: args.aligned,
+        timezone: Some(display_timezone(timezone, utc)),
+        ..Default::default()
+    }
+}
+
+/// `--stream --output text`: formats rows as the NDJSON export arrives, so
+/// large results print without ever being held in memory.
+#[allow(clippy::too_many_arguments)]
+async fn stream_text(
+    client: &Client,
+    team_id: i64,
+    source_id: i64,
+    request: &ExportSqlRequest,
+    args: &SqlArgs,
+    config: &Config,
+    quiet: bool,
+    fmt_options: &FormatOptions,
+    template: Option<&LineTemplate>,
+) -> Result<()> {
+    let highlighter = text_highlighter(args, config, quiet);
+
+    let rows = client
+        .stream_sql(team_id, source_id, request)
+        .await
+        .context("SQL stream failed")?;
+    let mut rows = std::pin::pin!(rows);
+    let mut stdout = std::io::stdout().lock();
+    while let Some(entry) = rows.next().await {
+        let entry = entry.context("Failed to read stream")?;
+        if let Some(min) = args.min_level
+            && !Severity::of_entry(&entry).is_some_and(|level| level >= min)
+        {
+            continue;
+        }
+        let line = match template {
+            Some(template) => template.render(&entry),
+            None => {
+                // Export rows carry no schema; their keys are the columns.
+                let columns: Vec<Column> = entry
+                    .keys()
+                    .map(|name| Column {
+                        name: name.clone(),
+                        column_type: String::new(),
+                        description: None,
+                    })
+                    .collect();
+                format_log_entry_with_options(&entry, &columns, fmt_options)
+            }
+        };
+        match &highlighter {
+            Some(h) => writeln!(stdout, "{}", h.highlight(&line)),
+            None => writeln!(stdout, "{}", line),
+        }
+        .context("Failed to write stream to stdout")?;
+    }
+    stdout.flush().context("Failed to flush stdout")?;
+    Ok(())
+}
+
 /// Resolves an absolute RFC3339 UTC time window for a VictoriaLogs `sql` query
 /// from --since/--from/--to. Returns None when no time flag is set.
 ///
@@ -527,17 +775,13 @@ fn vl_time_window(args: &SqlArgs, ctx: &Context) -> Result<Option<(String, Strin
     }
 
     let (start, end) = match (args.from.as_deref(), args.to.as_deref()) {
-        (Some(from), Some(to)) => {
-            let tz = resolve_timezone(ctx.defaults.timezone.as_deref());
-            (wall_clock_to_utc(from, tz)?, wall_clock_to_utc(to, tz)?)
-        }
+        (Some(from), Some(to)) => from_to_window(from, to, ctx.defaults.timezone.as_deref())?,
         (Some(_), None) => anyhow::bail!("--from requires --to to be specified"),
         (None, Some(_)) => anyhow::bail!("--to requires --from to be specified"),
-        (None, None) => {
-            let end = Utc::now();
-            let start = end - parse_duration(args.since.as_deref().unwrap_or("15m"))?;
-            (start, end)
-        }
+        (None, None) => since_window(
+            args.since.as_deref().unwrap_or("15m"),
+            ctx.defaults.timezone.as_deref(),
+        )?,
     };
 
     Ok(Some((
@@ -546,19 +790,6 @@ fn vl_time_window(args: &SqlArgs, ctx: &Context) -> Result<Option<(String, Strin
     )))
 }
 
-/// Parses a `YYYY-MM-DD HH:MM:SS` wall-clock string, interpreted in `tz`, into
-/// a UTC instant. Generic over the timezone so this file needs no direct
-/// chrono-tz dependency (the concrete zone comes from `resolve_timezone`).
-fn wall_clock_to_utc<Tz: chrono::TimeZone>(value: &str, tz: Tz) -> Result<DateTime<Utc>> {
-    let naive = NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%d %H:%M:%S")
-        .context("Invalid time format (expected YYYY-MM-DD HH:MM:SS)")?;
-    naive
-        .and_local_timezone(tz)
-        .single()
-        .map(|dt| dt.with_timezone(&Utc))
-        .ok_or_else(|| anyhow::anyhow!("Ambiguous or invalid local time '{}'", value))
-}
-
 /// ClickHouse time-range injection: splices a `toDateTime(...) BETWEEN` filter
 /// into the SQL string, or fills __START__/__END__ placeholders. This path is
 /// ClickHouse-only — VictoriaLogs uses [`vl_time_window`] instead.
@@ -610,49 +841,16 @@ fn parse_time_range(
     to: Option<&str>,
     configured_tz: Option<&str>,
 ) -> Result<logchef_core::timerange::ResolvedTimeRange> {
-    let input = match (from, to) {
-        (Some(from), Some(to)) => TimeInput::WallClock {
-            start: from,
-            end: to,
-        },
+    let (start, end) = match (from, to) {
+        (Some(from), Some(to)) => from_to_window(from, to, configured_tz)?,
         (Some(_), None) => anyhow::bail!("--from requires --to to be specified"),
         (None, Some(_)) => anyhow::bail!("--to requires --from to be specified"),
-        (None, None) => {
-            let end = Utc::now();
-            let start = end - parse_duration(since.unwrap_or("15m"))?;
-            TimeInput::Instant { start, end }
-        }
-    };
-    Ok(resolve_time_range(input, configured_tz))
-}
-
-fn parse_duration(s: &str) -> Result<ChronoDuration> {
-    let s = s.trim();
-    if s.is_empty() {
-        return Ok(ChronoDuration::minutes(15));
-    }
-
-    let (num, unit) = if s.ends_with('m') {
-        (s.trim_end_matches('m'), "m")
-    } else if s.ends_with('h') {
-        (s.trim_end_matches('h'), "h")
-    } else if s.ends_with('d') {
-        (s.trim_end_matches('d'), "d")
-    } else if s.ends_with('w') {
-        (s.trim_end_matches('w'), "w")
-    } else {
-        (s, "m")
+        (None, None) => since_window(since.unwrap_or("15m"), configured_tz)?,
     };
-
-    let num: i64 = num.parse().context("Invalid duration number")?;
-
-    match unit {
-        "m" => Ok(ChronoDuration::minutes(num)),
-        "h" => Ok(ChronoDuration::hours(num)),
-        "d" => Ok(ChronoDuration::days(num)),
-        "w" => Ok(ChronoDuration::weeks(num)),
-        _ => Ok(ChronoDuration::minutes(num)),
-    }
+    Ok(resolve_time_range(
+        TimeInput::Instant { start, end },
+        configured_tz,
+    ))
 }
 
 fn sql_time_condition(
@@ -964,10 +1162,7 @@ fn print_table(entries: &[logchef_core::api::LogEntry], columns: &[logchef_core:
             .map(|c| {
                 entry
                     .get(&c.name)
-                    .map(|v| match v {
-                        serde_json::Value::String(s) => s.clone(),
-                        _ => v.to_string(),
-                    })
+                    .map(super::table_cell)
                     .unwrap_or_default()
             })
             .collect();
@@ -1033,6 +1228,7 @@ async fn prompt_source_interactive(
             cache_entries.push((target_ref, s.id));
         }
     }
+    cache.observe_sources(team_id, &sources);
     cache.set_sources(team_id, &cache_entries);
 
     Ok(source.id)
@@ -1052,6 +1248,131 @@ fn prompt_sql_interactive() -> Result<String> {
     Ok(sql)
 }
 
+fn parse_delimiter(s: &str) -> std::result::Result<u8, String> {
+    match s {
+        "\\t" | "\t" | "tab" => Ok(b'\t'),
+        _ if s.len() == 1 && s.is_ascii() && !matches!(s, "\"" | "\n" | "\r") => {
+            Ok(s.as_bytes()[0])
+        }
+        _ => Err(format!(
+            "delimiter must be a single ASCII character other than a quote or newline, got '{}'",
+            s
+        )),
+    }
+}
+
+/// Streams the server's RFC 4180 CSV (comma-separated, header first) into a
+/// different delimiter and/or without the header. Works on arbitrary chunk
+/// boundaries; quoted fields pass through untouched, and unquoted fields that
+/// contain the new delimiter get quoted.
+struct CsvRewriter {
+    delimiter: u8,
+    skip_header: bool,
+    in_header: bool,
+    in_quotes: bool,
+    /// Saw a `"` inside a quoted field: either an escaped `""` or the closing
+    /// quote, decided by the next byte.
+    quote_pending: bool,
+    field: Vec<u8>,
+    field_quoted: bool,
+    record_started: bool,
+}
+
+impl CsvRewriter {
+    fn new(delimiter: u8, skip_header: bool) -> Self {
+        Self {
+            delimiter,
+            skip_header,
+            in_header: true,
+            in_quotes: false,
+            quote_pending: false,
+            field: Vec::new(),
+            field_quoted: false,
+            record_started: false,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(chunk.len());
+        for &b in chunk {
+            if self.in_quotes {
+                if self.quote_pending {
+                    self.quote_pending = false;
+                    if b == b'"' {
+                        self.field.push(b);
+                        continue;
+                    }
+                    self.in_quotes = false;
+                } else {
+                    self.field.push(b);
+                    self.quote_pending = b == b'"';
+                    continue;
+                }
+            }
+            self.record_started = true;
+            match b {
+                b'"' if self.field.is_empty() => {
+                    self.in_quotes = true;
+                    self.field_quoted = true;
+                    self.field.push(b);
+                }
+                b',' => {
+                    self.end_field(&mut out);
+                    if self.emitting() {
+                        out.push(self.delimiter);
+                    }
+                }
+                b'\n' => {
+                    let crlf = self.field.last() == Some(&b'\r');
+                    if crlf {
+                        self.field.pop();
+                    }
+                    self.end_field(&mut out);
+                    if self.emitting() {
+                        out.extend_from_slice(if crlf { b"\r\n" } else { b"\n" });
+                    }
+                    self.in_header = false;
+                    self.record_started = false;
+                }
+                _ => self.field.push(b),
+            }
+        }
+        out
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if self.record_started || !self.field.is_empty() {
+            self.end_field(&mut out);
+        }
+        out
+    }
+
+    fn emitting(&self) -> bool {
+        !(self.skip_header && self.in_header)
+    }
+
+    fn end_field(&mut self, out: &mut Vec<u8>) {
+        let field = std::mem::take(&mut self.field);
+        let quoted = std::mem::replace(&mut self.field_quoted, false);
+        if !self.emitting() {
+            return;
+        }
+        if !quoted && field.contains(&self.delimiter) {
+            out.push(b'"');
+            for &b in &field {
+                if b == b'"' {
+                    out.push(b'"');
+                }
+                out.push(b);
+            }
+            out.push(b'"');
+        } else {
+            out.extend_from_slice(&field);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1186,16 +1507,37 @@ mod tests {
         );
     }
 
+    fn rewrite(input: &str, delimiter: u8, skip_header: bool, chunk: usize) -> String {
+        let mut rewriter = CsvRewriter::new(delimiter, skip_header);
+        let mut out = Vec::new();
+        for piece in input.as_bytes().chunks(chunk) {
+            out.extend(rewriter.feed(piece));
+        }
+        out.extend(rewriter.finish());
+        String::from_utf8(out).unwrap()
+    }
+
     #[test]
-    fn wall_clock_to_utc_converts_from_zone() {
-        // Obtain the concrete zone via resolve_timezone so this test needs no
-        // direct chrono-tz dependency.
-        let tz = resolve_timezone(Some("Asia/Kolkata"));
-        // 09:15 IST (UTC+5:30) == 03:45 UTC.
-        let utc = wall_clock_to_utc("2026-05-19 09:15:00", tz).unwrap();
-        assert_eq!(
-            utc.to_rfc3339_opts(SecondsFormat::Secs, true),
-            "2026-05-19T03:45:00Z"
-        );
+    fn csv_rewriter_swaps_delimiter_and_drops_header() {
+        let input = "ts,msg,n\r\n1,\"a, \"\"quoted\"\"\nline\",2\r\n3,x;y,4";
+        for chunk in [1, 2, 3, 7, 1024] {
+            assert_eq!(
+                rewrite(input, b';', false, chunk),
+                "ts;msg;n\r\n1;\"a, \"\"quoted\"\"\nline\";2\r\n3;\"x;y\";4"
+            );
+            assert_eq!(
+                rewrite(input, b',', true, chunk),
+                "1,\"a, \"\"quoted\"\"\nline\",2\r\n3,x;y,4"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_delimiter_accepts_tab_aliases() {
+        assert_eq!(parse_delimiter("\\t"), Ok(b'\t'));
+        assert_eq!(parse_delimiter("tab"), Ok(b'\t'));
+        assert_eq!(parse_delimiter(";"), Ok(b';'));
+        assert!(parse_delimiter("\"").is_err());
+        assert!(parse_delimiter("ab").is_err());
     }
 }
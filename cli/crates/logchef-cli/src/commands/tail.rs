@@ -1,13 +1,15 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, Duration as ChronoDuration, NaiveDateTime, Utc};
+use chrono::{Duration as ChronoDuration, Utc};
 use clap::Args;
 use logchef_core::Config;
-use logchef_core::api::{Client, Column, LogEntry, QueryRequest};
+use logchef_core::api::{
+    Client, Column, DedupKey, LogEntry, QueryRequest, dedup_key, parse_entry_timestamp,
+};
 use logchef_core::cache::{Cache, Identifier, parse_identifier};
 use logchef_core::highlight::{
-    FormatOptions, HighlightOptions, Highlighter, format_log_entry_with_options,
+    FormatOptions, HighlightOptions, Highlighter, Severity, format_log_entry_with_options,
 };
-use logchef_core::timerange::{TimeInput, resolve_time_range};
+use logchef_core::timerange::{TimeInput, display_timezone, resolve_time_range};
 use serde::Serialize;
 use std::collections::HashMap;
 use tokio::time::{Duration, sleep};
@@ -84,6 +86,20 @@ pub struct TailArgs {
     #[arg(long)]
     no_timestamp: bool,
 
+    /// Pad timestamp and level into fixed-width columns in text output
+    #[arg(long)]
+    aligned: bool,
+
+    /// Print only entries whose `level`/`severity` is at or above this
+    /// (trace, debug, info, warn, error, fatal); filtered client-side.
+    #[arg(long, value_name = "LEVEL")]
+    min_level: Option<Severity>,
+
+    /// Dim lines that match none of your own highlight rules (`--highlight`
+    /// words, custom keywords/regexes), so matches stand out in the stream.
+    #[arg(long, alias = "highlight-matches-only-color")]
+    dim_unmatched: bool,
+
     /// Custom highlight rules (format: COLOR:word1,word2).
     #[arg(long = "highlight", value_name = "COLOR:WORDS")]
     highlights: Vec<String>,
@@ -97,6 +113,11 @@ pub struct TailArgs {
     /// including heartbeats, arrives within this window).
     #[arg(long, default_value = "30")]
     timeout: u32,
+
+    /// With `--poll`, look back further than the configured
+    /// `max_time_range`.
+    #[arg(long)]
+    force: bool,
 }
 
 #[derive(Clone, Debug, clap::ValueEnum)]
@@ -106,6 +127,43 @@ enum OutputFormat {
     Msg,
 }
 
+impl TailArgs {
+    /// An unfiltered follow of one source with the flag defaults above, for
+    /// `sources sample`.
+    pub(crate) fn sample(
+        team: Option<String>,
+        source: Option<String>,
+        max_lines: Option<usize>,
+        poll: bool,
+        jsonl: bool,
+    ) -> Self {
+        Self {
+            query: String::new(),
+            team,
+            source,
+            since: "30s".to_string(),
+            interval: 2,
+            poll,
+            limit: 100,
+            max_lines,
+            output: if jsonl {
+                OutputFormat::Jsonl
+            } else {
+                OutputFormat::Text
+            },
+            no_highlight: false,
+            no_timestamp: false,
+            aligned: false,
+            min_level: None,
+            dim_unmatched: false,
+            highlights: Vec::new(),
+            disable_highlights: Vec::new(),
+            timeout: 30,
+            force: false,
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct JsonlOutput<'a> {
     #[serde(flatten)]
@@ -120,6 +178,7 @@ const LOOKBACK_MARGIN: ChronoDuration = ChronoDuration::seconds(5);
 
 pub async fn run(args: TailArgs, global: GlobalArgs) -> Result<()> {
     let config = Config::load().context("Failed to load config")?;
+    super::check_dim_unmatched(args.dim_unmatched, &args.highlights, &config.highlights)?;
     let s = session::authed(&config, &global)?;
     let (client, ctx) = (&s.client, &s.ctx);
 
@@ -127,13 +186,12 @@ pub async fn run(args: TailArgs, global: GlobalArgs) -> Result<()> {
     let default_team = ctx.defaults.team_with_env();
     let default_source = ctx.defaults.source_with_env();
     let team_id = resolve_team_id(client, &mut cache, args.team.clone().or(default_team)).await?;
-    let source_id = resolve_source_id(
-        client,
-        &mut cache,
-        team_id,
-        args.source.clone().or(default_source),
-    )
-    .await?;
+    let source = args.source.clone().or(default_source);
+    let source_id = resolve_source_id(client, &mut cache, team_id, source.clone()).await?;
+    if args.poll {
+        let lookback = parse_duration(&args.since)?;
+        super::check_time_range_cap(ctx, source_id, source.as_deref(), lookback, args.force)?;
+    }
 
     let highlighter = if args.no_highlight || !ui::human(global.quiet) {
         None
@@ -146,6 +204,12 @@ pub async fn run(args: TailArgs, global: GlobalArgs) -> Result<()> {
     };
     let fmt_options = FormatOptions {
         show_timestamp: !args.no_timestamp,
+        aligned: args.aligned,
+        timezone: Some(display_timezone(
+            ctx.defaults.timezone.as_deref(),
+            global.utc,
+        )),
+        ..Default::default()
     };
 
     if args.poll {
@@ -249,8 +313,18 @@ async fn run_sse(
                 match event {
                     SseEvent::Rows(rows) => {
                         for entry in &rows {
+                            if !meets_min_level(entry, args.min_level) {
+                                continue;
+                            }
                             let columns = columns_from_entry(entry);
-                            print_entry(&args.output, entry, &columns, fmt_options, highlighter)?;
+                            print_entry(
+                                &args.output,
+                                entry,
+                                &columns,
+                                fmt_options,
+                                highlighter,
+                                args.dim_unmatched,
+                            )?;
                             printed += 1;
                             if let Some(max_lines) = args.max_lines
                                 && printed >= max_lines
@@ -397,7 +471,7 @@ fn parse_sse_block(block: &[u8]) -> Option<SseEvent> {
 /// Synthesizes column metadata from a streamed row's keys (sorted for a stable
 /// field order). The SSE stream sends rows without schema, and the text
 /// formatter needs columns to render non-priority fields.
-fn columns_from_entry(entry: &LogEntry) -> Vec<Column> {
+pub(crate) fn columns_from_entry(entry: &LogEntry) -> Vec<Column> {
     let mut names: Vec<&String> = entry.keys().collect();
     names.sort();
     names
@@ -433,6 +507,8 @@ async fn run_poll(
     let mut seen: HashMap<DedupKey, ()> = HashMap::new();
     let mut printed = 0usize;
     let mut backpressure_warned = false;
+    let mut backoff = Duration::from_millis(500);
+    let max_backoff = Duration::from_secs(5);
 
     loop {
         let end = Utc::now();
@@ -449,10 +525,25 @@ async fn run_poll(
             query_timeout: Some(args.timeout),
         };
 
-        let response = client
-            .query_logchefql(team_id, source_id, &request)
-            .await
-            .context("Tail query failed")?;
+        // Transient failures (network, 5xx) are retried with backoff. The
+        // cursor and dedup map carry over, so the next successful poll picks
+        // up where the last one left off without repeating lines.
+        let response = match client.query_logchefql(team_id, source_id, &request).await {
+            Ok(response) => {
+                backoff = Duration::from_millis(500);
+                response
+            }
+            Err(err) if is_transient(&err) => {
+                eprintln!("tail: poll failed ({err}); retrying");
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => return Ok(()),
+                    _ = sleep(backoff) => {}
+                }
+                backoff = (backoff * 2).min(max_backoff);
+                continue;
+            }
+            Err(err) => return Err(err).context("Tail query failed"),
+        };
 
         let returned = response.entries().len();
         let mut entries = response.entries().iter().collect::<Vec<_>>();
@@ -466,12 +557,16 @@ async fn run_poll(
                 continue;
             }
             newest = newest.max(ts);
+            if !meets_min_level(entry, args.min_level) {
+                continue;
+            }
             print_entry(
                 &args.output,
                 entry,
                 &response.columns,
                 fmt_options,
                 highlighter,
+                args.dim_unmatched,
             )?;
             printed += 1;
             if let Some(max_lines) = args.max_lines
@@ -512,27 +607,14 @@ async fn run_poll(
     }
 }
 
-#[derive(Hash, Eq, PartialEq, Clone, Debug)]
-struct DedupKey {
-    ts: Option<DateTime<Utc>>,
-    fingerprint: u64,
+/// Errors worth retrying in poll mode: the server or network may recover,
+/// whereas a rejected query or expired token will fail the same way again.
+fn is_transient(err: &logchef_core::Error) -> bool {
+    err.is_retryable()
 }
 
-fn dedup_key(entry: &LogEntry, ts: Option<DateTime<Utc>>) -> DedupKey {
-    use std::hash::{Hash, Hasher};
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    let mut keys: Vec<&String> = entry.keys().collect();
-    keys.sort();
-    for k in keys {
-        k.hash(&mut hasher);
-        if let Some(v) = entry.get(k) {
-            v.to_string().hash(&mut hasher);
-        }
-    }
-    DedupKey {
-        ts,
-        fingerprint: hasher.finish(),
-    }
+fn meets_min_level(entry: &LogEntry, min: Option<Severity>) -> bool {
+    min.is_none_or(|min| Severity::of_entry(entry).is_some_and(|level| level >= min))
 }
 
 fn print_entry(
@@ -541,6 +623,7 @@ fn print_entry(
     columns: &[Column],
     fmt_options: &FormatOptions,
     highlighter: Option<&Highlighter>,
+    dim_unmatched: bool,
 ) -> Result<()> {
     match output {
         OutputFormat::Jsonl => println!("{}", serde_json::to_string(&JsonlOutput { entry })?),
@@ -553,7 +636,11 @@ fn print_entry(
         OutputFormat::Text => {
             let line = format_log_entry_with_options(entry, columns, fmt_options);
             if let Some(highlighter) = highlighter {
-                println!("{}", highlighter.highlight(&line));
+                if dim_unmatched {
+                    println!("{}", highlighter.highlight_focused(&line));
+                } else {
+                    println!("{}", highlighter.highlight(&line));
+                }
             } else {
                 println!("{}", line);
             }
@@ -579,23 +666,6 @@ async fn fetch_ts_field(client: &Client, team_id: i64, source_id: i64) -> Option
     }
 }
 
-/// Extracts a row's timestamp for dedup/cursor purposes. `ts_field`, when
-/// present, is the source's configured `_meta_ts_field` and is tried first;
-/// otherwise (or if the field is absent from the row) falls back to probing
-/// the hardcoded `_timestamp`/`timestamp` keys used by older/ClickHouse-only
-/// behavior.
-fn parse_entry_timestamp(entry: &LogEntry, ts_field: Option<&str>) -> Option<DateTime<Utc>> {
-    let value = ts_field
-        .and_then(|field| entry.get(field))
-        .or_else(|| entry.get("_timestamp"))
-        .or_else(|| entry.get("timestamp"))?;
-    let s = value.as_str()?;
-    DateTime::parse_from_rfc3339(s)
-        .map(|dt| dt.with_timezone(&Utc))
-        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").map(|dt| dt.and_utc()))
-        .ok()
-}
-
 fn parse_duration(s: &str) -> Result<ChronoDuration> {
     let s = s.trim();
     if s.is_empty() {
@@ -669,7 +739,7 @@ async fn resolve_source_id(
     match parse_identifier(&source) {
         Identifier::Id(id) => Ok(id),
         Identifier::Name(name) => {
-            if let Some(id) = cache.get_source_id(team_id, &name) {
+            if let Some(id) = super::cached_source_id(client, cache, team_id, &name).await? {
                 return Ok(id);
             }
             let sources = client
@@ -685,6 +755,7 @@ async fn resolve_source_id(
                     cache_entries.push((table_ref, source.id));
                 }
             }
+            cache.observe_sources(team_id, &sources);
             cache.set_sources(team_id, &cache_entries);
             sources
                 .iter()
@@ -730,6 +801,7 @@ fn parse_highlight_args(args: &[String]) -> Vec<(String, Vec<String>)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::DateTime;
 
     #[test]
     fn parses_tail_timestamp() {
@@ -749,6 +821,20 @@ mod tests {
         e
     }
 
+    #[test]
+    fn poll_retries_only_transient_errors() {
+        assert!(is_transient(&logchef_core::Error::api(Some(503), "down")));
+        assert!(is_transient(&logchef_core::Error::api(
+            Some(429),
+            "slow down"
+        )));
+        assert!(!is_transient(&logchef_core::Error::api(
+            Some(400),
+            "bad query"
+        )));
+        assert!(!is_transient(&logchef_core::Error::NotAuthenticated));
+    }
+
     #[test]
     fn dedup_key_is_stable_across_insertion_order() {
         let a = entry_from(&[("_timestamp", "2026-05-19T09:15:00Z"), ("msg", "hi")]);
@@ -0,0 +1,275 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use logchef_core::Config;
+use logchef_core::api::{FieldValueInfo, FieldValuesQuery, FieldValuesResult};
+use logchef_core::cache::{Cache, ValuesCache};
+use serde::Serialize;
+
+use crate::cli::GlobalArgs;
+use crate::commands::{resolve_source, resolve_team};
+use crate::session;
+use crate::ui;
+
+#[derive(Args)]
+#[command(after_help = "EXAMPLES:
+  # Top 10 services in the last hour
+  logchef values service --since 1h
+
+  # Which services and hosts are returning 5xx?
+  logchef values service,host 'status>=500' --since 6h --limit 5
+
+  # As JSON, bypassing the one-minute result cache
+  logchef values level --output json --no-cache")]
+pub struct ValuesArgs {
+    /// Field(s) to break down, comma-separated (e.g. `service,level`)
+    fields: String,
+
+    /// Only count rows matching this LogchefQL filter (e.g. `status>=500`)
+    query: Option<String>,
+
+    /// Team ID or name
+    #[arg(long, short = 't')]
+    team: Option<String>,
+
+    /// Source ID or name
+    #[arg(long, short = 'S')]
+    source: Option<String>,
+
+    /// Relative lookback window (e.g. 15m, 1h, 24h) or a named window such
+    /// as today or this-week
+    #[arg(long, short = 's')]
+    since: Option<String>,
+
+    /// Absolute start time (same formats as `query --from`). Requires --to.
+    #[arg(long)]
+    from: Option<String>,
+
+    /// Absolute end time. Requires --from.
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Values to show per field, most frequent first (at most 100)
+    #[arg(
+        long,
+        short = 'l',
+        default_value = "10",
+        value_parser = clap::value_parser!(u32).range(1..=100)
+    )]
+    limit: u32,
+
+    /// Ask the server even if the same breakdown was fetched in the last
+    /// minute
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Run even if the time range exceeds the configured `max_time_range`.
+    #[arg(long)]
+    force: bool,
+
+    /// Output format
+    #[arg(long, default_value = "text")]
+    output: OutputFormat,
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    Jsonl,
+    Table,
+}
+
+#[derive(Serialize)]
+struct ValueLine<'a> {
+    field: &'a str,
+    value: &'a str,
+    count: i64,
+}
+
+pub async fn run(args: ValuesArgs, global: GlobalArgs) -> Result<()> {
+    let fields: Vec<&str> = args
+        .fields
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .collect();
+    if fields.is_empty() {
+        anyhow::bail!("No field given. List a source's fields with 'logchef fields'.");
+    }
+
+    let config = Config::load().context("Failed to load config")?;
+    let s = session::authed(&config, &global)?;
+    let (client, ctx) = (&s.client, &s.ctx);
+
+    let mut cache = Cache::new(&ctx.server_url);
+    let team = args.team.clone().or_else(|| ctx.defaults.team_with_env());
+    let source = args
+        .source
+        .clone()
+        .or_else(|| ctx.defaults.source_with_env());
+    let team_id = resolve_team(client, &mut cache, team).await?;
+    let source_id = resolve_source(client, &mut cache, team_id, source.clone()).await?;
+
+    let columns = client
+        .get_schema(team_id, source_id)
+        .await
+        .context("Failed to get schema")?;
+    let typed: Vec<(&str, &str)> = fields
+        .iter()
+        .map(|field| {
+            columns
+                .iter()
+                .find(|c| c.name == *field)
+                .map(|c| (*field, c.column_type.as_str()))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Field '{}' not found in source schema. List fields with 'logchef fields'.",
+                        field
+                    )
+                })
+        })
+        .collect::<Result<_>>()?;
+
+    let since = args
+        .since
+        .clone()
+        .unwrap_or_else(|| ctx.defaults.since.clone());
+    let (start, end) = super::histogram::resolve_instants(
+        &since,
+        args.from.as_deref(),
+        args.to.as_deref(),
+        ctx.defaults.timezone.as_deref(),
+    )?;
+    super::check_time_range_cap(ctx, source_id, source.as_deref(), end - start, args.force)?;
+    let (start, end) = (start.to_rfc3339(), end.to_rfc3339());
+    let filter = args.query.as_deref().unwrap_or_default().trim();
+    // Keyed on the window as given, not the resolved instants, so a relative
+    // window still hits the cache a few seconds later.
+    let window = match (&args.from, &args.to) {
+        (Some(from), Some(to)) => format!("{from}..{to}"),
+        _ => since.clone(),
+    };
+    let key = |field: &str| {
+        format!(
+            "{}/{}/{}/{}/{}/{}",
+            team_id, source_id, field, args.limit, window, filter
+        )
+    };
+
+    let mut values_cache = ValuesCache::new(&ctx.server_url);
+    let cached: Vec<Option<FieldValuesResult>> = typed
+        .iter()
+        .map(|(field, _)| {
+            (!args.no_cache)
+                .then(|| values_cache.get(&key(field)).cloned())
+                .flatten()
+        })
+        .collect();
+
+    let (start, end, limit) = (start.as_str(), end.as_str(), args.limit);
+    let spinner = ui::Spinner::start(global.quiet, "counting values");
+    let fetched = futures::future::try_join_all(
+        typed
+            .iter()
+            .zip(&cached)
+            .filter(|(_, cached)| cached.is_none())
+            .map(|((field, field_type), _)| async move {
+                client
+                    .get_field_values(
+                        team_id,
+                        source_id,
+                        &FieldValuesQuery {
+                            field_name: field,
+                            field_type,
+                            start,
+                            end,
+                            timezone: "UTC",
+                            limit,
+                            logchefql: Some(filter),
+                        },
+                    )
+                    .await
+            }),
+    )
+    .await;
+    spinner.finish();
+    let mut fetched = fetched.context("Failed to get field values")?.into_iter();
+
+    let mut results = Vec::with_capacity(typed.len());
+    for ((field, _), cached) in typed.iter().zip(cached) {
+        let result = match cached {
+            Some(result) => result,
+            None => {
+                let result = fetched.next().expect("one response per uncached field");
+                values_cache.insert(key(field), result.clone());
+                result
+            }
+        };
+        results.push((*field, result));
+    }
+
+    match args.output {
+        OutputFormat::Json => {
+            let results: Vec<_> = results.iter().map(|(_, result)| result).collect();
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        }
+        OutputFormat::Jsonl => {
+            for (field, result) in &results {
+                for FieldValueInfo { value, count } in &result.values {
+                    let line = ValueLine {
+                        field,
+                        value,
+                        count: *count,
+                    };
+                    println!("{}", serde_json::to_string(&line)?);
+                }
+            }
+        }
+        OutputFormat::Table => {
+            println!("{:<24} {:>12}  VALUE", "FIELD", "COUNT");
+            println!("{}", "-".repeat(70));
+            for (field, result) in &results {
+                for FieldValueInfo { value, count } in &result.values {
+                    println!("{:<24} {:>12}  {}", field, ui::thousands(*count), value);
+                }
+            }
+        }
+        OutputFormat::Text => {
+            for (i, (field, result)) in results.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                print_breakdown(field, result, &window, global.quiet);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// One field's top values, each with its share of the rows shown.
+fn print_breakdown(field: &str, result: &FieldValuesResult, window: &str, quiet: bool) {
+    let header = format!(
+        "{} · {} distinct",
+        field,
+        ui::thousands(result.total_distinct)
+    );
+    if ui::human(quiet) {
+        println!("\x1b[1m{}\x1b[0m", header);
+    } else {
+        println!("{}", header);
+    }
+    if result.values.is_empty() {
+        println!("  (no values in {})", window);
+        return;
+    }
+    let shown: i64 = result.values.iter().map(|v| v.count).sum();
+    for FieldValueInfo { value, count } in &result.values {
+        let share = if shown > 0 {
+            *count as f64 * 100.0 / shown as f64
+        } else {
+            0.0
+        };
+        println!("{:>12}  {:>5.1}%  {}", ui::thousands(*count), share, value);
+    }
+}
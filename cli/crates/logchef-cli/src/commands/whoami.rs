@@ -1,8 +1,7 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use logchef_core::Config;
-use logchef_core::api::{Team, User};
-use serde::Serialize;
+use logchef_core::api::IdentityInfo;
 
 use crate::cli::GlobalArgs;
 use crate::session;
@@ -13,7 +12,10 @@ use crate::session;
   logchef whoami
 
   # One-line JSON record (user + teams) for scripting
-  logchef whoami --output jsonl | jq '.teams[].name'")]
+  logchef whoami --output jsonl | jq '.teams[].name'
+
+  # Fail a CI step unless the token belongs to an admin
+  logchef whoami --output json | jq -e '.role == \"admin\"'")]
 pub struct WhoamiArgs {
     /// Output format.
     #[arg(long, default_value = "text")]
@@ -27,28 +29,6 @@ enum OutputFormat {
     Jsonl,
 }
 
-#[derive(Serialize)]
-struct WhoamiOutput {
-    user: UserOutput,
-    teams: Vec<TeamOutput>,
-}
-
-#[derive(Serialize)]
-struct UserOutput {
-    id: i64,
-    email: String,
-    full_name: Option<String>,
-    role: String,
-    status: Option<String>,
-}
-
-#[derive(Serialize)]
-struct TeamOutput {
-    id: i64,
-    name: String,
-    role: Option<String>,
-}
-
 pub async fn run(args: WhoamiArgs, global: GlobalArgs) -> Result<()> {
     let config = Config::load().context("Failed to load config")?;
     let s = session::authed(&config, &global)?;
@@ -63,25 +43,25 @@ pub async fn run(args: WhoamiArgs, global: GlobalArgs) -> Result<()> {
         .list_teams()
         .await
         .context("Failed to list teams")?;
-    let output = output(user, teams);
+    let output = IdentityInfo::authenticated(user).with_teams(teams);
 
     match args.output {
         OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&output)?),
         OutputFormat::Jsonl => println!("{}", serde_json::to_string(&output)?),
         OutputFormat::Text => {
-            println!(
-                "{}{}",
-                output.user.email,
-                output
-                    .user
-                    .full_name
-                    .as_ref()
-                    .map(|name| format!(" ({})", name))
-                    .unwrap_or_default()
-            );
-            println!("role: {}", output.user.role);
+            if let Some(user) = &output.user {
+                println!(
+                    "{}{}",
+                    user.email,
+                    user.full_name
+                        .as_ref()
+                        .map(|name| format!(" ({})", name))
+                        .unwrap_or_default()
+                );
+                println!("role: {}", user.role);
+            }
             println!("teams:");
-            for team in output.teams {
+            for team in output.teams.unwrap_or_default() {
                 println!(
                     "  {}  {}{}",
                     team.id,
@@ -96,23 +76,3 @@ pub async fn run(args: WhoamiArgs, global: GlobalArgs) -> Result<()> {
 
     Ok(())
 }
-
-fn output(user: User, teams: Vec<Team>) -> WhoamiOutput {
-    WhoamiOutput {
-        user: UserOutput {
-            id: user.id,
-            email: user.email,
-            full_name: user.full_name,
-            role: user.role,
-            status: user.status,
-        },
-        teams: teams
-            .into_iter()
-            .map(|team| TeamOutput {
-                id: team.id,
-                name: team.name,
-                role: team.role,
-            })
-            .collect(),
-    }
-}
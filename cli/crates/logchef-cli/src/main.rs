@@ -2,6 +2,7 @@ mod banner;
 mod cli;
 mod commands;
 mod env_flags;
+mod pager;
 mod session;
 mod ui;
 mod update;
@@ -0,0 +1,147 @@
+//! Pages human output through `$PAGER`, the way git does. A command starts
+//! the pager right before it renders results (after any interactive prompts
+//! and spinners); stdout — and stderr, when it's a terminal, so the stats
+//! line lands after the results — is pointed into the pager until the
+//! returned guard is dropped.
+//!
+//! The pager command is `LOGCHEF_PAGER`, then `PAGER`, then `less -R`, split
+//! into arguments the way a shell would and run directly, so a pager that
+//! doesn't exist fails before output is redirected. `LESS` defaults to
+//! `FRX`, so output that fits on one screen is printed as-is and nothing is
+//! cleared on exit. An empty command or `cat` disables paging, as do
+//! `--no-pager`, `pager: false` in the config, CI, and a non-TTY stdout.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cli::GlobalArgs;
+
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Set while stdout is redirected into a pager, so TTY-gated output (colors,
+/// stats) keeps behaving as if stdout were still the terminal.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// True while a pager owns stdout.
+pub fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Starts the pager when paging applies; `None` means write directly. The
+/// pager runs until the guard is dropped.
+pub fn start(config: &logchef_core::Config, global: &GlobalArgs) -> Option<Pager> {
+    use std::io::IsTerminal;
+
+    if global.no_pager
+        || !config.pager
+        || crate::env_flags::ci()
+        || !std::io::stdout().is_terminal()
+    {
+        return None;
+    }
+    let command = std::env::var("LOGCHEF_PAGER")
+        .or_else(|_| std::env::var("PAGER"))
+        .unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let command = command.trim();
+    if command.is_empty() || command == "cat" {
+        return None;
+    }
+    Pager::spawn(command)
+}
+
+#[cfg(unix)]
+pub struct Pager {
+    child: std::process::Child,
+    /// `(fd, saved copy)` for each stream redirected into the pager.
+    saved: Vec<(i32, i32)>,
+}
+
+#[cfg(not(unix))]
+pub struct Pager;
+
+#[cfg(unix)]
+impl Pager {
+    fn spawn(command: &str) -> Option<Self> {
+        use std::io::{IsTerminal, Write};
+        use std::os::fd::AsRawFd;
+        use std::process::{Command, Stdio};
+
+        let argv = shlex::split(command)?;
+        let (program, args) = argv.split_first()?;
+        let mut cmd = Command::new(program);
+        cmd.args(args).stdin(Stdio::piped());
+        if std::env::var_os("LESS").is_none() {
+            cmd.env("LESS", "FRX");
+        }
+        // A pager that can't start (a typo in `PAGER`, say) just means
+        // unpaged output.
+        let mut child = cmd.spawn().ok()?;
+        let stdin = child.stdin.take()?;
+
+        let mut fds = vec![libc::STDOUT_FILENO];
+        if std::io::stderr().is_terminal() {
+            fds.push(libc::STDERR_FILENO);
+        }
+        let _ = std::io::stdout().flush();
+        let mut saved = Vec::new();
+        for fd in fds {
+            // SAFETY: plain fd duplication on descriptors this process owns;
+            // the copies are restored and closed in `Drop`.
+            unsafe {
+                let copy = libc::dup(fd);
+                if copy < 0 {
+                    continue;
+                }
+                libc::dup2(stdin.as_raw_fd(), fd);
+                saved.push((fd, copy));
+            }
+        }
+        // Quitting the pager early should end the command quietly instead
+        // of panicking on the next write to the closed pipe.
+        // SAFETY: resetting a signal disposition to its default.
+        unsafe {
+            libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+        }
+        ACTIVE.store(true, Ordering::Relaxed);
+        Some(Self { child, saved })
+    }
+}
+
+#[cfg(not(unix))]
+impl Pager {
+    fn spawn(_command: &str) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Pager {
+    fn drop(&mut self) {
+        use std::io::Write;
+
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+        // Restoring the originals closes the last write ends of the pipe, so
+        // the pager sees EOF.
+        for (fd, copy) in self.saved.drain(..) {
+            // SAFETY: `copy` was duplicated from `fd` in `spawn`.
+            unsafe {
+                libc::dup2(copy, fd);
+                libc::close(copy);
+            }
+        }
+        ACTIVE.store(false, Ordering::Relaxed);
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_pager_leaves_output_unpaged() {
+        assert!(Pager::spawn("logchef-no-such-pager -R").is_none());
+        assert!(Pager::spawn("less 'unterminated").is_none());
+        assert!(!active());
+    }
+}
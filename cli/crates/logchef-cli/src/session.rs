@@ -8,6 +8,8 @@ use crate::cli::GlobalArgs;
 pub struct AuthedSession {
     pub client: Client,
     pub ctx: Context,
+    /// Name of the resolved context (`(ephemeral)` for a bare `--server`).
+    pub name: String,
 }
 
 pub fn authed(config: &Config, global: &GlobalArgs) -> Result<AuthedSession> {
@@ -17,6 +19,7 @@ pub fn authed(config: &Config, global: &GlobalArgs) -> Result<AuthedSession> {
     Ok(AuthedSession {
         client,
         ctx: resolved.ctx,
+        name: resolved.name,
     })
 }
 
@@ -32,6 +35,7 @@ pub fn authed_with_timeout(
     Ok(AuthedSession {
         client,
         ctx: resolved.ctx,
+        name: resolved.name,
     })
 }
 
@@ -61,8 +65,10 @@ pub fn resolve(config: &Config, global: &GlobalArgs) -> Result<ResolvedContext>
                 is_ephemeral: false,
             });
         }
+        let mut ctx = Context::new(url.clone());
+        ctx.apply_env()?;
         return Ok(ResolvedContext {
-            ctx: Context::new(url.clone()),
+            ctx,
             name: "(ephemeral)".to_string(),
             is_ephemeral: true,
         });
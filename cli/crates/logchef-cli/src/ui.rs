@@ -7,14 +7,17 @@
 //! gate every affordance below through [`human`] / [`stderr_human`] (which are
 //! false when stdout/stderr is not a TTY, or when `--quiet` is set).
 
+use logchef_core::api::timing::CallTiming;
+use logchef_core::highlight::Highlighter;
 use std::io::{IsTerminal, Write};
+use std::time::Duration;
 
 /// True when human "chrome" tied to stdout (stats lines, colored/highlighted
-/// stdout, tables) should be shown: stdout is a TTY and `--quiet` is unset.
-/// Piping stdout or passing `--quiet` makes this false, keeping json/jsonl and
-/// redirected output byte-for-byte clean.
+/// stdout, tables) should be shown: stdout is a TTY (or a pager showing on
+/// one) and `--quiet` is unset. Piping stdout or passing `--quiet` makes this
+/// false, keeping json/jsonl and redirected output byte-for-byte clean.
 pub fn human(quiet: bool) -> bool {
-    !quiet && std::io::stdout().is_terminal()
+    !quiet && (std::io::stdout().is_terminal() || crate::pager::active())
 }
 
 /// True when a stderr-only affordance (spinner, `--show-sql` trace, error
@@ -79,6 +82,91 @@ pub fn print_stats(quiet: bool, count: usize, exec_ms: i64, rows_read: i64) {
     );
 }
 
+/// Prints the `--timing` breakdown of `calls` to stderr: one row per API
+/// call, then how the total split between the server and the network.
+pub fn print_timings(calls: &[CallTiming]) {
+    if calls.is_empty() {
+        eprintln!("\ntiming: no API calls");
+        return;
+    }
+    let ms = |d: Option<Duration>| d.map_or_else(|| "-".to_string(), duration);
+    eprintln!(
+        "\n{:<6} {:>7} {:>8} {:>8} {:>8} {:>7} {:>8}  {:<18} CALL",
+        "STATUS", "DNS", "CONNECT", "SERVER", "DOWNLOAD", "PARSE", "TOTAL", "SIZE"
+    );
+    for call in calls {
+        let size = match &call.transfer {
+            Some(t) => match &t.encoding {
+                Some(encoding) => format!(
+                    "{} ({} {})",
+                    bytes(t.decoded_bytes),
+                    bytes(t.wire_bytes),
+                    encoding
+                ),
+                None => bytes(t.decoded_bytes),
+            },
+            None => "-".to_string(),
+        };
+        eprintln!(
+            "{:<6} {:>7} {:>8} {:>8} {:>8} {:>7} {:>8}  {:<18} {} {}",
+            call.status
+                .map_or_else(|| "-".to_string(), |s| s.to_string()),
+            ms(call.dns),
+            ms(call.connect),
+            ms(call.status.map(|_| call.first_byte)),
+            ms(call.download),
+            ms(call.deserialize),
+            duration(call.total()),
+            size,
+            call.method,
+            call.path
+        );
+    }
+    let total: Duration = calls.iter().map(CallTiming::total).sum();
+    let server: Duration = calls
+        .iter()
+        .filter(|c| c.status.is_some())
+        .map(|c| c.first_byte)
+        .sum();
+    let network: Duration = calls
+        .iter()
+        .flat_map(|c| [c.dns, c.connect, c.download])
+        .flatten()
+        .sum();
+    eprintln!(
+        "{} {}, {}: {} waiting on the server, {} on DNS, connecting and downloading",
+        calls.len(),
+        if calls.len() == 1 { "call" } else { "calls" },
+        duration(total),
+        duration(server),
+        duration(network)
+    );
+}
+
+/// `860µs` → `"0.9ms"`, `1234ms` → `"1.23s"`.
+fn duration(d: Duration) -> String {
+    let ms = d.as_secs_f64() * 1000.0;
+    if ms >= 1000.0 {
+        format!("{:.2}s", ms / 1000.0)
+    } else if ms >= 10.0 {
+        format!("{:.0}ms", ms)
+    } else {
+        format!("{:.1}ms", ms)
+    }
+}
+
+/// `512` → `"512 B"`, `12_800` → `"12.5 KB"`.
+fn bytes(n: u64) -> String {
+    let v = n as f64;
+    if v >= 1024.0 * 1024.0 {
+        format!("{:.1} MB", v / (1024.0 * 1024.0))
+    } else if v >= 1024.0 {
+        format!("{:.1} KB", v / 1024.0)
+    } else {
+        format!("{} B", n)
+    }
+}
+
 // ANSI styles for the tiny query highlighter. Kept local so machine output
 // never touches them.
 const RESET: &str = "\x1b[0m";
@@ -200,6 +288,22 @@ fn highlight_tokens(s: &str, keywords: &[&str]) -> String {
     out
 }
 
+/// Colorizes a serialized JSON document for `--output json` on a TTY with
+/// tailspin's JSON highlighter, the same one text output uses for JSON
+/// lines. The document is passed in one piece rather than line by line, as
+/// the lines of pretty-printed JSON aren't JSON on their own. Returns the
+/// input unchanged when `enabled` is false so piped output stays
+/// machine-readable.
+pub fn highlight_json(doc: &str, enabled: bool) -> String {
+    if !enabled {
+        return doc.to_string();
+    }
+    match Highlighter::json() {
+        Ok(highlighter) => highlighter.highlight(doc),
+        Err(_) => doc.to_string(),
+    }
+}
+
 /// A minimal stderr spinner for long-running queries. It runs a background
 /// task that repaints a braille frame + elapsed seconds on stderr, and clears
 /// the line on [`finish`](Spinner::finish). It is inert (prints nothing)
@@ -262,6 +366,12 @@ impl Drop for Spinner {
 /// unaffected and the process still exits non-zero.
 pub fn report_error(err: &anyhow::Error, quiet: bool) {
     eprintln!("Error: {err:?}");
+    if let Some(request_id) = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<logchef_core::Error>()?.request_id())
+    {
+        eprintln!("\nRequest ID: {request_id}");
+    }
     if stderr_human(quiet)
         && let Some(hint) = hint_for_error(err)
     {
@@ -337,39 +447,39 @@ pub fn hint_for_error(err: &anyhow::Error) -> Option<String> {
 }
 
 fn hint_for_core(err: &logchef_core::Error) -> Option<String> {
-    use logchef_core::Error;
-    match err {
-        Error::NotAuthenticated => {
-            Some("run `logchef auth` to sign in, then `logchef doctor` to verify".into())
+    use logchef_core::{Error, ErrorKind};
+    let hint = match (err.kind(), err) {
+        (_, Error::NotAuthenticated) => {
+            "run `logchef auth` to sign in, then `logchef doctor` to verify"
         }
-        Error::Api {
-            status: Some(401), ..
-        } => Some("token invalid or expired — run `logchef auth`".into()),
-        Error::Api {
-            status: Some(403), ..
-        } => Some("no access to that team/source — check `logchef whoami`".into()),
-        Error::Api {
-            error_type: Some(kind),
-            ..
-        } => hint_for_error_type(kind),
-        Error::Network(_) => {
-            Some("can't reach the server — check the URL/connection, then `logchef doctor`".into())
+        (_, Error::Network(_)) => {
+            "can't reach the server — check the URL/connection, then `logchef doctor`"
         }
-        _ => None,
-    }
-}
-
-fn hint_for_error_type(kind: &str) -> Option<String> {
-    match kind {
-        "unauthorized" | "authentication_required" | "invalid_token" => {
-            Some("run `logchef auth` to sign in again".into())
+        (
+            _,
+            Error::Api {
+                status: Some(429), ..
+            },
+        ) => {
+            "the server is rate limiting — wait a moment, or send fewer requests at once (`logchef config set concurrency 2`)"
         }
-        "forbidden" => Some("no access to that team/source — check `logchef whoami`".into()),
-        "not_found" => {
-            Some("that team/source/query doesn't exist — double-check the id/name".into())
+        (ErrorKind::Retryable, _) => {
+            "the server failed to answer — try again shortly; `logchef doctor` checks its health"
         }
-        _ => None,
-    }
+        (ErrorKind::AuthExpired, _) => "token invalid or expired — run `logchef auth`",
+        (ErrorKind::PermissionDenied, _) => {
+            "no access to that team/source — ask a team admin to add you (`logchef whoami` shows your teams)"
+        }
+        (ErrorKind::InvalidQuery, _) => {
+            "the server rejected the request — check the query with `logchef lint` or `logchef explain`"
+        }
+        (ErrorKind::NotFound, _) => {
+            "that team/source/query doesn't exist — double-check the id/name"
+        }
+        (ErrorKind::Config, _) => "check the context's settings with `logchef config show`",
+        _ => return None,
+    };
+    Some(hint.into())
 }
 
 #[cfg(test)]
@@ -393,6 +503,27 @@ mod tests {
         assert_eq!(compact(150_000), "150k");
     }
 
+    #[test]
+    fn core_errors_get_targeted_hints() {
+        let hint = |err: logchef_core::Error| hint_for_error(&anyhow::Error::new(err)).unwrap();
+        let api = |status, error_type: &str| {
+            logchef_core::Error::api_with_type(Some(status), "failed", Some(error_type.into()))
+        };
+        assert!(hint(api(403, "AuthorizationError")).contains("ask a team admin"));
+        assert!(hint(api(401, "AuthenticationError")).contains("run `logchef auth`"));
+        assert!(hint(api(400, "ValidationError")).contains("logchef lint"));
+        assert!(hint(api(429, "ValidationError")).contains("rate limiting"));
+    }
+
+    #[test]
+    fn timing_cells_scale_units() {
+        assert_eq!(duration(Duration::from_micros(860)), "0.9ms");
+        assert_eq!(duration(Duration::from_millis(42)), "42ms");
+        assert_eq!(duration(Duration::from_millis(1234)), "1.23s");
+        assert_eq!(bytes(512), "512 B");
+        assert_eq!(bytes(12_800), "12.5 KB");
+    }
+
     #[test]
     fn highlight_disabled_is_identity() {
         let sql = "SELECT * FROM logs.app WHERE level = 'error'";
@@ -408,4 +539,17 @@ mod tests {
         assert!(out.contains("SELECT"));
         assert!(out.contains("'z'"));
     }
+
+    #[test]
+    fn highlight_json_disabled_is_identity() {
+        let doc = "{\n  \"a\": \"x:y\",\n  \"n\": -1.5e3\n}";
+        assert_eq!(highlight_json(doc, false), doc);
+    }
+
+    #[test]
+    fn highlight_json_keeps_the_document() {
+        let doc = "[\n  {\n    \"k\": \"say \\\"hi\\\"\",\n    \"ok\": true\n  }\n]";
+        let colored = highlight_json(doc, true);
+        assert_eq!(logchef_core::highlight::strip_ansi(&colored), doc);
+    }
 }
@@ -0,0 +1,162 @@
+//! A builder for LogchefQL queries, for tools that embed this crate instead
+//! of shelling out to the CLI:
+//!
+//! ```no_run
+//! # async fn example(client: &logchef_core::api::Client) -> logchef_core::Result<()> {
+//! let response = client
+//!     .query(1, 2)
+//!     .logchefql(r#"level="error""#)
+//!     .since("1h")
+//!     .limit(500)
+//!     .run()
+//!     .await?;
+//! println!("{} rows", response.logs.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Time windows go through [`crate::timerange`], so `since` takes the same
+//! lookbacks and named windows as `--since`, and the wall-clock strings sent
+//! always match the request's timezone.
+
+use chrono::{DateTime, Utc};
+
+use super::{Client, QueryRequest, QueryResponse};
+use crate::error::Result;
+use crate::timerange::{self, TimeInput};
+
+/// Window used when neither [`QueryBuilder::since`] nor
+/// [`QueryBuilder::between`] is called, matching the CLI's default.
+const DEFAULT_SINCE: &str = "15m";
+
+enum Window {
+    Since(String),
+    Between(DateTime<Utc>, DateTime<Utc>),
+}
+
+/// A LogchefQL query against one source, started with [`Client::query`].
+#[must_use = "a query does nothing until `run` is awaited"]
+pub struct QueryBuilder<'a> {
+    client: &'a Client,
+    team_id: i64,
+    source_id: i64,
+    query: String,
+    window: Window,
+    timezone: Option<String>,
+    limit: Option<u32>,
+    timeout_secs: Option<u32>,
+}
+
+impl Client {
+    /// Starts a LogchefQL query against a source. Without a filter it
+    /// matches every row; without a window it covers the last 15 minutes.
+    pub fn query(&self, team_id: i64, source_id: i64) -> QueryBuilder<'_> {
+        QueryBuilder {
+            client: self,
+            team_id,
+            source_id,
+            query: String::new(),
+            window: Window::Since(DEFAULT_SINCE.to_string()),
+            timezone: None,
+            limit: None,
+            timeout_secs: None,
+        }
+    }
+}
+
+impl QueryBuilder<'_> {
+    /// LogchefQL filter, e.g. `level="error" and status>=500`.
+    pub fn logchefql(mut self, query: impl Into<String>) -> Self {
+        self.query = query.into();
+        self
+    }
+
+    /// Lookback ending now (`15m`, `1h`, `7d`) or a named window such as
+    /// `today` or `last-week`. Checked when the query runs.
+    pub fn since(mut self, since: impl Into<String>) -> Self {
+        self.window = Window::Since(since.into());
+        self
+    }
+
+    /// Absolute window from `start` to `end`.
+    pub fn between(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        self.window = Window::Between(start, end);
+        self
+    }
+
+    /// IANA zone the window is sent in and named windows are resolved in.
+    /// Defaults to the system zone, else UTC.
+    pub fn timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Most rows to return. Defaults to the server's limit.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Server-side query timeout in seconds.
+    pub fn timeout(mut self, secs: u32) -> Self {
+        self.timeout_secs = Some(secs);
+        self
+    }
+
+    /// The request [`run`](Self::run) sends, with the window resolved
+    /// against the current time.
+    pub fn request(&self) -> Result<QueryRequest> {
+        let timezone = self.timezone.as_deref();
+        let (start, end) = match &self.window {
+            Window::Since(since) => timerange::since_window(since, timezone)?,
+            Window::Between(start, end) => (*start, *end),
+        };
+        let range = timerange::resolve_time_range(TimeInput::Instant { start, end }, timezone);
+        Ok(QueryRequest {
+            query: self.query.clone(),
+            start_time: range.start,
+            end_time: range.end,
+            timezone: Some(range.timezone),
+            limit: self.limit,
+            query_timeout: self.timeout_secs,
+        })
+    }
+
+    /// Runs the query.
+    pub async fn run(self) -> Result<QueryResponse> {
+        let request = self.request()?;
+        self.client
+            .query_logchefql(self.team_id, self.source_id, &request)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn builder_resolves_the_window_in_its_timezone() {
+        let client = Client::new("http://localhost:8125", 30).unwrap();
+        let request = client
+            .query(1, 2)
+            .logchefql("level=\"error\"")
+            .between(
+                Utc.with_ymd_and_hms(2026, 7, 14, 9, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 7, 14, 10, 0, 0).unwrap(),
+            )
+            .timezone("Asia/Kolkata")
+            .limit(500)
+            .request()
+            .unwrap();
+        assert_eq!(request.query, "level=\"error\"");
+        assert_eq!(request.start_time, "2026-07-14 14:30:00");
+        assert_eq!(request.end_time, "2026-07-14 15:30:00");
+        assert_eq!(request.timezone.as_deref(), Some("Asia/Kolkata"));
+        assert_eq!(request.limit, Some(500));
+
+        let err = client.query(1, 2).since("soon").request().unwrap_err();
+        assert!(err.to_string().contains("Invalid time range 'soon'"));
+    }
+}
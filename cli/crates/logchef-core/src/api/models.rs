@@ -1,6 +1,5 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 #[derive(Debug, Deserialize)]
 pub struct ApiResponse<T> {
@@ -18,21 +17,49 @@ pub struct ApiErrorResponse {
 
 #[derive(Debug, Deserialize)]
 pub struct MetaResponse {
+    #[serde(default = "default_meta_status")]
     pub status: String,
     pub data: MetaData,
 }
 
+fn default_meta_status() -> String {
+    "success".to_string()
+}
+
+impl MetaResponse {
+    /// Parses a meta payload in either the usual `{status, data}` envelope or
+    /// as a bare object, as some older servers return it.
+    pub fn from_value(value: serde_json::Value) -> serde_json::Result<Self> {
+        if value.get("data").is_some_and(|data| data.is_object()) {
+            serde_json::from_value(value)
+        } else {
+            Ok(Self {
+                status: default_meta_status(),
+                data: serde_json::from_value(value)?,
+            })
+        }
+    }
+}
+
+/// Server metadata from `/api/v1/meta`. Every field is optional (and the
+/// camelCase spellings are accepted) so login keeps working across server
+/// versions.
 #[derive(Debug, Deserialize)]
 pub struct MetaData {
+    #[serde(default = "unknown_version")]
     pub version: String,
-    #[serde(default)]
+    #[serde(default, alias = "buildInfo")]
     pub build_info: Option<String>,
-    #[serde(default)]
+    #[serde(default, alias = "oidcIssuer")]
     pub oidc_issuer: Option<String>,
-    #[serde(default)]
+    #[serde(default, alias = "cliClientId")]
     pub cli_client_id: Option<String>,
 }
 
+fn unknown_version() -> String {
+    "unknown".to_string()
+}
+
 impl MetaData {
     pub fn oidc_enabled(&self) -> bool {
         self.oidc_issuer.is_some() && self.cli_client_id.is_some()
@@ -69,6 +96,77 @@ pub struct Team {
     pub member_count: Option<i32>,
 }
 
+/// Who the CLI is talking to the server as. Shared by `auth --status` and
+/// `whoami` so both emit the same shape for scripts to assert against.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IdentityInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    pub authenticated: bool,
+    /// Mirrors `user.role` at the top level for simple `jq '.role'` checks.
+    pub role: Option<String>,
+    pub user: Option<IdentityUser>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teams: Option<Vec<IdentityTeam>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IdentityUser {
+    pub id: i64,
+    pub email: String,
+    pub full_name: Option<String>,
+    pub role: String,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IdentityTeam {
+    pub id: i64,
+    pub name: String,
+    pub role: Option<String>,
+}
+
+impl IdentityInfo {
+    pub fn authenticated(user: User) -> Self {
+        Self {
+            authenticated: true,
+            role: Some(user.role.clone()),
+            user: Some(IdentityUser {
+                id: user.id,
+                email: user.email,
+                full_name: user.full_name,
+                role: user.role,
+                status: user.status,
+            }),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_teams(mut self, teams: Vec<Team>) -> Self {
+        self.teams = Some(
+            teams
+                .into_iter()
+                .map(|team| IdentityTeam {
+                    id: team.id,
+                    name: team.name,
+                    role: team.role,
+                })
+                .collect(),
+        );
+        self
+    }
+
+    pub fn with_context(mut self, name: impl Into<String>, server: impl Into<String>) -> Self {
+        self.context = Some(name.into());
+        self.server = Some(server.into());
+        self
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Source {
     pub id: i64,
@@ -83,6 +181,10 @@ pub struct Source {
     pub connection: Option<SourceConnection>,
     #[serde(default)]
     pub is_connected: bool,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub schema_version: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -98,6 +200,15 @@ pub struct SourceConnection {
 }
 
 impl Source {
+    /// Returns a tag that changes whenever the source is altered: the
+    /// server's `schema_version` when exposed, otherwise `updated_at`.
+    pub fn version_tag(&self) -> Option<&str> {
+        self.schema_version
+            .as_deref()
+            .or(self.updated_at.as_deref())
+            .filter(|tag| !tag.is_empty())
+    }
+
     /// Returns the database.table_name reference if both are available.
     pub fn table_ref(&self) -> Option<String> {
         self.connection
@@ -180,7 +291,7 @@ pub struct SqlQueryRequest {
     pub query_timeout: Option<u32>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExportSqlRequest {
     pub raw_sql: String,
     pub format: String,
@@ -327,7 +438,7 @@ pub struct HistogramBucket {
     pub group_value: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldValuesResult {
     #[serde(default)]
     pub field_name: String,
@@ -341,7 +452,7 @@ pub struct FieldValuesResult {
     pub total_distinct: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldValueInfo {
     pub value: String,
     #[serde(default)]
@@ -358,6 +469,8 @@ pub struct FieldValuesQuery<'a> {
     pub end: &'a str,
     pub timezone: &'a str,
     pub limit: u32,
+    /// Only count values on rows matching this LogchefQL filter.
+    pub logchefql: Option<&'a str>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -389,6 +502,124 @@ impl QueryResponse {
         }
     }
 
+    /// Gives every output a stable column order. When the server omits
+    /// `columns`, they are derived from the entries: priority fields
+    /// (timestamp, level, message) first, then the remaining keys sorted.
+    /// Each entry's keys are then reordered to follow `columns`, with keys not
+    /// listed there appended in sorted order.
+    pub fn normalize_columns(&mut self) {
+        if self.columns.is_empty() {
+            self.columns = derive_columns(self.entries());
+        }
+        let columns = &self.columns;
+        for entry in self.logs.iter_mut().chain(self.data.iter_mut()) {
+            order_entry(entry, columns);
+        }
+    }
+
+    /// Adds every column missing from an entry as an explicit `null`, so all
+    /// entries share the same key set.
+    pub fn fill_nulls(&mut self) {
+        let columns = &self.columns;
+        for entry in self.logs.iter_mut().chain(self.data.iter_mut()) {
+            for column in columns {
+                if !entry.contains_key(&column.name) {
+                    entry.insert(column.name.clone(), serde_json::Value::Null);
+                }
+            }
+            order_entry(entry, columns);
+        }
+    }
+
+    /// Drops null-valued fields from every entry.
+    pub fn omit_nulls(&mut self) {
+        for entry in self.logs.iter_mut().chain(self.data.iter_mut()) {
+            entry.retain(|_, value| !value.is_null());
+        }
+    }
+
+    /// Keeps only `fields` in every entry and orders `columns` to match,
+    /// dropping fields the response doesn't have.
+    pub fn project(&mut self, fields: &[String]) {
+        for entry in self.logs.iter_mut().chain(self.data.iter_mut()) {
+            entry.retain(|key, _| fields.contains(key));
+        }
+        let mut columns = std::mem::take(&mut self.columns);
+        self.columns = fields
+            .iter()
+            .filter_map(|field| {
+                let i = columns.iter().position(|column| &column.name == field)?;
+                Some(columns.swap_remove(i))
+            })
+            .collect();
+        self.normalize_columns();
+    }
+
+    /// Sorts entries by timestamp, oldest first. Entries without a parseable
+    /// timestamp keep their relative order at the end.
+    pub fn sort_oldest_first(&mut self) {
+        for entries in [&mut self.logs, &mut self.data] {
+            entries.sort_by_cached_key(|entry| {
+                let ts = entry_timestamp(entry);
+                (ts.is_none(), ts)
+            });
+        }
+    }
+
+    /// Combines the responses of one query run against several sources (or
+    /// servers) into a single result, newest first. Every entry gains `field`
+    /// ([`SOURCE_FIELD`] or [`CONTEXT_FIELD`]) set to the label it came with,
+    /// and `columns` is that field followed by the union of each response's
+    /// columns. Rows read are summed; since the queries run concurrently,
+    /// execution time is the slowest one's.
+    pub fn merge(field: &str, responses: Vec<(String, QueryResponse)>) -> Self {
+        let mut columns = vec![Column {
+            name: field.to_string(),
+            column_type: "String".to_string(),
+            description: None,
+        }];
+        let mut logs = Vec::new();
+        let mut stats = QueryStats::default();
+        for (label, response) in responses {
+            for column in &response.columns {
+                if !columns.iter().any(|c| c.name == column.name) {
+                    columns.push(column.clone());
+                }
+            }
+            stats.execution_time_ms = stats
+                .execution_time_ms
+                .max(response.stats.execution_time_ms);
+            stats.rows_read += response.stats.rows_read;
+            stats.bytes_read += response.stats.bytes_read;
+            let entries = if response.logs.is_empty() {
+                response.data
+            } else {
+                response.logs
+            };
+            logs.extend(entries.into_iter().map(|mut entry| {
+                entry.insert(field.to_string(), serde_json::Value::String(label.clone()));
+                entry
+            }));
+        }
+        logs.sort_by_cached_key(|entry| {
+            let ts = entry_timestamp(entry);
+            (ts.is_none(), std::cmp::Reverse(ts))
+        });
+
+        let mut merged = Self {
+            logs,
+            data: Vec::new(),
+            columns,
+            stats,
+            query_id: None,
+            generated_sql: None,
+            generated_query: None,
+            generated_query_language: None,
+        };
+        merged.normalize_columns();
+        merged
+    }
+
     pub fn generated_query(&self) -> Option<&str> {
         self.generated_query
             .as_deref()
@@ -402,7 +633,57 @@ impl QueryResponse {
     }
 }
 
-pub type LogEntry = HashMap<String, serde_json::Value>;
+/// One result row. Insertion-ordered; see [`QueryResponse::normalize_columns`].
+pub type LogEntry = serde_json::Map<String, serde_json::Value>;
+
+/// Field naming each entry's source in a [`QueryResponse::merge`]d result.
+pub const SOURCE_FIELD: &str = "_source";
+
+/// Field naming each entry's context (server) in a merged result.
+pub const CONTEXT_FIELD: &str = "_context";
+
+/// Fields placed first, in this order, when columns are derived from entries.
+const PRIORITY_FIELDS: [&str; 6] = [
+    "_timestamp",
+    "timestamp",
+    "level",
+    "severity",
+    "msg",
+    "message",
+];
+
+fn derive_columns(entries: &[LogEntry]) -> Vec<Column> {
+    let keys: std::collections::BTreeSet<&String> =
+        entries.iter().flat_map(|entry| entry.keys()).collect();
+    let priority = PRIORITY_FIELDS
+        .iter()
+        .filter(|field| keys.iter().any(|key| key == *field))
+        .map(|field| field.to_string());
+    let rest = keys
+        .iter()
+        .filter(|key| !PRIORITY_FIELDS.contains(&key.as_str()))
+        .map(|key| key.to_string());
+    priority
+        .chain(rest)
+        .map(|name| Column {
+            name,
+            column_type: String::new(),
+            description: None,
+        })
+        .collect()
+}
+
+fn order_entry(entry: &mut LogEntry, columns: &[Column]) {
+    let mut rest = std::mem::take(entry);
+    for column in columns {
+        if let Some(value) = rest.remove(&column.name) {
+            entry.insert(column.name.clone(), value);
+        }
+    }
+    let mut extra: Vec<(String, serde_json::Value)> = rest.into_iter().collect();
+    extra.sort_by(|a, b| a.0.cmp(&b.0));
+    entry.extend(extra);
+}
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct QueryStats {
@@ -463,6 +744,20 @@ pub struct Collection {
     pub source_name: Option<String>,
 }
 
+/// Body for `POST /api/v1/saved-queries`. `query_content` is the JSON-encoded
+/// [`CollectionQueryContent`].
+#[derive(Debug, Serialize)]
+pub struct CreateSavedQueryRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub source_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_from_team_id: Option<i64>,
+    pub query_language: String,
+    pub query_content: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolvedSavedQuery {
     #[serde(flatten)]
@@ -470,31 +765,76 @@ pub struct ResolvedSavedQuery {
     pub resolved_team_id: i64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// The JSON stored in a collection's (or saved query's) `query_content`.
+/// Every field is optional so older or newer server payloads still parse.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CollectionQueryContent {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<i32>,
-    #[serde(default, rename = "sourceId")]
+    #[serde(default, rename = "sourceId", skip_serializing_if = "Option::is_none")]
     pub source_id: Option<i64>,
-    #[serde(default, rename = "timeRange")]
+    #[serde(default, rename = "timeRange", skip_serializing_if = "Option::is_none")]
     pub time_range: Option<CollectionTimeRange>,
-    #[serde(default)]
+    #[serde(
+        default,
+        deserialize_with = "lenient_limit",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub limit: Option<u32>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub variables: Option<Vec<CollectionVariable>>,
+    /// Display columns chosen in the collection, when the server records them.
+    #[serde(default, alias = "columns", skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl CollectionQueryContent {
+    /// Parses `query_content`. An empty string is an empty query; on a schema
+    /// mismatch the error names the offending field (e.g. `timeRange.absolute.start`).
+    pub fn parse(raw: &str) -> crate::Result<Self> {
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let de = &mut serde_json::Deserializer::from_str(raw);
+        serde_path_to_error::deserialize(de).map_err(|err| {
+            let path = err.path().to_string();
+            let inner = err.into_inner();
+            if path == "." {
+                crate::Error::Other(format!("Invalid query content: {}", inner))
+            } else {
+                crate::Error::Other(format!("Invalid query content at `{}`: {}", path, inner))
+            }
+        })
+    }
+}
+
+/// Accepts a limit as a number, a numeric string, or null. Zero, negative, or
+/// fractional values mean "no limit" rather than a parse failure.
+fn lenient_limit<'de, D>(deserializer: D) -> std::result::Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(match value {
+        Some(serde_json::Value::Number(n)) => n.as_u64(),
+        Some(serde_json::Value::String(s)) => s.trim().parse::<u64>().ok(),
+        _ => None,
+    }
+    .filter(|limit| *limit > 0)
+    .map(|limit| u32::try_from(limit).unwrap_or(u32::MAX)))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollectionTimeRange {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub relative: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub absolute: Option<CollectionAbsoluteTime>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollectionAbsoluteTime {
     pub start: i64,
     pub end: i64,
@@ -516,15 +856,224 @@ pub struct QueryHistoryEntry {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollectionVariable {
     pub name: String,
-    #[serde(default, rename = "type")]
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
     pub var_type: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
-    #[serde(default, rename = "inputType")]
+    #[serde(default, rename = "inputType", skip_serializing_if = "Option::is_none")]
     pub input_type: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub value: Option<serde_json::Value>,
 }
+
+/// Reads `_timestamp` / `timestamp` as RFC3339, `YYYY-MM-DD HH:MM:SS[.f]`
+/// (UTC), or epoch milliseconds.
+pub(crate) fn entry_timestamp(
+    entry: &crate::api::LogEntry,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    parse_timestamp(entry.get("_timestamp").or_else(|| entry.get("timestamp"))?)
+}
+
+pub(crate) fn parse_timestamp(value: &serde_json::Value) -> Option<chrono::DateTime<chrono::Utc>> {
+    match value {
+        serde_json::Value::String(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+                    .ok()
+                    .map(|naive| naive.and_utc())
+            }),
+        serde_json::Value::Number(n) => {
+            n.as_i64().and_then(chrono::DateTime::from_timestamp_millis)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meta_accepts_bare_and_partial_payloads() {
+        let enveloped = MetaResponse::from_value(serde_json::json!({
+            "status": "success",
+            "data": {"version": "v1.2.0", "oidc_issuer": "https://idp", "cli_client_id": "cli"}
+        }))
+        .unwrap();
+        assert_eq!(enveloped.data.version, "v1.2.0");
+        assert!(enveloped.data.oidc_enabled());
+
+        let bare = MetaResponse::from_value(serde_json::json!({
+            "oidcIssuer": "https://idp",
+            "cliClientId": "cli",
+            "extra": true
+        }))
+        .unwrap();
+        assert_eq!(bare.status, "success");
+        assert_eq!(bare.data.version, "unknown");
+        assert!(bare.data.oidc_enabled());
+    }
+
+    fn response(json: serde_json::Value) -> QueryResponse {
+        let mut response: QueryResponse = serde_json::from_value(json).unwrap();
+        response.normalize_columns();
+        response
+    }
+
+    fn keys(entry: &LogEntry) -> Vec<&str> {
+        entry.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn entries_follow_server_columns() {
+        let response = response(serde_json::json!({
+            "columns": [
+                {"name": "service", "type": "String"},
+                {"name": "_timestamp", "type": "DateTime64"},
+                {"name": "msg", "type": "String"},
+            ],
+            "logs": [{"msg": "a", "zeta": 1, "_timestamp": "t", "service": "api", "alpha": 2}],
+        }));
+        assert_eq!(
+            keys(&response.logs[0]),
+            ["service", "_timestamp", "msg", "alpha", "zeta"]
+        );
+        assert_eq!(
+            serde_json::to_string(&response.logs[0]).unwrap(),
+            r#"{"service":"api","_timestamp":"t","msg":"a","alpha":2,"zeta":1}"#
+        );
+    }
+
+    #[test]
+    fn merged_sources_are_tagged_and_newest_first() {
+        let api = response(serde_json::json!({
+            "columns": [{"name": "timestamp", "type": "DateTime"}, {"name": "msg", "type": "String"}],
+            "logs": [
+                {"timestamp": "2026-07-14T09:00:03Z", "msg": "c"},
+                {"timestamp": "2026-07-14T09:00:01Z", "msg": "a"},
+            ],
+            "stats": {"execution_time_ms": 40, "rows_read": 10},
+        }));
+        let worker = response(serde_json::json!({
+            "columns": [{"name": "timestamp", "type": "DateTime"}, {"name": "job", "type": "String"}],
+            "logs": [{"timestamp": "2026-07-14T09:00:02Z", "job": "b"}],
+            "stats": {"execution_time_ms": 90, "rows_read": 5},
+        }));
+
+        let merged = QueryResponse::merge(
+            SOURCE_FIELD,
+            vec![("api".to_string(), api), ("worker".to_string(), worker)],
+        );
+        let order: Vec<_> = merged
+            .entries()
+            .iter()
+            .map(|entry| entry[SOURCE_FIELD].as_str().unwrap())
+            .collect();
+        assert_eq!(order, ["api", "worker", "api"]);
+        assert_eq!(keys(&merged.logs[1]), ["_source", "timestamp", "job"]);
+        let columns: Vec<_> = merged.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(columns, ["_source", "timestamp", "msg", "job"]);
+        assert_eq!(merged.stats.execution_time_ms, 90);
+        assert_eq!(merged.stats.rows_read, 15);
+    }
+
+    #[test]
+    fn nulls_can_be_filled_or_omitted() {
+        let body = serde_json::json!({
+            "columns": [
+                {"name": "msg", "type": "String"},
+                {"name": "host", "type": "String"},
+                {"name": "user", "type": "String"},
+            ],
+            "logs": [{"msg": "a", "user": null}, {"msg": "b", "host": "web-1"}],
+        });
+
+        let mut filled = response(body.clone());
+        filled.fill_nulls();
+        assert_eq!(
+            serde_json::to_value(&filled.logs).unwrap(),
+            serde_json::json!([
+                {"msg": "a", "host": null, "user": null},
+                {"msg": "b", "host": "web-1", "user": null},
+            ])
+        );
+        assert_eq!(keys(&filled.logs[0]), ["msg", "host", "user"]);
+
+        let mut omitted = response(body);
+        omitted.omit_nulls();
+        assert_eq!(
+            serde_json::to_value(&omitted.logs).unwrap(),
+            serde_json::json!([{"msg": "a"}, {"msg": "b", "host": "web-1"}])
+        );
+    }
+
+    #[test]
+    fn missing_columns_are_derived_in_stable_order() {
+        let body = serde_json::json!({
+            "logs": [
+                {"service": "api", "msg": "a", "level": "info", "_timestamp": "t"},
+                {"host": "web-1", "msg": "b", "_timestamp": "t"},
+            ],
+        });
+        for _ in 0..10 {
+            let response = response(body.clone());
+            let columns: Vec<&str> = response.columns.iter().map(|c| c.name.as_str()).collect();
+            assert_eq!(columns, ["_timestamp", "level", "msg", "host", "service"]);
+            assert_eq!(
+                keys(&response.logs[0]),
+                ["_timestamp", "level", "msg", "service"]
+            );
+            assert_eq!(keys(&response.logs[1]), ["_timestamp", "msg", "host"]);
+        }
+    }
+
+    #[test]
+    fn collection_content_tolerates_missing_fields() {
+        let content = CollectionQueryContent::parse(r#"{"content": "level=\"error\""}"#).unwrap();
+        assert_eq!(content.content.as_deref(), Some(r#"level="error""#));
+        assert!(content.variables.is_none());
+        assert!(content.time_range.is_none());
+        assert!(content.limit.is_none());
+
+        let content =
+            CollectionQueryContent::parse(r#"{"timeRange": {}, "variables": null}"#).unwrap();
+        assert!(content.content.is_none());
+        assert!(content.time_range.unwrap().relative.is_none());
+
+        assert!(CollectionQueryContent::parse("").unwrap().content.is_none());
+        assert!(
+            CollectionQueryContent::parse("{}")
+                .unwrap()
+                .content
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn collection_content_limit_is_lenient() {
+        let limit = |raw: &str| CollectionQueryContent::parse(raw).unwrap().limit;
+        assert_eq!(limit(r#"{"limit": 50}"#), Some(50));
+        assert_eq!(limit(r#"{"limit": "250"}"#), Some(250));
+        assert_eq!(limit(r#"{"limit": null}"#), None);
+        assert_eq!(limit(r#"{"limit": -1}"#), None);
+        assert_eq!(limit(r#"{"limit": 0}"#), None);
+    }
+
+    #[test]
+    fn collection_content_error_names_the_field() {
+        let err = CollectionQueryContent::parse(r#"{"timeRange": {"absolute": {"end": 1}}}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("timeRange.absolute"), "{}", err);
+
+        let err = CollectionQueryContent::parse(r#"{"variables": [{"label": "x"}]}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("variables[0]"), "{}", err);
+    }
+}
@@ -0,0 +1,278 @@
+//! Paging through every row of a LogchefQL query, newest first, for
+//! `query --all` and SDK consumers alike.
+//!
+//! The server has no result cursor, so each follow-up request moves the
+//! window's `end_time` back to just after the oldest row seen. Time bounds
+//! are whole seconds, so that second is fetched again and the rows already
+//! returned from it are dropped.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::HashSet;
+
+use super::{Client, LogEntry, QueryRequest, QueryResponse};
+use crate::error::{Error, Result};
+use crate::timerange::{TimeInput, resolve_time_range, wall_clock_to_epoch_millis};
+
+/// Page size when the request has no `limit`, matching the CLI's default.
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// One page from [`Client::query_pages`].
+#[derive(Debug)]
+pub struct QueryPage {
+    /// 1-based.
+    pub number: usize,
+    /// The page's rows, without those an earlier page already returned.
+    pub response: QueryResponse,
+    /// Where the next page's window ends; `None` on the last page.
+    pub next_end: Option<DateTime<Utc>>,
+}
+
+/// Identifies a row across overlapping fetches: its timestamp plus a hash
+/// of its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DedupKey {
+    pub ts: Option<DateTime<Utc>>,
+    pub fingerprint: u64,
+}
+
+pub fn dedup_key(entry: &LogEntry, ts: Option<DateTime<Utc>>) -> DedupKey {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut keys: Vec<&String> = entry.keys().collect();
+    keys.sort();
+    for k in keys {
+        k.hash(&mut hasher);
+        if let Some(v) = entry.get(k) {
+            v.to_string().hash(&mut hasher);
+        }
+    }
+    DedupKey {
+        ts,
+        fingerprint: hasher.finish(),
+    }
+}
+
+/// Extracts a row's timestamp for dedup/cursor purposes. `ts_field`, when
+/// present, is the source's configured `_meta_ts_field` and is tried first;
+/// otherwise (or if the field is absent from the row) falls back to probing
+/// the hardcoded `_timestamp`/`timestamp` keys used by older/ClickHouse-only
+/// behavior.
+pub fn parse_entry_timestamp(entry: &LogEntry, ts_field: Option<&str>) -> Option<DateTime<Utc>> {
+    let value = ts_field
+        .and_then(|field| entry.get(field))
+        .or_else(|| entry.get("_timestamp"))
+        .or_else(|| entry.get("timestamp"))?;
+    let s = value.as_str()?;
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").map(|dt| dt.and_utc()))
+        .ok()
+}
+
+impl Client {
+    /// Runs `request` page by page, newest first, until the window is
+    /// exhausted. Each page holds up to `request.limit` rows (100 if unset).
+    /// Dropping the stream stops paging. A page that cannot be followed
+    /// (every row in one second, or rows without a parseable timestamp) is
+    /// still yielded; the error comes next.
+    pub fn query_pages(
+        &self,
+        team_id: i64,
+        source_id: i64,
+        mut request: QueryRequest,
+    ) -> impl futures::Stream<Item = Result<QueryPage>> + '_ {
+        let page_size = *request.limit.get_or_insert(DEFAULT_PAGE_SIZE);
+        let pager = Pager {
+            client: self,
+            team_id,
+            source_id,
+            request,
+            page_size,
+            cursor: None,
+            ts_field: None,
+            number: 0,
+            done: false,
+            deferred: None,
+        };
+        futures::stream::try_unfold(pager, |mut pager| async move {
+            Ok(pager.next_page().await?.map(|page| (page, pager)))
+        })
+    }
+}
+
+struct Pager<'a> {
+    client: &'a Client,
+    team_id: i64,
+    source_id: i64,
+    request: QueryRequest,
+    page_size: u32,
+    /// Set once the first page is requested.
+    cursor: Option<PageCursor>,
+    /// The source's `meta_ts_field`, if it has one.
+    ts_field: Option<String>,
+    number: usize,
+    done: bool,
+    /// Why paging stopped early, reported after the page it happened on.
+    deferred: Option<Error>,
+}
+
+impl Pager<'_> {
+    async fn next_page(&mut self) -> Result<Option<QueryPage>> {
+        if let Some(err) = self.deferred.take() {
+            return Err(err);
+        }
+        if self.done {
+            return Ok(None);
+        }
+        let tz = self.request.timezone.clone();
+        if self.cursor.is_none() {
+            let end = wall_clock_to_epoch_millis(&self.request.end_time, tz.as_deref())
+                .and_then(DateTime::from_timestamp_millis)
+                .ok_or_else(|| Error::other("Failed to resolve the end of the time range"))?;
+            self.ts_field = self
+                .client
+                .get_source(self.team_id, self.source_id)
+                .await
+                .ok()
+                .and_then(|source| source.meta_ts_field)
+                .filter(|field| !field.is_empty());
+            self.cursor = Some(PageCursor::new(end));
+        }
+
+        let mut response = self
+            .client
+            .query_logchefql(self.team_id, self.source_id, &self.request)
+            .await?;
+        self.number += 1;
+        let cursor = self.cursor.as_mut().expect("cursor set above");
+        let ts_field = self.ts_field.as_deref();
+        let page = response.entries();
+        let fresh: Vec<LogEntry> = cursor.fresh(page, ts_field).into_iter().cloned().collect();
+
+        let mut next_end = None;
+        if (page.len() as u32) < self.page_size {
+            self.done = true;
+        } else {
+            match cursor.advance(page, ts_field, self.page_size) {
+                Ok(end) => {
+                    self.request.end_time =
+                        resolve_time_range(TimeInput::Instant { start: end, end }, tz.as_deref())
+                            .end;
+                    next_end = Some(end);
+                }
+                Err(err) => {
+                    self.done = true;
+                    self.deferred = Some(err);
+                }
+            }
+        }
+
+        response.logs = fresh;
+        response.data = Vec::new();
+        Ok(Some(QueryPage {
+            number: self.number,
+            response,
+            next_end,
+        }))
+    }
+}
+
+/// Where paging resumes: the end of the window for the next page, and the
+/// rows already returned from the second that page overlaps.
+struct PageCursor {
+    end: DateTime<Utc>,
+    overlap: HashSet<DedupKey>,
+}
+
+impl PageCursor {
+    fn new(end: DateTime<Utc>) -> Self {
+        Self {
+            end,
+            overlap: Default::default(),
+        }
+    }
+
+    /// The rows of `page` not returned with the previous page.
+    fn fresh<'a>(&self, page: &'a [LogEntry], ts_field: Option<&str>) -> Vec<&'a LogEntry> {
+        page.iter()
+            .filter(|entry| {
+                let ts = parse_entry_timestamp(entry, ts_field);
+                !self.overlap.contains(&dedup_key(entry, ts))
+            })
+            .collect()
+    }
+
+    /// Moves the end of the window to just after the oldest second in a
+    /// full `page`; server time bounds are whole seconds, so that second is
+    /// fetched again and its returned rows are skipped next time.
+    fn advance(
+        &mut self,
+        page: &[LogEntry],
+        ts_field: Option<&str>,
+        page_size: u32,
+    ) -> Result<DateTime<Utc>> {
+        let stamped: Vec<_> = page
+            .iter()
+            .map(|entry| (entry, parse_entry_timestamp(entry, ts_field)))
+            .collect();
+        let oldest = stamped
+            .iter()
+            .map(|(_, ts)| *ts)
+            .collect::<Option<Vec<_>>>()
+            .and_then(|stamps| stamps.into_iter().min())
+            .ok_or_else(|| Error::other("Paging needs a parseable timestamp on every row"))?;
+        let second = DateTime::from_timestamp(oldest.timestamp(), 0).unwrap_or(oldest);
+        let next_end = second + chrono::Duration::seconds(1);
+        if next_end >= self.end {
+            return Err(Error::other(format!(
+                "More than {} rows fall within the second {}; raise --limit so a page can get past it",
+                page_size,
+                second.to_rfc3339()
+            )));
+        }
+        self.end = next_end;
+        self.overlap = stamped
+            .into_iter()
+            .filter(|(_, ts)| ts.is_some_and(|ts| ts >= second))
+            .map(|(entry, ts)| dedup_key(entry, ts))
+            .collect();
+        Ok(next_end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_cursor_steps_back_and_skips_overlap() {
+        let row = |ts: &str, msg: &str| -> LogEntry {
+            serde_json::from_value(serde_json::json!({"timestamp": ts, "msg": msg})).unwrap()
+        };
+        let end = DateTime::parse_from_rfc3339("2026-07-14T10:00:00Z")
+            .unwrap()
+            .to_utc();
+        let mut cursor = PageCursor::new(end);
+        let first = [
+            row("2026-07-14T09:59:30Z", "a"),
+            row("2026-07-14T09:58:10.500Z", "b"),
+            row("2026-07-14T09:58:10.200Z", "c"),
+        ];
+        let next_end = cursor.advance(&first, None, 3).unwrap();
+        assert_eq!(next_end.to_rfc3339(), "2026-07-14T09:58:11+00:00");
+
+        let second = [
+            row("2026-07-14T09:58:10.500Z", "b"),
+            row("2026-07-14T09:58:10.200Z", "c"),
+            row("2026-07-14T09:58:10.100Z", "d"),
+        ];
+        let fresh: Vec<_> = cursor
+            .fresh(&second, None)
+            .iter()
+            .map(|entry| entry["msg"].as_str().unwrap())
+            .collect();
+        assert_eq!(fresh, ["d"]);
+        // The whole page sits in one second: no way forward at this size.
+        assert!(cursor.advance(&second, None, 3).is_err());
+    }
+}
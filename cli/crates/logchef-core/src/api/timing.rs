@@ -0,0 +1,221 @@
+//! Where each API call's time went, for `--timing`: resolving the host,
+//! connecting (TCP and TLS), waiting for the server, downloading the body
+//! and parsing it.
+//!
+//! Off until [`enable`] is called, which must happen before the first
+//! client is built: the DNS and connect phases come from a resolver and
+//! connector layer installed on the pooled HTTP client. Calls are collected
+//! process-wide and read back with [`take`].
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use super::{Transfer, Transport};
+use crate::error::Result;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CALLS: Mutex<Vec<CallTiming>> = Mutex::new(Vec::new());
+
+/// One request's phases. DNS and connect are `None` when the request went
+/// out on a pooled connection; download and parse are `None` for streamed
+/// bodies and for calls that failed before them.
+#[derive(Debug, Clone)]
+pub struct CallTiming {
+    pub method: String,
+    /// Path and query, e.g. `/api/v1/teams/1/sources`.
+    pub path: String,
+    /// `None` when no response came back.
+    pub status: Option<u16>,
+    pub dns: Option<Duration>,
+    /// TCP and TLS handshakes, after DNS.
+    pub connect: Option<Duration>,
+    /// From the connection being ready to the response headers: mostly the
+    /// server (and the query it ran).
+    pub first_byte: Duration,
+    pub download: Option<Duration>,
+    pub deserialize: Option<Duration>,
+    pub transfer: Option<Transfer>,
+}
+
+impl CallTiming {
+    pub fn total(&self) -> Duration {
+        [self.dns, self.connect, self.download, self.deserialize]
+            .into_iter()
+            .flatten()
+            .sum::<Duration>()
+            + self.first_byte
+    }
+}
+
+/// Starts recording every API call this process makes.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// The calls recorded so far, oldest first, clearing the record.
+pub fn take() -> Vec<CallTiming> {
+    std::mem::take(&mut *lock())
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Vec<CallTiming>> {
+    CALLS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// DNS and connect time of the connection opened for the request being
+/// sent, if one was.
+#[derive(Debug, Default)]
+struct Handshake {
+    dns: Option<Duration>,
+    connect: Option<Duration>,
+}
+
+tokio::task_local! {
+    static HANDSHAKE: Arc<Mutex<Handshake>>;
+}
+
+/// Index into the record of the call a response belongs to, carried in the
+/// response's extensions so reading and parsing the body can be added to it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CallId(usize);
+
+/// Sends `request` through `transport`, recording the call with its
+/// handshake and time to first byte. The response carries a [`CallId`] for
+/// [`record_body`].
+pub(crate) async fn timed(
+    transport: &dyn Transport,
+    request: reqwest::Request,
+) -> Result<reqwest::Response> {
+    let method = request.method().to_string();
+    let url = request.url();
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    let handshake = Arc::new(Mutex::new(Handshake::default()));
+    let started = Instant::now();
+    let outcome = HANDSHAKE
+        .scope(handshake.clone(), transport.send(request))
+        .await;
+    let elapsed = started.elapsed();
+
+    let Handshake { dns, connect } =
+        std::mem::take(&mut *handshake.lock().unwrap_or_else(|e| e.into_inner()));
+    let mut calls = lock();
+    let id = CallId(calls.len());
+    calls.push(CallTiming {
+        method,
+        path,
+        status: outcome.as_ref().ok().map(|r| r.status().as_u16()),
+        first_byte: elapsed
+            .saturating_sub(dns.unwrap_or_default())
+            .saturating_sub(connect.unwrap_or_default()),
+        dns,
+        connect,
+        download: None,
+        deserialize: None,
+        transfer: None,
+    });
+    drop(calls);
+
+    let mut response = outcome?;
+    response.extensions_mut().insert(id);
+    Ok(response)
+}
+
+/// Adds how long the body of call `id` took to download, and its size.
+pub(crate) fn record_body(id: CallId, download: Duration, transfer: Transfer) {
+    if let Some(call) = lock().get_mut(id.0) {
+        call.download = Some(download);
+        call.transfer = Some(transfer);
+    }
+}
+
+/// Adds how long the body of call `id` took to parse.
+pub(crate) fn record_deserialize(id: CallId, deserialize: Duration) {
+    if let Some(call) = lock().get_mut(id.0) {
+        call.deserialize = Some(deserialize);
+    }
+}
+
+/// Puts the DNS resolver and connector layer that time handshakes on
+/// `builder`.
+pub(crate) fn instrument(builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+    builder
+        .dns_resolver(Arc::new(TimedResolver))
+        .connector_layer(TimedConnectLayer)
+}
+
+/// The system resolver, timed.
+struct TimedResolver;
+
+impl reqwest::dns::Resolve for TimedResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let handshake = HANDSHAKE.try_with(Arc::clone).ok();
+        Box::pin(async move {
+            let started = Instant::now();
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if let Some(handshake) = handshake {
+                handshake.lock().unwrap_or_else(|e| e.into_inner()).dns = Some(started.elapsed());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+#[derive(Clone)]
+struct TimedConnectLayer;
+
+impl<S> tower_layer::Layer<S> for TimedConnectLayer {
+    type Service = TimedConnect<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimedConnect(inner)
+    }
+}
+
+/// Times the connector, which resolves the host and then makes the TCP and
+/// TLS handshakes; DNS is subtracted once it's known.
+#[derive(Clone)]
+struct TimedConnect<S>(S);
+
+impl<S, R> tower_service::Service<R> for TimedConnect<S>
+where
+    S: tower_service::Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = std::result::Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), S::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let handshake = HANDSHAKE.try_with(Arc::clone).ok();
+        let connecting = self.0.call(request);
+        Box::pin(async move {
+            let started = Instant::now();
+            let outcome = connecting.await;
+            if let Some(handshake) = handshake {
+                let mut handshake = handshake.lock().unwrap_or_else(|e| e.into_inner());
+                handshake.connect = Some(
+                    started
+                        .elapsed()
+                        .saturating_sub(handshake.dns.unwrap_or_default()),
+                );
+            }
+            outcome
+        })
+    }
+}
@@ -0,0 +1,156 @@
+//! How [`Client`](super::Client) puts requests on the wire. The client
+//! builds each request (URL, auth, body, timeout) and hands it to a
+//! [`Transport`]; the default one sends it with reqwest. Tests, and tools
+//! that reach Logchef some other way, plug in their own with
+//! [`Client::with_transport`](super::Client::with_transport).
+
+use futures::future::BoxFuture;
+
+use crate::error::Result;
+
+/// Sends a built request and returns the server's response.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: reqwest::Request) -> BoxFuture<'static, Result<reqwest::Response>>;
+}
+
+/// The default transport: a (pooled) reqwest client.
+pub struct ReqwestTransport(pub reqwest::Client);
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: reqwest::Request) -> BoxFuture<'static, Result<reqwest::Response>> {
+        let http = self.0.clone();
+        Box::pin(async move { Ok(http.execute(request).await?) })
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::{MockTransport, RecordedRequest};
+
+#[cfg(any(test, feature = "test-util"))]
+mod mock {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    /// A request [`MockTransport`] received.
+    #[derive(Debug, Clone)]
+    pub struct RecordedRequest {
+        pub method: reqwest::Method,
+        /// Path and query, e.g. `/api/v1/teams/1/sources?x=1`.
+        pub path: String,
+        pub body: Option<serde_json::Value>,
+    }
+
+    struct Route {
+        method: reqwest::Method,
+        path: String,
+        replies: VecDeque<(u16, serde_json::Value)>,
+    }
+
+    #[derive(Default)]
+    struct State {
+        routes: Vec<Route>,
+        requests: Vec<RecordedRequest>,
+    }
+
+    /// An in-memory server for tests: canned JSON replies by method and
+    /// path, and a log of every request. Clones share their state, so keep
+    /// one to inspect after handing another to the client.
+    ///
+    /// A route answers with its replies in order and repeats the last one.
+    /// Requests matching no route get a 404.
+    #[derive(Clone, Default)]
+    pub struct MockTransport {
+        state: Arc<Mutex<State>>,
+    }
+
+    impl MockTransport {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Answers `method path` with `status` and `body`. The path is
+        /// matched without its query string unless it has one itself.
+        /// Calling this again for the same route queues a further reply.
+        pub fn on(
+            self,
+            method: reqwest::Method,
+            path: &str,
+            status: u16,
+            body: serde_json::Value,
+        ) -> Self {
+            {
+                let mut state = self.lock();
+                match state
+                    .routes
+                    .iter_mut()
+                    .find(|route| route.method == method && route.path == path)
+                {
+                    Some(route) => route.replies.push_back((status, body)),
+                    None => state.routes.push(Route {
+                        method,
+                        path: path.to_string(),
+                        replies: VecDeque::from([(status, body)]),
+                    }),
+                }
+            }
+            self
+        }
+
+        /// Every request received so far, oldest first.
+        pub fn requests(&self) -> Vec<RecordedRequest> {
+            self.lock().requests.clone()
+        }
+
+        fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+            self.state.lock().unwrap_or_else(|e| e.into_inner())
+        }
+
+        fn reply(&self, request: &reqwest::Request) -> (u16, serde_json::Value) {
+            let url = request.url();
+            let full = match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None => url.path().to_string(),
+            };
+            let body = request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .and_then(|bytes| serde_json::from_slice(bytes).ok());
+
+            let mut state = self.lock();
+            state.requests.push(RecordedRequest {
+                method: request.method().clone(),
+                path: full.clone(),
+                body,
+            });
+            let route = state.routes.iter_mut().find(|route| {
+                route.method == request.method() && (route.path == full || route.path == url.path())
+            });
+            match route {
+                Some(route) if route.replies.len() > 1 => {
+                    route.replies.pop_front().expect("checked above")
+                }
+                Some(route) => route.replies[0].clone(),
+                None => (
+                    404,
+                    serde_json::json!({
+                        "status": "error",
+                        "message": format!("no mock for {} {}", request.method(), full),
+                    }),
+                ),
+            }
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn send(&self, request: reqwest::Request) -> BoxFuture<'static, Result<reqwest::Response>> {
+            let (status, body) = self.reply(&request);
+            let response = http::Response::builder()
+                .status(status)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_string())
+                .expect("status and header are valid");
+            Box::pin(async move { Ok(reqwest::Response::from(response)) })
+        }
+    }
+}
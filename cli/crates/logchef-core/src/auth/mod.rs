@@ -1,20 +1,41 @@
-use crate::api::Client;
+use crate::api::{Client, pooled_http};
+use crate::config::TlsConfig;
 use crate::error::{Error, Result};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Write};
+use std::io::{self, BufRead, BufReader, Write};
 use std::net::TcpListener;
-use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
+use tokio::sync::oneshot;
 use tracing::{debug, info};
 use url::Url;
 
 const CALLBACK_TIMEOUT: Duration = Duration::from_secs(600);
 const AUTH_HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+/// Discovery is retried on network errors, 5xx and 429 responses.
+const DISCOVERY_ATTEMPTS: u32 = 3;
+const DISCOVERY_RETRY_DELAY: Duration = Duration::from_millis(500);
+const CALLBACK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const CALLBACK_READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// RFC 8628 defaults: poll every 5 seconds, and back off by 5 more on
+/// `slow_down`.
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const DEVICE_SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const SCOPES: &str = "openid email profile";
+/// Port in the redirect URI used by `run_manual`: the loopback listener's
+/// first choice, which is the one IdP client registrations list.
+const MANUAL_REDIRECT_PORT: u16 = 19876;
 
 pub struct AuthFlow {
     server_url: String,
     oidc_issuer: String,
     client_id: String,
+    discovery_url: Option<String>,
+    client_secret: Option<String>,
+    tls: TlsConfig,
+    proxy_url: Option<String>,
 }
 
 pub struct AuthResult {
@@ -29,11 +50,42 @@ impl AuthFlow {
             server_url,
             oidc_issuer,
             client_id,
+            discovery_url: None,
+            client_secret: None,
+            tls: TlsConfig::default(),
+            proxy_url: None,
         }
     }
 
+    /// Overrides the OIDC discovery document URL. Used verbatim instead of
+    /// `{issuer}/.well-known/openid-configuration`, for IdPs that publish
+    /// discovery elsewhere (e.g. tenant-specific Azure AD endpoints).
+    pub fn with_discovery_url(mut self, url: Option<String>) -> Self {
+        self.discovery_url = url.filter(|u| !u.trim().is_empty());
+        self
+    }
+
+    /// Sends `client_secret` in the token exchange, for IdPs that register
+    /// the CLI as a confidential client. PKCE is kept either way.
+    pub fn with_client_secret(mut self, secret: Option<String>) -> Self {
+        self.client_secret = secret.filter(|s| !s.is_empty());
+        self
+    }
+
+    /// Applies the context's TLS settings to the IdP and Logchef requests.
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Sends the discovery, token and Logchef requests through a proxy.
+    pub fn with_proxy(mut self, proxy_url: Option<String>) -> Self {
+        self.proxy_url = proxy_url;
+        self
+    }
+
     pub async fn run(&self) -> Result<AuthResult> {
-        let listener = TcpListener::bind("127.0.0.1:19876")
+        let listener = TcpListener::bind(("127.0.0.1", MANUAL_REDIRECT_PORT))
             .or_else(|_| TcpListener::bind("127.0.0.1:19877"))
             .or_else(|_| TcpListener::bind("127.0.0.1:19878"))
             .or_else(|_| TcpListener::bind("127.0.0.1:0"))
@@ -51,16 +103,7 @@ impl AuthFlow {
         let state = generate_state()?;
 
         let oidc_config = self.discover_oidc_config().await?;
-
-        let auth_url = format!(
-            "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&state={}&code_challenge={}&code_challenge_method=S256",
-            oidc_config.authorization_endpoint,
-            urlencoding::encode(&self.client_id),
-            urlencoding::encode(&redirect_url),
-            urlencoding::encode("openid email profile"),
-            &state,
-            &pkce_challenge,
-        );
+        let auth_url = self.authorization_url(&oidc_config, &redirect_url, &state, &pkce_challenge);
 
         info!("Opening browser for authentication...");
         println!("\nOpening browser for authentication...");
@@ -71,17 +114,26 @@ impl AuthFlow {
             debug!(error = %e, "Failed to open browser automatically");
         }
 
-        let (tx, rx) = mpsc::channel();
+        let (tx, rx) = oneshot::channel();
         let expected_state = state.clone();
-
-        std::thread::spawn(move || {
-            if let Err(e) = listener.set_nonblocking(false) {
-                debug!(error = %e, "Failed to set callback listener to blocking mode");
-            }
-
-            for stream in listener.incoming() {
-                match stream {
-                    Ok(mut stream) => {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let listener_shutdown = Arc::clone(&shutdown);
+
+        // The listener polls in non-blocking mode so it can notice the shutdown
+        // flag and release the port when the wait is cancelled or times out.
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| Error::auth(format!("Failed to configure callback server: {}", e)))?;
+
+        let handle = std::thread::spawn(move || {
+            while !listener_shutdown.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((mut stream, _)) => {
+                        if let Err(e) = stream.set_nonblocking(false) {
+                            debug!(error = %e, "Failed to set callback stream to blocking mode");
+                            continue;
+                        }
+                        let _ = stream.set_read_timeout(Some(CALLBACK_READ_TIMEOUT));
                         let reader = match stream.try_clone() {
                             Ok(cloned) => cloned,
                             Err(e) => {
@@ -103,7 +155,10 @@ impl AuthFlow {
                         let full_url = format!("http://127.0.0.1{}", path);
                         let url = match Url::parse(&full_url) {
                             Ok(u) => u,
-                            Err(_) => continue,
+                            Err(e) => {
+                                debug!(error = %e, "Ignoring unparsable callback request");
+                                continue;
+                            }
                         };
 
                         let code = url
@@ -148,14 +203,30 @@ impl AuthFlow {
                             break;
                         }
                     }
-                    Err(_) => continue,
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(CALLBACK_POLL_INTERVAL);
+                    }
+                    Err(e) => {
+                        // e.g. EMFILE: retrying at once would spin.
+                        debug!(error = %e, "Failed to accept callback connection");
+                        std::thread::sleep(CALLBACK_POLL_INTERVAL);
+                    }
                 }
             }
         });
 
-        let (code, received_state) = rx
-            .recv_timeout(CALLBACK_TIMEOUT)
-            .map_err(|_| Error::AuthTimeout)?;
+        let outcome = tokio::select! {
+            received = rx => received.map_err(|_| Error::auth("Callback server stopped unexpectedly")),
+            _ = tokio::time::sleep(CALLBACK_TIMEOUT) => Err(Error::AuthTimeout),
+            _ = tokio::signal::ctrl_c() => Err(Error::AuthCancelled),
+        };
+
+        shutdown.store(true, Ordering::Relaxed);
+        if handle.join().is_err() {
+            debug!("Callback server thread panicked");
+        }
+
+        let (code, received_state) = outcome?;
 
         if received_state != expected_state {
             return Err(Error::auth("CSRF state mismatch"));
@@ -172,6 +243,187 @@ impl AuthFlow {
             )
             .await?;
 
+        self.finish(token_response).await
+    }
+
+    /// Browser sign-in without the loopback listener, for when the callback
+    /// can't reach this machine (e.g. SSH without port forwarding). Prints the
+    /// authorization URL and reads back the redirect URL, or just the code,
+    /// that the browser ends up on.
+    pub async fn run_manual(&self) -> Result<AuthResult> {
+        // Same redirect URI as the listener's first choice, so it matches
+        // what the IdP has registered; nothing needs to answer on it.
+        let redirect_url = format!("http://127.0.0.1:{}/callback", MANUAL_REDIRECT_PORT);
+        let (pkce_verifier, pkce_challenge) = generate_pkce()?;
+        let state = generate_state()?;
+
+        let oidc_config = self.discover_oidc_config().await?;
+        let auth_url = self.authorization_url(&oidc_config, &redirect_url, &state, &pkce_challenge);
+
+        println!("\nOpen this URL in a browser on any machine and sign in:");
+        println!("  {}\n", auth_url);
+        println!("The browser then fails to load a {} page.", redirect_url);
+        println!("Copy that page's full URL from the address bar and paste it here.\n");
+
+        let code = loop {
+            print!("Redirect URL or code: ");
+            io::stdout().flush()?;
+            let line = tokio::select! {
+                line = tokio::task::spawn_blocking(read_stdin_line) => line
+                    .map_err(|e| Error::auth(format!("Failed to read input: {}", e)))??,
+                _ = tokio::time::sleep(CALLBACK_TIMEOUT) => return Err(Error::AuthTimeout),
+                _ = tokio::signal::ctrl_c() => return Err(Error::AuthCancelled),
+            };
+            let Some(line) = line else {
+                return Err(Error::AuthCancelled);
+            };
+            match code_from_pasted_redirect(&line, &state)? {
+                Some(code) => break code,
+                None => println!("That doesn't contain an authorization code; try again."),
+            }
+        };
+
+        info!("Received authorization code, exchanging for token...");
+
+        let token_response = self
+            .exchange_code_for_tokens(
+                &oidc_config.token_endpoint,
+                &code,
+                &redirect_url,
+                &pkce_verifier,
+            )
+            .await?;
+
+        self.finish(token_response).await
+    }
+
+    fn authorization_url(
+        &self,
+        oidc_config: &OidcConfig,
+        redirect_url: &str,
+        state: &str,
+        pkce_challenge: &str,
+    ) -> String {
+        format!(
+            "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&state={}&code_challenge={}&code_challenge_method=S256",
+            oidc_config.authorization_endpoint,
+            urlencoding::encode(&self.client_id),
+            urlencoding::encode(redirect_url),
+            urlencoding::encode(SCOPES),
+            state,
+            pkce_challenge,
+        )
+    }
+
+    /// Signs in with the OAuth device authorization grant (RFC 8628), for
+    /// machines without a browser: prints a verification URL and user code,
+    /// then polls the token endpoint until the user approves the sign-in on
+    /// another device. Fails with [`Error::DeviceFlowUnsupported`] when the
+    /// IdP doesn't advertise a device authorization endpoint.
+    pub async fn run_device(&self) -> Result<AuthResult> {
+        let oidc_config = self.discover_oidc_config().await?;
+        let device_endpoint = oidc_config
+            .device_authorization_endpoint
+            .as_deref()
+            .ok_or(Error::DeviceFlowUnsupported)?;
+
+        let client = pooled_http(&self.tls, self.proxy_url.as_deref())?;
+        let mut params = vec![("client_id", self.client_id.as_str()), ("scope", SCOPES)];
+        if let Some(secret) = &self.client_secret {
+            params.push(("client_secret", secret.as_str()));
+        }
+        let response = client
+            .post(device_endpoint)
+            .timeout(AUTH_HTTP_TIMEOUT)
+            .form(&params)
+            .send()
+            .await
+            .map_err(|e| Error::oauth(format!("Device authorization request failed: {}", e)))?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(Error::oauth(format!(
+                "Device authorization failed: {}",
+                oauth_error_message(&body)
+            )));
+        }
+        let device: DeviceAuthorization = serde_json::from_str(&body)
+            .map_err(|e| Error::oauth(format!("Malformed device authorization response: {}", e)))?;
+
+        println!("\nTo sign in, open this URL on any device:");
+        println!("  {}", device.verification_uri);
+        println!("and enter the code: {}\n", device.user_code);
+        if let Some(complete) = &device.verification_uri_complete {
+            println!("Or open this link, which fills in the code:");
+            println!("  {}\n", complete);
+        }
+        println!("Waiting for approval...");
+
+        let expires_in = device
+            .expires_in
+            .map(Duration::from_secs)
+            .unwrap_or(CALLBACK_TIMEOUT);
+        let interval = device
+            .interval
+            .map(Duration::from_secs)
+            .unwrap_or(DEVICE_POLL_INTERVAL);
+        let token_response = tokio::select! {
+            polled = self.poll_device_token(&oidc_config.token_endpoint, &device.device_code, interval) => polled?,
+            _ = tokio::time::sleep(expires_in) => return Err(Error::AuthTimeout),
+            _ = tokio::signal::ctrl_c() => return Err(Error::AuthCancelled),
+        };
+
+        info!("Device sign-in approved, exchanging for token...");
+        self.finish(token_response).await
+    }
+
+    async fn poll_device_token(
+        &self,
+        token_endpoint: &str,
+        device_code: &str,
+        mut interval: Duration,
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        let client = pooled_http(&self.tls, self.proxy_url.as_deref())?;
+        let mut params = vec![
+            ("grant_type", DEVICE_GRANT_TYPE),
+            ("client_id", self.client_id.as_str()),
+            ("device_code", device_code),
+        ];
+        if let Some(secret) = &self.client_secret {
+            params.push(("client_secret", secret.as_str()));
+        }
+
+        loop {
+            tokio::time::sleep(interval).await;
+            let response = match client
+                .post(token_endpoint)
+                .timeout(AUTH_HTTP_TIMEOUT)
+                .form(&params)
+                .send()
+                .await
+            {
+                Ok(response) => response,
+                // A dropped poll is retried at the next interval.
+                Err(e) => {
+                    debug!(error = %e, "Device token poll failed");
+                    continue;
+                }
+            };
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            match device_poll_outcome(status, &body)? {
+                DevicePoll::Pending => {}
+                DevicePoll::SlowDown => interval += DEVICE_SLOW_DOWN_STEP,
+                DevicePoll::Tokens(tokens) => return Ok(tokens),
+            }
+        }
+    }
+
+    /// Trades the IdP's ID token for a Logchef API token.
+    async fn finish(
+        &self,
+        token_response: HashMap<String, serde_json::Value>,
+    ) -> Result<AuthResult> {
         let id_token = token_response
             .get("id_token")
             .and_then(|v| v.as_str())
@@ -179,7 +431,8 @@ impl AuthFlow {
 
         info!("Exchanging OIDC token for Logchef API token...");
 
-        let api_client = Client::new(&self.server_url, 30)?;
+        let api_client =
+            Client::with_network(&self.server_url, 30, &self.tls, self.proxy_url.as_deref())?;
         let exchange_response = api_client.exchange_token(id_token).await?;
 
         Ok(AuthResult {
@@ -190,31 +443,56 @@ impl AuthFlow {
     }
 
     async fn discover_oidc_config(&self) -> Result<OidcConfig> {
-        let discovery_url = format!(
-            "{}/.well-known/openid-configuration",
-            self.oidc_issuer.trim_end_matches('/')
-        );
+        let discovery_url = self.discovery_url.clone().unwrap_or_else(|| {
+            format!(
+                "{}/.well-known/openid-configuration",
+                self.oidc_issuer.trim_end_matches('/')
+            )
+        });
 
         debug!(url = %discovery_url, "Discovering OIDC configuration");
 
-        let client = build_http_client()?;
-        let response = client
-            .get(&discovery_url)
-            .send()
-            .await
-            .map_err(|e| Error::oauth(format!("Failed to fetch OIDC configuration: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Err(Error::oauth(format!(
-                "OIDC discovery failed with status {}",
-                response.status()
-            )));
-        }
+        let client = pooled_http(&self.tls, self.proxy_url.as_deref())?;
+        let mut attempt = 1;
+        let (status, body) = loop {
+            let outcome = match client
+                .get(&discovery_url)
+                .timeout(AUTH_HTTP_TIMEOUT)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    let status = response.status();
+                    match response.text().await {
+                        Ok(body) => Ok((status, body)),
+                        Err(e) => Err(error_chain(&e)),
+                    }
+                }
+                Err(e) => Err(error_chain(&e)),
+            };
+            let retryable = match &outcome {
+                Ok((status, _)) => {
+                    status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(_) => true,
+            };
+            if !retryable || attempt >= DISCOVERY_ATTEMPTS {
+                match outcome {
+                    Ok(result) => break result,
+                    Err(e) => {
+                        return Err(Error::oauth(format!(
+                            "Identity provider unreachable: could not fetch {} after {} attempts: {}",
+                            discovery_url, attempt, e
+                        )));
+                    }
+                }
+            }
+            debug!(attempt, url = %discovery_url, "OIDC discovery failed, retrying");
+            tokio::time::sleep(DISCOVERY_RETRY_DELAY * attempt).await;
+            attempt += 1;
+        };
 
-        response
-            .json::<OidcConfig>()
-            .await
-            .map_err(|e| Error::oauth(format!("Failed to parse OIDC configuration: {}", e)))
+        parse_discovery_document(&discovery_url, status, &body)
     }
 
     async fn exchange_code_for_tokens(
@@ -224,18 +502,22 @@ impl AuthFlow {
         redirect_uri: &str,
         pkce_verifier: &str,
     ) -> Result<HashMap<String, serde_json::Value>> {
-        let client = build_http_client()?;
+        let client = pooled_http(&self.tls, self.proxy_url.as_deref())?;
 
-        let params = [
+        let mut params = vec![
             ("grant_type", "authorization_code"),
             ("client_id", self.client_id.as_str()),
             ("code", code),
             ("redirect_uri", redirect_uri),
             ("code_verifier", pkce_verifier),
         ];
+        if let Some(secret) = &self.client_secret {
+            params.push(("client_secret", secret.as_str()));
+        }
 
         let response = client
             .post(token_endpoint)
+            .timeout(AUTH_HTTP_TIMEOUT)
             .form(&params)
             .send()
             .await
@@ -257,13 +539,157 @@ impl AuthFlow {
 struct OidcConfig {
     authorization_endpoint: String,
     token_endpoint: String,
+    #[serde(default)]
+    device_authorization_endpoint: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    /// Google still sends the pre-RFC `verification_url`.
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    interval: Option<u64>,
+}
+
+#[derive(Debug)]
+enum DevicePoll {
+    Pending,
+    SlowDown,
+    Tokens(HashMap<String, serde_json::Value>),
+}
+
+/// Reads one device-code token poll: still waiting, back off, done, or a
+/// terminal error (denied, expired, misconfigured client).
+fn device_poll_outcome(status: reqwest::StatusCode, body: &str) -> Result<DevicePoll> {
+    if status.is_success() {
+        return serde_json::from_str(body)
+            .map(DevicePoll::Tokens)
+            .map_err(|e| Error::oauth(format!("Failed to parse token response: {}", e)));
+    }
+    let code = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v.get("error")?.as_str().map(str::to_string));
+    match code.as_deref() {
+        Some("authorization_pending") => Ok(DevicePoll::Pending),
+        Some("slow_down") => Ok(DevicePoll::SlowDown),
+        Some("access_denied") => Err(Error::oauth("Sign-in was denied on the verification page")),
+        Some("expired_token") => Err(Error::AuthTimeout),
+        _ => Err(Error::oauth(format!(
+            "Token request failed: {}",
+            oauth_error_message(body)
+        ))),
+    }
+}
+
+/// Reads one line from stdin; `None` at end of input.
+fn read_stdin_line() -> Result<Option<String>> {
+    let mut line = String::new();
+    let read = io::stdin().lock().read_line(&mut line)?;
+    Ok((read > 0).then_some(line))
+}
+
+/// Pulls the authorization code out of what the user pasted for
+/// [`AuthFlow::run_manual`]: the redirect URL, its query string, or the bare
+/// code. `Ok(None)` means the input had nothing usable.
+fn code_from_pasted_redirect(input: &str, expected_state: &str) -> Result<Option<String>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    if !input.contains('=') && !input.contains("://") {
+        // A bare code can't carry the state, so there's nothing to check.
+        return Ok(Some(input.to_string()));
+    }
+
+    let query = match Url::parse(input) {
+        Ok(url) => url.query().unwrap_or_default().to_string(),
+        Err(_) => input.trim_start_matches('?').to_string(),
+    };
+    let params: HashMap<String, String> = url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+
+    if let Some(error) = params.get("error") {
+        return Err(Error::oauth(match params.get("error_description") {
+            Some(description) => format!("{}: {}", error, description),
+            None => error.clone(),
+        }));
+    }
+    let Some(code) = params.get("code").filter(|c| !c.is_empty()) else {
+        return Ok(None);
+    };
+    if params.get("state").map(String::as_str) != Some(expected_state) {
+        return Err(Error::auth("CSRF state mismatch"));
+    }
+    Ok(Some(code.clone()))
 }
 
-fn build_http_client() -> Result<reqwest::Client> {
-    reqwest::Client::builder()
-        .timeout(AUTH_HTTP_TIMEOUT)
-        .build()
-        .map_err(|e| Error::oauth(format!("Failed to create HTTP client: {}", e)))
+/// `error: error_description` from an OAuth error body, or the body itself.
+fn oauth_error_message(body: &str) -> String {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(body) else {
+        return body.to_string();
+    };
+    match (
+        value.get("error").and_then(|v| v.as_str()),
+        value.get("error_description").and_then(|v| v.as_str()),
+    ) {
+        (Some(error), Some(description)) => format!("{}: {}", error, description),
+        (Some(error), None) => error.to_string(),
+        _ => body.to_string(),
+    }
+}
+
+/// Turns a discovery response into an [`OidcConfig`], telling an unreachable
+/// IdP, a wrong issuer URL and a malformed document apart in the error.
+fn parse_discovery_document(
+    url: &str,
+    status: reqwest::StatusCode,
+    body: &str,
+) -> Result<OidcConfig> {
+    if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(Error::oauth(format!(
+            "Identity provider unavailable: {} returned HTTP {}",
+            url, status
+        )));
+    }
+    if !status.is_success() {
+        return Err(Error::oauth(format!(
+            "No OIDC discovery document at {} (HTTP {}); check the issuer URL (oidc_issuer / oidc_discovery_url)",
+            url, status
+        )));
+    }
+    let document: serde_json::Value = serde_json::from_str(body).map_err(|_| {
+        Error::oauth(format!(
+            "{} did not return a JSON discovery document; check the issuer URL (oidc_issuer / oidc_discovery_url)",
+            url
+        ))
+    })?;
+    serde_json::from_value(document).map_err(|e| {
+        Error::oauth(format!(
+            "Malformed OIDC discovery document at {}: {}",
+            url, e
+        ))
+    })
+}
+
+/// An error with its causes, e.g. `error sending request: dns error: ...`;
+/// reqwest's own message leaves out why the request failed.
+fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut out = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        out.push_str(": ");
+        out.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    out
 }
 
 fn generate_pkce() -> Result<(String, String)> {
@@ -290,3 +716,115 @@ fn generate_state() -> Result<String> {
         .map_err(|e| Error::auth(format!("Failed to generate random bytes: {}", e)))?;
     Ok(URL_SAFE_NO_PAD.encode(state_bytes))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    const URL: &str = "https://idp.example.com/.well-known/openid-configuration";
+
+    fn message(result: Result<OidcConfig>) -> String {
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn pasted_redirects_yield_the_code() {
+        let url = "http://127.0.0.1:19876/callback?code=abc%2F1&state=s1";
+        assert_eq!(
+            code_from_pasted_redirect(url, "s1").unwrap().as_deref(),
+            Some("abc/1")
+        );
+        assert_eq!(
+            code_from_pasted_redirect("  ?state=s1&code=xyz\n", "s1")
+                .unwrap()
+                .as_deref(),
+            Some("xyz")
+        );
+        assert_eq!(
+            code_from_pasted_redirect("xyz", "s1").unwrap().as_deref(),
+            Some("xyz")
+        );
+        assert_eq!(code_from_pasted_redirect(" \n", "s1").unwrap(), None);
+        assert_eq!(
+            code_from_pasted_redirect("http://127.0.0.1:19876/callback", "s1").unwrap(),
+            None
+        );
+
+        let err = code_from_pasted_redirect("code=xyz&state=other", "s1").unwrap_err();
+        assert!(err.to_string().contains("state mismatch"), "{}", err);
+        let err = code_from_pasted_redirect(
+            "http://127.0.0.1:19876/callback?error=access_denied&error_description=Denied&state=s1",
+            "s1",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("access_denied: Denied"), "{}", err);
+    }
+
+    #[test]
+    fn discovery_errors_name_the_likely_cause() {
+        let doc =
+            r#"{"authorization_endpoint":"https://idp/auth","token_endpoint":"https://idp/token"}"#;
+        let config = parse_discovery_document(URL, StatusCode::OK, doc).unwrap();
+        assert_eq!(config.token_endpoint, "https://idp/token");
+
+        let unavailable = message(parse_discovery_document(URL, StatusCode::BAD_GATEWAY, ""));
+        assert!(unavailable.contains("unavailable"), "{unavailable}");
+
+        let not_found = message(parse_discovery_document(URL, StatusCode::NOT_FOUND, ""));
+        assert!(not_found.contains("check the issuer URL"), "{not_found}");
+
+        let html = message(parse_discovery_document(
+            URL,
+            StatusCode::OK,
+            "<html></html>",
+        ));
+        assert!(html.contains("check the issuer URL"), "{html}");
+
+        let malformed = message(parse_discovery_document(
+            URL,
+            StatusCode::OK,
+            r#"{"authorization_endpoint":"https://idp/auth"}"#,
+        ));
+        assert!(malformed.contains("Malformed"), "{malformed}");
+        assert!(malformed.contains("token_endpoint"), "{malformed}");
+    }
+
+    #[test]
+    fn device_polls_wait_back_off_and_finish() {
+        let poll = |status, body| device_poll_outcome(status, body);
+        assert!(matches!(
+            poll(
+                StatusCode::BAD_REQUEST,
+                r#"{"error":"authorization_pending"}"#
+            ),
+            Ok(DevicePoll::Pending)
+        ));
+        assert!(matches!(
+            poll(StatusCode::BAD_REQUEST, r#"{"error":"slow_down"}"#),
+            Ok(DevicePoll::SlowDown)
+        ));
+        match poll(StatusCode::OK, r#"{"id_token":"abc"}"#) {
+            Ok(DevicePoll::Tokens(tokens)) => assert_eq!(tokens["id_token"], "abc"),
+            other => panic!("{other:?}"),
+        }
+        assert!(matches!(
+            poll(StatusCode::BAD_REQUEST, r#"{"error":"expired_token"}"#),
+            Err(Error::AuthTimeout)
+        ));
+        let denied = poll(StatusCode::BAD_REQUEST, r#"{"error":"access_denied"}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(denied.contains("denied"), "{denied}");
+        let other = poll(
+            StatusCode::UNAUTHORIZED,
+            r#"{"error":"unauthorized_client","error_description":"device grant not enabled"}"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            other.contains("unauthorized_client: device grant not enabled"),
+            "{other}"
+        );
+    }
+}
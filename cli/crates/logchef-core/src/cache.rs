@@ -1,17 +1,23 @@
+use crate::api::{FieldValuesResult, Source};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 const CACHE_TTL_SECS: u64 = 600; // 10 minutes
+const VALUES_TTL_SECS: u64 = 60;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct CacheData {
     teams: HashMap<String, TeamCache>,
     #[serde(default)]
     updated_at: u64,
+    /// Last seen version tag per source ID (`schema_version` or `updated_at`).
+    #[serde(default)]
+    source_versions: HashMap<i64, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,13 +39,7 @@ impl Cache {
     }
 
     fn cache_path(server_url: &str) -> PathBuf {
-        let cache_dir = ProjectDirs::from("", "", "logchef")
-            .map(|dirs| dirs.cache_dir().to_path_buf())
-            .unwrap_or_else(|| std::env::temp_dir().join("logchef"));
-        fs::create_dir_all(&cache_dir).ok();
-
-        let safe_name: String = server_url.replace("://", "_").replace(['/', ':', '.'], "_");
-        cache_dir.join(format!("resolve_{}.json", safe_name))
+        cache_file("resolve", server_url)
     }
 
     fn load_from_disk(path: &PathBuf) -> Option<CacheData> {
@@ -49,16 +49,32 @@ impl Cache {
 
     fn save_to_disk(&self) {
         if let Ok(content) = serde_json::to_string_pretty(&self.data) {
-            fs::write(&self.path, content).ok();
+            write_atomically(&self.path, content);
         }
     }
 
     fn is_expired(&self) -> bool {
+        now_secs().saturating_sub(self.data.updated_at) > CACHE_TTL_SECS
+    }
+
+    /// Time since the cache was last written, or `None` if it never was.
+    pub fn age(&self) -> Option<std::time::Duration> {
+        if self.data.updated_at == 0 {
+            return None;
+        }
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
-        now.saturating_sub(self.data.updated_at) > CACHE_TTL_SECS
+        Some(std::time::Duration::from_secs(
+            now.saturating_sub(self.data.updated_at),
+        ))
+    }
+
+    /// Whether lookups are currently served from the cache (it exists and is
+    /// within the TTL).
+    pub fn is_fresh(&self) -> bool {
+        self.data.updated_at != 0 && !self.is_expired()
     }
 
     fn touch(&mut self) {
@@ -132,12 +148,170 @@ impl Cache {
         self.save_to_disk();
     }
 
+    /// Records the version tag of each source in a freshly fetched team
+    /// listing. Cached name mappings are dropped for sources whose tag changed
+    /// since they were last seen, and for sources that no longer exist, so a
+    /// renamed or recreated source is re-resolved instead of served stale.
+    /// Sources without a tag are left to the TTL.
+    pub fn observe_sources(&mut self, team_id: i64, sources: &[Source]) {
+        let mut changed = false;
+        for source in sources {
+            changed |= self.record_source_version(source);
+        }
+        if let Some(team) = self.data.teams.values_mut().find(|t| t.id == team_id) {
+            let before = team.sources.len();
+            team.sources
+                .retain(|_, id| sources.iter().any(|source| source.id == *id));
+            changed |= team.sources.len() != before;
+        }
+        if changed {
+            self.save_to_disk();
+        }
+    }
+
+    /// Records the version tag of a single fetched source, dropping its cached
+    /// name mappings if the tag changed. Returns `true` when it did.
+    pub fn observe_source(&mut self, source: &Source) -> bool {
+        let changed = self.record_source_version(source);
+        if changed {
+            self.save_to_disk();
+        }
+        changed
+    }
+
+    fn record_source_version(&mut self, source: &Source) -> bool {
+        let Some(tag) = source.version_tag() else {
+            return false;
+        };
+        let previous = self.data.source_versions.insert(source.id, tag.to_string());
+        match previous {
+            Some(previous) if previous != tag => {
+                for team in self.data.teams.values_mut() {
+                    team.sources.retain(|_, id| *id != source.id);
+                }
+                true
+            }
+            Some(_) => false,
+            // First sighting: nothing to compare against, but persist the tag.
+            None => true,
+        }
+    }
+
+    /// Drops every cached name mapping to `source_id`, and its version tag,
+    /// after the server said it doesn't know that source, or knows it by
+    /// another name: it was deleted, recreated or renamed since it was
+    /// cached, which the TTL alone would hide.
+    pub fn forget_source(&mut self, source_id: i64) {
+        for team in self.data.teams.values_mut() {
+            team.sources.retain(|_, id| *id != source_id);
+        }
+        self.data.source_versions.remove(&source_id);
+        self.save_to_disk();
+    }
+
     pub fn clear(&mut self) {
         self.data = CacheData::default();
         fs::remove_file(&self.path).ok();
     }
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ValuesData {
+    entries: HashMap<String, CachedValues>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedValues {
+    stored_at: u64,
+    result: FieldValuesResult,
+}
+
+/// Field-value results from the last minute, so re-running `logchef values`
+/// while narrowing down a filter doesn't re-scan the table each time.
+/// Entries are keyed by the caller (field, filter, window and limit).
+pub struct ValuesCache {
+    path: PathBuf,
+    data: ValuesData,
+}
+
+impl ValuesCache {
+    pub fn new(server_url: &str) -> Self {
+        let path = cache_file("values", server_url);
+        let data = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path, data }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&FieldValuesResult> {
+        self.data
+            .entries
+            .get(key)
+            .filter(|entry| now_secs().saturating_sub(entry.stored_at) <= VALUES_TTL_SECS)
+            .map(|entry| &entry.result)
+    }
+
+    /// Stores a result, dropping any that have expired.
+    pub fn insert(&mut self, key: String, result: FieldValuesResult) {
+        let now = now_secs();
+        self.data
+            .entries
+            .retain(|_, entry| now.saturating_sub(entry.stored_at) <= VALUES_TTL_SECS);
+        self.data.entries.insert(
+            key,
+            CachedValues {
+                stored_at: now,
+                result,
+            },
+        );
+        if let Ok(content) = serde_json::to_string(&self.data) {
+            write_atomically(&self.path, content);
+        }
+    }
+}
+
+/// `<cache dir>/<prefix>_<server>.json`.
+fn cache_file(prefix: &str, server_url: &str) -> PathBuf {
+    let cache_dir = ProjectDirs::from("", "", "logchef")
+        .map(|dirs| dirs.cache_dir().to_path_buf())
+        .unwrap_or_else(|| std::env::temp_dir().join("logchef"));
+    fs::create_dir_all(&cache_dir).ok();
+
+    let safe_name: String = server_url.replace("://", "_").replace(['/', ':', '.'], "_");
+    cache_dir.join(format!("{}_{}.json", prefix, safe_name))
+}
+
+/// Writes via a uniquely named temp file and a rename, so parallel `logchef`
+/// invocations never leave (or read) a half-written file; the last writer
+/// wins. Failures are ignored, as the caches are best-effort.
+fn write_atomically(path: &std::path::Path, content: String) {
+    static SAVE_SEQ: AtomicU64 = AtomicU64::new(0);
+    let tmp_path = path.with_extension(format!(
+        "json.{}-{}.tmp",
+        std::process::id(),
+        SAVE_SEQ.fetch_add(1, Ordering::Relaxed)
+    ));
+    if fs::write(&tmp_path, content).is_err() {
+        fs::remove_file(&tmp_path).ok();
+        return;
+    }
+    if fs::rename(&tmp_path, path).is_err() {
+        // Windows refuses to rename over an existing file.
+        fs::remove_file(path).ok();
+        if fs::rename(&tmp_path, path).is_err() {
+            fs::remove_file(&tmp_path).ok();
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 pub fn parse_identifier(input: &str) -> Identifier {
     if input.chars().all(|c| c.is_ascii_digit())
         && let Ok(id) = input.parse::<i64>()
@@ -152,3 +326,146 @@ pub enum Identifier {
     Id(i64),
     Name(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_cache() -> Cache {
+        Cache {
+            path: std::env::temp_dir().join(format!(
+                "logchef-cache-test-{}-{:?}.json",
+                std::process::id(),
+                std::thread::current().id()
+            )),
+            data: CacheData::default(),
+        }
+    }
+
+    fn source(id: i64, name: &str, updated_at: &str) -> Source {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": name,
+            "updated_at": updated_at,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn concurrent_saves_leave_a_readable_file() {
+        let path =
+            std::env::temp_dir().join(format!("logchef-cache-race-{}.json", std::process::id()));
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let mut cache = Cache {
+                        path,
+                        data: CacheData::default(),
+                    };
+                    for round in 0..20 {
+                        let teams: Vec<_> = (0..50)
+                            .map(|t| (format!("team-{}-{}-{}", i, round, t), t))
+                            .collect();
+                        cache.set_teams(&teams);
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        let data = Cache::load_from_disk(&path).expect("cache file should parse");
+        assert_eq!(data.teams.len(), 20 * 50);
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn changed_source_version_invalidates_cached_ids() {
+        let mut cache = test_cache();
+        cache.set_teams(&[("ops".to_string(), 1)]);
+        cache.observe_sources(1, &[source(7, "app", "2024-01-01T00:00:00Z")]);
+        cache.set_sources(1, &[("app".to_string(), 7)]);
+        assert_eq!(cache.get_source_id(1, "app"), Some(7));
+
+        assert!(!cache.observe_source(&source(7, "app", "2024-01-01T00:00:00Z")));
+        assert_eq!(cache.get_source_id(1, "app"), Some(7));
+
+        assert!(cache.observe_source(&source(7, "app", "2024-02-01T00:00:00Z")));
+        assert_eq!(cache.get_source_id(1, "app"), None);
+        cache.clear();
+    }
+
+    #[test]
+    fn schema_version_takes_precedence_over_updated_at() {
+        let source: Source = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "name": "app",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "schema_version": "v3",
+        }))
+        .unwrap();
+        assert_eq!(source.version_tag(), Some("v3"));
+    }
+
+    #[test]
+    fn removed_sources_are_pruned_from_team_listing() {
+        let mut cache = test_cache();
+        cache.set_teams(&[("ops".to_string(), 1)]);
+        cache.set_sources(1, &[("app".to_string(), 7), ("old".to_string(), 8)]);
+        cache.observe_sources(1, &[source(7, "app", "t1")]);
+        assert_eq!(cache.get_source_id(1, "app"), Some(7));
+        assert_eq!(cache.get_source_id(1, "old"), None);
+        cache.clear();
+    }
+
+    #[test]
+    fn forgotten_source_is_re_resolved() {
+        let mut cache = test_cache();
+        cache.set_teams(&[("ops".to_string(), 1)]);
+        cache.observe_sources(1, &[source(7, "app", "t1")]);
+        cache.set_sources(1, &[("app".to_string(), 7), ("logs.app".to_string(), 7)]);
+        cache.forget_source(7);
+        assert_eq!(cache.get_source_id(1, "app"), None);
+        assert_eq!(cache.get_source_id(1, "logs.app"), None);
+        assert!(!cache.data.source_versions.contains_key(&7));
+        assert_eq!(cache.get_team_id("ops"), Some(1));
+        cache.clear();
+    }
+
+    #[test]
+    fn values_expire_after_a_minute() {
+        let mut cache = ValuesCache {
+            path: std::env::temp_dir()
+                .join(format!("logchef-values-test-{}.json", std::process::id())),
+            data: ValuesData::default(),
+        };
+        let result: FieldValuesResult = serde_json::from_value(serde_json::json!({
+            "field_name": "service",
+            "values": [{"value": "api", "count": 3}],
+        }))
+        .unwrap();
+        cache.insert("1/2/service".to_string(), result);
+        assert_eq!(cache.get("1/2/service").unwrap().values[0].value, "api");
+        assert!(cache.get("1/2/level").is_none());
+
+        cache.data.entries.get_mut("1/2/service").unwrap().stored_at -= VALUES_TTL_SECS + 1;
+        assert!(cache.get("1/2/service").is_none());
+        fs::remove_file(&cache.path).ok();
+    }
+
+    #[test]
+    fn age_and_freshness_follow_updated_at() {
+        let mut cache = test_cache();
+        assert_eq!(cache.age(), None);
+        assert!(!cache.is_fresh());
+
+        cache.set_teams(&[("ops".to_string(), 1)]);
+        assert!(cache.is_fresh());
+        assert!(cache.age().unwrap().as_secs() < 5);
+
+        cache.data.updated_at -= CACHE_TTL_SECS + 1;
+        assert!(!cache.is_fresh());
+        assert_eq!(cache.get_team_id("ops"), None);
+    }
+}
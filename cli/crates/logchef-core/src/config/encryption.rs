@@ -0,0 +1,308 @@
+//! Optional encryption of context secrets inside the config file
+//! (`"encryption": "machine"` or `"passphrase"`), for machines without an OS
+//! keyring. On save, `token`, `fallback_token` and `oidc_client_secret` are
+//! sealed with XChaCha20-Poly1305 and written as
+//! `encrypted:<salt>:<nonce and ciphertext>`; on load they're opened again,
+//! so the rest of the CLI only ever sees the real values.
+//!
+//! The key comes from the machine's ID (`/etc/machine-id`, the macOS
+//! platform UUID or the Windows machine GUID), or from a passphrase run
+//! through Argon2id. The passphrase is read from `LOGCHEF_PASSPHRASE`, else
+//! asked for through the prompt the CLI registers with
+//! [`set_passphrase_prompt`]. All secrets of one save share a salt, so a
+//! load derives the key once.
+
+use std::sync::OnceLock;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use sha2::{Digest, Sha256};
+
+use super::{Config, Encryption, SealingKey};
+use crate::error::{Error, Result};
+
+const PREFIX: &str = "encrypted:";
+const NONCE_LEN: usize = 24;
+
+static PROMPT: OnceLock<fn() -> Option<String>> = OnceLock::new();
+
+/// Registers how to ask for the passphrase when `LOGCHEF_PASSPHRASE` is
+/// unset, e.g. a terminal prompt. Only the first call counts.
+pub fn set_passphrase_prompt(prompt: fn() -> Option<String>) {
+    let _ = PROMPT.set(prompt);
+}
+
+/// What the key for `mode` is derived from.
+pub(super) fn key_material(mode: Encryption) -> Result<Vec<u8>> {
+    match mode {
+        Encryption::Machine => machine_id()
+            .map(String::into_bytes)
+            .ok_or_else(|| {
+                Error::config(
+                    "Could not read this machine's ID to decrypt the config's secrets; use `logchef config encrypt --with passphrase` instead",
+                )
+            }),
+        Encryption::Passphrase => std::env::var("LOGCHEF_PASSPHRASE")
+            .ok()
+            .filter(|passphrase| !passphrase.is_empty())
+            .or_else(|| PROMPT.get().and_then(|prompt| prompt()))
+            .filter(|passphrase| !passphrase.is_empty())
+            .map(String::into_bytes)
+            .ok_or_else(|| {
+                Error::config(
+                    "The config's secrets are encrypted with a passphrase; set LOGCHEF_PASSPHRASE",
+                )
+            }),
+    }
+}
+
+impl SealingKey {
+    pub(super) fn derive(mode: Encryption, salt: [u8; 16], material: &[u8]) -> Result<Self> {
+        let mut key = [0u8; 32];
+        match mode {
+            // Machine IDs are already random; a passphrase needs stretching.
+            Encryption::Machine => {
+                key.copy_from_slice(
+                    &Sha256::new()
+                        .chain_update(b"logchef config secrets\0")
+                        .chain_update(salt)
+                        .chain_update(material)
+                        .finalize(),
+                );
+            }
+            Encryption::Passphrase => argon2::Argon2::default()
+                .hash_password_into(material, &salt, &mut key)
+                .map_err(|e| {
+                    Error::config(format!("Failed to derive the encryption key: {}", e))
+                })?,
+        }
+        Ok(Self { mode, salt, key })
+    }
+
+    fn fresh(mode: Encryption, material: &[u8]) -> Result<Self> {
+        let mut salt = [0u8; 16];
+        getrandom::getrandom(&mut salt)
+            .map_err(|e| Error::config(format!("Failed to generate a salt: {}", e)))?;
+        Self::derive(mode, salt, material)
+    }
+
+    fn seal(&self, secret: &str) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce)
+            .map_err(|e| Error::config(format!("Failed to generate a nonce: {}", e)))?;
+        let sealed = XChaCha20Poly1305::new(&self.key.into())
+            .encrypt(XNonce::from_slice(&nonce), secret.as_bytes())
+            .map_err(|_| Error::config("Failed to encrypt a secret"))?;
+        Ok(format!(
+            "{}{}:{}",
+            PREFIX,
+            URL_SAFE_NO_PAD.encode(self.salt),
+            URL_SAFE_NO_PAD.encode([nonce.as_slice(), &sealed].concat())
+        ))
+    }
+
+    /// Decrypts a sealed body (the part after the salt); `None` when the key
+    /// is wrong or the value was tampered with.
+    fn open(&self, body: &[u8]) -> Option<String> {
+        let (nonce, sealed) = body.split_at_checked(NONCE_LEN)?;
+        let secret = XChaCha20Poly1305::new(&self.key.into())
+            .decrypt(XNonce::from_slice(nonce), sealed)
+            .ok()?;
+        String::from_utf8(secret).ok()
+    }
+}
+
+/// Splits an `encrypted:` value into its salt and sealed body.
+fn parse(value: &str) -> Option<([u8; 16], Vec<u8>)> {
+    let (salt, body) = value.strip_prefix(PREFIX)?.split_once(':')?;
+    let salt = URL_SAFE_NO_PAD.decode(salt).ok()?.try_into().ok()?;
+    Some((salt, URL_SAFE_NO_PAD.decode(body).ok()?))
+}
+
+impl Config {
+    /// Decrypts `encrypted:` secrets, asking `material` for the key material
+    /// only if there are any. Unlike an unreadable keyring entry this fails
+    /// the load: a wrong passphrase shouldn't look like being signed out,
+    /// and a save after it would lose the secrets.
+    pub(super) fn open_secrets(
+        &mut self,
+        material: impl FnOnce(Encryption) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let mut material = Some(material);
+        let mut secret: Option<Vec<u8>> = None;
+        // Values from different saves have different salts.
+        let mut keys: Vec<SealingKey> = Vec::new();
+        for (name, ctx) in &mut self.contexts {
+            for (field, slot) in ctx.secrets_mut() {
+                let Some(value) = slot.as_deref().filter(|v| v.starts_with(PREFIX)) else {
+                    continue;
+                };
+                let undecryptable = || {
+                    Error::config(format!(
+                        "Could not decrypt {} of context '{}': wrong passphrase, another machine's config, or a damaged value",
+                        field, name
+                    ))
+                };
+                let (salt, body) = parse(value).ok_or_else(undecryptable)?;
+                let mode = self.encryption.ok_or_else(|| {
+                    Error::config(format!(
+                        "{} of context '{}' is encrypted but the config doesn't set `encryption`",
+                        field, name
+                    ))
+                })?;
+                if !keys.iter().any(|key| key.salt == salt) {
+                    if let Some(fetch) = material.take() {
+                        secret = Some(fetch(mode)?);
+                    }
+                    let secret = secret.as_deref().expect("fetched above");
+                    keys.push(SealingKey::derive(mode, salt, secret)?);
+                }
+                let key = keys
+                    .iter()
+                    .find(|key| key.salt == salt)
+                    .expect("derived above");
+                *slot = Some(key.open(&body).ok_or_else(undecryptable)?);
+            }
+        }
+        self.sealing_key = keys.pop();
+        Ok(())
+    }
+
+    /// Derives a new key from `passphrase` for the next save, e.g. when
+    /// turning passphrase encryption on or changing the passphrase.
+    pub fn use_passphrase(&mut self, passphrase: &str) -> Result<()> {
+        self.sealing_key = Some(SealingKey::fresh(
+            Encryption::Passphrase,
+            passphrase.as_bytes(),
+        )?);
+        Ok(())
+    }
+
+    /// Encrypts the secrets of `self` (the config as it's about to be
+    /// written) when `encryption` is on and the keyring isn't. Keyring
+    /// references are left alone.
+    pub(super) fn seal_secrets(
+        &mut self,
+        material: impl FnOnce(Encryption) -> Result<Vec<u8>>,
+    ) -> Result<()> {
+        let Some(mode) = self.encryption.filter(|_| !self.keyring) else {
+            return Ok(());
+        };
+        let key = match self.sealing_key.clone().filter(|key| key.mode == mode) {
+            Some(key) => key,
+            None => {
+                let has_secrets = self.contexts.values_mut().any(|ctx| {
+                    ctx.secrets_mut()
+                        .into_iter()
+                        .any(|(_, slot)| slot.is_some())
+                });
+                if !has_secrets {
+                    return Ok(());
+                }
+                SealingKey::fresh(mode, &material(mode)?)?
+            }
+        };
+        for ctx in self.contexts.values_mut() {
+            for (_, slot) in ctx.secrets_mut() {
+                if let Some(secret) = slot
+                    .as_deref()
+                    .filter(|v| !v.starts_with(super::keyring::REFERENCE_PREFIX))
+                {
+                    *slot = Some(key.seal(secret)?);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+}
+
+#[cfg(target_os = "macos")]
+fn machine_id() -> Option<String> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("IOPlatformUUID"))?
+        .rsplit('"')
+        .nth(1)
+        .map(str::to_string)
+}
+
+#[cfg(windows)]
+fn machine_id() -> Option<String> {
+    let output = std::process::Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("MachineGuid"))?
+        .split_whitespace()
+        .last()
+        .map(str::to_string)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn machine_id() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_round_trip_and_fail_loudly_with_the_wrong_passphrase() {
+        let mut config = Config::default();
+        let mut ctx = super::super::Context::new("https://logs.example.com".to_string());
+        ctx.token = Some("secret".to_string());
+        config.add_context("prod".to_string(), ctx).unwrap();
+        config.encryption = Some(Encryption::Passphrase);
+
+        let mut on_disk = config.clone();
+        on_disk
+            .seal_secrets(|_| Ok(b"correct horse".to_vec()))
+            .unwrap();
+        let sealed = on_disk.contexts["prod"].token.clone().unwrap();
+        assert!(sealed.starts_with("encrypted:"));
+        assert!(!sealed.contains("secret"));
+
+        let mut loaded = on_disk.clone();
+        loaded
+            .open_secrets(|_| Ok(b"correct horse".to_vec()))
+            .unwrap();
+        assert_eq!(loaded.contexts["prod"].token.as_deref(), Some("secret"));
+        // The key is kept, so saving again needs no passphrase.
+        loaded
+            .clone()
+            .seal_secrets(|_| panic!("asked for the passphrase again"))
+            .unwrap();
+
+        let err = on_disk
+            .clone()
+            .open_secrets(|_| Ok(b"battery staple".to_vec()))
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Could not decrypt token of context 'prod'")
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! `LOGCHEF_*` environment variables, which override context settings from
+//! the config file so a container or CI job can run without one. Highest
+//! precedence first: command-line flags, these variables, the config file,
+//! built-in defaults.
+//!
+//! Overrides apply to every context as it's loaded (and to the throwaway
+//! context a bare `--server` makes), and are taken back out before saving,
+//! so `config set` in a shell that exports them never writes them to disk.
+
+use serde_json::Value;
+
+use super::{Config, Context};
+use crate::error::{Error, Result};
+
+enum Kind {
+    Text,
+    Number,
+    Flag,
+}
+
+/// Each variable, the context setting it overrides (a JSON pointer into
+/// [`Context`]) and how its value is read.
+const VARS: &[(&str, &str, Kind)] = &[
+    ("LOGCHEF_TIMEOUT", "/timeout_secs", Kind::Number),
+    ("LOGCHEF_RETRIES", "/retries", Kind::Number),
+    ("LOGCHEF_PROXY_URL", "/proxy_url", Kind::Text),
+    (
+        "LOGCHEF_COMPRESS_REQUESTS",
+        "/compress_requests",
+        Kind::Flag,
+    ),
+    ("LOGCHEF_MAX_TIME_RANGE", "/max_time_range", Kind::Text),
+    (
+        "LOGCHEF_OIDC_DISCOVERY_URL",
+        "/oidc_discovery_url",
+        Kind::Text,
+    ),
+    ("LOGCHEF_TLS_CA_CERT", "/tls/ca_cert", Kind::Text),
+    (
+        "LOGCHEF_TLS_INSECURE_SKIP_VERIFY",
+        "/tls/insecure_skip_verify",
+        Kind::Flag,
+    ),
+    ("LOGCHEF_TLS_MIN_VERSION", "/tls/min_version", Kind::Text),
+    ("LOGCHEF_TLS_CLIENT_CERT", "/tls/client_cert", Kind::Text),
+    ("LOGCHEF_TLS_CLIENT_KEY", "/tls/client_key", Kind::Text),
+    ("LOGCHEF_DEFAULT_TEAM", "/defaults/team", Kind::Text),
+    ("LOGCHEF_DEFAULT_SOURCE", "/defaults/source", Kind::Text),
+    ("LOGCHEF_DEFAULT_LIMIT", "/defaults/limit", Kind::Number),
+    ("LOGCHEF_DEFAULT_SINCE", "/defaults/since", Kind::Text),
+    ("LOGCHEF_TIMEZONE", "/defaults/timezone", Kind::Text),
+    (
+        "LOGCHEF_TRACE_ID_COLUMN",
+        "/defaults/trace_id_column",
+        Kind::Text,
+    ),
+    ("LOGCHEF_TRACE_SINCE", "/defaults/trace_since", Kind::Text),
+    (
+        "LOGCHEF_ALL_MAX_ROWS",
+        "/defaults/all_max_rows",
+        Kind::Number,
+    ),
+    ("LOGCHEF_CONCURRENCY", "/defaults/concurrency", Kind::Number),
+];
+
+impl Context {
+    /// Applies the `LOGCHEF_*` variables that are set. Fails on a value that
+    /// doesn't fit its setting, naming the variable.
+    pub fn apply_env(&mut self) -> Result<()> {
+        self.apply_env_from(|name| std::env::var(name).ok())
+    }
+
+    pub(super) fn apply_env_from(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let mut value = serde_json::to_value(&*self)?;
+        let mut changed = false;
+        for (var, pointer, kind) in VARS {
+            let Some(raw) = lookup(var)
+                .map(|raw| raw.trim().to_string())
+                .filter(|raw| !raw.is_empty())
+            else {
+                continue;
+            };
+            let invalid = |why: &str| Error::config(format!("{}={:?}: {}", var, raw, why));
+            let parsed = match kind {
+                Kind::Text => Value::String(raw.clone()),
+                Kind::Number => raw
+                    .parse::<u64>()
+                    .map(Value::from)
+                    .map_err(|_| invalid("expected a whole number"))?,
+                Kind::Flag => match raw.to_ascii_lowercase().as_str() {
+                    "1" | "true" | "yes" | "on" => Value::Bool(true),
+                    "0" | "false" | "no" | "off" => Value::Bool(false),
+                    _ => return Err(invalid("expected true or false")),
+                },
+            };
+            set(&mut value, pointer, parsed);
+            serde_json::from_value::<Context>(value.clone())
+                .map_err(|e| invalid(&e.to_string()))?;
+            changed = true;
+        }
+        if changed {
+            *self = serde_json::from_value(value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Applies the `LOGCHEF_*` variables to every context, remembering what
+    /// the file said so [`without_env`](Self::without_env) can restore it.
+    pub(super) fn apply_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<()> {
+        for (name, ctx) in &mut self.contexts {
+            let file = serde_json::to_value(&*ctx)?;
+            ctx.apply_env_from(&lookup)?;
+            let applied = serde_json::to_value(&*ctx)?;
+            if applied != file {
+                self.env_overridden.insert(name.clone(), (file, applied));
+            }
+        }
+        Ok(())
+    }
+
+    /// Records that the setting at `path` (starting `["contexts", name]`)
+    /// was written on purpose, so saving keeps it even when it equals the
+    /// variable overriding it, as in `LOGCHEF_DEFAULT_TEAM=payments logchef
+    /// config set team payments`.
+    pub fn mark_set(&mut self, path: &[&str]) -> Result<()> {
+        let ["contexts", name, setting @ ..] = path else {
+            return Ok(());
+        };
+        let (Some((file, _)), Some(ctx)) =
+            (self.env_overridden.get_mut(*name), self.contexts.get(*name))
+        else {
+            return Ok(());
+        };
+        let pointer = super::path::pointer(setting);
+        match serde_json::to_value(ctx)?.pointer(&pointer) {
+            Some(written) => set(file, &pointer, written.clone()),
+            None => remove(file, &pointer),
+        }
+        Ok(())
+    }
+
+    /// The config as the file had it: settings that came from the
+    /// environment are put back, unless something set them since.
+    pub(super) fn without_env(&self) -> Result<Config> {
+        let mut config = self.clone();
+        for (name, (file, applied)) in &self.env_overridden {
+            let Some(ctx) = config.contexts.get_mut(name) else {
+                continue;
+            };
+            let mut current = serde_json::to_value(&*ctx)?;
+            for (_, pointer, _) in VARS {
+                if current.pointer(pointer) != applied.pointer(pointer) {
+                    continue;
+                }
+                match file.pointer(pointer) {
+                    Some(original) => set(&mut current, pointer, original.clone()),
+                    None => remove(&mut current, pointer),
+                }
+            }
+            *ctx = serde_json::from_value(current)?;
+        }
+        config.env_overridden.clear();
+        Ok(config)
+    }
+}
+
+fn set(value: &mut Value, pointer: &str, new: Value) {
+    let path: Vec<&str> = pointer.trim_start_matches('/').split('/').collect();
+    super::path::place(value, &path, new);
+}
+
+fn remove(value: &mut Value, pointer: &str) {
+    let (parent, key) = pointer.rsplit_once('/').expect("pointers start with /");
+    if let Some(Value::Object(map)) = value.pointer_mut(parent) {
+        map.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_overrides_apply_on_load_and_stay_out_of_the_file() {
+        let mut config: Config = serde_json::from_value(serde_json::json!({
+            "current_context": "prod",
+            "contexts": {"prod": {
+                "server_url": "https://logs.example.com",
+                "defaults": {"team": "platform", "limit": 50}
+            }}
+        }))
+        .unwrap();
+        let env = |name: &str| match name {
+            "LOGCHEF_DEFAULT_TEAM" => Some("payments".to_string()),
+            "LOGCHEF_TIMEOUT" => Some("5".to_string()),
+            "LOGCHEF_TLS_INSECURE_SKIP_VERIFY" => Some("true".to_string()),
+            _ => None,
+        };
+        config.apply_env(env).unwrap();
+        let prod = config.get_context_mut("prod").unwrap();
+        assert_eq!(prod.defaults.team.as_deref(), Some("payments"));
+        assert_eq!(prod.timeout_secs, 5);
+        assert!(prod.tls.insecure_skip_verify);
+        prod.defaults.limit = 500;
+        prod.timeout_secs = 10;
+
+        let saved = config.without_env().unwrap();
+        let prod = saved.get_context("prod").unwrap();
+        assert_eq!(prod.defaults.team.as_deref(), Some("platform"));
+        assert_eq!(prod.defaults.limit, 500);
+        // Changed after loading, so it's kept.
+        assert_eq!(prod.timeout_secs, 10);
+        assert!(!prod.tls.insecure_skip_verify);
+
+        // Set to what the variable already says, so only marking it keeps it.
+        config
+            .set_path(&["contexts", "prod", "defaults", "team"], "payments")
+            .unwrap();
+        let saved = config.without_env().unwrap();
+        let prod = saved.get_context("prod").unwrap();
+        assert_eq!(prod.defaults.team.as_deref(), Some("payments"));
+
+        let mut ctx = Context::new("https://logs.example.com".to_string());
+        let err = ctx
+            .apply_env_from(|name| (name == "LOGCHEF_TLS_MIN_VERSION").then(|| "1.1".to_string()))
+            .unwrap_err();
+        assert!(err.to_string().contains("LOGCHEF_TLS_MIN_VERSION=\"1.1\""));
+    }
+}
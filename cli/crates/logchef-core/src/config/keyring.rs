@@ -0,0 +1,244 @@
+//! Optional OS-keyring storage for context secrets (`"keyring": true` in the
+//! config). On save, `token`, `fallback_token` and `oidc_client_secret` go to
+//! the keyring under the `logchef` service and the file keeps only a
+//! `keyring:<context>/<field>` reference; on load the references are
+//! resolved, so the rest of the CLI only ever sees the real values.
+//!
+//! Turning the flag on migrates plaintext secrets on the next save; turning
+//! it off writes them back to the file and removes the keyring entries.
+
+use super::{Config, Context};
+use crate::error::{Error, Result};
+use std::collections::HashSet;
+use tracing::warn;
+
+const SERVICE: &str = "logchef";
+pub(super) const REFERENCE_PREFIX: &str = "keyring:";
+
+impl Context {
+    pub(super) fn secrets_mut(&mut self) -> [(&'static str, &mut Option<String>); 3] {
+        [
+            ("token", &mut self.token),
+            ("fallback_token", &mut self.fallback_token),
+            ("oidc_client_secret", &mut self.oidc_client_secret),
+        ]
+    }
+}
+
+impl Config {
+    /// Replaces `keyring:` references with the secrets they point at. A
+    /// secret that can't be read is dropped with a warning, which leaves
+    /// that context signed out rather than failing every command; its
+    /// reference is kept for the next save.
+    pub(super) fn resolve_keyring_refs(&mut self, mut fetch: impl FnMut(&str) -> Result<String>) {
+        for (name, ctx) in &mut self.contexts {
+            for (field, slot) in ctx.secrets_mut() {
+                let Some(account) = slot
+                    .as_deref()
+                    .and_then(|v| v.strip_prefix(REFERENCE_PREFIX))
+                else {
+                    continue;
+                };
+                let account = account.to_string();
+                match fetch(&account) {
+                    Ok(secret) => {
+                        *slot = Some(secret.clone());
+                        self.keyring_secrets.insert(account, secret);
+                    }
+                    Err(e) => {
+                        warn!(
+                            "could not read {} for context '{}' from the OS keyring: {}",
+                            field, name, e
+                        );
+                        *slot = None;
+                        self.keyring_unread
+                            .entry(name.clone())
+                            .or_default()
+                            .insert(field, account);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The config as it should be written to disk: with `keyring` on, every
+    /// secret is handed to `store` (unless the keyring already holds that
+    /// value) and swapped for its reference. Also returns the keyring
+    /// accounts loaded earlier that nothing refers to any more.
+    pub(super) fn with_keyring_refs(
+        &self,
+        mut store: impl FnMut(&str, &str) -> Result<()>,
+    ) -> Result<(Config, Vec<String>)> {
+        let mut on_disk = self.clone();
+        let mut referenced = HashSet::new();
+        for (name, ctx) in &mut on_disk.contexts {
+            let Some(unread) = self.keyring_unread.get(name) else {
+                continue;
+            };
+            for (field, slot) in ctx.secrets_mut() {
+                // Still unset since the failed read: keep pointing at it.
+                if slot.is_none()
+                    && let Some(account) = unread.get(field)
+                {
+                    *slot = Some(format!("{}{}", REFERENCE_PREFIX, account));
+                }
+            }
+        }
+        if self.keyring {
+            for (name, ctx) in &mut on_disk.contexts {
+                for (field, slot) in ctx.secrets_mut() {
+                    let Some(secret) = slot.as_deref().filter(|v| !v.starts_with(REFERENCE_PREFIX))
+                    else {
+                        continue;
+                    };
+                    let account = format!("{}/{}", name, field);
+                    if self.keyring_secrets.get(&account).map(String::as_str) != Some(secret) {
+                        store(&account, secret)?;
+                    }
+                    *slot = Some(format!("{}{}", REFERENCE_PREFIX, account));
+                    referenced.insert(account);
+                }
+            }
+        }
+        let stale = self
+            .keyring_secrets
+            .keys()
+            .filter(|account| !referenced.contains(*account))
+            .cloned()
+            .collect();
+        Ok((on_disk, stale))
+    }
+}
+
+fn entry(account: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, account)
+}
+
+/// Runs a blocking keyring call; on a multi-threaded runtime the worker is
+/// told it's about to block, since D-Bus round trips can be slow.
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+pub(super) fn get(account: &str) -> Result<String> {
+    blocking(|| entry(account).and_then(|e| e.get_password()))
+        .map_err(|e| Error::config(e.to_string()))
+}
+
+pub(super) fn set(account: &str, secret: &str) -> Result<()> {
+    blocking(|| entry(account).and_then(|e| e.set_password(secret))).map_err(|e| {
+        Error::config(format!(
+            "Could not store {} in the OS keyring: {}; set `keyring` to false to keep secrets in the config file",
+            account, e
+        ))
+    })
+}
+
+pub(super) fn delete(account: &str) {
+    match blocking(|| entry(account).and_then(|e| e.delete_credential())) {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => warn!("could not remove {} from the OS keyring: {}", account, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config_with_token(token: &str) -> Config {
+        let mut config = Config::default();
+        let mut ctx = Context::new("https://logs.example.com".to_string());
+        ctx.token = Some(token.to_string());
+        config.add_context("prod".to_string(), ctx).unwrap();
+        config
+    }
+
+    #[test]
+    fn keyring_swaps_secrets_for_references_and_back() {
+        let mut store = HashMap::new();
+
+        // Opting in moves the plaintext token into the keyring.
+        let mut config = config_with_token("secret");
+        config.keyring = true;
+        let (on_disk, stale) = config
+            .with_keyring_refs(|account, secret| {
+                store.insert(account.to_string(), secret.to_string());
+                Ok(())
+            })
+            .unwrap();
+        assert!(stale.is_empty());
+        assert_eq!(
+            on_disk.contexts["prod"].token.as_deref(),
+            Some("keyring:prod/token")
+        );
+        assert_eq!(store["prod/token"], "secret");
+
+        // Loading resolves the reference; saving again writes nothing new.
+        let mut loaded: Config =
+            serde_json::from_str(&serde_json::to_string(&on_disk).unwrap()).unwrap();
+        loaded.resolve_keyring_refs(|account| {
+            store
+                .get(account)
+                .cloned()
+                .ok_or_else(|| Error::config("missing"))
+        });
+        assert_eq!(loaded.contexts["prod"].token.as_deref(), Some("secret"));
+        loaded
+            .with_keyring_refs(|_, _| panic!("unchanged secret rewritten"))
+            .unwrap();
+
+        // Opting out writes plaintext and frees the keyring entry.
+        loaded.keyring = false;
+        let (on_disk, stale) = loaded.with_keyring_refs(|_, _| unreachable!()).unwrap();
+        assert_eq!(on_disk.contexts["prod"].token.as_deref(), Some("secret"));
+        assert_eq!(stale, ["prod/token"]);
+    }
+
+    #[test]
+    fn unreadable_keyring_secret_signs_the_context_out() {
+        let mut config = config_with_token("keyring:prod/token");
+        config.resolve_keyring_refs(|_| Err(Error::config("no secret service")));
+        assert!(!config.contexts["prod"].is_authenticated());
+    }
+
+    #[test]
+    fn unreadable_keyring_reference_survives_a_save() {
+        let mut config = config_with_token("keyring:prod/token");
+        config.keyring = true;
+        config.resolve_keyring_refs(|_| Err(Error::config("no secret service")));
+        config.current_context = Some("prod".to_string());
+
+        let (on_disk, stale) = config
+            .with_keyring_refs(|_, _| panic!("nothing to store"))
+            .unwrap();
+        assert_eq!(
+            on_disk.contexts["prod"].token.as_deref(),
+            Some("keyring:prod/token")
+        );
+        assert!(stale.is_empty());
+
+        // Signing in again replaces it.
+        config.get_context_mut("prod").unwrap().token = Some("fresh".to_string());
+        let mut stored = None;
+        let (on_disk, _) = config
+            .with_keyring_refs(|account, secret| {
+                stored = Some((account.to_string(), secret.to_string()));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            stored,
+            Some(("prod/token".to_string(), "fresh".to_string()))
+        );
+        assert_eq!(
+            on_disk.contexts["prod"].token.as_deref(),
+            Some("keyring:prod/token")
+        );
+    }
+}
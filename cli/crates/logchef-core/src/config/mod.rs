@@ -1,36 +1,89 @@
+#[cfg(feature = "native")]
+mod encryption;
+#[cfg(feature = "native")]
+mod env;
+#[cfg(feature = "native")]
+mod keyring;
+mod path;
 mod schema;
+#[cfg(feature = "native")]
+mod toml_file;
 
 pub use schema::*;
 
+#[cfg(feature = "native")]
+pub use encryption::set_passphrase_prompt;
+
 use crate::error::{Error, Result};
+#[cfg(feature = "native")]
 use directories::ProjectDirs;
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
+
+/// The formats the config file can be written in. Both hold the same
+/// settings; TOML can also carry comments, which saving keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "logchef.json",
+            ConfigFormat::Toml => "logchef.toml",
+        }
+    }
 
-const CONFIG_FILE: &str = "logchef.json";
-const APP_QUALIFIER: &str = "app";
-const APP_ORG: &str = "logchef";
-const APP_NAME: &str = "logchef";
+    /// The format of `path`, by extension; anything but `.toml` is JSON.
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
+        }
+    }
+}
 
+/// Reading and writing the config file, which needs the OS's config
+/// directory and keyring.
+#[cfg(feature = "native")]
 impl Config {
-    pub fn config_dir() -> Result<PathBuf> {
-        ProjectDirs::from(APP_QUALIFIER, APP_ORG, APP_NAME)
+    const APP_QUALIFIER: &str = "app";
+    const APP_ORG: &str = "logchef";
+    const APP_NAME: &str = "logchef";
+
+    pub fn config_dir() -> Result<std::path::PathBuf> {
+        ProjectDirs::from(Self::APP_QUALIFIER, Self::APP_ORG, Self::APP_NAME)
             .map(|dirs| dirs.config_dir().to_path_buf())
             .ok_or_else(|| Error::config("Could not determine config directory"))
     }
 
-    pub fn config_path() -> Result<PathBuf> {
-        Ok(Self::config_dir()?.join(CONFIG_FILE))
+    /// `logchef.toml` if there is one, else `logchef.json`.
+    pub fn config_path() -> Result<std::path::PathBuf> {
+        let toml = Self::config_path_for(ConfigFormat::Toml)?;
+        if toml.exists() {
+            return Ok(toml);
+        }
+        Self::config_path_for(ConfigFormat::Json)
+    }
+
+    pub fn config_path_for(format: ConfigFormat) -> Result<std::path::PathBuf> {
+        Ok(Self::config_dir()?.join(format.file_name()))
     }
 
     pub fn load() -> Result<Self> {
-        let path = Self::config_path()?;
+        Self::load_from(&Self::config_path()?)
+    }
 
+    /// Reads the config at `path`, in the format its extension names. A
+    /// missing file is an empty config.
+    pub fn load_from(path: &Path) -> Result<Self> {
         if !path.exists() {
             return Ok(Self::default());
         }
 
-        let content = fs::read_to_string(&path).map_err(|e| {
+        let content = fs::read_to_string(path).map_err(|e| {
             Error::config(format!(
                 "Failed to read config file {}: {}",
                 path.display(),
@@ -38,7 +91,7 @@ impl Config {
             ))
         })?;
 
-        let config: Config = serde_json::from_str(&content).map_err(|e| {
+        let mut config = Self::parse(&content, ConfigFormat::of(path)).map_err(|e| {
             Error::config(format!(
                 "Failed to parse config file {}: {}",
                 path.display(),
@@ -52,13 +105,85 @@ impl Config {
                 config.version, CONFIG_VERSION
             )));
         }
+        config.resolve_keyring_refs(keyring::get);
+        config.open_secrets(encryption::key_material)?;
+        config.apply_env(|name| std::env::var(name).ok())?;
 
         Ok(config)
     }
 
+    fn parse(content: &str, format: ConfigFormat) -> std::result::Result<Self, String> {
+        match format {
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml_file::parse(content),
+        }
+    }
+
+    /// Checks config file contents before they replace the real file: that
+    /// they parse, and that settings which would otherwise only fail once
+    /// used (highlight regexes, time ranges, timezones, server URLs) are
+    /// valid. Returns every problem found.
+    pub fn validate(content: &str, format: ConfigFormat) -> std::result::Result<(), Vec<String>> {
+        let config = Self::parse(content, format).map_err(|e| vec![e])?;
+        let mut problems = Vec::new();
+        if config.version > CONFIG_VERSION {
+            problems.push(format!(
+                "version {} is newer than this CLI supports ({})",
+                config.version, CONFIG_VERSION
+            ));
+        }
+        if let Some(current) = &config.current_context
+            && !config.contexts.contains_key(current)
+        {
+            problems.push(format!(
+                "current_context '{}' is not one of the contexts",
+                current
+            ));
+        }
+        let mut names: Vec<&String> = config.contexts.keys().collect();
+        names.sort();
+        for name in names {
+            let ctx = &config.contexts[name];
+            if !matches!(
+                url::Url::parse(&ctx.server_url).map(|url| url.scheme().to_string()),
+                Ok(scheme) if scheme == "http" || scheme == "https"
+            ) {
+                problems.push(format!(
+                    "contexts.{}.server_url: {:?} is not an http(s) URL",
+                    name, ctx.server_url
+                ));
+            }
+            if let Err(e) = crate::timerange::since_window(&ctx.defaults.since, None) {
+                problems.push(format!("contexts.{}.defaults.since: {}", name, e));
+            }
+            if let Some(tz) = &ctx.defaults.timezone
+                && tz.parse::<chrono_tz::Tz>().is_err()
+            {
+                problems.push(format!(
+                    "contexts.{}.defaults.timezone: unknown timezone {:?}",
+                    name, tz
+                ));
+            }
+        }
+        for (i, rule) in config.highlights.custom_regexes.iter().enumerate() {
+            if let Err(e) = regex::Regex::new(&rule.pattern) {
+                problems.push(format!("highlights.custom_regexes[{}]: {}", i, e));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
     pub fn save(&self) -> Result<()> {
-        let path = Self::config_path()?;
+        self.save_to(&Self::config_path()?)
+    }
 
+    /// Writes the config to `path`, in the format its extension names. An
+    /// existing TOML file is updated in place, keeping its comments.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).map_err(|e| {
                 Error::config(format!(
@@ -69,8 +194,22 @@ impl Config {
             })?;
         }
 
-        let content = serde_json::to_string_pretty(self)?;
-        let tmp_path = path.with_extension("json.tmp");
+        // Secrets reach the keyring before the file refers to them, and old
+        // entries are only removed once the file no longer does.
+        let (mut on_disk, stale) = self.without_env()?.with_keyring_refs(keyring::set)?;
+        on_disk.seal_secrets(encryption::key_material)?;
+        let format = ConfigFormat::of(path);
+        let content = match format {
+            ConfigFormat::Json => serde_json::to_string_pretty(&on_disk)?,
+            ConfigFormat::Toml => {
+                let existing = fs::read_to_string(path).ok();
+                toml_file::render(&on_disk, existing.as_deref())?
+            }
+        };
+        let tmp_path = path.with_extension(match format {
+            ConfigFormat::Json => "json.tmp",
+            ConfigFormat::Toml => "toml.tmp",
+        });
 
         #[cfg(unix)]
         {
@@ -91,7 +230,7 @@ impl Config {
                     e
                 ))
             })?;
-            fs::rename(&tmp_path, &path).map_err(|e| {
+            fs::rename(&tmp_path, path).map_err(|e| {
                 Error::config(format!(
                     "Failed to replace config file {}: {}",
                     path.display(),
@@ -109,8 +248,8 @@ impl Config {
                     e
                 ))
             })?;
-            let _ = fs::remove_file(&path);
-            fs::rename(&tmp_path, &path).map_err(|e| {
+            let _ = fs::remove_file(path);
+            fs::rename(&tmp_path, path).map_err(|e| {
                 Error::config(format!(
                     "Failed to replace config file {}: {}",
                     path.display(),
@@ -119,9 +258,14 @@ impl Config {
             })?;
         }
 
+        for account in &stale {
+            keyring::delete(account);
+        }
         Ok(())
     }
+}
 
+impl Config {
     pub fn current_context_name(&self) -> Option<&str> {
         self.current_context.as_deref()
     }
@@ -199,6 +343,12 @@ impl Config {
         }
         if let Some(context) = self.contexts.remove(old_name) {
             self.contexts.insert(new_name.to_string(), context);
+            if let Some(overridden) = self.env_overridden.remove(old_name) {
+                self.env_overridden.insert(new_name.to_string(), overridden);
+            }
+            if let Some(unread) = self.keyring_unread.remove(old_name) {
+                self.keyring_unread.insert(new_name.to_string(), unread);
+            }
             if self.current_context.as_deref() == Some(old_name) {
                 self.current_context = Some(new_name.to_string());
             }
@@ -206,6 +356,32 @@ impl Config {
         Ok(())
     }
 
+    /// Copies `source` to a new context named `dest`, optionally without its
+    /// credentials (tokens and OIDC client secret). The current context is
+    /// left unchanged.
+    pub fn clone_context(&mut self, source: &str, dest: &str, keep_token: bool) -> Result<()> {
+        let mut context = self
+            .contexts
+            .get(source)
+            .cloned()
+            .ok_or_else(|| Error::config(format!("Context '{}' not found", source)))?;
+        if self.contexts.contains_key(dest) {
+            return Err(Error::config(format!("Context '{}' already exists", dest)));
+        }
+        if !keep_token {
+            context.token = None;
+            context.token_expires_at = None;
+            context.token_type = None;
+            context.fallback_token = None;
+            context.oidc_client_secret = None;
+        }
+        self.contexts.insert(dest.to_string(), context);
+        if let Some(overridden) = self.env_overridden.get(source).cloned() {
+            self.env_overridden.insert(dest.to_string(), overridden);
+        }
+        Ok(())
+    }
+
     pub fn context_names(&self) -> Vec<&str> {
         self.contexts.keys().map(|s| s.as_str()).collect()
     }
@@ -214,3 +390,139 @@ impl Config {
         self.contexts.is_empty()
     }
 }
+
+impl HighlightsConfig {
+    /// Reads a standalone highlights file (the same shape as the config's
+    /// `highlights` section, as JSON or TOML), e.g. a per-incident rule set. Unlike the
+    /// global config, bad regexes are rejected up front since the file was
+    /// asked for explicitly.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            Error::config(format!(
+                "Failed to read highlight config {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let parsed = match ConfigFormat::of(path) {
+            ConfigFormat::Json => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml_edit::de::from_str(&content).map_err(|e| e.to_string()),
+        };
+        let highlights: HighlightsConfig = parsed.map_err(|e| {
+            Error::config(format!(
+                "Failed to parse highlight config {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        for rule in &highlights.custom_regexes {
+            regex::Regex::new(&rule.pattern).map_err(|e| {
+                Error::config(format!(
+                    "Invalid regex {:?} in {}: {}",
+                    rule.pattern,
+                    path.display(),
+                    e
+                ))
+            })?;
+        }
+        Ok(highlights)
+    }
+
+    /// Adds `other`'s rules on top of these; nothing already configured is
+    /// dropped.
+    pub fn merge(&mut self, other: HighlightsConfig) {
+        for keyword in other.custom_keywords {
+            if !self.custom_keywords.contains(&keyword) {
+                self.custom_keywords.push(keyword);
+            }
+        }
+        self.custom_regexes.extend(other.custom_regexes);
+        for group in other.disabled_groups {
+            if !self.disabled_groups.contains(&group) {
+                self.disabled_groups.push(group);
+            }
+        }
+        self.disable_builtin |= other.disable_builtin;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlights_merge_is_additive() {
+        let mut base = HighlightsConfig {
+            custom_keywords: vec!["timeout".to_string()],
+            disabled_groups: vec!["numbers".to_string()],
+            ..Default::default()
+        };
+        let incident: HighlightsConfig = serde_json::from_str(
+            r#"{"custom_keywords": ["timeout", "req-42"], "custom_regexes": [{"pattern": "user_id=\\d+"}]}"#,
+        )
+        .unwrap();
+        base.merge(incident);
+        assert_eq!(base.custom_keywords, ["timeout", "req-42"]);
+        assert_eq!(base.custom_regexes.len(), 1);
+        assert_eq!(base.custom_regexes[0].color, "magenta");
+        assert_eq!(base.disabled_groups, ["numbers"]);
+        assert!(!base.disable_builtin);
+    }
+
+    #[test]
+    fn clone_context_copies_settings_and_optionally_token() {
+        let mut config = Config::default();
+        let mut ctx = Context::new("https://logs.example.com".to_string());
+        ctx.token = Some("secret".to_string());
+        ctx.token_type = Some(TokenType::ApiToken);
+        ctx.fallback_token = Some("service".to_string());
+        ctx.oidc_client_secret = Some("client".to_string());
+        ctx.defaults.team = Some("platform".to_string());
+        config.add_context("prod".to_string(), ctx).unwrap();
+
+        config.clone_context("prod", "prod-ro", false).unwrap();
+        let clone = config.get_context("prod-ro").unwrap();
+        assert_eq!(clone.server_url, "https://logs.example.com");
+        assert_eq!(clone.defaults.team.as_deref(), Some("platform"));
+        assert!(clone.token.is_none());
+        assert!(clone.token_type.is_none());
+        assert!(clone.fallback_token.is_none());
+        assert!(clone.oidc_client_secret.is_none());
+        assert_eq!(config.current_context_name(), Some("prod"));
+
+        config.clone_context("prod", "prod-2", true).unwrap();
+        assert_eq!(
+            config.get_context("prod-2").unwrap().token.as_deref(),
+            Some("secret")
+        );
+        assert!(config.clone_context("prod", "prod-2", true).is_err());
+        assert!(config.clone_context("missing", "x", true).is_err());
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn validate_lists_every_problem() {
+        let content = r#"{
+            "current_context": "prod",
+            "contexts": {"staging": {
+                "server_url": "logs.example.com",
+                "defaults": {"since": "lately", "timezone": "Mars/Olympus"}
+            }},
+            "highlights": {"custom_regexes": [{"pattern": "trace=("}]}
+        }"#;
+        let problems = Config::validate(content, ConfigFormat::Json).unwrap_err();
+        assert_eq!(problems.len(), 5, "{:?}", problems);
+        assert!(problems[0].starts_with("current_context 'prod'"));
+        assert!(problems[4].starts_with("highlights.custom_regexes[0]"));
+
+        let broken = Config::validate("{\"contexts\": [}", ConfigFormat::Json).unwrap_err();
+        assert!(broken[0].contains("line 1"));
+        assert!(
+            Config::validate(
+                "current_context = \"x\"\n[contexts.x]\nserver_url = \"https://a\"\n",
+                ConfigFormat::Toml
+            )
+            .is_ok()
+        );
+    }
+}
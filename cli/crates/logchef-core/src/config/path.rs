@@ -0,0 +1,243 @@
+//! Settings addressed by path, e.g. `["contexts", "prod", "defaults",
+//! "team"]`, for `logchef config set`, `unset`, `add` and `remove`. A change
+//! is made to the config's JSON form and read back, so serde checks the
+//! value, and a path that names no setting shows up as the change not
+//! surviving the round trip.
+
+use serde_json::Value;
+
+use super::Config;
+use crate::error::{Error, Result};
+
+impl Config {
+    /// The value at `path`, or `None` when it's unset.
+    pub fn get_path(&self, path: &[&str]) -> Result<Option<Value>> {
+        Ok(serde_json::to_value(self)?
+            .pointer(&pointer(path))
+            .filter(|value| !value.is_null())
+            .cloned())
+    }
+
+    /// Sets the setting at `path` to `raw`, read as JSON (a number, `true`,
+    /// a list) when that fits the setting and as text otherwise, so a team
+    /// called `42` stays a string.
+    pub fn set_path(&mut self, path: &[&str], raw: &str) -> Result<()> {
+        let mut first_error = None;
+        for value in candidates(raw) {
+            let mut json = serde_json::to_value(&*self)?;
+            place(&mut json, path, value.clone());
+            match self.read_back(path, json, |found| found == Some(&value)) {
+                Ok(()) => {
+                    #[cfg(feature = "native")]
+                    self.mark_set(path)?;
+                    return Ok(());
+                }
+                Err(e) => first_error.get_or_insert(e),
+            };
+        }
+        Err(first_error.expect("there is always a candidate"))
+    }
+
+    /// Removes the setting at `path`, so its default applies again. Returns
+    /// whether it was set.
+    pub fn unset_path(&mut self, path: &[&str]) -> Result<bool> {
+        let Some((key, parent)) = path.split_last() else {
+            return Ok(false);
+        };
+        let mut json = serde_json::to_value(&*self)?;
+        let removed = json
+            .pointer_mut(&pointer(parent))
+            .and_then(Value::as_object_mut)
+            .and_then(|map| map.remove(*key));
+        if removed.is_none_or(|value| value.is_null()) {
+            return Ok(false);
+        }
+        self.read_back(path, json, |_| true)?;
+        Ok(true)
+    }
+
+    /// Appends `raw` (read as in [`set_path`](Self::set_path)) to the list
+    /// at `path`. Returns false when it's already there.
+    pub fn add_to_path(&mut self, path: &[&str], raw: &str) -> Result<bool> {
+        let mut first_error = None;
+        for item in candidates(raw) {
+            let mut json = serde_json::to_value(&*self)?;
+            let list = list_at(&mut json, path)?;
+            if list.iter().any(|existing| matches(existing, &item)) {
+                return Ok(false);
+            }
+            list.push(item.clone());
+            let added = |found: Option<&Value>| {
+                found
+                    .and_then(Value::as_array)
+                    .is_some_and(|list| list.iter().any(|existing| matches(existing, &item)))
+            };
+            match self.read_back(path, json, added) {
+                Ok(()) => return Ok(true),
+                Err(e) => first_error.get_or_insert(e),
+            };
+        }
+        Err(first_error.expect("there is always a candidate"))
+    }
+
+    /// Removes the items of the list at `path` that match `raw`. For a list
+    /// of objects `raw` may give only some fields, e.g.
+    /// `{"pattern": "ERR-\\d+"}`. Returns whether anything was removed.
+    pub fn remove_from_path(&mut self, path: &[&str], raw: &str) -> Result<bool> {
+        let targets = candidates(raw);
+        let mut json = serde_json::to_value(&*self)?;
+        if json.pointer(&pointer(path)).is_none_or(Value::is_null) {
+            return Ok(false);
+        }
+        let list = list_at(&mut json, path)?;
+        let before = list.len();
+        list.retain(|item| !targets.iter().any(|target| matches(item, target)));
+        if list.len() == before {
+            return Ok(false);
+        }
+        self.read_back(path, json, |_| true)?;
+        Ok(true)
+    }
+
+    /// Replaces `self` with `json` read back as a config, once `check` has
+    /// accepted what ended up at `path`.
+    fn read_back(
+        &mut self,
+        path: &[&str],
+        json: Value,
+        check: impl Fn(Option<&Value>) -> bool,
+    ) -> Result<()> {
+        let name = path.join(".");
+        let mut updated: Config =
+            serde_json::from_value(json).map_err(|e| Error::config(format!("{}: {}", name, e)))?;
+        if !check(serde_json::to_value(&updated)?.pointer(&pointer(path))) {
+            return Err(Error::config(format!(
+                "Unknown setting: '{}' (`logchef config schema` lists them)",
+                name
+            )));
+        }
+        // Kept next to the file's settings rather than in them.
+        updated.sealing_key = self.sealing_key.take();
+        updated.keyring_secrets = std::mem::take(&mut self.keyring_secrets);
+        updated.keyring_unread = std::mem::take(&mut self.keyring_unread);
+        updated.env_overridden = std::mem::take(&mut self.env_overridden);
+        *self = updated;
+        Ok(())
+    }
+}
+
+/// Puts `new` at `path` inside `value`, creating objects on the way.
+pub(super) fn place(value: &mut Value, path: &[&str], new: Value) {
+    let mut slot = value;
+    for segment in path {
+        if !slot.is_object() {
+            *slot = Value::Object(Default::default());
+        }
+        slot = slot
+            .as_object_mut()
+            .expect("made an object above")
+            .entry(*segment)
+            .or_insert(Value::Null);
+    }
+    *slot = new;
+}
+
+pub(super) fn pointer(path: &[&str]) -> String {
+    path.iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// The ways `raw` can be read: as JSON first, then as text.
+fn candidates(raw: &str) -> Vec<Value> {
+    let text = Value::String(raw.to_string());
+    match serde_json::from_str::<Value>(raw) {
+        Ok(parsed) if parsed != text => vec![parsed, text],
+        _ => vec![text],
+    }
+}
+
+/// The list at `path`, made empty if it's unset.
+fn list_at<'a>(json: &'a mut Value, path: &[&str]) -> Result<&'a mut Vec<Value>> {
+    if json.pointer(&pointer(path)).is_none_or(Value::is_null) {
+        place(json, path, Value::Array(Vec::new()));
+    }
+    json.pointer_mut(&pointer(path))
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| Error::config(format!("'{}' is not a list", path.join("."))))
+}
+
+/// Whether `item` is `target`, or has all of its fields when both are
+/// objects.
+fn matches(item: &Value, target: &Value) -> bool {
+    match (item, target) {
+        (Value::Object(item), Value::Object(target)) => target
+            .iter()
+            .all(|(key, value)| item.get(key) == Some(value)),
+        _ => item == target,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_change_by_path_and_unknown_ones_are_refused() {
+        let mut config: Config = serde_json::from_value(serde_json::json!({
+            "current_context": "prod",
+            "contexts": {"prod": {
+                "server_url": "https://logs.example.com",
+                "defaults": {"limit": 50}
+            }}
+        }))
+        .unwrap();
+
+        config
+            .set_path(&["contexts", "prod", "defaults", "team"], "42")
+            .unwrap();
+        config
+            .set_path(&["contexts", "prod", "retries"], "4")
+            .unwrap();
+        let prod = config.get_context("prod").unwrap();
+        assert_eq!(prod.defaults.team.as_deref(), Some("42"));
+        assert_eq!(prod.retries, Some(4));
+
+        let err = config
+            .set_path(&["contexts", "prod", "defaults", "limit"], "lots")
+            .unwrap_err();
+        assert!(err.to_string().contains("contexts.prod.defaults.limit"));
+        let err = config
+            .set_path(&["contexts", "prod", "defaults", "teem"], "x")
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown setting"));
+
+        let limit = ["contexts", "prod", "defaults", "limit"];
+        assert!(config.unset_path(&limit).unwrap());
+        assert_eq!(config.get_path(&limit).unwrap(), Some(100.into()));
+        assert!(
+            !config
+                .unset_path(&["contexts", "prod", "proxy_url"])
+                .unwrap()
+        );
+
+        let keywords = ["highlights", "custom_keywords"];
+        assert!(config.add_to_path(&keywords, "PAYMENT").unwrap());
+        assert!(!config.add_to_path(&keywords, "PAYMENT").unwrap());
+        assert!(config.add_to_path(&keywords, "REFUND").unwrap());
+        assert!(config.remove_from_path(&keywords, "PAYMENT").unwrap());
+        assert_eq!(config.highlights.custom_keywords, ["REFUND"]);
+
+        let regexes = ["highlights", "custom_regexes"];
+        config
+            .add_to_path(&regexes, r#"{"pattern": "ERR-\\d+", "color": "red"}"#)
+            .unwrap();
+        assert_eq!(config.highlights.custom_regexes[0].color, "red");
+        assert!(
+            config
+                .remove_from_path(&regexes, r#"{"pattern": "ERR-\\d+"}"#)
+                .unwrap()
+        );
+        assert!(config.highlights.custom_regexes.is_empty());
+    }
+}
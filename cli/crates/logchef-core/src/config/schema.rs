@@ -1,10 +1,11 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub const CONFIG_VERSION: u32 = 1;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     #[serde(default = "default_version")]
     pub version: u32,
@@ -27,6 +28,48 @@ pub struct Config {
     /// only). Defaults to true; absent in old config files, which load fine.
     #[serde(default = "default_true")]
     pub check_updates: bool,
+
+    /// Page long text/table output through `$PAGER` when stdout is a TTY.
+    /// Defaults to true; absent in old config files, which load fine.
+    #[serde(default = "default_true")]
+    pub pager: bool,
+
+    /// Keep context tokens and client secrets in the OS keyring, with only a
+    /// `keyring:` reference in this file. Off by default.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keyring: bool,
+
+    /// Encrypt context tokens and client secrets inside this file, for
+    /// machines without an OS keyring. Ignored while `keyring` is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<Encryption>,
+
+    /// The key the file's secrets were decrypted with, reused to encrypt
+    /// them on save.
+    #[serde(skip)]
+    #[schemars(skip)]
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    pub(super) sealing_key: Option<SealingKey>,
+
+    /// Secrets read from (or known to be in) the keyring, by account, so a
+    /// save only rewrites the ones that changed.
+    #[serde(skip)]
+    #[schemars(skip)]
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    pub(super) keyring_secrets: HashMap<String, String>,
+
+    /// `keyring:` references that couldn't be read on load, by context and
+    /// field, so a save writes them back instead of losing the secret.
+    #[serde(skip)]
+    #[schemars(skip)]
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    pub(super) keyring_unread: HashMap<String, HashMap<&'static str, String>>,
+
+    /// Contexts changed by `LOGCHEF_*` variables on load, by name: how they
+    /// read in the file, and after the overrides.
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub(super) env_overridden: HashMap<String, (serde_json::Value, serde_json::Value)>,
 }
 
 fn default_version() -> u32 {
@@ -37,6 +80,13 @@ fn default_true() -> bool {
     true
 }
 
+impl Config {
+    /// JSON Schema for `logchef.json`, for editor validation and completion.
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(Config)).unwrap_or_default()
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -46,11 +96,18 @@ impl Default for Config {
             highlights: HighlightsConfig::default(),
             show_banner: true,
             check_updates: true,
+            pager: true,
+            keyring: false,
+            encryption: None,
+            sealing_key: None,
+            keyring_secrets: HashMap::new(),
+            keyring_unread: HashMap::new(),
+            env_overridden: HashMap::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Context {
     pub server_url: String,
 
@@ -63,10 +120,209 @@ pub struct Context {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub token_expires_at: Option<DateTime<Utc>>,
 
+    /// How `token` was obtained. Absent in configs written before API tokens
+    /// were supported, which only ever held OIDC tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<TokenType>,
+
+    /// Retried once when `token` is rejected with a 401, e.g. a long-lived
+    /// service token behind a short-lived OIDC one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_token: Option<String>,
+
     #[serde(default)]
     pub defaults: ContextDefaults,
+
+    /// OIDC discovery document URL, used verbatim instead of
+    /// `{issuer}/.well-known/openid-configuration` when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oidc_discovery_url: Option<String>,
+
+    /// Client secret sent in the OIDC token exchange, for IdPs that register
+    /// the CLI as a confidential client. PKCE is still used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oidc_client_secret: Option<String>,
+
+    /// Gzip request bodies over 64 KiB (e.g. generated SQL with huge IN
+    /// lists). Only enable against servers that accept `Content-Encoding: gzip`;
+    /// a 415 response falls back to plain bodies.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub compress_requests: bool,
+
+    /// Widest query window allowed without `--force` (e.g. `7d`). A guardrail
+    /// for shared environments; unset means no cap.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_time_range: Option<String>,
+
+    /// Per-source overrides of `max_time_range`, keyed by source name or ID.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub source_max_time_range: HashMap<String, String>,
+
+    #[serde(default, skip_serializing_if = "TlsConfig::is_default")]
+    pub tls: TlsConfig,
+
+    /// Proxy for every request of this context, e.g. `http://proxy:3128` or
+    /// `socks5h://127.0.0.1:1080`. `NO_PROXY` is still honored. When unset,
+    /// `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY` from the environment apply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+
+    /// Extra attempts for reads (GETs and queries) that fail with 429, 502,
+    /// 503 or a dropped connection, with jittered exponential backoff. `0`
+    /// turns retrying off; unset means [`DEFAULT_RETRIES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+
+    /// Personal LogchefQL snippets by name, for `query --saved`. Managed with
+    /// `logchef saved`; unlike collections they never leave this machine.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub saved: HashMap<String, SavedQuery>,
+}
+
+/// A query stored with `logchef saved add`. Team, source and lookback apply
+/// when the command line doesn't give its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SavedQuery {
+    pub query: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+}
+
+/// TLS settings for servers behind an internal CA or a TLS-terminating proxy.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TlsConfig {
+    /// PEM file of CA certificates trusted in addition to the system roots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+
+    /// Accept any server certificate. For throwaway test setups only.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure_skip_verify: bool,
+
+    /// Oldest TLS version to negotiate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<TlsVersion>,
+
+    /// PEM client certificate (chain) presented to mTLS-enforcing proxies.
+    /// Needs `client_key`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<String>,
+
+    /// Unencrypted PEM private key for `client_cert`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<String>,
+}
+
+impl TlsConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum TlsVersion {
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl std::str::FromStr for TlsVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().trim_start_matches(['v', 'V']) {
+            "1.2" => Ok(Self::Tls12),
+            "1.3" => Ok(Self::Tls13),
+            other => Err(format!(
+                "Unsupported TLS version '{}'. Use 1.2 or 1.3.",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Tls12 => "1.2",
+            Self::Tls13 => "1.3",
+        })
+    }
+}
+
+/// What [`Config::encryption`] derives its key from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Encryption {
+    /// This machine's ID: the file is useless elsewhere, e.g. in a dotfiles
+    /// repository or a backup, but anyone on this machine can decrypt it.
+    Machine,
+    /// A passphrase, from `LOGCHEF_PASSPHRASE` or asked for when needed.
+    Passphrase,
 }
 
+impl Encryption {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Machine => "machine key",
+            Self::Passphrase => "passphrase",
+        }
+    }
+}
+
+/// A derived encryption key and the salt it was derived with.
+#[derive(Clone)]
+pub(super) struct SealingKey {
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    pub(super) mode: Encryption,
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    pub(super) salt: [u8; 16],
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    pub(super) key: [u8; 32],
+}
+
+impl std::fmt::Debug for SealingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SealingKey")
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Kind of credential stored in [`Context::token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenType {
+    /// Short-lived token from the `logchef auth` OIDC login.
+    Oidc,
+    /// Long-lived personal access token, e.g. for CI jobs and cron scripts.
+    ApiToken,
+}
+
+impl TokenType {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Oidc => "OIDC",
+            Self::ApiToken => "API token",
+        }
+    }
+}
+
+/// Retries for a context that doesn't set `retries`.
+pub const DEFAULT_RETRIES: u32 = 2;
+
+/// In-flight request limit for a context that doesn't set
+/// `defaults.concurrency`.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
 fn default_timeout() -> u64 {
     30
 }
@@ -78,16 +334,48 @@ impl Context {
             timeout_secs: default_timeout(),
             token: None,
             token_expires_at: None,
+            token_type: None,
+            fallback_token: None,
             defaults: ContextDefaults::default(),
+            oidc_discovery_url: None,
+            oidc_client_secret: None,
+            compress_requests: false,
+            max_time_range: None,
+            source_max_time_range: HashMap::new(),
+            tls: TlsConfig::default(),
+            proxy_url: None,
+            retries: None,
+            saved: HashMap::new(),
         }
     }
 
     pub fn is_authenticated(&self) -> bool {
         self.token.is_some()
     }
+
+    pub fn retries(&self) -> u32 {
+        self.retries.unwrap_or(DEFAULT_RETRIES)
+    }
+
+    /// Returns the query window cap for a source: a per-source override
+    /// matched by ID, then (case-insensitively) by name, else the
+    /// context-wide `max_time_range`.
+    pub fn max_time_range_for(&self, source_id: i64, source_name: Option<&str>) -> Option<&str> {
+        self.source_max_time_range
+            .get(&source_id.to_string())
+            .or_else(|| {
+                let name = source_name?;
+                self.source_max_time_range
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                    .map(|(_, cap)| cap)
+            })
+            .map(String::as_str)
+            .or(self.max_time_range.as_deref())
+    }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ContextDefaults {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub team: Option<String>,
@@ -103,9 +391,46 @@ pub struct ContextDefaults {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
+
+    /// Column `query --trace-id` filters on. Defaults to `trace_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id_column: Option<String>,
+
+    /// Lookback used by `query --trace-id` when no time flag is given, since
+    /// a trace can be older than the usual `since`. Defaults to `7d`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_since: Option<String>,
+
+    /// Most rows `query --all` fetches before stopping. Defaults to
+    /// 1,000,000.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all_max_rows: Option<u64>,
+
+    /// Most API requests in flight at once from one command, e.g. the
+    /// queries of `collections --all` or a multi-source query. Defaults to
+    /// [`DEFAULT_CONCURRENCY`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<u32>,
 }
 
 impl ContextDefaults {
+    pub fn trace_id_column(&self) -> &str {
+        self.trace_id_column.as_deref().unwrap_or("trace_id")
+    }
+
+    pub fn trace_since(&self) -> &str {
+        self.trace_since.as_deref().unwrap_or("7d")
+    }
+
+    pub fn all_max_rows(&self) -> u64 {
+        self.all_max_rows.unwrap_or(1_000_000)
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+            .map_or(DEFAULT_CONCURRENCY, |n| n.max(1) as usize)
+    }
+
     pub fn team_with_env(&self) -> Option<String> {
         env_default("LOGCHEF_DEFAULT_TEAM").or_else(|| self.team.clone())
     }
@@ -130,7 +455,7 @@ fn default_since() -> String {
     "15m".to_string()
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct HighlightsConfig {
     #[serde(default)]
     pub custom_keywords: Vec<String>,
@@ -145,7 +470,7 @@ pub struct HighlightsConfig {
     pub disabled_groups: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RegexHighlight {
     pub pattern: String,
 
@@ -181,6 +506,7 @@ mod tests {
         let config: Config = serde_json::from_str(json).expect("should load old config");
         assert!(config.show_banner);
         assert!(config.check_updates);
+        assert!(config.pager);
     }
 
     #[test]
@@ -193,4 +519,50 @@ mod tests {
         assert!(!reloaded.show_banner);
         assert!(reloaded.check_updates);
     }
+
+    #[test]
+    fn max_time_range_prefers_source_override() {
+        let mut ctx = Context::new("https://logs.example.com".to_string());
+        assert_eq!(ctx.max_time_range_for(7, Some("app")), None);
+
+        ctx.max_time_range = Some("7d".to_string());
+        ctx.source_max_time_range
+            .insert("App".to_string(), "1d".to_string());
+        ctx.source_max_time_range
+            .insert("9".to_string(), "12h".to_string());
+        assert_eq!(ctx.max_time_range_for(7, Some("app")), Some("1d"));
+        assert_eq!(ctx.max_time_range_for(9, None), Some("12h"));
+        assert_eq!(ctx.max_time_range_for(8, Some("other")), Some("7d"));
+        // Both keys set: the ID wins, whatever the map's order.
+        assert_eq!(ctx.max_time_range_for(9, Some("app")), Some("12h"));
+    }
+
+    #[test]
+    fn token_type_is_optional_and_snake_case() {
+        let ctx: Context =
+            serde_json::from_str(r#"{"server_url":"https://logs.example.com","token":"t"}"#)
+                .unwrap();
+        assert_eq!(ctx.token_type, None);
+
+        let mut ctx = ctx;
+        ctx.token_type = Some(TokenType::ApiToken);
+        let json = serde_json::to_value(&ctx).unwrap();
+        assert_eq!(json["token_type"], "api_token");
+    }
+
+    #[test]
+    fn json_schema_describes_contexts() {
+        let schema = Config::json_schema();
+        assert_eq!(schema["title"], "Config");
+        assert!(schema["properties"]["contexts"].is_object());
+        let defs = &schema["$defs"];
+        assert!(defs["Context"]["properties"]["server_url"].is_object());
+        assert!(
+            defs["Context"]["required"]
+                .as_array()
+                .unwrap()
+                .contains(&serde_json::json!("server_url"))
+        );
+        assert!(defs["HighlightsConfig"].is_object());
+    }
 }
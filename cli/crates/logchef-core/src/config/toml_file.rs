@@ -0,0 +1,84 @@
+//! `logchef.toml`. Saving edits the existing file in place rather than
+//! rewriting it, so comments and the order of keys a user chose survive
+//! `config set` and a fresh login.
+
+use toml_edit::{DocumentMut, Item, Table};
+
+use super::Config;
+use crate::error::{Error, Result};
+
+pub(super) fn parse(content: &str) -> std::result::Result<Config, String> {
+    toml_edit::de::from_str(content).map_err(|e| e.to_string().trim_end().to_string())
+}
+
+/// Renders `config` as TOML, on top of `existing` (the file's current
+/// contents) when there is one.
+pub(super) fn render(config: &Config, existing: Option<&str>) -> Result<String> {
+    let fresh: DocumentMut = toml_edit::ser::to_string_pretty(config)
+        .map_err(|e| Error::config(format!("Failed to write config as TOML: {}", e)))?
+        .parse()
+        .map_err(|e| Error::config(format!("Failed to write config as TOML: {}", e)))?;
+    let Some(mut document) = existing.and_then(|content| content.parse::<DocumentMut>().ok())
+    else {
+        return Ok(fresh.to_string());
+    };
+    merge(document.as_table_mut(), fresh.as_table().clone());
+    Ok(document.to_string())
+}
+
+/// Makes `existing` hold `fresh`'s keys and values while keeping its own
+/// comments, whitespace and key order.
+fn merge(existing: &mut Table, fresh: Table) {
+    existing.retain(|key, _| fresh.contains_key(key));
+    for (key, item) in fresh {
+        match (existing.get_mut(&key), item) {
+            (Some(Item::Table(old)), Item::Table(new)) => merge(old, new),
+            (Some(Item::Value(old)), Item::Value(mut new)) => {
+                let mut bare = old.clone();
+                bare.decor_mut().clear();
+                if bare.to_string() != new.to_string() {
+                    *new.decor_mut() = old.decor().clone();
+                    *old = new;
+                }
+            }
+            (Some(slot), item) => *slot = item,
+            (None, item) => {
+                existing.insert(&key, item);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saving_keeps_comments_and_layout() {
+        let existing = r#"# Written by hand.
+current_context = "prod"
+
+[contexts.prod]
+server_url = "https://logs.example.com" # the main cluster
+timeout_secs = 30
+
+[contexts.prod.defaults]
+# Most of my work is here.
+team = "platform"
+limit = 100
+since = "15m"
+"#;
+        let mut config = parse(existing).unwrap();
+        let prod = config.get_context_mut("prod").unwrap();
+        prod.defaults.team = Some("payments".to_string());
+        prod.retries = Some(4);
+
+        let saved = render(&config, Some(existing)).unwrap();
+        assert!(saved.starts_with("# Written by hand.\n"));
+        assert!(saved.contains("server_url = \"https://logs.example.com\" # the main cluster\n"));
+        assert!(saved.contains("# Most of my work is here.\nteam = \"payments\"\n"));
+        assert!(saved.contains("retries = 4"));
+        let reloaded = parse(&saved).unwrap();
+        assert_eq!(reloaded.get_context("prod").unwrap().retries, Some(4));
+    }
+}
@@ -18,6 +18,9 @@ pub enum Error {
         /// The server's `error_type` from `ApiErrorResponse`, when present.
         /// Used to select an actionable CLI hint (see `ui::hint_for_error`).
         error_type: Option<String>,
+        /// `X-Request-Id` (or a similar header) of the failed response, to
+        /// quote when reporting the failure.
+        request_id: Option<String>,
     },
 
     #[error("Network error: {0}")]
@@ -41,12 +44,47 @@ pub enum Error {
     #[error("User cancelled authentication")]
     AuthCancelled,
 
+    /// The server predates `/api/v1/cli/token`, so an OIDC login can't be
+    /// exchanged for an API token.
+    #[error(
+        "This LogChef server version doesn't support CLI authentication; upgrade the server or use --token."
+    )]
+    CliAuthUnsupported,
+
+    /// The IdP's discovery document has no `device_authorization_endpoint`,
+    /// so `logchef auth --device` can't be used against it.
+    #[error(
+        "The identity provider doesn't support the device authorization grant (no device_authorization_endpoint in its discovery document)"
+    )]
+    DeviceFlowUnsupported,
+
     #[error("{0}")]
     Other(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// What to do about an [`Error`], from [`Error::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// May succeed if tried again later: rate limiting, a server error or
+    /// an unreachable server.
+    Retryable,
+    /// No valid credentials; sign in again.
+    AuthExpired,
+    /// Signed in, but not allowed; a team admin has to grant access.
+    PermissionDenied,
+    /// The server rejected the query or request as invalid; it fails the
+    /// same way until it's changed.
+    InvalidQuery,
+    /// The team, source or saved query doesn't exist.
+    NotFound,
+    /// The local configuration is unusable.
+    Config,
+    Other,
+}
+
 impl Error {
     pub fn config(msg: impl Into<String>) -> Self {
         Self::Config(msg.into())
@@ -61,6 +99,7 @@ impl Error {
             status,
             message: msg.into(),
             error_type: None,
+            request_id: None,
         }
     }
 
@@ -73,6 +112,7 @@ impl Error {
             status,
             message: msg.into(),
             error_type,
+            request_id: None,
         }
     }
 
@@ -83,4 +123,81 @@ impl Error {
     pub fn other(msg: impl Into<String>) -> Self {
         Self::Other(msg.into())
     }
+
+    /// Classifies the error by status first, then by the server's
+    /// `error_type`.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::NotAuthenticated => ErrorKind::AuthExpired,
+            Self::Network(_) => ErrorKind::Retryable,
+            Self::Config(_) | Self::InvalidUrl(_) => ErrorKind::Config,
+            Self::Api {
+                status, error_type, ..
+            } => match (status, error_type.as_deref()) {
+                (Some(429 | 500..), _) => ErrorKind::Retryable,
+                (Some(401), _) => ErrorKind::AuthExpired,
+                (Some(403), _) => ErrorKind::PermissionDenied,
+                (Some(404), _) => ErrorKind::NotFound,
+                (
+                    _,
+                    Some(
+                        "AuthenticationError"
+                        | "unauthorized"
+                        | "authentication_required"
+                        | "invalid_token",
+                    ),
+                ) => ErrorKind::AuthExpired,
+                (_, Some("AuthorizationError" | "forbidden")) => ErrorKind::PermissionDenied,
+                (_, Some("NotFoundError" | "not_found")) => ErrorKind::NotFound,
+                (Some(400 | 422), _) | (_, Some("ValidationError")) => ErrorKind::InvalidQuery,
+                _ => ErrorKind::Other,
+            },
+            _ => ErrorKind::Other,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.kind() == ErrorKind::Retryable
+    }
+
+    /// The failed response's request ID, if the server or a proxy sent one.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Self::Api { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_errors_classify_by_status_then_type() {
+        let api = |status, error_type: Option<&str>| {
+            Error::api_with_type(status, "failed", error_type.map(str::to_string))
+        };
+        assert_eq!(
+            api(Some(429), Some("ValidationError")).kind(),
+            ErrorKind::Retryable
+        );
+        assert_eq!(api(Some(503), None).kind(), ErrorKind::Retryable);
+        assert_eq!(api(Some(401), None).kind(), ErrorKind::AuthExpired);
+        assert_eq!(api(Some(403), None).kind(), ErrorKind::PermissionDenied);
+        assert_eq!(
+            api(Some(400), Some("ValidationError")).kind(),
+            ErrorKind::InvalidQuery
+        );
+        assert_eq!(
+            api(None, Some("AuthorizationError")).kind(),
+            ErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            api(Some(409), Some("ConflictError")).kind(),
+            ErrorKind::Other
+        );
+        assert_eq!(Error::NotAuthenticated.kind(), ErrorKind::AuthExpired);
+        assert!(!api(Some(400), None).is_retryable());
+    }
 }
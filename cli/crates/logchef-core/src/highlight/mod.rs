@@ -1,3 +1,7 @@
+mod template;
+
+pub use template::LineTemplate;
+
 use tailspin::Highlighter as TailspinHighlighter;
 use tailspin::config::{
     DateTimeConfig, IpV4Config, IpV6Config, JsonConfig, KeyValueConfig, KeywordConfig,
@@ -6,11 +10,18 @@ use tailspin::config::{
 };
 use tailspin::style::{Color, Style};
 
+use crate::api::{entry_timestamp, parse_timestamp};
 use crate::config::HighlightsConfig;
 use crate::error::Result;
 
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
 pub struct Highlighter {
     inner: TailspinHighlighter,
+    /// The user's own rules (custom keywords, `--highlight` words, custom
+    /// regexes), used by `is_match` to tell focus lines from context.
+    focus: Vec<regex::Regex>,
 }
 
 #[derive(Default, Clone)]
@@ -35,6 +46,22 @@ impl Highlighter {
             .collect();
 
         let mut keywords = Vec::new();
+        let mut focus = Vec::new();
+        let focus_words: Vec<&String> = config
+            .custom_keywords
+            .iter()
+            .chain(options.adhoc_highlights.iter().flat_map(|(_, words)| words))
+            .collect();
+        if !focus_words.is_empty() {
+            let alternation = focus_words
+                .iter()
+                .map(|word| regex::escape(word))
+                .collect::<Vec<_>>()
+                .join("|");
+            if let Ok(re) = regex::Regex::new(&format!(r"\b(?:{})\b", alternation)) {
+                focus.push(re);
+            }
+        }
 
         if !config.disable_builtin && !disabled.contains(&"keywords") {
             keywords.extend(default_log_level_keywords());
@@ -60,6 +87,9 @@ impl Highlighter {
         }
 
         for regex_cfg in &config.custom_regexes {
+            if let Ok(re) = regex::Regex::new(&regex_cfg.pattern) {
+                focus.push(re);
+            }
             let style = parse_color_style(&regex_cfg.color)
                 .bold_if(regex_cfg.bold)
                 .italic_if(regex_cfg.italic);
@@ -69,6 +99,12 @@ impl Highlighter {
             });
         }
 
+        if !config.disable_builtin && !disabled.contains(&"http_status") {
+            for regex in http_status_regexes() {
+                builder.with_regex_highlighter(regex);
+            }
+        }
+
         if !disabled.contains(&"dates") {
             builder.with_date_time_highlighters(DateTimeConfig::default());
         }
@@ -106,18 +142,93 @@ impl Highlighter {
             .build()
             .map_err(|e| crate::error::Error::Config(e.to_string()))?;
 
-        Ok(Self { inner })
+        Ok(Self { inner, focus })
+    }
+
+    /// Only tailspin's JSON group, for whole documents such as `--output
+    /// json`.
+    pub fn json() -> Result<Self> {
+        let inner = TailspinHighlighter::builder()
+            .with_json_highlighter(JsonConfig::default())
+            .build()
+            .map_err(|e| crate::error::Error::Config(e.to_string()))?;
+        Ok(Self {
+            inner,
+            focus: Vec::new(),
+        })
     }
 
     pub fn highlight(&self, line: &str) -> String {
         self.inner.apply(line).to_string()
     }
+
+    /// Whether any user rule is configured, i.e. `is_match` can tell lines
+    /// apart.
+    pub fn has_focus_rules(&self) -> bool {
+        !self.focus.is_empty()
+    }
+
+    /// Whether `line` matches one of the user's highlight rules (custom
+    /// keywords, `--highlight` words, custom regexes). Built-in groups don't
+    /// count, since nearly every line has a date or number in it.
+    pub fn is_match(&self, line: &str) -> bool {
+        self.focus.iter().any(|re| re.is_match(line))
+    }
+
+    /// Full highlighting for lines matching a user rule; everything else is
+    /// rendered plain and dimmed so the matches stand out.
+    pub fn highlight_focused(&self, line: &str) -> String {
+        if self.is_match(line) {
+            self.highlight(line)
+        } else {
+            format!("{}{}{}", DIM, line, RESET)
+        }
+    }
+}
+
+/// Removes ANSI escape sequences (CSI such as SGR colors, and OSC such as
+/// hyperlinks) from highlighted output, leaving the plain text.
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters/intermediates, then a final byte in @..~.
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: terminated by BEL or ST (ESC \).
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Two-byte escapes (ESC c, ESC =, ...): drop both.
+            _ => {}
+        }
+    }
+    out
 }
 
 impl Default for Highlighter {
     fn default() -> Self {
         Self::new(&HighlightsConfig::default()).unwrap_or_else(|_| Self {
             inner: TailspinHighlighter::default(),
+            focus: Vec::new(),
         })
     }
 }
@@ -159,6 +270,34 @@ impl StyleExt for Style {
     }
 }
 
+/// HTTP status codes colored by class (2xx green, 3xx cyan, 4xx yellow, 5xx
+/// red). Only codes in a status position are matched — `status=404`-style
+/// fields and the code after `HTTP/1.1"` in access-log lines — so arbitrary
+/// three-digit numbers are left to the number highlighter.
+fn http_status_regexes() -> Vec<RegexConfig> {
+    let mut regexes = Vec::new();
+    for class in ['2', '3', '4', '5'] {
+        let style = || match class {
+            '2' => Style::new().fg(Color::Green),
+            '3' => Style::new().fg(Color::Cyan),
+            '4' => Style::new().fg(Color::Yellow),
+            _ => Style::new().fg(Color::Red).bold(),
+        };
+        regexes.push(RegexConfig {
+            regex: format!(
+                r"\b(?:status|status_code|statusCode|http_status|response_code)=({}\d\d)\b",
+                class
+            ),
+            style: style(),
+        });
+        regexes.push(RegexConfig {
+            regex: format!(r#"HTTP/\d(?:\.\d)?"? ({}\d\d)\b"#, class),
+            style: style(),
+        });
+    }
+    regexes
+}
+
 fn default_log_level_keywords() -> Vec<KeywordConfig> {
     vec![
         KeywordConfig {
@@ -219,14 +358,314 @@ fn default_log_level_keywords() -> Vec<KeywordConfig> {
 
 pub struct FormatOptions {
     pub show_timestamp: bool,
+    /// Prefix non-priority fields with `key=`. When false only the values are
+    /// printed, e.g. `[ERROR] something failed 500 api`.
+    pub show_key_names: bool,
+    /// Pad the timestamp and level into fixed-width columns so messages line
+    /// up vertically across lines.
+    pub aligned: bool,
+    /// How the `_timestamp` / `timestamp` field is rendered.
+    pub time_display: TimeDisplay,
+    /// Zone the timestamp is converted to before printing; `None` prints it
+    /// as returned by the server.
+    pub timezone: Option<chrono_tz::Tz>,
+    /// Field pulled to the front of every line as a fixed-width column, e.g.
+    /// `host`, so the source of each line is easy to track.
+    pub context_column: Option<ContextColumn>,
+    /// Pad every field to a shared width so `key=value` parts form columns;
+    /// see [`FieldWidths`].
+    pub field_widths: Option<FieldWidths>,
+    /// Print `columns` exactly in the order given, skipping the usual
+    /// timestamp/level/message lead, e.g. for `query --fields`.
+    pub column_order: bool,
+}
+
+/// A field rendered as the leading, fixed-width column of each text line.
+#[derive(Clone, Debug)]
+pub struct ContextColumn {
+    pub name: String,
+    /// Column width in characters; longer values are truncated with `…`.
+    pub width: usize,
+}
+
+/// Widest context column; wider values are truncated to fit.
+pub const CONTEXT_COLUMN_MAX_WIDTH: usize = 24;
+
+impl ContextColumn {
+    /// Sizes the column to the widest value of `name` across `entries`,
+    /// capped at [`CONTEXT_COLUMN_MAX_WIDTH`].
+    pub fn fit(name: &str, entries: &[crate::api::LogEntry]) -> Self {
+        let width = entries
+            .iter()
+            .map(|entry| context_value(entry.get(name)).chars().count())
+            .max()
+            .unwrap_or(1)
+            .clamp(1, CONTEXT_COLUMN_MAX_WIDTH);
+        Self {
+            name: name.to_string(),
+            width,
+        }
+    }
+
+    /// The padded (or truncated) prefix for `entry`; `-` when the field is
+    /// missing or null.
+    pub fn render(&self, entry: &crate::api::LogEntry) -> String {
+        let value = context_value(entry.get(&self.name));
+        let value = if value.chars().count() > self.width {
+            let kept: String = value.chars().take(self.width.saturating_sub(1)).collect();
+            format!("{}…", kept)
+        } else {
+            value
+        };
+        format!("{:<width$}", value, width = self.width)
+    }
+
+    /// Splits a formatted line into the context prefix and the rest, so the
+    /// prefix can be styled apart from the highlighted remainder.
+    pub fn split_line<'a>(&self, line: &'a str) -> (&'a str, &'a str) {
+        let at = line
+            .char_indices()
+            .nth(self.width)
+            .map_or(line.len(), |(i, _)| i);
+        let (prefix, rest) = line.split_at(at);
+        (prefix, rest.strip_prefix(' ').unwrap_or(rest))
+    }
+
+    /// Style for a prefix: a color picked from the value itself, so each
+    /// host (or pod, ...) keeps the same color on every line.
+    pub fn style(prefix: &str) -> &'static str {
+        const PALETTE: [&str; 6] = [
+            "\x1b[1;36m",
+            "\x1b[1;35m",
+            "\x1b[1;33m",
+            "\x1b[1;32m",
+            "\x1b[1;34m",
+            "\x1b[1;31m",
+        ];
+        let hash = prefix
+            .trim_end()
+            .bytes()
+            .fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(u32::from(b)));
+        PALETTE[hash as usize % PALETTE.len()]
+    }
+
+    /// Wraps an (unstyled) prefix in its color.
+    pub fn paint(prefix: &str) -> String {
+        format!("{}{}{}", Self::style(prefix), prefix, RESET)
+    }
+}
+
+fn context_value(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => "-".to_string(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Rendering of the timestamp field in text output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeDisplay {
+    /// The value as returned by the server.
+    #[default]
+    Absolute,
+    /// Age relative to now, e.g. `2m ago`.
+    Relative,
+    /// The absolute value followed by its age, e.g. `... (2m ago)`.
+    Both,
+}
+
+impl std::str::FromStr for TimeDisplay {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "absolute" => Ok(Self::Absolute),
+            "relative" => Ok(Self::Relative),
+            "both" => Ok(Self::Both),
+            _ => Err(format!(
+                "unknown time display '{s}' (expected absolute, relative or both)"
+            )),
+        }
+    }
+}
+
+/// Humanizes an entry's age to its two largest units: `42s ago`, `2m ago`,
+/// `1h3m ago`, `2d4h ago`. Future instants (clock skew) read `in 5s`.
+pub fn humanize_age(age: chrono::Duration) -> String {
+    let secs = age.num_seconds();
+    let total = secs.unsigned_abs();
+    let (days, hours, mins) = (total / 86_400, total % 86_400 / 3_600, total % 3_600 / 60);
+    let span = if days > 0 {
+        if hours > 0 {
+            format!("{days}d{hours}h")
+        } else {
+            format!("{days}d")
+        }
+    } else if hours > 0 {
+        if mins > 0 {
+            format!("{hours}h{mins}m")
+        } else {
+            format!("{hours}h")
+        }
+    } else if mins > 0 {
+        format!("{mins}m")
+    } else {
+        format!("{total}s")
+    };
+    if secs < 0 {
+        format!("in {span}")
+    } else {
+        format!("{span} ago")
+    }
 }
 
+/// Column width for the timestamp in aligned mode; fits
+/// `2026-07-14T09:00:00.000Z`. Longer values simply overflow.
+const ALIGNED_TIMESTAMP_WIDTH: usize = 24;
+/// Aligned timestamp width once converted to a zone other than UTC; fits
+/// `2026-07-14T14:30:00.000+05:30`.
+const ALIGNED_ZONED_TIMESTAMP_WIDTH: usize = 29;
+/// Column width for the level in aligned mode; fits `[WARNING]`.
+const ALIGNED_LEVEL_WIDTH: usize = 9;
+
 impl Default for FormatOptions {
     fn default() -> Self {
         Self {
             show_timestamp: true,
+            show_key_names: true,
+            aligned: false,
+            time_display: TimeDisplay::Absolute,
+            timezone: None,
+            context_column: None,
+            field_widths: None,
+            column_order: false,
+        }
+    }
+}
+
+/// Collapses consecutive entries that share the same message (`msg` /
+/// `message`) within a time window into a single line annotated with a
+/// repeat count, e.g. `... connection refused (x42)`. Feed formatted lines
+/// through [`push`](Self::push) in output order and call
+/// [`finish`](Self::finish) at the end to flush the last run. Entries without
+/// a message are never collapsed; entries without a parseable timestamp are
+/// treated as falling inside the window.
+pub struct RepeatCollapser {
+    window: chrono::Duration,
+    pending: Option<PendingRepeat>,
+}
+
+struct PendingRepeat {
+    message: Option<String>,
+    line: String,
+    first_ts: Option<chrono::DateTime<chrono::Utc>>,
+    count: usize,
+}
+
+impl PendingRepeat {
+    fn render(self) -> String {
+        if self.count > 1 {
+            format!("{} (x{})", self.line, self.count)
+        } else {
+            self.line
+        }
+    }
+}
+
+impl RepeatCollapser {
+    pub fn new(window: chrono::Duration) -> Self {
+        Self {
+            window,
+            pending: None,
+        }
+    }
+
+    /// Adds one entry and its formatted line. Returns the previous run's
+    /// line once this entry ends it.
+    pub fn push(&mut self, entry: &crate::api::LogEntry, line: String) -> Option<String> {
+        let message = ["msg", "message"]
+            .iter()
+            .find_map(|field| entry.get(*field))
+            .map(|value| match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            });
+        let ts = entry_timestamp(entry);
+
+        if let Some(pending) = &mut self.pending
+            && message.is_some()
+            && pending.message == message
+            && match (pending.first_ts, ts) {
+                (Some(first), Some(ts)) => (ts - first).abs() <= self.window,
+                _ => true,
+            }
+        {
+            pending.count += 1;
+            return None;
+        }
+
+        self.pending
+            .replace(PendingRepeat {
+                message,
+                line,
+                first_ts: ts,
+                count: 1,
+            })
+            .map(PendingRepeat::render)
+    }
+
+    /// Flushes the final run, if any.
+    pub fn finish(&mut self) -> Option<String> {
+        self.pending.take().map(PendingRepeat::render)
+    }
+}
+
+/// Log severity in ascending order, for `--min-level` style filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl Severity {
+    /// Parses a level name case-insensitively, accepting common aliases
+    /// (`warning`, `err`, `critical`, ...).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "trace" => Some(Self::Trace),
+            "debug" | "dbg" => Some(Self::Debug),
+            "info" | "information" | "notice" => Some(Self::Info),
+            "warn" | "warning" => Some(Self::Warn),
+            "error" | "err" => Some(Self::Error),
+            "fatal" | "critical" | "crit" | "panic" | "emerg" | "alert" => Some(Self::Fatal),
+            _ => None,
         }
     }
+
+    /// Severity from the entry's `level` (or `severity`) field; `None` when
+    /// absent or unrecognised.
+    pub fn of_entry(entry: &crate::api::LogEntry) -> Option<Self> {
+        entry
+            .get("level")
+            .or_else(|| entry.get("severity"))
+            .and_then(|value| value.as_str())
+            .and_then(Self::parse)
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::parse(s).ok_or_else(|| {
+            format!("unknown level '{s}' (expected trace, debug, info, warn, error or fatal)")
+        })
+    }
 }
 
 pub fn format_log_entry(entry: &crate::api::LogEntry, columns: &[crate::api::Column]) -> String {
@@ -238,7 +677,20 @@ pub fn format_log_entry_with_options(
     columns: &[crate::api::Column],
     options: &FormatOptions,
 ) -> String {
-    let priority_fields: &[&str] = if options.show_timestamp {
+    let parts = entry_parts(entry, columns, options);
+    match &options.field_widths {
+        Some(widths) => widths.layout(parts),
+        None => parts
+            .into_iter()
+            .map(|(_, part)| part)
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Fields rendered before the remaining columns, in display order.
+fn priority_fields(options: &FormatOptions) -> &'static [&'static str] {
+    if options.show_timestamp {
         &[
             "_timestamp",
             "timestamp",
@@ -249,37 +701,212 @@ pub fn format_log_entry_with_options(
         ]
     } else {
         &["level", "severity", "msg", "message"]
-    };
+    }
+}
 
+/// Whether a result column is printed after the priority fields.
+fn shows_column(col: &crate::api::Column, options: &FormatOptions) -> bool {
+    let timestamp_fields = ["_timestamp", "timestamp"];
+    let dominated_by_priority = priority_fields(options).contains(&col.name.as_str());
+    let is_hidden_timestamp =
+        !options.show_timestamp && timestamp_fields.contains(&col.name.as_str());
+    let is_internal = col.name.starts_with('_');
+    let is_context = options
+        .context_column
+        .as_ref()
+        .is_some_and(|context| context.name == col.name);
+    !dominated_by_priority && !is_hidden_timestamp && !is_internal && !is_context
+}
+
+/// The rendered pieces of a line, each keyed by the field it came from (the
+/// first field of the group for aligned timestamp/level columns).
+fn entry_parts(
+    entry: &crate::api::LogEntry,
+    columns: &[crate::api::Column],
+    options: &FormatOptions,
+) -> Vec<(String, String)> {
     let timestamp_fields = ["_timestamp", "timestamp"];
     let mut parts = Vec::new();
 
-    for field in priority_fields {
-        if let Some(value) = entry.get(*field) {
-            parts.push(format_value(field, value));
+    if let Some(context) = &options.context_column {
+        parts.push((context.name.clone(), context.render(entry)));
+    }
+
+    if options.column_order {
+        for col in columns {
+            let is_context = options
+                .context_column
+                .as_ref()
+                .is_some_and(|context| context.name == col.name);
+            if !is_context
+                && let Some(value) = entry.get(&col.name)
+                && !value.is_null()
+            {
+                parts.push((col.name.clone(), format_value(&col.name, value, options)));
+            }
+        }
+        return parts;
+    }
+
+    if options.aligned {
+        // One padded column per group (first present field wins), blank when
+        // absent, so every line keeps the same layout.
+        let mut groups: Vec<(&[&str], usize)> = Vec::new();
+        if options.show_timestamp {
+            let width = match options.timezone {
+                Some(tz) if tz != chrono_tz::UTC => ALIGNED_ZONED_TIMESTAMP_WIDTH,
+                _ => ALIGNED_TIMESTAMP_WIDTH,
+            };
+            groups.push((&timestamp_fields, width));
+        }
+        groups.push((&["level", "severity"], ALIGNED_LEVEL_WIDTH));
+        for (fields, width) in groups {
+            let rendered = fields
+                .iter()
+                .find_map(|field| {
+                    entry
+                        .get(*field)
+                        .map(|value| format_value(field, value, options))
+                })
+                .unwrap_or_default();
+            parts.push((
+                fields[0].to_string(),
+                format!("{:<width$}", rendered, width = width),
+            ));
+        }
+        for field in ["msg", "message"] {
+            if let Some(value) = entry.get(field) {
+                parts.push((field.to_string(), format_value(field, value, options)));
+            }
+        }
+    } else {
+        for field in priority_fields(options) {
+            if let Some(value) = entry.get(*field) {
+                parts.push((field.to_string(), format_value(field, value, options)));
+            }
         }
     }
 
     for col in columns {
-        let dominated_by_priority = priority_fields.contains(&col.name.as_str());
-        let is_hidden_timestamp =
-            !options.show_timestamp && timestamp_fields.contains(&col.name.as_str());
-        let is_internal = col.name.starts_with('_');
-
-        if !dominated_by_priority
-            && !is_hidden_timestamp
-            && !is_internal
+        if shows_column(col, options)
             && let Some(value) = entry.get(&col.name)
             && !value.is_null()
         {
-            parts.push(format_value(&col.name, value));
+            parts.push((col.name.clone(), format_value(&col.name, value, options)));
+        }
+    }
+
+    parts
+}
+
+/// Widest slot `--inline-kv-align` pads to; longer values overflow and only
+/// push their own line out of alignment.
+pub const FIELD_ALIGN_MAX_WIDTH: usize = 60;
+
+/// Per-field column widths for a whole result set, so `key=value` parts line
+/// up vertically across lines. Built with [`measure`](Self::measure) over
+/// the entries about to be printed, then set on
+/// [`FormatOptions::field_widths`].
+#[derive(Clone, Debug, Default)]
+pub struct FieldWidths {
+    /// Display order, with the widest rendering of each field.
+    slots: Vec<(String, usize)>,
+}
+
+impl FieldWidths {
+    /// Measures every field across `entries` as they'd be formatted with
+    /// `options` (whose own `field_widths` is ignored).
+    pub fn measure<'a>(
+        entries: impl IntoIterator<Item = &'a crate::api::LogEntry>,
+        columns: &[crate::api::Column],
+        options: &FormatOptions,
+    ) -> Self {
+        let mut order: Vec<String> = Vec::new();
+        if let Some(context) = &options.context_column {
+            order.push(context.name.clone());
+        }
+        if options.column_order {
+            order.extend(columns.iter().map(|col| col.name.clone()));
+        } else {
+            order.extend(priority_fields(options).iter().map(|f| f.to_string()));
+            order.extend(
+                columns
+                    .iter()
+                    .filter(|col| shows_column(col, options))
+                    .map(|col| col.name.clone()),
+            );
+        }
+
+        let mut widths: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+        for entry in entries {
+            for (key, part) in entry_parts(entry, columns, options) {
+                let width = part.chars().count().min(FIELD_ALIGN_MAX_WIDTH);
+                let slot = widths.entry(key).or_default();
+                *slot = (*slot).max(width);
+            }
+        }
+
+        let mut slots = Vec::new();
+        for key in order {
+            if slots.iter().any(|(k, _)| *k == key) {
+                continue;
+            }
+            if let Some(width) = widths.get(&key) {
+                slots.push((key, *width));
+            }
         }
+        Self { slots }
     }
 
-    parts.join(" ")
+    /// Pads each part into its slot; fields an entry lacks become blanks so
+    /// the following ones stay in place.
+    fn layout(&self, mut parts: Vec<(String, String)>) -> String {
+        let mut cells = Vec::with_capacity(self.slots.len());
+        for (key, width) in &self.slots {
+            let part = parts
+                .iter()
+                .position(|(k, _)| k == key)
+                .map(|i| parts.remove(i).1)
+                .unwrap_or_default();
+            cells.push(format!("{:<width$}", part, width = width));
+        }
+        // Fields that weren't measured (none for the measured entries) go last.
+        cells.extend(parts.into_iter().map(|(_, part)| part));
+        cells.join(" ").trim_end().to_string()
+    }
+}
+
+/// Renders a timestamp in `tz` as RFC3339, keeping sub-second digits only
+/// when present: `2026-07-14T14:30:00+05:30`, `2026-07-14T09:00:01.250Z`.
+pub fn format_in_timezone(ts: chrono::DateTime<chrono::Utc>, tz: chrono_tz::Tz) -> String {
+    ts.with_timezone(&tz)
+        .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
 }
 
-fn format_value(key: &str, value: &serde_json::Value) -> String {
+fn format_value(key: &str, value: &serde_json::Value, options: &FormatOptions) -> String {
+    if (key == "_timestamp" || key == "timestamp")
+        && (options.time_display != TimeDisplay::Absolute || options.timezone.is_some())
+        && let Some(ts) = parse_timestamp(value)
+    {
+        let absolute = match (options.timezone, value) {
+            (Some(tz), _) => format_in_timezone(ts, tz),
+            (None, serde_json::Value::String(s)) => s.clone(),
+            (None, other) => other.to_string(),
+        };
+        let age = || humanize_age(chrono::Utc::now() - ts);
+        return match options.time_display {
+            TimeDisplay::Absolute => absolute,
+            TimeDisplay::Relative => age(),
+            TimeDisplay::Both => format!("{} ({})", absolute, age()),
+        };
+    }
+    let keyed = |rendered: String| {
+        if options.show_key_names {
+            format!("{}={}", key, rendered)
+        } else {
+            rendered
+        }
+    };
     match value {
         serde_json::Value::String(s) => {
             if key == "_timestamp" || key == "timestamp" {
@@ -289,21 +916,273 @@ fn format_value(key: &str, value: &serde_json::Value) -> String {
             } else if key == "msg" || key == "message" {
                 s.clone()
             } else {
-                format!("{}={}", key, s)
+                keyed(s.clone())
             }
         }
         serde_json::Value::Number(n) => {
             if key == "_timestamp" || key == "timestamp" {
                 n.to_string()
             } else {
-                format!("{}={}", key, n)
+                keyed(n.to_string())
             }
         }
-        serde_json::Value::Bool(b) => format!("{}={}", key, b),
+        serde_json::Value::Bool(b) => keyed(b.to_string()),
         serde_json::Value::Null => String::new(),
-        serde_json::Value::Array(arr) => format!("{}={:?}", key, arr),
-        serde_json::Value::Object(obj) => {
-            format!("{}={}", key, serde_json::to_string(obj).unwrap_or_default())
+        serde_json::Value::Array(arr) => keyed(format!("{:?}", arr)),
+        serde_json::Value::Object(obj) => keyed(serde_json::to_string(obj).unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(json: serde_json::Value) -> crate::api::LogEntry {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn aligned_pads_timestamp_and_level_columns() {
+        let options = FormatOptions {
+            aligned: true,
+            ..Default::default()
+        };
+        let a = entry(serde_json::json!({
+            "_timestamp": "2026-07-14T09:00:00Z",
+            "level": "info",
+            "msg": "started",
+        }));
+        let b = entry(serde_json::json!({
+            "_timestamp": "2026-07-14T09:00:01.250Z",
+            "level": "warning",
+            "msg": "slow",
+        }));
+        let line_a = format_log_entry_with_options(&a, &[], &options);
+        let line_b = format_log_entry_with_options(&b, &[], &options);
+        assert_eq!(line_a.find("started"), line_b.find("slow"));
+        assert!(line_a.starts_with("2026-07-14T09:00:00Z     [INFO]"));
+    }
+
+    #[test]
+    fn relative_time_humanizes_age() {
+        assert_eq!(humanize_age(chrono::Duration::seconds(42)), "42s ago");
+        assert_eq!(humanize_age(chrono::Duration::seconds(125)), "2m ago");
+        assert_eq!(humanize_age(chrono::Duration::seconds(3_780)), "1h3m ago");
+        assert_eq!(humanize_age(chrono::Duration::hours(50)), "2d2h ago");
+        assert_eq!(humanize_age(chrono::Duration::seconds(-5)), "in 5s");
+
+        let ts = (chrono::Utc::now() - chrono::Duration::minutes(5)).to_rfc3339();
+        let e = entry(serde_json::json!({"_timestamp": ts, "msg": "boom"}));
+        let both = FormatOptions {
+            time_display: TimeDisplay::Both,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_log_entry_with_options(&e, &[], &both),
+            format!("{} (5m ago) boom", ts)
+        );
+    }
+
+    #[test]
+    fn timestamps_convert_to_the_display_timezone() {
+        let e = entry(serde_json::json!({
+            "_timestamp": "2026-07-14 09:00:01.250",
+            "level": "info",
+            "msg": "started",
+        }));
+        let zoned = |tz| FormatOptions {
+            timezone: Some(tz),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_log_entry_with_options(&e, &[], &zoned(chrono_tz::Asia::Kolkata)),
+            "2026-07-14T14:30:01.250+05:30 [INFO] started"
+        );
+        assert_eq!(
+            format_log_entry_with_options(&e, &[], &zoned(chrono_tz::UTC)),
+            "2026-07-14T09:00:01.250Z [INFO] started"
+        );
+        // Without a zone the server's value passes through untouched.
+        assert!(format_log_entry(&e, &[]).starts_with("2026-07-14 09:00:01.250 [INFO]"));
+    }
+
+    #[test]
+    fn context_column_leads_with_padded_value() {
+        let entries = vec![
+            entry(serde_json::json!({"msg": "a", "host": "web-1"})),
+            entry(serde_json::json!({"msg": "b", "host": "db-primary"})),
+            entry(serde_json::json!({"msg": "c"})),
+        ];
+        let columns: Vec<crate::api::Column> = serde_json::from_value(serde_json::json!([
+            {"name": "host", "type": "String"}
+        ]))
+        .unwrap();
+        let context = ContextColumn::fit("host", &entries);
+        assert_eq!(context.width, 10);
+        let options = FormatOptions {
+            context_column: Some(context.clone()),
+            ..Default::default()
+        };
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|e| format_log_entry_with_options(e, &columns, &options))
+            .collect();
+        assert_eq!(lines, ["web-1      a", "db-primary b", "-          c"]);
+        assert_eq!(context.split_line(&lines[0]), ("web-1     ", "a"));
+
+        let narrow = ContextColumn {
+            name: "host".to_string(),
+            width: 4,
+        };
+        assert_eq!(narrow.render(&entries[1]), "db-…");
+    }
+
+    #[test]
+    fn field_widths_line_up_key_value_columns() {
+        let entries = vec![
+            entry(serde_json::json!({"level": "info", "msg": "ok", "svc": "api", "code": 200})),
+            entry(serde_json::json!({"level": "error", "msg": "failed", "code": 500})),
+            entry(serde_json::json!({"level": "warn", "msg": "slow", "svc": "billing"})),
+        ];
+        let columns: Vec<crate::api::Column> = serde_json::from_value(serde_json::json!([
+            {"name": "svc", "type": "String"},
+            {"name": "code", "type": "UInt16"}
+        ]))
+        .unwrap();
+        let mut options = FormatOptions::default();
+        options.field_widths = Some(FieldWidths::measure(&entries, &columns, &options));
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|e| format_log_entry_with_options(e, &columns, &options))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "[INFO]  ok     svc=api     code=200",
+                "[ERROR] failed             code=500",
+                "[WARN]  slow   svc=billing",
+            ]
+        );
+    }
+
+    #[test]
+    fn no_key_names_prints_values_only() {
+        let options = FormatOptions {
+            show_key_names: false,
+            ..Default::default()
+        };
+        let columns: Vec<crate::api::Column> = serde_json::from_value(serde_json::json!([
+            {"name": "service", "type": "String"}
+        ]))
+        .unwrap();
+        let e = entry(serde_json::json!({"level": "error", "msg": "boom", "service": "api"}));
+        assert_eq!(
+            format_log_entry_with_options(&e, &columns, &options),
+            "[ERROR] boom api"
+        );
+    }
+
+    #[test]
+    fn column_order_follows_the_given_columns() {
+        let options = FormatOptions {
+            column_order: true,
+            ..Default::default()
+        };
+        let columns: Vec<crate::api::Column> = serde_json::from_value(serde_json::json!([
+            {"name": "service", "type": "String"},
+            {"name": "msg", "type": "String"},
+            {"name": "level", "type": "String"}
+        ]))
+        .unwrap();
+        let e = entry(serde_json::json!({
+            "timestamp": "2026-07-14T09:00:00Z",
+            "level": "error",
+            "msg": "boom",
+            "service": "api"
+        }));
+        assert_eq!(
+            format_log_entry_with_options(&e, &columns, &options),
+            "service=api boom [ERROR]"
+        );
+    }
+
+    #[test]
+    fn repeat_collapser_counts_runs_within_window() {
+        let mut collapser = RepeatCollapser::new(chrono::Duration::seconds(5));
+        let rows = [
+            ("2026-07-14T09:00:00Z", "boom"),
+            ("2026-07-14T09:00:01Z", "boom"),
+            ("2026-07-14T09:00:03Z", "boom"),
+            ("2026-07-14T09:00:30Z", "boom"),
+            ("2026-07-14T09:00:31Z", "ok"),
+        ];
+        let mut out = Vec::new();
+        for (ts, msg) in rows {
+            let e = entry(serde_json::json!({"_timestamp": ts, "msg": msg}));
+            out.extend(collapser.push(&e, format!("{} {}", ts, msg)));
         }
+        out.extend(collapser.finish());
+        assert_eq!(
+            out,
+            vec![
+                "2026-07-14T09:00:00Z boom (x3)",
+                "2026-07-14T09:00:30Z boom",
+                "2026-07-14T09:00:31Z ok",
+            ]
+        );
+    }
+
+    #[test]
+    fn severity_parses_aliases_and_orders() {
+        assert_eq!(Severity::parse("WARNING"), Some(Severity::Warn));
+        assert_eq!(Severity::parse("err"), Some(Severity::Error));
+        assert_eq!(Severity::parse("verbose"), None);
+        assert!(Severity::Trace < Severity::Debug && Severity::Error < Severity::Fatal);
+
+        let e = entry(serde_json::json!({"severity": "Info", "msg": "x"}));
+        assert_eq!(Severity::of_entry(&e), Some(Severity::Info));
+        let e = entry(serde_json::json!({"msg": "x"}));
+        assert_eq!(Severity::of_entry(&e), None);
+    }
+
+    #[test]
+    fn strip_ansi_removes_csi_and_osc_sequences() {
+        assert_eq!(
+            strip_ansi("\x1b[1;31mERROR\x1b[0m status=\x1b[32m200\x1b[0m"),
+            "ERROR status=200"
+        );
+        assert_eq!(
+            strip_ansi("\x1b]8;;https://x\x1b\\link\x1b]8;;\x07 ok"),
+            "link ok"
+        );
+        assert_eq!(strip_ansi("plain ✓"), "plain ✓");
+
+        let h = Highlighter::default();
+        let line = "level=error took 42ms from 10.0.0.1";
+        assert_eq!(strip_ansi(&h.highlight(line)), line);
+    }
+
+    #[test]
+    fn focused_highlighting_dims_lines_without_a_user_match() {
+        let config = HighlightsConfig {
+            custom_regexes: vec![crate::config::RegexHighlight {
+                pattern: "trace_id=[a-f0-9]+".to_string(),
+                color: "cyan".to_string(),
+                bold: false,
+                italic: false,
+            }],
+            ..Default::default()
+        };
+        let options = HighlightOptions {
+            adhoc_highlights: vec![("red".to_string(), vec!["timeout".to_string()])],
+            ..Default::default()
+        };
+        let h = Highlighter::with_options(&config, &options).unwrap();
+        assert!(h.has_focus_rules());
+        assert!(h.is_match("upstream timeout after 30s"));
+        assert!(h.is_match("request trace_id=ab12 done"));
+        assert!(!h.is_match("timeouts are configured"));
+        assert_eq!(h.highlight_focused("all good"), "\x1b[2mall good\x1b[0m");
+        assert!(!Highlighter::default().has_focus_rules());
     }
 }
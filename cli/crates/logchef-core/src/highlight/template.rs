@@ -0,0 +1,179 @@
+use crate::api::LogEntry;
+use crate::error::{Error, Result};
+
+/// A one-line rendering of a log entry, e.g. `{timestamp} [{level}] {msg}`.
+///
+/// - `{field}` prints a field's value; strings are printed raw, anything
+///   else as JSON.
+/// - `{a.b.c}` reaches into nested objects (a `kubernetes` map column, say).
+///   A top-level column literally named `a.b.c` takes precedence.
+/// - `{service|app|"-"}` tries each field in turn and falls back to the
+///   quoted literal; a placeholder with nothing to show renders empty.
+/// - `{{` and `}}` print literal braces.
+#[derive(Clone, Debug)]
+pub struct LineTemplate {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug)]
+enum Part {
+    Text(String),
+    Field(Vec<Alternative>),
+}
+
+#[derive(Clone, Debug)]
+enum Alternative {
+    Path(String),
+    Literal(String),
+}
+
+impl LineTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.char_indices().peekable();
+        while let Some((pos, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().is_some_and(|(_, next)| *next == '{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek().is_some_and(|(_, next)| *next == '}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => {
+                    return Err(Error::other(format!(
+                        "unmatched '}}' at position {} (use '}}}}' for a literal brace)",
+                        pos
+                    )));
+                }
+                '{' => {
+                    let mut spec = String::new();
+                    let mut in_quotes = false;
+                    let mut closed = false;
+                    for (_, c) in chars.by_ref() {
+                        match c {
+                            '"' => in_quotes = !in_quotes,
+                            '}' if !in_quotes => {
+                                closed = true;
+                                break;
+                            }
+                            _ => {}
+                        }
+                        spec.push(c);
+                    }
+                    if !closed {
+                        return Err(Error::other(format!("unclosed '{{' at position {}", pos)));
+                    }
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(parse_alternatives(&spec)?));
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Self { parts })
+    }
+
+    pub fn render(&self, entry: &LogEntry) -> String {
+        let mut line = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => line.push_str(text),
+                Part::Field(alternatives) => {
+                    if let Some(value) = alternatives.iter().find_map(|alt| resolve(entry, alt)) {
+                        line.push_str(&value);
+                    }
+                }
+            }
+        }
+        line
+    }
+}
+
+fn parse_alternatives(spec: &str) -> Result<Vec<Alternative>> {
+    let mut alternatives = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in spec.chars().chain(std::iter::once('|')) {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            '|' if !in_quotes => {
+                let alt = current.trim();
+                let alternative = match alt.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+                    Some(literal) => Alternative::Literal(literal.to_string()),
+                    None if alt.is_empty() || alt.contains('"') => {
+                        return Err(Error::other(format!("invalid placeholder '{{{}}}'", spec)));
+                    }
+                    None => Alternative::Path(alt.to_string()),
+                };
+                alternatives.push(alternative);
+                current.clear();
+            }
+            c => current.push(c),
+        }
+    }
+    Ok(alternatives)
+}
+
+fn resolve(entry: &LogEntry, alternative: &Alternative) -> Option<String> {
+    let path = match alternative {
+        Alternative::Literal(literal) => return Some(literal.clone()),
+        Alternative::Path(path) => path,
+    };
+    let value = entry.get(path).or_else(|| {
+        let mut segments = path.split('.');
+        let first = entry.get(segments.next()?)?;
+        segments.try_fold(first, |value, segment| value.get(segment))
+    })?;
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(value: serde_json::Value) -> LogEntry {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn renders_fields_fallbacks_and_nested_paths() {
+        let e = entry(serde_json::json!({
+            "timestamp": "2026-07-14T09:00:00Z",
+            "level": "error",
+            "status": 500,
+            "app": "billing",
+            "kubernetes": {"pod_name": "api-7f9c"},
+            "trace.id": "abc"
+        }));
+        let template = LineTemplate::parse(
+            r#"{timestamp} [{level}] {service|app} {msg|"-"} {status} {kubernetes.pod_name} {trace.id} {{x}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            template.render(&e),
+            "2026-07-14T09:00:00Z [error] billing - 500 api-7f9c abc {x}"
+        );
+        assert_eq!(LineTemplate::parse("{missing}!").unwrap().render(&e), "!");
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        for bad in ["{level", "level}", "{}", "{a||b}", r#"{a"b}"#] {
+            assert!(LineTemplate::parse(bad).is_err(), "{bad}");
+        }
+    }
+}
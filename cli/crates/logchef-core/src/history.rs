@@ -0,0 +1,232 @@
+//! Local record of the queries run from this machine, kept as JSON lines in
+//! the cache directory (`history.jsonl`) so past investigations can be
+//! listed, searched and re-run without the server.
+
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Entries kept on disk; older ones are dropped once the file grows past
+/// twice this.
+pub const MAX_ENTRIES: usize = 1000;
+
+/// Language a recorded query was written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryKind {
+    /// `logchef query`.
+    Logchefql,
+    /// `logchef sql`: ClickHouse SQL or LogsQL.
+    Native,
+}
+
+impl QueryKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Logchefql => "logchefql",
+            Self::Native => "native",
+        }
+    }
+}
+
+/// One executed query.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub executed_at: DateTime<Utc>,
+    /// Context it ran against (`(ephemeral)` for a bare `--server`).
+    pub context: String,
+    pub server_url: String,
+    pub team_id: i64,
+    pub source_id: i64,
+    /// Source as given on the command line or in `defaults.source`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub kind: QueryKind,
+    pub query: String,
+    /// The `--since` lookback, when the window was relative; re-runs resolve
+    /// it against the new "now".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Rows returned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<u64>,
+    /// Server-side execution time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+}
+
+impl HistoryEntry {
+    /// Case-insensitive substring match against the query, source and
+    /// context.
+    pub fn matches(&self, needle: &str) -> bool {
+        let needle = needle.to_lowercase();
+        [
+            Some(self.query.as_str()),
+            self.source.as_deref(),
+            Some(self.context.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(&needle))
+    }
+}
+
+pub struct History {
+    path: PathBuf,
+    max_entries: usize,
+}
+
+impl History {
+    /// The history file in the cache directory.
+    pub fn open() -> Self {
+        let cache_dir = ProjectDirs::from("", "", "logchef")
+            .map(|dirs| dirs.cache_dir().to_path_buf())
+            .unwrap_or_else(|| std::env::temp_dir().join("logchef"));
+        Self::at(cache_dir.join("history.jsonl"))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_entries: MAX_ENTRIES,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends an entry, trimming the file back to [`MAX_ENTRIES`] once it
+    /// has doubled.
+    pub fn record(&self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        // One write per line, so concurrent appends don't interleave.
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())?;
+
+        let lines = fs::read_to_string(&self.path)?.lines().count();
+        if lines > self.max_entries * 2 {
+            let entries = self.load()?;
+            let keep = entries.len().saturating_sub(self.max_entries);
+            self.rewrite(&entries[keep..])?;
+        }
+        Ok(())
+    }
+
+    /// All entries, most recent first. Lines that don't parse (a partial
+    /// write, an entry from a newer version) are skipped.
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>> {
+        let mut entries = self.load()?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// The `index`-th most recent entry, counting from 1.
+    pub fn get(&self, index: usize) -> Result<Option<HistoryEntry>> {
+        let Some(skip) = index.checked_sub(1) else {
+            return Ok(None);
+        };
+        Ok(self.entries()?.into_iter().nth(skip))
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Oldest first, as stored.
+    fn load(&self) -> Result<Vec<HistoryEntry>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    fn rewrite(&self, entries: &[HistoryEntry]) -> Result<()> {
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        let tmp = self
+            .path
+            .with_extension(format!("jsonl.{}.tmp", std::process::id()));
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(query: &str) -> HistoryEntry {
+        HistoryEntry {
+            executed_at: Utc::now(),
+            context: "prod".to_string(),
+            server_url: "https://logs.example.com".to_string(),
+            team_id: 1,
+            source_id: 2,
+            source: Some("nginx".to_string()),
+            kind: QueryKind::Logchefql,
+            query: query.to_string(),
+            since: Some("1h".to_string()),
+            start: None,
+            end: None,
+            limit: None,
+            rows: Some(3),
+            duration_ms: Some(12),
+        }
+    }
+
+    #[test]
+    fn entries_come_back_newest_first_and_trimmed() {
+        let path =
+            std::env::temp_dir().join(format!("logchef-history-{}.jsonl", std::process::id()));
+        let history = History {
+            path,
+            max_entries: 5,
+        };
+        history.clear().unwrap();
+        assert!(history.entries().unwrap().is_empty());
+
+        for i in 0..=10 {
+            history.record(&entry(&format!("status={i}"))).unwrap();
+        }
+        let entries = history.entries().unwrap();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].query, "status=10");
+        assert_eq!(history.get(2).unwrap().unwrap().query, "status=9");
+        assert!(history.get(0).unwrap().is_none());
+
+        assert!(entries[0].matches("STATUS"));
+        assert!(entries[0].matches("nginx"));
+        assert!(!entries[0].matches("level"));
+        history.clear().unwrap();
+    }
+}
@@ -1,11 +1,18 @@
 pub mod api;
+#[cfg(feature = "native")]
 pub mod auth;
+#[cfg(feature = "native")]
 pub mod cache;
 pub mod config;
 pub mod error;
+#[cfg(feature = "native")]
 pub mod highlight;
+#[cfg(feature = "native")]
+pub mod history;
+pub mod logchefql;
 pub mod timerange;
 
+#[cfg(feature = "native")]
 pub use cache::Cache;
 pub use config::Config;
-pub use error::{Error, Result};
+pub use error::{Error, ErrorKind, Result};
@@ -0,0 +1,755 @@
+//! A local LogchefQL parser, so syntax errors are caught before a request
+//! is made and reported with the offending span rather than as a generic
+//! 400 from the server.
+//!
+//! The grammar mirrors the server's (`internal/logchefql/grammar.go`): the
+//! same tokens, matched in the same order, and the same productions:
+//!
+//! ```text
+//! query      = [ or_expr ] [ "|" field_path { field_path } ]
+//! or_expr    = and_expr { "or" and_expr }
+//! and_expr   = term { "and" term }
+//! term       = "(" or_expr ")" | field_path operator value
+//! field_path = segment { "." segment }      segment = ident | string
+//! value      = string | number | ident
+//! ```
+//!
+//! Anything this parser accepts, the server does too; when in doubt it errs
+//! on the side of accepting, since the server remains the authority.
+
+use serde::Serialize;
+use std::fmt;
+use std::ops::Range;
+
+/// Longest query the server will parse.
+pub const MAX_QUERY_LENGTH: usize = 64 * 1024;
+
+/// Deepest `(...)` nesting the server will parse.
+pub const MAX_NESTING_DEPTH: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    /// The conditions before `|`, if any.
+    pub filter: Option<Expr>,
+    /// The fields after `|`, if any.
+    pub select: Vec<FieldPath>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Group(Box<Expr>),
+    Comparison(Comparison),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    pub field: FieldPath,
+    pub operator: Operator,
+    pub value: Value,
+}
+
+/// A column, or a path into one (`log.level`, `attrs."user-id"`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldPath(pub Vec<String>);
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join("."))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    NotEq,
+    Regex,
+    NotRegex,
+    Gt,
+    Lt,
+    Gte,
+    Lte,
+}
+
+impl Operator {
+    pub const ALL: [&'static str; 8] = ["=", "!=", "~", "!~", ">", "<", ">=", "<="];
+
+    fn parse(op: &str) -> Option<Self> {
+        Some(match op {
+            "=" => Self::Eq,
+            "!=" => Self::NotEq,
+            "~" => Self::Regex,
+            "!~" => Self::NotRegex,
+            ">" => Self::Gt,
+            "<" => Self::Lt,
+            ">=" => Self::Gte,
+            "<=" => Self::Lte,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A quoted string, unescaped.
+    String(String),
+    Number(f64),
+    /// A bare word: `true`, `false`, `null`, or an unquoted value.
+    Ident(String),
+}
+
+/// Same codes as the server's `ParseError`, so JSON output lines up with
+/// `logchef explain`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    UnterminatedString,
+    UnexpectedEnd,
+    UnexpectedToken,
+    ExpectedOperator,
+    ExpectedValue,
+    ExpectedClosingParen,
+    UnknownOperator,
+    MissingBooleanOperator,
+    QueryTooLong,
+    QueryTooDeeplyNested,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Where and why a query failed to parse.
+#[derive(Clone, Debug, PartialEq, Serialize, thiserror::Error)]
+#[error("{message} (line {}, column {})", position.line, position.column)]
+pub struct ParseError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// 1-based, in characters.
+    pub position: Position,
+    /// Byte range of the offending text.
+    #[serde(skip)]
+    pub span: Range<usize>,
+}
+
+impl ParseError {
+    fn new(code: ErrorCode, message: impl Into<String>, span: Range<usize>, input: &str) -> Self {
+        let before = &input[..span.start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            code,
+            message: message.into(),
+            position: Position {
+                line: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+            },
+            span,
+        }
+    }
+
+    /// The error with the offending line of `input` and a caret underline:
+    ///
+    /// ```text
+    /// unknown operator '=='; use one of = != ~ !~ > < >= <=
+    ///   |
+    /// 1 | service=="api"
+    ///   |        ^^
+    /// ```
+    pub fn render(&self, input: &str) -> String {
+        let line_start = input[..self.span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[line_start..]
+            .find('\n')
+            .map_or(input.len(), |i| line_start + i);
+        let line = input[line_start..line_end].trim_end_matches('\r');
+        let end = self.span.end.min(line_end).max(self.span.start);
+        let width = input[self.span.start..end].chars().count().max(1);
+        let number = self.position.line.to_string();
+        let gutter = " ".repeat(number.len());
+        format!(
+            "{}\n{gutter} |\n{number} | {line}\n{gutter} | {}{}",
+            self.message,
+            " ".repeat(self.position.column - 1),
+            "^".repeat(width),
+        )
+    }
+}
+
+/// Parses a LogchefQL query. An empty (or all-whitespace) query is valid and
+/// matches everything.
+pub fn parse(input: &str) -> Result<Query, ParseError> {
+    if input.len() > MAX_QUERY_LENGTH {
+        return Err(ParseError::new(
+            ErrorCode::QueryTooLong,
+            format!(
+                "query too long: {} bytes exceeds the maximum of {} bytes",
+                input.len(),
+                MAX_QUERY_LENGTH
+            ),
+            0..0,
+            input,
+        ));
+    }
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        input,
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    parser.query()
+}
+
+/// Checks a query's syntax without keeping the parse.
+pub fn validate(input: &str) -> Result<(), ParseError> {
+    parse(input).map(|_| ())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    String,
+    Operator,
+    Pipe,
+    LParen,
+    RParen,
+    Dot,
+    Number,
+    Ident,
+    End,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: Kind,
+    span: Range<usize>,
+}
+
+/// Splits `input` into tokens, trying each rule in the server lexer's order
+/// (the first rule that matches wins, not the longest).
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        let kind = match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                i += 1;
+                continue;
+            }
+            b'"' | b'\'' => {
+                i += 1;
+                loop {
+                    match bytes.get(i) {
+                        None => {
+                            return Err(ParseError::new(
+                                ErrorCode::UnterminatedString,
+                                format!("unterminated string: missing closing {}", c as char),
+                                start..start + 1,
+                                input,
+                            ));
+                        }
+                        Some(b'\\') if i + 1 < bytes.len() => i += 2,
+                        Some(&b) if b == c => {
+                            i += 1;
+                            break;
+                        }
+                        Some(_) => i += 1,
+                    }
+                }
+                Kind::String
+            }
+            b'!' | b'>' | b'<' => {
+                i += 1;
+                match (c, bytes.get(i)) {
+                    (b'!', Some(b'=' | b'~')) | (b'>' | b'<', Some(b'=')) => i += 1,
+                    (b'!', _) => {
+                        return Err(ParseError::new(
+                            ErrorCode::UnknownOperator,
+                            format!(
+                                "unknown operator '!'; use one of {}",
+                                Operator::ALL.join(" ")
+                            ),
+                            start..i,
+                            input,
+                        ));
+                    }
+                    _ => {}
+                }
+                Kind::Operator
+            }
+            b'=' | b'~' => {
+                i += 1;
+                Kind::Operator
+            }
+            b'|' => {
+                i += 1;
+                Kind::Pipe
+            }
+            b'(' => {
+                i += 1;
+                Kind::LParen
+            }
+            b')' => {
+                i += 1;
+                Kind::RParen
+            }
+            b'.' => {
+                i += 1;
+                Kind::Dot
+            }
+            b'-' | b'+' | b'0'..=b'9' => match number_len(&bytes[i..]) {
+                Some(len) => {
+                    i += len;
+                    Kind::Number
+                }
+                None => return Err(unexpected_character(input, start)),
+            },
+            b'@' | b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                let first = if c == b'@' { i + 1 } else { i };
+                if !bytes
+                    .get(first)
+                    .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_')
+                {
+                    return Err(unexpected_character(input, start));
+                }
+                i = first + 1;
+                while bytes.get(i).is_some_and(|b| {
+                    b.is_ascii_alphanumeric() || matches!(b, b'_' | b':' | b'@' | b'-')
+                }) {
+                    i += 1;
+                }
+                Kind::Ident
+            }
+            _ => return Err(unexpected_character(input, start)),
+        };
+        tokens.push(Token {
+            kind,
+            span: start..i,
+        });
+    }
+    tokens.push(Token {
+        kind: Kind::End,
+        span: input.len()..input.len(),
+    });
+    Ok(tokens)
+}
+
+/// Length of a `[-+]?[0-9]*\.?[0-9]+` match at the start of `bytes`.
+fn number_len(bytes: &[u8]) -> Option<usize> {
+    let sign = usize::from(matches!(bytes.first(), Some(b'-' | b'+')));
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+    let whole = digits(sign);
+    let dot = sign + whole;
+    if bytes.get(dot) == Some(&b'.') {
+        let fraction = digits(dot + 1);
+        if fraction > 0 {
+            return Some(dot + 1 + fraction);
+        }
+    }
+    (whole > 0).then_some(dot)
+}
+
+fn unexpected_character(input: &str, at: usize) -> ParseError {
+    let c = input[at..].chars().next().unwrap_or_default();
+    let hint = match c {
+        '&' => "; combine conditions with 'and'",
+        ',' => "; list fields after '|' separated by spaces",
+        '[' | ']' => "; use '.' to reach nested fields",
+        _ => "",
+    };
+    ParseError::new(
+        ErrorCode::UnexpectedToken,
+        format!("unexpected character '{c}'{hint}"),
+        at..at + c.len_utf8(),
+        input,
+    )
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != Kind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn text(&self, token: &Token) -> &str {
+        &self.input[token.span.clone()]
+    }
+
+    fn is_keyword(&self, token: &Token, keyword: &str) -> bool {
+        token.kind == Kind::Ident && self.text(token).eq_ignore_ascii_case(keyword)
+    }
+
+    fn error(&self, code: ErrorCode, message: impl Into<String>, span: Range<usize>) -> ParseError {
+        ParseError::new(code, message, span, self.input)
+    }
+
+    fn query(&mut self) -> Result<Query, ParseError> {
+        let filter = match self.peek().kind {
+            Kind::Pipe | Kind::End => None,
+            _ => Some(self.or_expr()?),
+        };
+        let mut select = Vec::new();
+        if self.peek().kind == Kind::Pipe {
+            let pipe = self.next();
+            if self.peek().kind == Kind::Pipe {
+                let span = pipe.span.start..self.peek().span.end;
+                return Err(self.error(
+                    ErrorCode::UnexpectedToken,
+                    "'||' is not an operator; combine conditions with 'or'",
+                    span,
+                ));
+            }
+            while matches!(self.peek().kind, Kind::Ident | Kind::String) {
+                select.push(self.field_path()?);
+            }
+            if select.is_empty() {
+                return Err(self.unexpected(self.peek().clone(), "a field name after '|'"));
+            }
+        }
+        let token = self.peek().clone();
+        if let Some(err) = self.unquoted_value() {
+            return Err(err);
+        }
+        match token.kind {
+            Kind::End => Ok(Query { filter, select }),
+            Kind::RParen => {
+                Err(self.error(ErrorCode::UnexpectedToken, "unmatched ')'", token.span))
+            }
+            Kind::Ident | Kind::String if filter.is_some() && select.is_empty() => Err(self.error(
+                ErrorCode::MissingBooleanOperator,
+                format!("expected 'and' or 'or' before '{}'", self.text(&token)),
+                token.span,
+            )),
+            _ => Err(self.unexpected(token, "'and', 'or', '|' or the end of the query")),
+        }
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut terms = vec![self.and_expr()?];
+        while self.is_keyword(self.peek(), "or") {
+            self.next();
+            terms.push(self.and_expr()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::Or(terms)
+        })
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut terms = vec![self.term()?];
+        while self.is_keyword(self.peek(), "and") {
+            self.next();
+            terms.push(self.term()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::And(terms)
+        })
+    }
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        if self.peek().kind != Kind::LParen {
+            return self.comparison().map(Expr::Comparison);
+        }
+        let open = self.next();
+        self.depth += 1;
+        if self.depth > MAX_NESTING_DEPTH {
+            return Err(self.error(
+                ErrorCode::QueryTooDeeplyNested,
+                format!(
+                    "query too deeply nested: exceeds the maximum nesting depth of {}",
+                    MAX_NESTING_DEPTH
+                ),
+                open.span,
+            ));
+        }
+        let inner = self.or_expr()?;
+        if self.peek().kind != Kind::RParen {
+            let token = self.peek().clone();
+            if token.kind == Kind::End {
+                return Err(self.error(
+                    ErrorCode::ExpectedClosingParen,
+                    "unclosed '(': missing ')'",
+                    open.span,
+                ));
+            }
+            return Err(self
+                .unquoted_value()
+                .unwrap_or_else(|| self.unexpected(token, "'and', 'or' or ')'")));
+        }
+        self.next();
+        self.depth -= 1;
+        Ok(Expr::Group(Box::new(inner)))
+    }
+
+    fn comparison(&mut self) -> Result<Comparison, ParseError> {
+        let field = self.field_path()?;
+        let token = self.peek().clone();
+        if token.kind != Kind::Operator {
+            let field_end = self.tokens[self.pos - 1].span.end;
+            return Err(match token.kind {
+                Kind::End => self.error(
+                    ErrorCode::ExpectedOperator,
+                    format!("expected an operator after '{field}'"),
+                    field_end..field_end,
+                ),
+                _ => self.error(
+                    ErrorCode::ExpectedOperator,
+                    format!(
+                        "expected an operator ({}) after '{field}', found '{}'",
+                        Operator::ALL.join(" "),
+                        self.text(&token)
+                    ),
+                    token.span,
+                ),
+            });
+        }
+        self.next();
+        // `==`, `<>`, `=~`: two operator tokens back to back.
+        let next = self.peek().clone();
+        if next.kind == Kind::Operator && next.span.start == token.span.end {
+            let span = token.span.start..next.span.end;
+            return Err(self.error(
+                ErrorCode::UnknownOperator,
+                format!(
+                    "unknown operator '{}'; use one of {}",
+                    &self.input[span.clone()],
+                    Operator::ALL.join(" ")
+                ),
+                span,
+            ));
+        }
+        let operator = Operator::parse(self.text(&token)).expect("lexed as an operator");
+        let value = self.value(&token)?;
+        Ok(Comparison {
+            field,
+            operator,
+            value,
+        })
+    }
+
+    fn value(&mut self, operator: &Token) -> Result<Value, ParseError> {
+        let token = self.peek().clone();
+        let text = self.text(&token);
+        let value = match token.kind {
+            Kind::String => Value::String(unquote(text)),
+            Kind::Number => Value::Number(text.parse().unwrap_or_default()),
+            Kind::Ident => Value::Ident(text.to_string()),
+            Kind::End => {
+                let end = operator.span.end;
+                return Err(self.error(
+                    ErrorCode::ExpectedValue,
+                    format!("expected a value after '{}'", self.text(operator)),
+                    end..end,
+                ));
+            }
+            _ => {
+                return Err(self.error(
+                    ErrorCode::ExpectedValue,
+                    format!(
+                        "expected a value after '{}', found '{}'",
+                        self.text(operator),
+                        text
+                    ),
+                    token.span,
+                ));
+            }
+        };
+        self.next();
+        Ok(value)
+    }
+
+    fn field_path(&mut self) -> Result<FieldPath, ParseError> {
+        let mut segments = vec![self.segment()?];
+        while self.peek().kind == Kind::Dot {
+            self.next();
+            segments.push(self.segment()?);
+        }
+        Ok(FieldPath(segments))
+    }
+
+    fn segment(&mut self) -> Result<String, ParseError> {
+        let token = self.peek().clone();
+        let segment = match token.kind {
+            Kind::Ident => self.text(&token).to_string(),
+            Kind::String => unquote(self.text(&token)),
+            _ => return Err(self.unexpected(token, "a field name")),
+        };
+        self.next();
+        Ok(segment)
+    }
+
+    /// A bare value the lexer split in two (`5xx`, `10.0.0.1`): the current
+    /// token runs straight on from the value before it.
+    fn unquoted_value(&self) -> Option<ParseError> {
+        let value = &self.tokens[self.pos.checked_sub(1)?];
+        if !matches!(value.kind, Kind::Number | Kind::Ident)
+            || self.pos < 2
+            || self.tokens[self.pos - 2].kind != Kind::Operator
+        {
+            return None;
+        }
+        let mut end = value.span.end;
+        for token in &self.tokens[self.pos..] {
+            if token.span.start != end
+                || !matches!(token.kind, Kind::Number | Kind::Ident | Kind::Dot)
+            {
+                break;
+            }
+            end = token.span.end;
+        }
+        if end == value.span.end {
+            return None;
+        }
+        let span = value.span.start..end;
+        Some(self.error(
+            ErrorCode::UnexpectedToken,
+            format!(
+                "unquoted value '{0}'; quote it: \"{0}\"",
+                &self.input[span.clone()]
+            ),
+            span,
+        ))
+    }
+
+    fn unexpected(&self, token: Token, expected: &str) -> ParseError {
+        if token.kind == Kind::End {
+            return self.error(
+                ErrorCode::UnexpectedEnd,
+                format!("unexpected end of query: expected {expected}"),
+                token.span,
+            );
+        }
+        self.error(
+            ErrorCode::UnexpectedToken,
+            format!("expected {expected}, found '{}'", self.text(&token)),
+            token.span,
+        )
+    }
+}
+
+/// Strips the quotes from a string token and resolves its escapes the way
+/// the server does.
+fn unquote(token: &str) -> String {
+    let inner = &token[1..token.len() - 1];
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_what_the_server_grammar_accepts() {
+        for query in [
+            "",
+            "   ",
+            r#"level="error""#,
+            r#"level="error" AND (service='api' or status>=500)"#,
+            r#"log.level!="debug" and "user-id"~"^a\"b""#,
+            "msg!~timeout and duration_ms<=-1.5 and @timestamp>0",
+            r#"level="error" | msg service"#,
+            "| msg",
+            "ok=true and deleted=null",
+        ] {
+            parse(query).unwrap_or_else(|err| panic!("{query}: {err}"));
+        }
+
+        let query = parse(r#"(a=1 or b="x\ty") and c~d | e.f"#).unwrap();
+        assert_eq!(query.select, vec![FieldPath(vec!["e".into(), "f".into()])]);
+        let Some(Expr::And(terms)) = query.filter else {
+            panic!("expected an and");
+        };
+        let Expr::Group(group) = &terms[0] else {
+            panic!("expected a group");
+        };
+        let Expr::Or(alternatives) = group.as_ref() else {
+            panic!("expected an or");
+        };
+        assert_eq!(
+            alternatives[1],
+            Expr::Comparison(Comparison {
+                field: FieldPath(vec!["b".into()]),
+                operator: Operator::Eq,
+                value: Value::String("x\ty".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let cases = [
+            (r#"level="error"#, ErrorCode::UnterminatedString, 1, 7),
+            ("(a=1 and b=2", ErrorCode::ExpectedClosingParen, 1, 1),
+            ("a=1)", ErrorCode::UnexpectedToken, 1, 4),
+            ("a==1", ErrorCode::UnknownOperator, 1, 2),
+            ("a<>1", ErrorCode::UnknownOperator, 1, 2),
+            ("a=", ErrorCode::ExpectedValue, 1, 3),
+            ("level error", ErrorCode::ExpectedOperator, 1, 7),
+            ("a=1 b=2", ErrorCode::MissingBooleanOperator, 1, 5),
+            ("a=1 &&\n b=2", ErrorCode::UnexpectedToken, 1, 5),
+            ("a=1 and\n  =2", ErrorCode::UnexpectedToken, 2, 3),
+            ("status=5xx", ErrorCode::UnexpectedToken, 1, 8),
+            ("(ip=10.0.0.1)", ErrorCode::UnexpectedToken, 1, 5),
+            ("a=1 |", ErrorCode::UnexpectedEnd, 1, 6),
+        ];
+        for (query, code, line, column) in cases {
+            let err = parse(query).expect_err(query);
+            assert_eq!(err.code, code, "{query}: {err}");
+            assert_eq!(err.position, Position { line, column }, "{query}: {err}");
+        }
+
+        let deep = format!("{}a=1{}", "(".repeat(101), ")".repeat(101));
+        assert_eq!(
+            parse(&deep).unwrap_err().code,
+            ErrorCode::QueryTooDeeplyNested
+        );
+
+        let query = r#"level="error" and service=="api""#;
+        assert_eq!(
+            parse(query).unwrap_err().render(query),
+            "unknown operator '=='; use one of = != ~ !~ > < >= <=\n  |\n1 | level=\"error\" and service==\"api\"\n  |                          ^^"
+        );
+    }
+}
@@ -11,8 +11,13 @@
 //!
 //! Every command should go through [`resolve_time_range`] rather than
 //! formatting timestamps and picking a timezone independently.
+//!
+//! `--since` values are parsed here too ([`since_window`]), as are
+//! `--from`/`--to` ([`from_to_window`]), so every command accepts the same
+//! lookbacks, named windows like `today`, and timestamp formats.
 
-use chrono::{DateTime, Utc};
+use crate::error::{Error, Result};
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 
 const WALL_CLOCK_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
@@ -27,6 +32,16 @@ pub fn resolve_timezone(configured: Option<&str>) -> Tz {
     resolve_timezone_with(configured, iana_time_zone::get_timezone().ok().as_deref())
 }
 
+/// Zone timestamps are printed in: UTC when `utc` is set (`--utc`), else
+/// the same zone [`resolve_timezone`] picks for requests.
+pub fn display_timezone(configured: Option<&str>, utc: bool) -> Tz {
+    if utc {
+        Tz::UTC
+    } else {
+        resolve_timezone(configured)
+    }
+}
+
 /// Same resolution logic as [`resolve_timezone`], but with the "system
 /// timezone" fed in explicitly rather than detected — this is what lets the
 /// fallback path be unit tested without depending on the host's actual
@@ -44,6 +59,244 @@ fn parse_tz(value: Option<&str>) -> Option<Tz> {
         .and_then(|s| s.parse::<Tz>().ok())
 }
 
+/// Named `--since` windows, resolved in the effective timezone. Listed in
+/// error messages.
+pub const SINCE_KEYWORDS: &str =
+    "today, yesterday, this-week, last-week, this-month, last-month, last-hour";
+
+/// Parses a lookback such as `15m`, `1h`, `7d` or `2w`. A bare number is
+/// minutes, and an empty string means 15 minutes.
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(Duration::minutes(15));
+    }
+
+    let (num, unit) = match s.char_indices().last() {
+        Some((i, unit @ ('m' | 'h' | 'd' | 'w'))) => (&s[..i], unit),
+        _ => (s, 'm'),
+    };
+    let num: i64 = num.parse().map_err(|_| {
+        Error::other(format!(
+            "Invalid duration '{}': expected e.g. 15m, 1h, 7d or 2w",
+            s
+        ))
+    })?;
+
+    match unit {
+        'h' => Duration::try_hours(num),
+        'd' => Duration::try_days(num),
+        'w' => Duration::try_weeks(num),
+        _ => Duration::try_minutes(num),
+    }
+    .ok_or_else(|| Error::other(format!("Duration '{}' is out of range", s)))
+}
+
+/// Resolves a `--since` value to a UTC window: a lookback ending now, or a
+/// named calendar window (see [`SINCE_KEYWORDS`]) whose day, week (from
+/// Monday) and month boundaries fall at midnight in the effective timezone.
+/// Windows that are over, like `yesterday`, end at their boundary rather
+/// than now.
+pub fn since_window(
+    since: &str,
+    configured_tz: Option<&str>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    since_window_at(since, resolve_timezone(configured_tz), Utc::now())
+}
+
+fn since_window_at(
+    since: &str,
+    tz: Tz,
+    now: DateTime<Utc>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let today = now.with_timezone(&tz).date_naive();
+    let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+    let first_of_month = today.with_day(1).expect("day 1 exists in every month");
+    let first_of_last_month = (first_of_month - Duration::days(1))
+        .with_day(1)
+        .expect("day 1 exists in every month");
+    let midnight = |date: NaiveDate| local_midnight(tz, date);
+
+    let keyword = since.trim().to_ascii_lowercase().replace('_', "-");
+    Ok(match keyword.as_str() {
+        "today" => (midnight(today), now),
+        "yesterday" => (midnight(today - Duration::days(1)), midnight(today)),
+        "this-week" => (midnight(monday), now),
+        "last-week" => (midnight(monday - Duration::weeks(1)), midnight(monday)),
+        "this-month" => (midnight(first_of_month), now),
+        "last-month" => (midnight(first_of_last_month), midnight(first_of_month)),
+        "last-hour" => (now - Duration::hours(1), now),
+        _ => {
+            let lookback = parse_duration(since).map_err(|_| {
+                Error::other(format!(
+                    "Invalid time range '{}': use a duration like 15m, 1h, 7d or 2w, or one of {}",
+                    since.trim(),
+                    SINCE_KEYWORDS
+                ))
+            })?;
+            let start = now.checked_sub_signed(lookback).ok_or_else(|| {
+                Error::other(format!("Time range '{}' is out of range", since.trim()))
+            })?;
+            (start, now)
+        }
+    })
+}
+
+/// Local date-time layouts accepted by [`parse_timestamp`], read in the
+/// effective timezone.
+const LOCAL_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+];
+
+const TIMESTAMP_FORMATS_HINT: &str = "use RFC3339 (2026-07-14T09:30:00Z), \
+    'YYYY-MM-DD HH:MM:SS' or a date (2026-07-14) in the effective timezone, \
+    epoch milliseconds, or a relative time like '2 hours ago'";
+
+/// Parses a `--from`/`--to` value into an instant. Accepts RFC3339 with an
+/// offset; `YYYY-MM-DD HH:MM[:SS]` (space or `T`) and date-only values, read
+/// in the effective timezone; epoch milliseconds (13 digits) or seconds (10
+/// digits); `now`, `today`, `yesterday`; and relative times like
+/// `2 hours ago` or `30m ago`. Local times that a DST change makes ambiguous
+/// or skips are rejected rather than guessed.
+pub fn parse_timestamp(value: &str, configured_tz: Option<&str>) -> Result<DateTime<Utc>> {
+    parse_timestamp_at(value, resolve_timezone(configured_tz), Utc::now())
+}
+
+/// Parses `--from` and `--to` against the same "now", naming the offending
+/// flag in errors and rejecting windows that end before they start.
+pub fn from_to_window(
+    from: &str,
+    to: &str,
+    configured_tz: Option<&str>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let tz = resolve_timezone(configured_tz);
+    let now = Utc::now();
+    let parse = |value: &str, flag: &str| {
+        parse_timestamp_at(value, tz, now)
+            .map_err(|e| Error::other(format!("Invalid {} '{}': {}", flag, value.trim(), e)))
+    };
+    let (start, end) = (parse(from, "--from")?, parse(to, "--to")?);
+    if start >= end {
+        return Err(Error::other(format!(
+            "--from ({}) must be before --to ({})",
+            start.to_rfc3339(),
+            end.to_rfc3339()
+        )));
+    }
+    Ok((start, end))
+}
+
+fn parse_timestamp_at(value: &str, tz: Tz, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    let today = now.with_timezone(&tz).date_naive();
+
+    match value.to_ascii_lowercase().as_str() {
+        "now" => return Ok(now),
+        "today" => return Ok(local_midnight(tz, today)),
+        "yesterday" => return Ok(local_midnight(tz, today - Duration::days(1))),
+        _ => {}
+    }
+    if let Some(ago) = parse_ago(value) {
+        return ago?
+            .and_then(|duration| now.checked_sub_signed(duration))
+            .ok_or_else(|| Error::other(format!("'{}' is out of range", value)));
+    }
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        let number: i64 = value
+            .parse()
+            .map_err(|_| Error::other("epoch value is out of range"))?;
+        let instant = match value.len() {
+            13 => DateTime::from_timestamp_millis(number),
+            10 => DateTime::from_timestamp(number, 0),
+            _ => {
+                return Err(Error::other(
+                    "ambiguous number; give epoch milliseconds (13 digits) or seconds (10 digits)",
+                ));
+            }
+        };
+        return instant.ok_or_else(|| Error::other("epoch value is out of range"));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Some(naive) = LOCAL_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    {
+        return match tz.from_local_datetime(&naive) {
+            LocalResult::Single(dt) => Ok(dt.with_timezone(&Utc)),
+            LocalResult::Ambiguous(..) => Err(Error::other(format!(
+                "{} occurs twice in {} (DST change); add a UTC offset, e.g. {}",
+                value,
+                tz,
+                naive.format("%Y-%m-%dT%H:%M:%S+HH:MM")
+            ))),
+            LocalResult::None => Err(Error::other(format!(
+                "{} doesn't exist in {} (skipped by a DST change)",
+                value, tz
+            ))),
+        };
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(local_midnight(tz, date));
+    }
+    Err(Error::other(TIMESTAMP_FORMATS_HINT))
+}
+
+/// Parses `2 hours ago`, `1 day ago` or `30m ago`; `None` when `value` isn't
+/// of that shape.
+fn parse_ago(value: &str) -> Option<Result<Option<Duration>>> {
+    let amount = value.to_ascii_lowercase();
+    let amount = amount.strip_suffix("ago")?.trim();
+    let split = amount
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(amount.len());
+    let (number, unit) = amount.split_at(split);
+    let Ok(number) = number.parse::<i64>() else {
+        return Some(Err(Error::other(format!(
+            "expected a number before the unit, as in '2 hours ago', got '{}'",
+            value
+        ))));
+    };
+    let unit = unit.trim();
+    // Plural `s`, but not the one in a bare `s` or `ms`.
+    let unit = unit
+        .strip_suffix('s')
+        .filter(|singular| singular.len() > 1)
+        .unwrap_or(unit);
+    // `None` when the amount is too large to represent.
+    let duration = match unit {
+        "s" | "sec" | "second" => Duration::try_seconds(number),
+        "m" | "min" | "minute" => Duration::try_minutes(number),
+        "h" | "hr" | "hour" => Duration::try_hours(number),
+        "d" | "day" => Duration::try_days(number),
+        "w" | "week" => Duration::try_weeks(number),
+        _ => {
+            return Some(Err(Error::other(format!(
+                "unknown unit in '{}'; use seconds, minutes, hours, days or weeks",
+                value
+            ))));
+        }
+    };
+    Some(Ok(duration))
+}
+
+/// Start of `date` in `tz`. Where a DST change skips midnight, the day
+/// starts at the first wall-clock time that exists.
+fn local_midnight(tz: Tz, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    (0..=120)
+        .step_by(15)
+        .find_map(|minutes| {
+            tz.from_local_datetime(&(midnight + Duration::minutes(minutes)))
+                .earliest()
+        })
+        .map_or_else(|| midnight.and_utc(), |dt| dt.with_timezone(&Utc))
+}
+
 /// Input to [`resolve_time_range`].
 pub enum TimeInput<'a> {
     /// Wall-clock strings already expressed in the effective timezone (e.g.
@@ -229,6 +482,101 @@ mod tests {
         assert_eq!(tz, Tz::UTC);
     }
 
+    #[test]
+    fn since_keywords_follow_calendar_boundaries_in_the_zone() {
+        // Thursday 2026-07-16 10:00 in Kolkata.
+        let now = Utc.with_ymd_and_hms(2026, 7, 16, 4, 30, 0).unwrap();
+        let window = |since| since_window_at(since, kolkata(), now).unwrap();
+        let local = |y, m, d| kolkata().with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+
+        assert_eq!(window("today"), (local(2026, 7, 16).to_utc(), now));
+        assert_eq!(
+            window("yesterday"),
+            (local(2026, 7, 15).to_utc(), local(2026, 7, 16).to_utc())
+        );
+        assert_eq!(window("This_Week"), (local(2026, 7, 13).to_utc(), now));
+        assert_eq!(
+            window("last-week"),
+            (local(2026, 7, 6).to_utc(), local(2026, 7, 13).to_utc())
+        );
+        assert_eq!(window("this-month"), (local(2026, 7, 1).to_utc(), now));
+        assert_eq!(
+            window("last-month"),
+            (local(2026, 6, 1).to_utc(), local(2026, 7, 1).to_utc())
+        );
+        assert_eq!(window("last-hour"), (now - Duration::hours(1), now));
+        assert_eq!(window("90"), (now - Duration::minutes(90), now));
+        assert_eq!(window("2w"), (now - Duration::weeks(2), now));
+
+        let err = since_window_at("fortnight", kolkata(), now).unwrap_err();
+        assert!(err.to_string().contains("this-week"), "{}", err);
+    }
+
+    #[test]
+    fn timestamps_parse_in_every_accepted_format() {
+        let now = Utc.with_ymd_and_hms(2026, 7, 16, 4, 30, 0).unwrap();
+        let parse = |value| parse_timestamp_at(value, kolkata(), now).unwrap();
+        // 09:15 IST (UTC+5:30) is 03:45 UTC.
+        let expected = Utc.with_ymd_and_hms(2026, 5, 19, 3, 45, 0).unwrap();
+
+        assert_eq!(parse("2026-05-19 09:15:00"), expected);
+        assert_eq!(parse("2026-05-19T09:15"), expected);
+        assert_eq!(parse("2026-05-19T03:45:00Z"), expected);
+        assert_eq!(parse("2026-05-19T09:15:00+05:30"), expected);
+        assert_eq!(parse("1779162300000"), expected);
+        assert_eq!(parse("1779162300"), expected);
+        assert_eq!(
+            parse("2026-05-19"),
+            Utc.with_ymd_and_hms(2026, 5, 18, 18, 30, 0).unwrap()
+        );
+        assert_eq!(parse("2 hours ago"), now - Duration::hours(2));
+        assert_eq!(parse("30m ago"), now - Duration::minutes(30));
+        assert_eq!(parse("now"), now);
+    }
+
+    #[test]
+    fn unclear_timestamps_are_rejected() {
+        let now = Utc.with_ymd_and_hms(2026, 7, 16, 4, 30, 0).unwrap();
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        let err = |value| {
+            parse_timestamp_at(value, new_york, now)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(err("2026-11-01 01:30:00").contains("occurs twice"));
+        assert!(err("2026-03-08 02:30:00").contains("doesn't exist"));
+        assert!(err("17791623").contains("ambiguous number"));
+        assert!(err("07/14/2026").contains("RFC3339"));
+        assert!(err("3 fortnights ago").contains("unknown unit"));
+        assert!(err("500ms ago").contains("unknown unit"));
+
+        let err = from_to_window("2026-07-14", "2026-07-13", Some("UTC")).unwrap_err();
+        assert!(err.to_string().contains("must be before --to"), "{}", err);
+    }
+
+    #[test]
+    fn huge_lookbacks_are_errors_not_panics() {
+        let now = Utc.with_ymd_and_hms(2026, 7, 16, 4, 30, 0).unwrap();
+        assert!(
+            parse_duration("99999999999999w")
+                .unwrap_err()
+                .to_string()
+                .contains("out of range")
+        );
+        let err = since_window_at("1000000000w", Tz::UTC, now).unwrap_err();
+        assert!(err.to_string().contains("out of range"), "{}", err);
+    }
+
+    #[test]
+    fn huge_relative_timestamps_are_errors_not_panics() {
+        let now = Utc.with_ymd_and_hms(2026, 7, 16, 4, 30, 0).unwrap();
+        for value in ["1000000000 weeks ago", "99999999999999999 days ago"] {
+            let err = parse_timestamp_at(value, Tz::UTC, now).unwrap_err();
+            assert!(err.to_string().contains("out of range"), "{}", err);
+        }
+    }
+
     #[test]
     fn public_resolve_timezone_never_panics_and_yields_a_valid_zone() {
         // Exercises the real system-detection path (host-dependent), only